@@ -218,7 +218,7 @@ pub fn generate_metrics_with_seed(count_per_series: usize, seed: u64) -> Vec<Val
             let change = if rng.random_bool(params.burst_probability as f64) {
                 rng.random_range(-100.0..100.0) * params.volatility
             } else {
-                (rng.random_range(-1.0..1.0) * params.volatility) as f32
+                rng.random_range(-1.0..1.0) * params.volatility
             };
 
             current_value = (current_value + change).max(params.min).min(params.max);
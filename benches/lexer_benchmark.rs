@@ -0,0 +1,113 @@
+//! Lexer allocation benchmark
+//!
+//! Identifier and string tokens borrow from the source unless they contain
+//! escapes. Besides the criterion timings, this prints how many heap
+//! allocations lexing a 10,000-row file takes with borrowed tokens versus
+//! converting every token to an owned one, as the lexer used to produce.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauq::Lexer;
+
+/// Counts allocations made through the global allocator
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ROWS: usize = 10_000;
+
+/// A schema block followed by `ROWS` rows of barewords and quoted strings
+fn source() -> String {
+    let mut source = String::from("!def User id name email role bio active\n");
+    for i in 0..ROWS {
+        if i % 2 == 0 {
+            source.push_str(&format!(
+                "{} user{} user{}@example.com admin \"Plain biography {}\" true\n",
+                i, i, i, i
+            ));
+        } else {
+            // Escapes force an owned string even when borrowing
+            source.push_str(&format!(
+                "{} user{} user{}@example.com viewer \"Likes \\\"tauq\\\" and tea\" false\n",
+                i, i, i
+            ));
+        }
+    }
+    source
+}
+
+fn count_tokens(source: &str) -> usize {
+    let mut lexer = Lexer::new(source);
+    let mut count = 0;
+    while let Some(token) = lexer.next_token() {
+        black_box(&token);
+        count += 1;
+    }
+    count
+}
+
+fn count_owned_tokens(source: &str) -> usize {
+    let mut lexer = Lexer::new(source);
+    let mut count = 0;
+    while let Some(token) = lexer.next_token() {
+        black_box(token.into_owned());
+        count += 1;
+    }
+    count
+}
+
+fn allocations(f: impl FnOnce() -> usize) -> (usize, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let tokens = f();
+    (tokens, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+fn report_allocations(source: &str) {
+    let (tokens, borrowed) = allocations(|| count_tokens(source));
+    let (_, owned) = allocations(|| count_owned_tokens(source));
+    println!(
+        "lexing {} rows ({} tokens): {} allocations borrowed, {} owned ({:.1}% fewer)",
+        ROWS,
+        tokens,
+        borrowed,
+        owned,
+        100.0 * (owned - borrowed) as f64 / owned as f64
+    );
+}
+
+fn bench_lexer(c: &mut Criterion) {
+    let source = source();
+    report_allocations(&source);
+
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("borrowed", |b| b.iter(|| count_tokens(black_box(&source))));
+    group.bench_function("owned", |b| {
+        b.iter(|| count_owned_tokens(black_box(&source)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexer);
+criterion_main!(benches);
@@ -273,12 +273,116 @@ fn bench_scalability(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark named-argument rows against a wide schema (field lookup by name)
+fn bench_named_args(c: &mut Criterion) {
+    let mut group = c.benchmark_group("named_args");
+
+    let field_count = 50;
+    let fields: Vec<String> = (0..field_count).map(|i| format!("f{}", i)).collect();
+    let mut source = format!("!def Wide {}\n", fields.join(" "));
+    for row in 0..1000 {
+        // Name fields in reverse declaration order so lookups hit the far end
+        let values: Vec<String> = fields
+            .iter()
+            .rev()
+            .map(|f| format!("{}:{}", f, row))
+            .collect();
+        source.push_str(&values.join(" "));
+        source.push('\n');
+    }
+
+    group.bench_function("wide_schema_50_fields", |b| {
+        b.iter(|| {
+            let value = tauq::compile_tauq(black_box(&source)).unwrap();
+            black_box(value);
+        });
+    });
+
+    group.finish();
+}
+
+/// Benchmark SAX events against the record-building streaming parser
+fn bench_sax(c: &mut Criterion) {
+    use tauq::tauq::{StreamingParser, TauqHandler, TauqValue, parse_sax};
+
+    struct FieldCounter(usize);
+    impl TauqHandler for FieldCounter {
+        fn on_field(&mut self, _name: &str, value: TauqValue<'_>) {
+            black_box(value);
+            self.0 += 1;
+        }
+    }
+
+    let mut group = c.benchmark_group("sax_vs_streaming");
+
+    for size in [1000, 10000].iter() {
+        let data: Vec<_> = (1..=*size)
+            .map(|i| {
+                json!({
+                    "id": i,
+                    "name": format!("Item{}", i),
+                    "value": i * 10,
+                    "active": i % 2 == 0
+                })
+            })
+            .collect();
+        let source = json_to_tauq(&json!(data));
+
+        group.bench_with_input(BenchmarkId::new("sax", size), &source, |b, s| {
+            b.iter(|| {
+                let mut counter = FieldCounter(0);
+                parse_sax(black_box(s), &mut counter).unwrap();
+                black_box(counter.0);
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("streaming", size), &source, |b, s| {
+            b.iter(|| {
+                let mut parser = StreamingParser::new(black_box(s));
+                let mut count = 0;
+                while let Some(record) = parser.next_record() {
+                    black_box(record.unwrap());
+                    count += 1;
+                }
+                black_box(count);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark `tauq count` end to end, process start included
+fn bench_count_cli(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("users.tqn");
+    let mut source = String::from("!def User id name tags\n");
+    for i in 0..1000 {
+        source.push_str(&format!("{} \"user {}\" [a b]\n", i, i));
+    }
+    std::fs::write(&path, source).unwrap();
+
+    c.bench_function("count_cli_1000_rows", |b| {
+        b.iter(|| {
+            let output = std::process::Command::new(env!("CARGO_BIN_EXE_tauq"))
+                .arg("count")
+                .arg(&path)
+                .output()
+                .unwrap();
+            assert_eq!(output.stdout, b"1000\n");
+        });
+    });
+}
+
 criterion_group!(
     benches,
     bench_parse,
     bench_format,
     bench_format_optimized,
     bench_roundtrip,
-    bench_scalability
+    bench_scalability,
+    bench_named_args,
+    bench_sax,
+    bench_count_cli
 );
 criterion_main!(benches);
@@ -94,6 +94,28 @@ public class Tauq {
      */
     public static native String tbfToTauq(byte[] data);
 
+    /**
+     * Parse Tauq source one record at a time.
+     * <p>
+     * The returned parser must be used from the calling thread and closed when done.
+     * </p>
+     *
+     * @param tauqSource The Tauq input string.
+     * @return Iterator over the records as JSON strings.
+     */
+    public static TauqStreamingParser streamingParser(String tauqSource) {
+        return new TauqStreamingParser(tauqSource);
+    }
+
+    /** Create a native streaming parser; returns a handle for the methods below. */
+    static native long streamingParserNew(String input);
+
+    /** Next record as JSON, or null at the end of the stream. */
+    static native String streamingParserNext(long handle);
+
+    /** Release a handle from streamingParserNew. */
+    static native void streamingParserFree(long handle);
+
     /**
      * Convenience method to parse Tauq and return a Jackson JsonNode (if available).
      * Since we want zero-dep bindings, we stick to String.
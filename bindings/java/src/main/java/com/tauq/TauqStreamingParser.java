@@ -0,0 +1,80 @@
+package com.tauq;
+
+import java.util.Iterator;
+import java.util.NoSuchElementException;
+
+/**
+ * Iterates over the records of a Tauq source, one JSON string per record.
+ * <p>
+ * Records are parsed lazily by the native library, so large inputs never need to be
+ * converted to one JSON document. The native parser is bound to the thread that created
+ * it: calling {@link #hasNext()}, {@link #next()} or {@link #close()} from another thread
+ * throws {@link IllegalStateException}. Close the parser (or use try-with-resources) to
+ * release native memory; it is also released once iteration reaches the end.
+ * </p>
+ *
+ * <pre>{@code
+ * try (TauqStreamingParser records = Tauq.streamingParser(source)) {
+ *     while (records.hasNext()) {
+ *         String json = records.next();
+ *     }
+ * }
+ * }</pre>
+ */
+public class TauqStreamingParser implements Iterator<String>, AutoCloseable {
+
+    private long handle;
+    private String pending;
+
+    /**
+     * Start parsing Tauq source.
+     *
+     * @param tauqSource The Tauq input string.
+     */
+    public TauqStreamingParser(String tauqSource) {
+        this.handle = Tauq.streamingParserNew(tauqSource);
+    }
+
+    /**
+     * @throws IllegalArgumentException if the next record fails to parse.
+     */
+    @Override
+    public boolean hasNext() {
+        if (pending == null && handle != 0) {
+            try {
+                pending = Tauq.streamingParserNext(handle);
+            } catch (IllegalArgumentException e) {
+                close();
+                throw e;
+            }
+            if (pending == null) {
+                close();
+            }
+        }
+        return pending != null;
+    }
+
+    /**
+     * @return The next record as a JSON string.
+     * @throws NoSuchElementException if there are no more records.
+     * @throws IllegalArgumentException if the next record fails to parse.
+     */
+    @Override
+    public String next() {
+        if (!hasNext()) {
+            throw new NoSuchElementException();
+        }
+        String record = pending;
+        pending = null;
+        return record;
+    }
+
+    /** Release the native parser. Safe to call more than once. */
+    @Override
+    public void close() {
+        if (handle != 0) {
+            Tauq.streamingParserFree(handle);
+            handle = 0;
+        }
+    }
+}
@@ -0,0 +1,72 @@
+package com.tauq;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertFalse;
+import static org.junit.jupiter.api.Assertions.assertThrows;
+
+import java.util.ArrayList;
+import java.util.List;
+import java.util.NoSuchElementException;
+import java.util.concurrent.atomic.AtomicReference;
+import org.junit.jupiter.api.Test;
+
+class TauqStreamingParserTest {
+
+    @Test
+    void yieldsEveryRecord() {
+        StringBuilder source = new StringBuilder("!def User id name\n");
+        for (int i = 0; i < 1000; i++) {
+            source.append(i).append(" user").append(i).append('\n');
+        }
+        List<String> records = new ArrayList<>();
+        try (TauqStreamingParser parser = Tauq.streamingParser(source.toString())) {
+            parser.forEachRemaining(records::add);
+        }
+        assertEquals(1000, records.size());
+        assertEquals("{\"id\":42,\"name\":\"user42\"}", records.get(42));
+    }
+
+    @Test
+    void endOfStream() {
+        TauqStreamingParser parser = Tauq.streamingParser("!def User id\n1\n");
+        assertEquals("{\"id\":1}", parser.next());
+        assertFalse(parser.hasNext());
+        assertThrows(NoSuchElementException.class, parser::next);
+        parser.close();
+    }
+
+    @Test
+    void parseErrorThrows() {
+        TauqStreamingParser parser = Tauq.streamingParser("!def User id name\n1 Alice\n2 [3\n");
+        assertEquals("{\"id\":1,\"name\":\"Alice\"}", parser.next());
+        assertThrows(IllegalArgumentException.class, parser::next);
+        assertFalse(parser.hasNext());
+    }
+
+    @Test
+    void nativeLifecycle() {
+        long handle = Tauq.streamingParserNew("a 1\n");
+        assertEquals("{\"a\":1}", Tauq.streamingParserNext(handle));
+        assertEquals(null, Tauq.streamingParserNext(handle));
+        Tauq.streamingParserFree(handle);
+        assertThrows(IllegalStateException.class, () -> Tauq.streamingParserNext(0));
+    }
+
+    @Test
+    void otherThreadIsRejected() throws InterruptedException {
+        TauqStreamingParser parser = Tauq.streamingParser("!def User id\n1\n2\n");
+        AtomicReference<Throwable> thrown = new AtomicReference<>();
+        Thread other = new Thread(() -> {
+            try {
+                parser.next();
+            } catch (Throwable t) {
+                thrown.set(t);
+            }
+        });
+        other.start();
+        other.join();
+        assertEquals(IllegalStateException.class, thrown.get().getClass());
+        assertEquals("{\"id\":1}", parser.next());
+        parser.close();
+    }
+}
@@ -0,0 +1,7 @@
+export * from './pkg/tauq';
+
+/**
+ * Parse a stream of Tauq text, yielding each record as soon as it is complete.
+ * Throws `Stream Parse Error: ...` when the input does not parse.
+ */
+export function parseStream(stream: ReadableStream<Uint8Array | string>): AsyncGenerator<any, void, undefined>;
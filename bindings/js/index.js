@@ -0,0 +1,44 @@
+const wasm = require('./pkg/tauq.js');
+
+/**
+ * Parse a stream of Tauq text, yielding each record as soon as it is complete.
+ *
+ * @param {ReadableStream<Uint8Array | string>} stream
+ * @returns {AsyncGenerator<any>}
+ */
+async function* parseStream(stream) {
+  const reader = stream.getReader();
+  const decoder = new TextDecoder('utf-8');
+  const handle = wasm.stream_parser_new('');
+  let pending = '';
+
+  function* drain() {
+    let record;
+    while ((record = wasm.stream_parser_next(handle)) !== undefined) {
+      yield record;
+    }
+  }
+
+  try {
+    for (;;) {
+      const { done, value } = await reader.read();
+      if (done) break;
+      pending += typeof value === 'string' ? value : decoder.decode(value, { stream: true });
+      // Only hand complete lines to the parser
+      const end = pending.lastIndexOf('\n') + 1;
+      if (end > 0) {
+        wasm.stream_parser_push(handle, pending.slice(0, end));
+        pending = pending.slice(end);
+        yield* drain();
+      }
+    }
+    wasm.stream_parser_push(handle, pending + decoder.decode());
+    wasm.stream_parser_end(handle);
+    yield* drain();
+  } finally {
+    wasm.stream_parser_free(handle);
+    reader.releaseLock();
+  }
+}
+
+module.exports = { ...wasm, parseStream };
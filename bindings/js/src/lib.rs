@@ -1,15 +1,99 @@
-use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use tauq::{compile_tauq, compile_tauqq, format_to_tauq, minify_tauq_str};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface ValidationError {
+  line: number;
+  column: number;
+  message: string;
+}
+
+export interface ValidationResult {
+  valid: boolean;
+  errors: ValidationError[];
+}
+
+export interface SchemaInfo {
+  name: string;
+  fields: string[];
+}
+"#;
+
+#[derive(Serialize)]
+struct ValidationError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ValidationResult {
+    valid: bool,
+    errors: Vec<ValidationError>,
+}
+
+#[derive(Serialize)]
+struct SchemaInfo {
+    name: String,
+    fields: Vec<String>,
+}
 
 #[wasm_bindgen]
 pub fn parse(input: &str) -> Result<JsValue, JsValue> {
-    let json_val = compile_tauq(input)
-        .map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
+    let json_val =
+        compile_tauq(input).map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
 
     serde_wasm_bindgen::to_value(&json_val)
         .map_err(|e| JsValue::from_str(&format!("Serialization Error: {}", e)))
 }
 
+/// Check a tauq string without building its value. Every `---`-separated
+/// document is checked, so one call can report several errors.
+#[wasm_bindgen(unchecked_return_type = "ValidationResult")]
+pub fn validate(input: &str) -> JsValue {
+    let mut parser = tauq::Parser::new(input);
+    let errors: Vec<ValidationError> = parser
+        .parse_documents()
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|e| ValidationError {
+            line: e.span.line,
+            column: e.span.column,
+            message: e.message,
+        })
+        .collect();
+
+    let result = ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+    };
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// The `!def` schemas in a tauq string, in declaration order.
+#[wasm_bindgen(unchecked_return_type = "SchemaInfo[]")]
+pub fn get_schemas(input: &str) -> Result<JsValue, JsValue> {
+    let mut parser = tauq::Parser::new(input);
+    parser
+        .parse()
+        .map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
+
+    let schemas: Vec<SchemaInfo> = parser
+        .schema_definitions()
+        .into_iter()
+        .map(|def| SchemaInfo {
+            name: def.name,
+            fields: def.fields.into_iter().map(|f| f.name).collect(),
+        })
+        .collect();
+    serde_wasm_bindgen::to_value(&schemas)
+        .map_err(|e| JsValue::from_str(&format!("Serialization Error: {}", e)))
+}
+
 #[wasm_bindgen]
 pub fn exec(input: &str, safe_mode: bool) -> Result<JsValue, JsValue> {
     let json_val = compile_tauqq(input, safe_mode)
@@ -21,8 +105,8 @@ pub fn exec(input: &str, safe_mode: bool) -> Result<JsValue, JsValue> {
 
 #[wasm_bindgen]
 pub fn minify(input: &str) -> Result<String, JsValue> {
-    let json_val = compile_tauq(input)
-        .map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
+    let json_val =
+        compile_tauq(input).map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
 
     Ok(minify_tauq_str(&json_val))
 }
@@ -37,8 +121,8 @@ pub fn stringify(value: JsValue) -> Result<String, JsValue> {
 
 #[wasm_bindgen]
 pub fn to_json(input: &str) -> Result<String, JsValue> {
-    let json_val = compile_tauq(input)
-        .map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
+    let json_val =
+        compile_tauq(input).map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
 
     serde_json::to_string(&json_val)
         .map_err(|e| JsValue::from_str(&format!("JSON Serialize Error: {}", e)))
@@ -47,7 +131,8 @@ pub fn to_json(input: &str) -> Result<String, JsValue> {
 #[wasm_bindgen]
 pub fn to_tbf(input: &str) -> Result<Box<[u8]>, JsValue> {
     let json_val = if input.trim_start().starts_with('{') || input.trim_start().starts_with('[') {
-        serde_json::from_str(input).map_err(|e| JsValue::from_str(&format!("JSON Parse Error: {}", e)))?
+        serde_json::from_str(input)
+            .map_err(|e| JsValue::from_str(&format!("JSON Parse Error: {}", e)))?
     } else {
         compile_tauq(input).map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?
     };
@@ -89,3 +174,82 @@ pub fn parse_streaming(input: &str) -> Result<JsValue, JsValue> {
     serde_wasm_bindgen::to_value(&array)
         .map_err(|e| JsValue::from_str(&format!("Serialization Error: {}", e)))
 }
+
+/// Generate TypeScript interfaces for the `!def` schemas in a tauq string.
+#[wasm_bindgen]
+pub fn get_typescript_interfaces(tauq_source: &str) -> Result<String, JsValue> {
+    let mut parser = tauq::Parser::new(tauq_source);
+    parser
+        .parse()
+        .map_err(|e| JsValue::from_str(&format!("Tauq Parse Error: {}", e)))?;
+
+    let shapes = parser
+        .schema_definitions()
+        .into_iter()
+        .map(|def| (def.name, def.fields))
+        .collect();
+    Ok(tauq::tauq::tauq_schemas_to_typescript(&shapes))
+}
+
+thread_local! {
+    static STREAMS: RefCell<HashMap<StreamHandle, tauq::tauq::PushParser>> =
+        RefCell::new(HashMap::new());
+    static NEXT_STREAM: Cell<StreamHandle> = const { Cell::new(1) };
+}
+
+/// Handle to a streaming parser created by `stream_parser_new`.
+pub type StreamHandle = u32;
+
+fn with_stream<T>(
+    handle: StreamHandle,
+    f: impl FnOnce(&mut tauq::tauq::PushParser) -> T,
+) -> Result<T, JsValue> {
+    STREAMS.with(|streams| {
+        streams
+            .borrow_mut()
+            .get_mut(&handle)
+            .map(f)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown stream handle: {}", handle)))
+    })
+}
+
+/// Start a streaming parse with the first chunk of input (may be empty).
+/// Free the handle with `stream_parser_free`.
+#[wasm_bindgen]
+pub fn stream_parser_new(source_chunk: &str) -> StreamHandle {
+    let handle = NEXT_STREAM.with(|next| next.replace(next.get().wrapping_add(1).max(1)));
+    let mut parser = tauq::tauq::PushParser::new();
+    parser.push(source_chunk);
+    STREAMS.with(|streams| streams.borrow_mut().insert(handle, parser));
+    handle
+}
+
+/// Append a chunk of input to a streaming parse.
+#[wasm_bindgen]
+pub fn stream_parser_push(handle: StreamHandle, chunk: &str) -> Result<(), JsValue> {
+    with_stream(handle, |parser| parser.push(chunk))
+}
+
+/// Signal the end of the input, so the last records can complete.
+#[wasm_bindgen]
+pub fn stream_parser_end(handle: StreamHandle) -> Result<(), JsValue> {
+    with_stream(handle, |parser| parser.finish())
+}
+
+/// The next complete record, or `undefined` when more input is needed or
+/// the stream has ended. Throws on parse errors.
+#[wasm_bindgen]
+pub fn stream_parser_next(handle: StreamHandle) -> Result<JsValue, JsValue> {
+    match with_stream(handle, |parser| parser.next_record())? {
+        Some(Ok(record)) => serde_wasm_bindgen::to_value(&record)
+            .map_err(|e| JsValue::from_str(&format!("Serialization Error: {}", e))),
+        Some(Err(e)) => Err(JsValue::from_str(&format!("Stream Parse Error: {}", e))),
+        None => Ok(JsValue::UNDEFINED),
+    }
+}
+
+/// Release a streaming parser. Unknown handles are ignored.
+#[wasm_bindgen]
+pub fn stream_parser_free(handle: StreamHandle) {
+    STREAMS.with(|streams| streams.borrow_mut().remove(&handle));
+}
@@ -0,0 +1,49 @@
+// Run with `npm run build && npm test`
+const test = require('node:test');
+const assert = require('node:assert');
+const tauq = require('../index.js');
+
+function streamOf(chunks) {
+  return new ReadableStream({
+    start(controller) {
+      for (const chunk of chunks) controller.enqueue(chunk);
+      controller.close();
+    },
+  });
+}
+
+function split(source, n) {
+  const size = Math.ceil(source.length / n);
+  const chunks = [];
+  for (let i = 0; i < source.length; i += size) chunks.push(source.slice(i, i + size));
+  return chunks;
+}
+
+async function collect(stream) {
+  const records = [];
+  for await (const record of tauq.parseStream(stream)) records.push(record);
+  return records;
+}
+
+test('parseStream yields 1000 rows fed in 10 chunks', async () => {
+  let source = '!def User id name active\n';
+  for (let i = 0; i < 1000; i++) source += `${i} "user ${i}" ${i % 2 === 0}\n`;
+  const encoder = new TextEncoder();
+  const chunks = split(source, 10).map((c) => encoder.encode(c));
+  assert.strictEqual(chunks.length, 10);
+
+  const records = await collect(streamOf(chunks));
+  assert.strictEqual(records.length, 1000);
+  records.forEach((record, i) => {
+    assert.deepStrictEqual(record, { id: i, name: `user ${i}`, active: i % 2 === 0 });
+  });
+});
+
+test('parseStream accepts string chunks and a final line without newline', async () => {
+  const records = await collect(streamOf(['!def Point x y\n1 ', '2\n3 4']));
+  assert.deepStrictEqual(records, [{ x: 1, y: 2 }, { x: 3, y: 4 }]);
+});
+
+test('parseStream throws on invalid input', async () => {
+  await assert.rejects(collect(streamOf(['!def User id name\n1 Alice\n2 [3\n'])), /Stream Parse Error/);
+});
@@ -0,0 +1,38 @@
+// Run with `npm run build && npm test`
+const test = require('node:test');
+const assert = require('node:assert');
+const tauq = require('../pkg/tauq.js');
+
+test('validate accepts valid Tauq', () => {
+  assert.deepStrictEqual(tauq.validate('host localhost'), { valid: true, errors: [] });
+});
+
+test('validate reports the error position', () => {
+  const result = tauq.validate('[ unclosed');
+  assert.strictEqual(result.valid, false);
+  assert.strictEqual(result.errors.length, 1);
+  assert.strictEqual(result.errors[0].line, 1);
+  assert.strictEqual(result.errors[0].column, 1);
+  assert.match(result.errors[0].message, /Unclosed list/);
+});
+
+test('validate reports one error per failing document', () => {
+  const result = tauq.validate('a 1\n---\nb [\n---\nc }\n');
+  assert.strictEqual(result.valid, false);
+  assert.deepStrictEqual(
+    result.errors.map((e) => e.line),
+    [4, 5]
+  );
+});
+
+test('get_schemas lists schema names and fields', () => {
+  const schemas = tauq.get_schemas('!def User id name\n1 Alice\n!def Tag label\nx\n');
+  assert.deepStrictEqual(schemas, [
+    { name: 'User', fields: ['id', 'name'] },
+    { name: 'Tag', fields: ['label'] },
+  ]);
+});
+
+test('get_schemas throws on invalid Tauq', () => {
+  assert.throws(() => tauq.get_schemas('[ unclosed'), /Tauq Parse Error/);
+});
@@ -1,11 +1,22 @@
-from typing import Any, Union, Dict, List
+from typing import Any, Iterator, Optional, Union, Dict, List
 import os
 
 __version__: str
 __doc__: str
+__all__: List[str]
+
+class TauqException(ValueError):
+    line: Optional[int]
+    column: Optional[int]
+
+class TauqLexError(TauqException): ...
+class TauqParseError(TauqException): ...
+class TauqInterpretError(TauqException): ...
+class TauqIOError(TauqException): ...
 
 def loads(source: str) -> Any: ...
 def load(path: Union[str, os.PathLike]) -> Any: ...
+def streaming_loads(source: str) -> TauqRecords: ...
 def exec_tauqq(source: str) -> Any: ...
 def dumps(obj: Any) -> str: ...
 def minify(source: str) -> str: ...
@@ -21,3 +32,22 @@ class TauqStream:
     def __init__(self) -> None: ...
     def push(self, chunk: str) -> List[Any]: ...
     def finish(self) -> List[Any]: ...
+
+class TauqRecords(Iterator[Any]):
+    def __iter__(self) -> TauqRecords: ...
+    def __next__(self) -> Any: ...
+
+class TauqFile:
+    def __init__(self, path: Union[str, os.PathLike]) -> None: ...
+    def __enter__(self) -> TauqRecords: ...
+    def __exit__(self, exc_type: Optional[type], exc_value: Optional[BaseException], traceback: Any) -> bool: ...
+
+class Schema:
+    name: str
+    fields: List[str]
+    def __init__(self, name: str, fields: List[str]) -> None: ...
+    @classmethod
+    def from_dicts(cls, name: str, records: List[Dict[str, Any]]) -> Schema: ...
+    def header(self) -> str: ...
+    def row(self, values: Dict[str, Any]) -> str: ...
+    def dumps(self, records: List[Dict[str, Any]]) -> str: ...
@@ -0,0 +1,56 @@
+import unittest
+
+import tauq
+
+
+class ExceptionTest(unittest.TestCase):
+    def test_hierarchy(self):
+        for cls in (
+            tauq.TauqLexError,
+            tauq.TauqParseError,
+            tauq.TauqInterpretError,
+            tauq.TauqIOError,
+        ):
+            self.assertTrue(issubclass(cls, tauq.TauqException))
+        # Existing `except ValueError` handlers keep working
+        self.assertTrue(issubclass(tauq.TauqException, ValueError))
+        self.assertIn("TauqException", tauq.__all__)
+
+    def test_parse_error_has_position(self):
+        try:
+            tauq.loads("{ unclosed")
+        except tauq.TauqParseError as e:
+            self.assertEqual(e.line, 1)
+            self.assertEqual(e.column, 1)
+        else:
+            self.fail("TauqParseError not raised")
+
+    def test_parse_error_on_later_line(self):
+        with self.assertRaises(tauq.TauqParseError) as ctx:
+            tauq.loads("a 1\nb }\n")
+        self.assertEqual(ctx.exception.line, 2)
+        self.assertEqual(ctx.exception.column, 3)
+
+    def test_unterminated_string(self):
+        # Lexer errors are reported by the parser with its position
+        with self.assertRaises(tauq.TauqParseError) as ctx:
+            tauq.loads('name "unterminated')
+        self.assertEqual((ctx.exception.line, ctx.exception.column), (1, 6))
+
+    def test_io_error(self):
+        with self.assertRaises(tauq.TauqIOError) as ctx:
+            tauq.load("/nonexistent/config.tqn")
+        self.assertIsNone(ctx.exception.line)
+        self.assertIsNone(ctx.exception.column)
+
+    def test_tbf_decode_error(self):
+        with self.assertRaises(tauq.TauqException):
+            tauq.tbf_loads(b"not tbf")
+
+    def test_streaming_error(self):
+        with self.assertRaises(tauq.TauqParseError):
+            list(tauq.streaming_loads("!def User id name\n1 [2\n"))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,73 @@
+import unittest
+
+import tauq
+
+
+class SchemaTest(unittest.TestCase):
+    def test_header(self):
+        schema = tauq.Schema("User", ["id", "name"])
+        self.assertEqual(schema.header(), "!def User id name")
+        self.assertEqual(schema.name, "User")
+        self.assertEqual(schema.fields, ["id", "name"])
+
+    def test_row_orders_values_by_field(self):
+        schema = tauq.Schema("User", ["id", "name", "email"])
+        row = schema.row({"name": "Alice Smith", "id": 1, "email": "alice@example.com"})
+        self.assertEqual(row, '1 "Alice Smith" alice@example.com')
+
+    def test_row_missing_fields_are_null(self):
+        schema = tauq.Schema("User", ["id", "name", "role"])
+        self.assertEqual(schema.row({"id": 1, "role": "admin"}), "1 null admin")
+        self.assertEqual(schema.row({"id": 2}), "2 null null")
+
+        records = [{"id": 1, "role": "admin"}, {"id": 2}]
+        self.assertEqual(
+            tauq.loads(schema.dumps(records)),
+            [
+                {"id": 1, "name": None, "role": "admin"},
+                {"id": 2, "name": None, "role": None},
+            ],
+        )
+
+    def test_row_rejects_unknown_field(self):
+        schema = tauq.Schema("User", ["id"])
+        with self.assertRaises(ValueError):
+            schema.row({"id": 1, "name": "Alice"})
+
+    def test_dumps_is_valid_tauq(self):
+        source = tauq.Schema("User", ["id", "name"]).dumps(
+            [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]
+        )
+        self.assertEqual(source, "!def User id name\n1 Alice\n2 Bob")
+        self.assertEqual(
+            tauq.loads(source),
+            [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+        )
+
+    def test_dumps_single_record(self):
+        source = tauq.Schema("User", ["id", "name"]).dumps([{"id": 1, "name": "Alice"}])
+        self.assertEqual(tauq.loads(source), {"id": 1, "name": "Alice"})
+
+    def test_from_dicts_infers_fields(self):
+        events = [
+            {"ts": 1700000000, "kind": "login", "user": "alice"},
+            {"ts": 1700000060, "kind": "logout", "user": "alice"},
+        ]
+        schema = tauq.Schema.from_dicts("Event", events)
+        self.assertEqual(schema.name, "Event")
+        self.assertEqual(schema.fields, ["ts", "kind", "user"])
+        self.assertEqual(tauq.loads(schema.dumps(events)), events)
+
+    def test_from_dicts_empty_list(self):
+        with self.assertRaises(ValueError):
+            tauq.Schema.from_dicts("Event", [])
+
+    def test_invalid_names(self):
+        with self.assertRaises(ValueError):
+            tauq.Schema("My User", ["id"])
+        with self.assertRaises(ValueError):
+            tauq.Schema("User", ["first name"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,57 @@
+import os
+import tempfile
+import unittest
+
+import tauq
+
+
+def user_table(rows):
+    lines = ["!def User id name active"]
+    lines += ['%d "user%d" %s' % (i, i, "true" if i % 2 else "false") for i in range(rows)]
+    return "\n".join(lines) + "\n"
+
+
+class StreamingLoadsTest(unittest.TestCase):
+    def test_yields_every_row(self):
+        records = list(tauq.streaming_loads(user_table(1000)))
+        self.assertEqual(len(records), 1000)
+        self.assertTrue(all(isinstance(r, dict) for r in records))
+        self.assertEqual(records[0], {"id": 0, "name": "user0", "active": False})
+        self.assertEqual(records[999]["name"], "user999")
+
+    def test_is_lazy_iterator(self):
+        records = tauq.streaming_loads(user_table(3))
+        self.assertIs(iter(records), records)
+        self.assertEqual(next(records)["id"], 0)
+        self.assertEqual(next(records)["id"], 1)
+        self.assertEqual(next(records)["id"], 2)
+        with self.assertRaises(StopIteration):
+            next(records)
+
+    def test_parse_error_raises_value_error(self):
+        records = tauq.streaming_loads('!def User id name\n1 "Alice"\n2 [3\n')
+        self.assertEqual(next(records), {"id": 1, "name": "Alice"})
+        with self.assertRaises(ValueError):
+            next(records)
+        with self.assertRaises(StopIteration):
+            next(records)
+
+
+class TauqFileTest(unittest.TestCase):
+    def test_context_manager_streams_file(self):
+        with tempfile.TemporaryDirectory() as dir:
+            path = os.path.join(dir, "users.tqn")
+            with open(path, "w") as f:
+                f.write(user_table(1000))
+            with tauq.TauqFile(path) as records:
+                count = sum(1 for r in records if isinstance(r, dict))
+            self.assertEqual(count, 1000)
+
+    def test_missing_file_raises_on_enter(self):
+        with self.assertRaises(ValueError):
+            with tauq.TauqFile("/nonexistent/users.tqn"):
+                pass
+
+
+if __name__ == "__main__":
+    unittest.main()
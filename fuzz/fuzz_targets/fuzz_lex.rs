@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauq::Lexer;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        let mut lexer = Lexer::new(s);
+        // Every token consumes at least one character, so more tokens than
+        // bytes means the lexer has stopped making progress
+        let mut tokens = 0;
+        while lexer.next_token().is_some() {
+            tokens += 1;
+            assert!(tokens <= s.len(), "lexer did not advance");
+        }
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tauq::{Parser, StreamingParser};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // Errors are fine; panics and hangs are not
+        let _ = Parser::new(s).parse();
+
+        // The streaming parser skips past errors, so it must also terminate
+        let records = StreamingParser::new(s).count();
+        assert!(records <= s.len() + 1, "streaming parser did not advance");
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = std::str::from_utf8(data) {
+        // Safe mode: malformed directives must error, never shell out or panic
+        let mut vars = HashMap::new();
+        let _ = tauqq::process(s, &mut vars, true);
+    }
+});
@@ -0,0 +1,19 @@
+#!/usr/bin/env bash
+# Seed the fuzz corpora with the Tauq sources from tests/fixtures and examples.
+# Run from anywhere; existing corpus entries are kept.
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+
+for target in fuzz_lex fuzz_parse fuzz_parser fuzz_tauqq; do
+    corpus="$root/fuzz/corpus/$target"
+    mkdir -p "$corpus"
+    find "$root/tests/fixtures" "$root/examples" -type f \
+        \( -name '*.tqn' -o -name '*.tqq' -o -name '*.json' \) |
+        while read -r file; do
+            name="${file#"$root"/}"
+            cp "$file" "$corpus/${name//\//_}"
+        done
+done
+
+echo "Seeded $(ls "$root/fuzz/corpus/fuzz_parse" | wc -l) inputs per target"
@@ -3,6 +3,7 @@
 
 #include <stdbool.h>
 #include <stddef.h>
+#include <stdint.h>
 
 #ifdef __cplusplus
 extern "C" {
@@ -45,6 +46,63 @@ char* tauq_minify(const char* input);
  */
 char* json_to_tauq_c(const char* input);
 
+/**
+ * Formatting options for tauq_format_json_with_options.
+ */
+typedef struct tauq_format_options_t {
+    /** Row delimiter: 's' for spaces, 'c' for commas */
+    unsigned char delimiter;
+    /** Spaces per indentation level */
+    uint32_t indent_size;
+    /** Single-line output */
+    bool minify;
+    /** Sort object keys for byte-identical output across runs */
+    bool stable;
+} tauq_format_options_t;
+
+/**
+ * Default formatting options, matching json_to_tauq_c.
+ */
+tauq_format_options_t tauq_format_options_default(void);
+
+/**
+ * Format JSON string to Tauq with formatting options.
+ * A NULL opts uses tauq_format_options_default().
+ * Returns NULL on error. Use tauq_get_last_error to retrieve error details.
+ * Caller must free result with tauq_free_string.
+ */
+char* tauq_format_json_with_options(const char* input, const tauq_format_options_t* opts);
+
+/**
+ * Check whether input is valid Tauq without producing any output.
+ * Returns false on error. Use tauq_get_last_error to retrieve error details.
+ */
+bool tauq_validate(const char* input);
+
+/**
+ * Opaque handle for parsing Tauq one record at a time.
+ */
+typedef struct TauqStreamHandle TauqStreamHandle;
+
+/**
+ * Start parsing Tauq one record at a time. The input is copied.
+ * Returns NULL on error. Free the handle with tauq_parse_streaming_free.
+ */
+TauqStreamHandle* tauq_parse_streaming_new(const char* input);
+
+/**
+ * Parse the next record and return it as a JSON string.
+ * Returns NULL at the end of the stream (last error cleared) or on error
+ * (last error set); every later call also returns NULL.
+ * Caller must free result with tauq_free_string.
+ */
+char* tauq_parse_streaming_next(TauqStreamHandle* handle);
+
+/**
+ * Free a handle returned by tauq_parse_streaming_new. NULL is ignored.
+ */
+void tauq_parse_streaming_free(TauqStreamHandle* handle);
+
 /**
  * Free string returned by tauq functions.
  */
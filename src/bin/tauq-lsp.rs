@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauq::tauq::Parser;
+use tauq::tauq::token::Token;
+use tauq::tauq::{IncrementalParser, Lexer, LintSeverity, Linter, Parser, SingularisationConfig};
 use tokio::sync::RwLock;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
@@ -13,6 +15,9 @@ struct Document {
     #[allow(dead_code)]
     version: i32,
     schemas: Vec<SchemaInfo>,
+    enums: Vec<EnumInfo>,
+    /// Parse of `content`, updated in place by incremental changes
+    parser: IncrementalParser,
 }
 
 /// Information about a schema definition
@@ -20,14 +25,88 @@ struct Document {
 struct SchemaInfo {
     name: String,
     fields: Vec<String>,
+    /// Type annotation for each field (`id:int` -> `int`), parallel to `fields`
+    types: Vec<Option<String>>,
     line: u32,
     character: u32,
 }
 
+/// Information about an `!enum` declaration
+#[derive(Debug, Clone)]
+struct EnumInfo {
+    name: String,
+    variants: Vec<String>,
+}
+
+/// A list of inline objects that all share one key set, such as
+/// `users [{ id 1 name Alice } { id 2 name Bob }]`
+#[derive(Debug, Clone)]
+struct InlineTable {
+    /// Key the list is assigned to, used to name the extracted schema
+    key: Option<String>,
+    /// From the `[` through the `]`
+    range: Range,
+    /// Keys in the order of the first object
+    fields: Vec<String>,
+    /// Source text of each object's values, in `fields` order
+    rows: Vec<Vec<String>>,
+}
+
+/// `!def` schemas of every `.tqn` file in the workspace, so a schema shared
+/// through `!import` is known in the files that use it
+#[derive(Debug, Default)]
+struct WorkspaceSchemas {
+    files: HashMap<Url, Vec<SchemaInfo>>,
+}
+
+impl WorkspaceSchemas {
+    /// Replace the schemas indexed for `uri`
+    fn update(&mut self, uri: Url, schemas: Vec<SchemaInfo>) {
+        self.files.insert(uri, schemas);
+    }
+
+    fn remove(&mut self, uri: &Url) {
+        self.files.remove(uri);
+    }
+
+    /// First definition of `name` in a file other than `exclude`
+    fn find(&self, name: &str, exclude: &Url) -> Option<(&Url, &SchemaInfo)> {
+        let mut found: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(uri, _)| *uri != exclude)
+            .flat_map(|(uri, schemas)| schemas.iter().map(move |s| (uri, s)))
+            .filter(|(_, s)| s.name == name)
+            .collect();
+        // Deterministic choice when several files define the same name
+        found.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        found.into_iter().next()
+    }
+
+    /// Schemas defined outside `exclude`, by name, each listed once
+    fn others(&self, exclude: &Url) -> Vec<(&Url, &SchemaInfo)> {
+        let mut names: Vec<&str> = self
+            .files
+            .iter()
+            .filter(|(uri, _)| *uri != exclude)
+            .flat_map(|(_, schemas)| schemas.iter().map(|s| s.name.as_str()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|name| self.find(name, exclude))
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 struct Backend {
     client: Client,
     documents: Arc<RwLock<HashMap<Url, Document>>>,
+    workspace: Arc<RwLock<WorkspaceSchemas>>,
+    /// Workspace folders from `initialize`, indexed on `initialized`
+    roots: Arc<RwLock<Vec<PathBuf>>>,
 }
 
 impl Backend {
@@ -35,9 +114,53 @@ impl Backend {
         Self {
             client,
             documents: Arc::new(RwLock::new(HashMap::new())),
+            workspace: Arc::new(RwLock::new(WorkspaceSchemas::default())),
+            roots: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
+    /// `.tqn` files under `dir`, skipping hidden directories and build output
+    fn find_tqn_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if path.is_dir() {
+                if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                    Self::find_tqn_files(&path, files);
+                }
+            } else if path.extension().is_some_and(|e| e == "tqn") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// Index `path` from disk unless it is open, in which case the editor's
+    /// copy is already indexed
+    async fn index_file(&self, path: &Path) {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return;
+        };
+        if self.documents.read().await.contains_key(&uri) {
+            return;
+        }
+        let schemas = match std::fs::read_to_string(path) {
+            Ok(content) => Self::extract_schemas(&content),
+            Err(_) => Vec::new(),
+        };
+        self.workspace.write().await.update(uri, schemas);
+    }
+
+    /// Words of a schema definition; like the lexer, commas separate fields
+    /// as whitespace does (`!def User id,name`)
+    fn def_words(text: &str) -> impl Iterator<Item = &str> {
+        text.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty())
+    }
+
     /// Extract schema definitions from document content
     fn extract_schemas(content: &str) -> Vec<SchemaInfo> {
         let mut schemas = Vec::new();
@@ -47,21 +170,82 @@ impl Backend {
 
             // Check for !def directive
             if let Some(rest) = trimmed.strip_prefix("!def ") {
-                let parts: Vec<&str> = rest.split_whitespace().collect();
+                let parts: Vec<&str> = Self::def_words(rest).collect();
                 if !parts.is_empty() {
                     let name = parts[0].to_string();
-                    let fields: Vec<String> = parts[1..]
+                    // `!def Child !extends Parent ...` starts with the parent's fields
+                    let (mut fields, mut types, declared) = match parts.get(1..3) {
+                        Some(["!extends", parent]) => {
+                            let parent = schemas
+                                .iter()
+                                .rev()
+                                .find(|s: &&SchemaInfo| s.name == *parent);
+                            (
+                                parent.map(|p| p.fields.clone()).unwrap_or_default(),
+                                parent.map(|p| p.types.clone()).unwrap_or_default(),
+                                &parts[3..],
+                            )
+                        }
+                        _ => (Vec::new(), Vec::new(), &parts[1..]),
+                    };
+                    let own_fields: Vec<String> = declared
                         .iter()
-                        .map(|s| s.split(':').next().unwrap_or(s).to_string())
+                        .map(|s| {
+                            // `role?` and `port=8080` declare fields named
+                            // `role` and `port`
+                            let name = s.split([':', '=']).next().unwrap_or(s);
+                            name.strip_suffix('?').unwrap_or(name).to_string()
+                        })
                         .collect();
+                    let own_types = declared.iter().map(|s| {
+                        let (_, t) = s.split_once(':')?;
+                        Some(t.split('=').next().unwrap_or(t).to_string())
+                    });
+                    // Redeclared parent fields are replaced in place
+                    let parent_len = fields.len();
+                    for (field, ty) in own_fields.into_iter().zip(own_types) {
+                        match fields[..parent_len].iter().position(|f| *f == field) {
+                            Some(i) => types[i] = ty,
+                            None => {
+                                fields.push(field);
+                                types.push(ty);
+                            }
+                        }
+                    }
 
                     schemas.push(SchemaInfo {
                         name,
                         fields,
+                        types,
                         line: line_num as u32,
                         character: line.find("!def").unwrap_or(0) as u32,
                     });
                 }
+            } else if let Some(rest) = trimmed.strip_prefix("!compose ") {
+                // `!compose Name A B` joins the parts' fields, first occurrence winning
+                let parts: Vec<&str> = Self::def_words(rest).collect();
+                if let Some((name, sources)) = parts.split_first() {
+                    let mut fields: Vec<String> = Vec::new();
+                    let mut types = Vec::new();
+                    for source in sources {
+                        let Some(part) = schemas.iter().rev().find(|s| s.name == *source) else {
+                            continue;
+                        };
+                        for (field, ty) in part.fields.iter().zip(&part.types) {
+                            if !fields.contains(field) {
+                                fields.push(field.clone());
+                                types.push(ty.clone());
+                            }
+                        }
+                    }
+                    schemas.push(SchemaInfo {
+                        name: name.to_string(),
+                        fields,
+                        types,
+                        line: line_num as u32,
+                        character: line.find("!compose").unwrap_or(0) as u32,
+                    });
+                }
             }
 
             // Check for schema block definitions
@@ -72,13 +256,1134 @@ impl Backend {
         schemas
     }
 
-    /// Generate diagnostics for a document
-    async fn generate_diagnostics(&self, _uri: &Url, content: &str) -> Vec<Diagnostic> {
-        let mut diagnostics = Vec::new();
+    /// Number of rows after each `!def` line, keyed by that line. Counting
+    /// stops at the next `!def`, a `!use` of another schema, or `---`; a row
+    /// spanning several lines counts once.
+    fn row_counts(content: &str) -> HashMap<u32, usize> {
+        let mut counts = HashMap::new();
+        // `!def` line and schema name of the table being counted
+        let mut current: Option<(u32, &str)> = None;
+        let mut depth = 0usize;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let code = line.split('#').next().unwrap_or(line);
+            let trimmed = code.trim();
+            let top_level = depth == 0;
+            depth = Self::bracket_depth(depth, code);
+            if !top_level || trimmed.is_empty() {
+                continue;
+            }
+
+            let mut words = trimmed.split_whitespace();
+            match words.next() {
+                Some("!def") => {
+                    current = words.next().map(|name| (line_num as u32, name));
+                    if let Some((line, _)) = current {
+                        counts.insert(line, 0);
+                    }
+                }
+                Some("!use") if current.is_some_and(|(_, name)| words.next() == Some(name)) => {}
+                Some("!use" | "---") => current = None,
+                Some(word) if word.starts_with('!') => {}
+                _ => {
+                    if let Some((line, _)) = current {
+                        *counts.entry(line).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// Foldable regions: multi-line `{ }` and `[ ]` literals, `!schemas`
+    /// blocks, and the rows under each `!def` or `!use`
+    fn folding_ranges(content: &str) -> Vec<FoldingRange> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut ranges = Vec::new();
+        let fold = |start: usize, end: usize, kind: Option<FoldingRangeKind>| {
+            (end > start).then_some(FoldingRange {
+                start_line: start as u32,
+                start_character: None,
+                end_line: end as u32,
+                end_character: None,
+                kind,
+                collapsed_text: None,
+            })
+        };
+
+        // Brackets, skipping strings and comments
+        let mut open: Vec<usize> = Vec::new();
+        for (line_num, line) in lines.iter().enumerate() {
+            let mut in_string = false;
+            let mut escaped = false;
+            for (i, c) in line.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if in_string => escaped = true,
+                    '"' => in_string = !in_string,
+                    '#' if !in_string => break,
+                    '{' | '[' if !in_string => open.push(line_num),
+                    '}' | ']' if !in_string => {
+                        let Some(start) = open.pop() else {
+                            continue;
+                        };
+                        // Keep a closing bracket on its own line visible
+                        let end = match line[..i].trim().is_empty() {
+                            true => line_num.saturating_sub(1),
+                            false => line_num,
+                        };
+                        ranges.extend(fold(start, end, None));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // `!schemas` blocks and row tables, at the top level only
+        let mut block: Option<usize> = None;
+        let mut table: Option<(usize, usize)> = None;
+        let mut depth = 0usize;
+        for (line_num, line) in lines.iter().enumerate() {
+            let code = line.split('#').next().unwrap_or(line);
+            let trimmed = code.trim();
+            let top_level = depth == 0;
+            depth = Self::bracket_depth(depth, code);
+            if !top_level {
+                // A multi-line row extends the table
+                if let Some((_, last)) = &mut table {
+                    *last = line_num;
+                }
+                continue;
+            }
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(start) = block {
+                if trimmed == "---" {
+                    ranges.extend(fold(start, line_num, Some(FoldingRangeKind::Region)));
+                    block = None;
+                }
+                continue;
+            }
+            if trimmed.starts_with('!') || trimmed == "---" {
+                if let Some((start, last)) = table.take() {
+                    ranges.extend(fold(start, last, None));
+                }
+                let directive = trimmed.split_whitespace().next();
+                match directive {
+                    Some("!schemas" | "!models") => block = Some(line_num),
+                    Some("!def" | "!use") => table = Some((line_num, line_num)),
+                    _ => {}
+                }
+            } else if let Some((_, last)) = &mut table {
+                *last = line_num;
+            }
+        }
+        if let Some((start, last)) = table {
+            ranges.extend(fold(start, last, None));
+        }
+        if let Some(start) = block {
+            ranges.extend(fold(
+                start,
+                lines.len().saturating_sub(1),
+                Some(FoldingRangeKind::Region),
+            ));
+        }
+
+        ranges.sort_by_key(|r| (r.start_line, std::cmp::Reverse(r.end_line)));
+        ranges
+    }
+
+    /// Links for the file arguments of `!import`, `!read`, `!json` and `!run`,
+    /// resolved against the document's directory. Links to missing files
+    /// have no target.
+    fn document_links(uri: &Url, content: &str) -> Vec<DocumentLink> {
+        let base = uri
+            .to_file_path()
+            .ok()
+            .and_then(|p| p.parent().map(Path::to_path_buf));
+        let mut links = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            let Some(directive) = trimmed.split_whitespace().next() else {
+                continue;
+            };
+            let rest_start = line.len() - trimmed.len() + directive.len();
+            let args = Self::row_values(&line[rest_start..]);
+            let paths: Vec<(usize, &str)> = match directive {
+                "!import" | "!read" | "!json" | "!csv" => args.into_iter().take(1).collect(),
+                // Only the program and arguments that look like files
+                "!run" => args
+                    .into_iter()
+                    .filter(|(_, a)| *a != "{" && !a.starts_with('-'))
+                    .filter(|(_, a)| {
+                        let a = a.trim_matches('"');
+                        a.contains('/')
+                            || Path::new(a).extension().is_some_and(|e| {
+                                e.to_string_lossy().chars().all(char::is_alphanumeric)
+                            })
+                    })
+                    .collect(),
+                _ => continue,
+            };
+
+            for (offset, arg) in paths {
+                let quoted = arg.len() >= 2 && arg.starts_with('"') && arg.ends_with('"');
+                let (start, path) = match quoted {
+                    true => (rest_start + offset + 1, &arg[1..arg.len() - 1]),
+                    false => (rest_start + offset, arg),
+                };
+                if path.is_empty() {
+                    continue;
+                }
+                let resolved = match (Path::new(path).is_absolute(), &base) {
+                    (true, _) => Some(PathBuf::from(path)),
+                    (false, Some(base)) => Some(Self::normalize_path(&base.join(path))),
+                    (false, None) => None,
+                };
+                let target = resolved
+                    .filter(|p| p.exists())
+                    .and_then(|p| Url::from_file_path(p).ok());
+                let character = |byte: usize| line[..byte].chars().count() as u32;
+                links.push(DocumentLink {
+                    range: Range {
+                        start: Position::new(line_num as u32, character(start)),
+                        end: Position::new(line_num as u32, character(start + path.len())),
+                    },
+                    tooltip: target
+                        .is_none()
+                        .then(|| format!("File not found: {}", path)),
+                    target,
+                    data: None,
+                });
+            }
+        }
+
+        links
+    }
+
+    /// Resolve `.` and `..` components without touching the filesystem
+    fn normalize_path(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other),
+            }
+        }
+        normalized
+    }
+
+    /// Find lists of two or more flat inline objects with identical keys
+    fn find_inline_tables(content: &str) -> Vec<InlineTable> {
+        let mut lexer = Lexer::new(content);
+        let mut tokens = Vec::new();
+        while let Some(st) = lexer.next_token() {
+            tokens.push(st);
+        }
+        let position = |loc: tauq::tauq::token::Location| {
+            Position::new(loc.line as u32 - 1, loc.column as u32 - 1)
+        };
+        let is_gap = |t: &Token| matches!(t, Token::Newline | Token::Semi);
+
+        let mut tables = Vec::new();
+        for open in 0..tokens.len() {
+            if tokens[open].token != Token::LBracket {
+                continue;
+            }
+
+            // [ { key value ... } { ... } ]
+            let mut i = open + 1;
+            let mut objects: Vec<Vec<(String, String)>> = Vec::new();
+            let close = loop {
+                match tokens.get(i).map(|t| &t.token) {
+                    Some(t) if is_gap(t) => i += 1,
+                    Some(Token::RBracket) => break Some(i),
+                    Some(Token::LBrace) => {
+                        i += 1;
+                        let mut pairs = Vec::new();
+                        loop {
+                            match tokens.get(i).map(|t| &t.token) {
+                                Some(t) if is_gap(t) => i += 1,
+                                Some(Token::RBrace) => break,
+                                Some(Token::Ident(key) | Token::String(key)) => {
+                                    let value = tokens.get(i + 1).filter(|v| {
+                                        !matches!(
+                                            v.token,
+                                            Token::LBrace
+                                                | Token::LBracket
+                                                | Token::RBrace
+                                                | Token::RBracket
+                                                | Token::Newline
+                                                | Token::Semi
+                                                | Token::Colon
+                                                | Token::TripleDash
+                                                | Token::Directive(_)
+                                        )
+                                    });
+                                    let Some(value) = value else {
+                                        pairs.clear();
+                                        break;
+                                    };
+                                    let text = &content[value.start.offset..value.end.offset];
+                                    pairs.push((key.to_string(), text.to_string()));
+                                    i += 2;
+                                }
+                                _ => {
+                                    pairs.clear();
+                                    break;
+                                }
+                            }
+                        }
+                        if pairs.is_empty() {
+                            break None;
+                        }
+                        objects.push(pairs);
+                        i += 1;
+                    }
+                    _ => break None,
+                }
+            };
+            let Some(close) = close else {
+                continue;
+            };
+            if objects.len() < 2 {
+                continue;
+            }
+
+            let fields: Vec<String> = objects[0].iter().map(|(k, _)| k.clone()).collect();
+            let rows: Option<Vec<Vec<String>>> = objects
+                .iter()
+                .map(|pairs| {
+                    if pairs.len() != fields.len() {
+                        return None;
+                    }
+                    fields
+                        .iter()
+                        .map(|f| {
+                            let mut matches = pairs.iter().filter(|(k, _)| k == f);
+                            let (_, value) = matches.next()?;
+                            matches.next().is_none().then(|| value.clone())
+                        })
+                        .collect()
+                })
+                .collect();
+            let Some(rows) = rows else {
+                continue;
+            };
+
+            let key = open.checked_sub(1).and_then(|k| match &tokens[k].token {
+                Token::Ident(key) | Token::String(key) => Some(key.to_string()),
+                _ => None,
+            });
+            tables.push(InlineTable {
+                key,
+                range: Range {
+                    start: position(tokens[open].start),
+                    end: position(tokens[close].end),
+                },
+                fields,
+                rows,
+            });
+        }
+
+        tables
+    }
+
+    /// "Extract schema" refactoring for `table`: a `!def` at the top of the
+    /// document and the list rewritten as `!use` rows
+    fn extract_schema_action(uri: &Url, content: &str, table: &InlineTable) -> CodeAction {
+        let taken: Vec<String> = Self::document_symbols(content)
+            .into_iter()
+            .flat_map(|s| match s.kind {
+                SymbolKind::NAMESPACE => s.children.unwrap_or_default(),
+                _ => vec![s],
+            })
+            .filter(|s| s.kind == SymbolKind::CLASS)
+            .map(|s| s.name)
+            .collect();
+        let base = table
+            .key
+            .as_deref()
+            .map(|key| SingularisationConfig::default().schema_name(key))
+            .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_alphanumeric() || c == '_'))
+            .unwrap_or_else(|| "Row".to_string());
+        let name = std::iter::once(base.clone())
+            .chain((2..).map(|n| format!("{}{}", base, n)))
+            .find(|n| !taken.contains(n))
+            .unwrap_or(base);
+
+        // Content before the first `!def` would otherwise read as rows of
+        // the new schema
+        let first_line = content
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'));
+        let separator = if first_line.is_some_and(|l| l.starts_with("!def ")) {
+            ""
+        } else {
+            "---\n"
+        };
+        let def = format!("!def {} {}\n{}", name, table.fields.join(" "), separator);
+
+        let line = content.lines().nth(table.range.start.line as usize);
+        let indent: String = line
+            .unwrap_or("")
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let mut block = format!("[!use {}\n", name);
+        for row in &table.rows {
+            block.push_str(&format!("{}  {}\n", indent, row.join(" ")));
+        }
+        block.push_str(&indent);
+        block.push(']');
+
+        let edits = vec![
+            TextEdit {
+                range: Range {
+                    start: Position::new(0, 0),
+                    end: Position::new(0, 0),
+                },
+                new_text: def,
+            },
+            TextEdit {
+                range: table.range,
+                new_text: block,
+            },
+        ];
+        CodeAction {
+            title: format!("Extract schema '{}' from inline objects", name),
+            kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), edits)])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Outline of a document: `!def` schemas with their fields, `!schemas`
+    /// blocks, and top-level keys. Lines inside an active schema are data rows
+    /// and contribute nothing.
+    fn document_symbols(content: &str) -> Vec<DocumentSymbol> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut symbols = Vec::new();
+        // `!schemas` line and the schemas declared so far
+        let mut block: Option<(u32, Vec<DocumentSymbol>)> = None;
+        let mut in_rows = false;
+        let mut depth = 0usize;
+
+        for (line_num, line) in lines.iter().enumerate() {
+            let line_num = line_num as u32;
+            let code = line.split('#').next().unwrap_or(line);
+            let trimmed = code.trim();
+            let indent = code.len() - code.trim_start().len();
+            let top_level = depth == 0;
+            depth = Self::bracket_depth(depth, code);
+
+            if !top_level || trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some((start, schemas)) = &mut block {
+                if trimmed == "---" {
+                    symbols.push(Self::namespace_symbol(
+                        *start,
+                        line_num,
+                        line.len() as u32,
+                        std::mem::take(schemas),
+                    ));
+                    block = None;
+                } else if !trimmed.starts_with('!')
+                    && let Some(symbol) = Self::schema_symbol(line_num, code, indent)
+                {
+                    schemas.push(symbol);
+                }
+                continue;
+            }
+
+            let directive = trimmed.split_whitespace().next().unwrap_or("");
+            match directive {
+                "!schemas" | "!models" => block = Some((line_num, Vec::new())),
+                "!def" => {
+                    let rest = &code[indent + "!def".len()..];
+                    let name_start = code.len() - rest.trim_start().len();
+                    if let Some(mut symbol) = Self::schema_symbol(line_num, code, name_start) {
+                        symbol.range.start.character = indent as u32;
+                        symbols.push(symbol);
+                    }
+                    in_rows = true;
+                }
+                "!use" => in_rows = true,
+                "---" => in_rows = false,
+                _ if trimmed.starts_with('!') || in_rows => {}
+                key => {
+                    let name = key.trim_matches('"');
+                    let value = trimmed[key.len()..].trim();
+                    // A nested object or list extends the symbol to its close
+                    let end_line = if depth > 0 {
+                        (line_num as usize..lines.len())
+                            .scan(0usize, |d, i| {
+                                *d = Self::bracket_depth(*d, lines[i].split('#').next()?);
+                                Some((i, *d))
+                            })
+                            .skip(1)
+                            .find(|&(_, d)| d == 0)
+                            .map_or(lines.len() - 1, |(i, _)| i)
+                    } else {
+                        line_num as usize
+                    };
+                    #[allow(deprecated)]
+                    symbols.push(DocumentSymbol {
+                        name: name.to_string(),
+                        detail: (depth == 0 && !value.is_empty()).then(|| value.to_string()),
+                        kind: SymbolKind::VARIABLE,
+                        tags: None,
+                        deprecated: None,
+                        range: Range {
+                            start: Position::new(line_num, indent as u32),
+                            end: Position::new(end_line as u32, lines[end_line].len() as u32),
+                        },
+                        selection_range: Range {
+                            start: Position::new(line_num, indent as u32),
+                            end: Position::new(line_num, (indent + key.len()) as u32),
+                        },
+                        children: None,
+                    });
+                }
+            }
+        }
+
+        // Unterminated block runs to the end of the document
+        if let Some((start, schemas)) = block {
+            let last = lines.len().saturating_sub(1);
+            let end_char = lines.last().map_or(0, |l| l.len()) as u32;
+            symbols.push(Self::namespace_symbol(
+                start,
+                last as u32,
+                end_char,
+                schemas,
+            ));
+        }
+
+        symbols
+    }
+
+    /// Nesting depth after `code`, ignoring brackets inside strings
+    fn bracket_depth(mut depth: usize, code: &str) -> usize {
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in code.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '{' | '[' if !in_string => depth += 1,
+                '}' | ']' if !in_string => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        depth
+    }
+
+    /// Class symbol for `Name field...` starting at byte `name_start` of `code`,
+    /// with one child per declared field
+    fn schema_symbol(line_num: u32, code: &str, name_start: usize) -> Option<DocumentSymbol> {
+        let mut words = Self::def_words(&code[name_start..])
+            .map(|w| (w.as_ptr() as usize - code.as_ptr() as usize, w));
+        let (_, name) = words.next()?;
+        let mut words = words.peekable();
+        // Inherited fields belong to the parent's symbol
+        if words.peek().is_some_and(|(_, w)| *w == "!extends") {
+            words.next();
+            words.next();
+        }
+
+        let children = words
+            .map(|(start, word)| {
+                let field = word.split([':', '=']).next().unwrap_or(word);
+                let field = field.strip_suffix('?').unwrap_or(field);
+                let range = Range {
+                    start: Position::new(line_num, start as u32),
+                    end: Position::new(line_num, (start + word.len()) as u32),
+                };
+                #[allow(deprecated)]
+                DocumentSymbol {
+                    name: field.to_string(),
+                    detail: word.split_once(':').map(|(_, t)| t.to_string()),
+                    kind: SymbolKind::FIELD,
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range: Range {
+                        start: range.start,
+                        end: Position::new(line_num, (start + field.len()) as u32),
+                    },
+                    children: None,
+                }
+            })
+            .collect();
+
+        #[allow(deprecated)]
+        Some(DocumentSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind: SymbolKind::CLASS,
+            tags: None,
+            deprecated: None,
+            range: Range {
+                start: Position::new(line_num, name_start as u32),
+                end: Position::new(line_num, code.trim_end().len() as u32),
+            },
+            selection_range: Range {
+                start: Position::new(line_num, name_start as u32),
+                end: Position::new(line_num, (name_start + name.len()) as u32),
+            },
+            children: Some(children),
+        })
+    }
+
+    fn namespace_symbol(
+        start: u32,
+        end: u32,
+        end_char: u32,
+        schemas: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        #[allow(deprecated)]
+        DocumentSymbol {
+            name: "!schemas".to_string(),
+            detail: Some(format!("{} schemas", schemas.len())),
+            kind: SymbolKind::NAMESPACE,
+            tags: None,
+            deprecated: None,
+            range: Range {
+                start: Position::new(start, 0),
+                end: Position::new(end, end_char),
+            },
+            selection_range: Range {
+                start: Position::new(start, 0),
+                end: Position::new(start, "!schemas".len() as u32),
+            },
+            children: Some(schemas),
+        }
+    }
+
+    /// Semantic tokens for the whole document, from the lexer's token
+    /// stream. Comments are not tokens, so they are found in the text
+    /// between tokens. Legend indices: 0 keyword, 1 type, 3 string,
+    /// 4 number, 5 comment.
+    fn semantic_tokens(content: &str) -> Vec<SemanticToken> {
+        let content = content.strip_prefix('\u{FEFF}').unwrap_or(content);
+        // (line, start, length, type), 0-based, in document order
+        let mut spans: Vec<(u32, u32, u32, u32)> = Vec::new();
+        let mut push_comments = |gap: &str, mut line: usize, mut column: usize| {
+            let mut chars = gap.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\n' => {
+                        line += 1;
+                        column = 1;
+                    }
+                    '#' => {
+                        let length = 1 + chars.by_ref().take_while(|&c| c != '\n').count();
+                        spans.push(((line - 1) as u32, (column - 1) as u32, length as u32, 5));
+                        line += 1;
+                        column = 1;
+                    }
+                    _ => column += 1,
+                }
+            }
+        };
+
+        let mut previous: Option<Token> = None;
+        let mut gap_start = (0, 1, 1); // byte offset, line, column
+        let mut token_spans = Vec::new();
+        for st in Lexer::new(content).tokens() {
+            push_comments(
+                &content[gap_start.0..st.start.offset],
+                gap_start.1,
+                gap_start.2,
+            );
+            gap_start = (st.end.offset, st.end.line, st.end.column);
+
+            let token_type = match &st.token {
+                Token::Directive(_) | Token::Bool(_) | Token::Null => Some(0),
+                // Schema name after `!def`, `!use`, `!enum`...
+                Token::Ident(_) if matches!(previous, Some(Token::Directive(_))) => Some(1),
+                Token::String(_) => Some(3),
+                Token::Integer(_) | Token::UnsignedInteger(_) | Token::Float(_) => Some(4),
+                _ => None,
+            };
+            // Multi-line strings and heredocs are left unhighlighted
+            if let Some(token_type) = token_type
+                && st.start.line == st.end.line
+            {
+                token_spans.push((
+                    (st.start.line - 1) as u32,
+                    (st.start.column - 1) as u32,
+                    (st.end.column - st.start.column) as u32,
+                    token_type,
+                ));
+            }
+            previous = Some(st.token);
+        }
+        push_comments(&content[gap_start.0..], gap_start.1, gap_start.2);
+
+        // Comments only come from gaps, so merge the two ordered lists
+        spans.extend(token_spans);
+        spans.sort_unstable();
+
+        let mut tokens = Vec::with_capacity(spans.len());
+        let (mut prev_line, mut prev_char) = (0, 0);
+        for (line, start, length, token_type) in spans {
+            let delta_line = line - prev_line;
+            let delta_start = if delta_line == 0 {
+                start - prev_char
+            } else {
+                start
+            };
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset: 0,
+            });
+            prev_line = line;
+            prev_char = start;
+        }
+        tokens
+    }
+
+    /// Byte offset of an LSP position, whose column counts UTF-16 code
+    /// units; positions past the end of a line or the document clamp to it
+    fn offset_at(content: &str, position: Position) -> usize {
+        let mut offset = 0;
+        for _ in 0..position.line {
+            match content[offset..].find('\n') {
+                Some(i) => offset += i + 1,
+                None => return content.len(),
+            }
+        }
+        let line = &content[offset..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        let mut units = 0;
+        for (i, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return offset + i;
+            }
+            units += c.len_utf16();
+        }
+        offset + line.len()
+    }
+
+    /// Extract `!enum Name variant...` declarations from document content
+    fn extract_enums(content: &str) -> Vec<EnumInfo> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let rest = line.trim().strip_prefix("!enum ")?;
+                let rest = rest.split('#').next().unwrap_or(rest);
+                let mut parts = rest.split_whitespace();
+                let name = parts.next()?.to_string();
+                let variants = parts.map(|v| v.trim_matches('"').to_string()).collect();
+                Some(EnumInfo { name, variants })
+            })
+            .collect()
+    }
+
+    /// Name of the schema active at `line_idx`, found by replaying `!def`,
+    /// `!use` and `---`
+    fn active_schema_name<'a>(lines: &[&'a str], line_idx: usize) -> Option<&'a str> {
+        let mut active: Option<&str> = None;
+        for line in lines.iter().take(line_idx) {
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed
+                .strip_prefix("!def ")
+                .or_else(|| trimmed.strip_prefix("!use "))
+            {
+                active = rest.split_whitespace().next();
+            } else if trimmed.starts_with("---") {
+                active = None;
+            }
+        }
+        active
+    }
+
+    /// Find the schema active at `line_idx` among `schemas`
+    fn active_schema_at<'a>(
+        lines: &[&str],
+        line_idx: usize,
+        schemas: &'a [SchemaInfo],
+    ) -> Option<&'a SchemaInfo> {
+        let name = Self::active_schema_name(lines, line_idx)?;
+        schemas.iter().rev().find(|s| s.name == name)
+    }
+
+    /// Hover for the row value under `character` on `line`: the field it
+    /// fills, its type, and a link to the schema's definition in `def_uri`
+    fn field_hover(
+        line: &str,
+        line_num: u32,
+        character: u32,
+        schema: &SchemaInfo,
+        def_uri: &Url,
+    ) -> Option<Hover> {
+        let is_named = |value: &str| {
+            value
+                .split_once(':')
+                .is_some_and(|(key, _)| schema.fields.iter().any(|f| f == key))
+        };
+        let values = Self::row_values(line);
+        // Nested values span several row values; leave them to the parser
+        if values.iter().any(|(_, v)| v.starts_with(['{', '['])) {
+            return None;
+        }
+        let mut column = 0;
+        let mut found = None;
+        for (start, value) in values {
+            let first = line[..start].chars().count() as u32;
+            let last = first + value.chars().count() as u32;
+            if (first..=last).contains(&character) {
+                found = Some((first, last, value));
+                break;
+            }
+            if !is_named(value) {
+                column += 1;
+            }
+        }
+        let (first, last, value) = found?;
+        let index = match value.split_once(':') {
+            Some((key, _)) if is_named(value) => schema.fields.iter().position(|f| f == key)?,
+            _ => column,
+        };
+        let field = schema.fields.get(index)?;
+
+        let declared: Vec<String> = schema
+            .fields
+            .iter()
+            .zip(&schema.types)
+            .map(|(field, ty)| match ty {
+                Some(ty) => format!("{}:{}", field, ty),
+                None => field.clone(),
+            })
+            .collect();
+        let mut value = format!(
+            "**{}.{}** (field {} of {})",
+            schema.name,
+            field,
+            index + 1,
+            schema.fields.len()
+        );
+        if let Some(Some(ty)) = schema.types.get(index) {
+            value.push_str(&format!("\n\nType: {}", ty));
+        }
+        value.push_str(&format!(
+            "\n\nDefined in [`!def {} {}`]({}#L{})",
+            schema.name,
+            declared.join(" "),
+            def_uri,
+            schema.line + 1
+        ));
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(Range::new(
+                Position::new(line_num, first),
+                Position::new(line_num, last),
+            )),
+        })
+    }
+
+    /// Field-name hints before each value of the data rows in `lines`.
+    /// Rows using `field:value` arguments or nested values are left alone.
+    fn row_inlay_hints(content: &str, schemas: &[SchemaInfo], lines: Range) -> Vec<InlayHint> {
+        let mut hints = Vec::new();
+        let mut active: Option<&SchemaInfo> = None;
+        let mut depth = 0usize;
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line_num = line_num as u32;
+            if line_num > lines.end.line {
+                break;
+            }
+            let top_level = depth == 0;
+            depth = Self::bracket_depth(depth, line.split('#').next().unwrap_or(line));
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed
+                .strip_prefix("!def ")
+                .or_else(|| trimmed.strip_prefix("!use "))
+            {
+                let name = rest.split_whitespace().next();
+                active = schemas.iter().rev().find(|s| Some(s.name.as_str()) == name);
+                continue;
+            } else if trimmed.starts_with("---") {
+                active = None;
+                continue;
+            }
+            let Some(schema) = active else {
+                continue;
+            };
+            if !top_level || line_num < lines.start.line || trimmed.starts_with('!') {
+                continue;
+            }
+
+            let values = Self::row_values(line);
+            let named = values.iter().any(|(_, v)| {
+                v.split_once(':')
+                    .is_some_and(|(k, _)| schema.fields.iter().any(|f| f == k))
+            });
+            let nested = values.iter().any(|(_, v)| v.starts_with(['{', '[']));
+            if named || nested {
+                continue;
+            }
+            for ((start, _), field) in values.iter().zip(&schema.fields) {
+                hints.push(InlayHint {
+                    position: Position::new(line_num, line[..*start].chars().count() as u32),
+                    label: InlayHintLabel::String(field.clone()),
+                    kind: Some(InlayHintKind::PARAMETER),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: None,
+                    padding_right: Some(true),
+                    data: None,
+                });
+            }
+        }
+
+        hints
+    }
+
+    /// Values of a row line with their byte offsets, split on whitespace or
+    /// commas, keeping quoted strings together and stopping at a comment
+    fn row_values(line: &str) -> Vec<(usize, &str)> {
+        let mut values = Vec::new();
+        let mut start = None;
+        let mut in_quotes = false;
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_quotes => escaped = true,
+                '"' => {
+                    in_quotes = !in_quotes;
+                    start.get_or_insert(i);
+                }
+                '#' if !in_quotes && start.is_none() => break,
+                c if (c.is_whitespace() || c == ',') && !in_quotes => {
+                    if let Some(s) = start.take() {
+                        values.push((s, &line[s..i]));
+                    }
+                }
+                _ => {
+                    start.get_or_insert(i);
+                }
+            }
+        }
+        if let Some(s) = start {
+            values.push((s, &line[s..]));
+        }
+        values
+    }
 
-        // Parse and collect errors
+    /// Split a row prefix into values on whitespace or commas, keeping quoted
+    /// strings together.
+    /// Returns the completed values and the partial value under the cursor.
+    fn split_row_prefix(prefix: &str) -> (Vec<&str>, &str) {
+        let mut values = Vec::new();
+        let mut start = None;
+        let mut in_quotes = false;
+        for (i, c) in prefix.char_indices() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    start.get_or_insert(i);
+                }
+                c if (c.is_whitespace() || c == ',') && !in_quotes => {
+                    if let Some(s) = start.take() {
+                        values.push(&prefix[s..i]);
+                    }
+                }
+                _ => {
+                    start.get_or_insert(i);
+                }
+            }
+        }
+        let partial = start.map(|s| &prefix[s..]).unwrap_or("");
+        (values, partial)
+    }
+
+    /// Index of the field being typed at the end of a row prefix: the named
+    /// argument (`status:ac`) or the positional column
+    fn field_index_at(schema: &SchemaInfo, prefix: &str) -> Option<usize> {
+        let (values, partial) = Self::split_row_prefix(prefix);
+        match partial.split_once(':') {
+            Some((key, _)) => schema.fields.iter().position(|f| f == key),
+            None => Some(values.iter().filter(|v| !v.contains(':')).count()),
+        }
+    }
+
+    /// `User(id:int, name, role)` for the schema of the row being typed, with
+    /// the field under the cursor active
+    fn row_signature(
+        lines: &[&str],
+        line_idx: usize,
+        prefix: &str,
+        schemas: &[SchemaInfo],
+    ) -> Option<SignatureHelp> {
+        let trimmed = prefix.trim_start();
+        if trimmed.starts_with('!') || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            return None;
+        }
+        let schema = Self::active_schema_at(lines, line_idx, schemas)?;
+        let active = Self::field_index_at(schema, prefix).filter(|&i| i < schema.fields.len())?;
+
+        let mut label = format!("{}(", schema.name);
+        let mut parameters = Vec::new();
+        for (i, (field, ty)) in schema.fields.iter().zip(&schema.types).enumerate() {
+            if i > 0 {
+                label.push_str(", ");
+            }
+            let start = label.chars().count() as u32;
+            label.push_str(field);
+            if let Some(ty) = ty {
+                label.push(':');
+                label.push_str(ty);
+            }
+            parameters.push(ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start, label.chars().count() as u32]),
+                documentation: None,
+            });
+        }
+        label.push(')');
+
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label,
+                documentation: None,
+                parameters: Some(parameters),
+                active_parameter: Some(active as u32),
+            }],
+            active_signature: Some(0),
+            active_parameter: Some(active as u32),
+        })
+    }
+
+    /// Completions for a value in a data row, based on the field's type annotation
+    fn field_value_completions(
+        lines: &[&str],
+        line_idx: usize,
+        prefix: &str,
+        schemas: &[SchemaInfo],
+        enums: &[EnumInfo],
+    ) -> Vec<CompletionItem> {
+        let trimmed = prefix.trim_start();
+        if trimmed.starts_with('!') || trimmed.starts_with('#') {
+            return Vec::new();
+        }
+        let Some(schema) = Self::active_schema_at(lines, line_idx, schemas) else {
+            return Vec::new();
+        };
+
+        let Some(idx) = Self::field_index_at(schema, prefix) else {
+            return Vec::new();
+        };
+        let (Some(field), Some(Some(type_name))) = (schema.fields.get(idx), schema.types.get(idx))
+        else {
+            return Vec::new();
+        };
+
+        if let Some(e) = enums.iter().find(|e| e.name == *type_name) {
+            return e
+                .variants
+                .iter()
+                .map(|v| CompletionItem {
+                    label: v.clone(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    detail: Some(format!("{} variant for field '{}'", e.name, field)),
+                    ..Default::default()
+                })
+                .collect();
+        }
+
+        let snippet = |label: &str, placeholder: &str| CompletionItem {
+            label: label.to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!("{} value for field '{}'", label, field)),
+            insert_text: Some(format!("${{1:{}}}", placeholder)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        };
+
+        match type_name.as_str() {
+            "bool" => ["true", "false"]
+                .iter()
+                .map(|b| CompletionItem {
+                    label: b.to_string(),
+                    kind: Some(CompletionItemKind::CONSTANT),
+                    detail: Some(format!("Boolean for field '{}'", field)),
+                    ..Default::default()
+                })
+                .collect(),
+            "int" => vec![snippet("int", "0")],
+            "float" => vec![snippet("float", "0.0")],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Generate diagnostics for a document, parsing it from scratch
+    #[cfg(test)]
+    async fn generate_diagnostics(&self, uri: &Url, content: &str) -> Vec<Diagnostic> {
         let mut parser = Parser::new(content);
-        if let Err(e) = parser.parse() {
+        let documents = parser.parse_documents();
+        self.diagnostics_for(uri, content, documents, parser.take_warnings())
+            .await
+    }
+
+    /// Diagnostics for `content`, given its parsed documents and warnings
+    async fn diagnostics_for(
+        &self,
+        uri: &Url,
+        content: &str,
+        documents: Vec<std::result::Result<serde_json::Value, tauq::error::ParseError>>,
+        warnings: Vec<tauq::ParseWarning>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        // Collect errors from each `---`- or `===`-separated document;
+        // documents that parse are linted as well
+        let (errors, lint) = {
+            let mut values = Vec::new();
+            let mut errors = Vec::new();
+            for doc in documents {
+                match doc {
+                    Ok(value) => values.push(value),
+                    Err(e) => errors.push(e),
+                }
+            }
+            let lint = if errors.is_empty() {
+                let value = if values.len() == 1 {
+                    values.remove(0)
+                } else {
+                    serde_json::Value::Array(values)
+                };
+                Linter::new().lint(&value, warnings, content)
+            } else {
+                Vec::new()
+            };
+            (errors, lint)
+        };
+
+        for e in errors {
             let diagnostic = Diagnostic {
                 range: Range {
                     start: Position {
@@ -86,8 +1391,8 @@ impl Backend {
                         character: (e.span.column.saturating_sub(1)) as u32,
                     },
                     end: Position {
-                        line: (e.span.line.saturating_sub(1)) as u32,
-                        character: (e.span.column) as u32,
+                        line: (e.span.end_line.saturating_sub(1)) as u32,
+                        character: (e.span.end_column.saturating_sub(1)) as u32,
                     },
                 },
                 severity: Some(DiagnosticSeverity::ERROR),
@@ -105,12 +1410,16 @@ impl Backend {
         // Check for undefined schema references
         let schemas = Self::extract_schemas(content);
         let schema_names: Vec<&str> = schemas.iter().map(|s| s.name.as_str()).collect();
+        let workspace = self.workspace.read().await;
 
         for (line_num, line) in content.lines().enumerate() {
             let trimmed = line.trim();
             if let Some(schema_ref) = trimmed.strip_prefix("!use ") {
                 let schema_ref = schema_ref.trim();
-                if !schema_names.contains(&schema_ref) && !schema_ref.is_empty() {
+                if !schema_names.contains(&schema_ref)
+                    && !schema_ref.is_empty()
+                    && workspace.find(schema_ref, uri).is_none()
+                {
                     diagnostics.push(Diagnostic {
                         range: Range {
                             start: Position {
@@ -126,7 +1435,10 @@ impl Backend {
                         code: None,
                         code_description: None,
                         source: Some("tauq-lsp".to_string()),
-                        message: format!("Schema '{}' is not defined in this file", schema_ref),
+                        message: format!(
+                            "Schema '{}' is not defined in this file or workspace",
+                            schema_ref
+                        ),
                         related_information: None,
                         tags: None,
                         data: None,
@@ -135,19 +1447,56 @@ impl Backend {
             }
         }
 
+        // Lint findings rank below errors and unresolved schemas
+        for d in lint {
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: Position {
+                        line: (d.span.line.saturating_sub(1)) as u32,
+                        character: (d.span.column.saturating_sub(1)) as u32,
+                    },
+                    end: Position {
+                        line: (d.span.end_line.saturating_sub(1)) as u32,
+                        character: (d.span.end_column.saturating_sub(1)) as u32,
+                    },
+                },
+                severity: Some(match d.severity {
+                    LintSeverity::Warning => DiagnosticSeverity::INFORMATION,
+                    LintSeverity::Info => DiagnosticSeverity::HINT,
+                }),
+                code: Some(NumberOrString::String(d.rule.to_string())),
+                code_description: None,
+                source: Some("tauq-lint".to_string()),
+                message: d.message,
+                related_information: None,
+                tags: None,
+                data: None,
+            });
+        }
+
         diagnostics
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        #[allow(deprecated)]
+        let roots = match params.workspace_folders {
+            Some(folders) => folders.into_iter().map(|f| f.uri).collect(),
+            None => params.root_uri.into_iter().collect::<Vec<_>>(),
+        };
+        *self.roots.write().await = roots
+            .iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect();
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                         will_save: None,
                         will_save_wait_until: None,
                         save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
@@ -162,6 +1511,28 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec![" ".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                document_link_provider: Some(DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::REFACTOR_EXTRACT]),
+                        ..Default::default()
+                    },
+                )),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
                 document_formatting_provider: Some(OneOf::Left(true)),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(
@@ -196,8 +1567,22 @@ impl LanguageServer for Backend {
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        let mut files = Vec::new();
+        for root in self.roots.read().await.iter() {
+            Self::find_tqn_files(root, &mut files);
+        }
+        for file in &files {
+            self.index_file(file).await;
+        }
+
         self.client
-            .log_message(MessageType::INFO, "Tauq Language Server initialized!")
+            .log_message(
+                MessageType::INFO,
+                format!(
+                    "Tauq Language Server initialized! Indexed {} workspace files",
+                    files.len()
+                ),
+            )
             .await;
     }
 
@@ -211,7 +1596,14 @@ impl LanguageServer for Backend {
         let version = params.text_document.version;
 
         let schemas = Self::extract_schemas(&content);
-
+        let enums = Self::extract_enums(&content);
+        self.workspace
+            .write()
+            .await
+            .update(uri.clone(), schemas.clone());
+
+        let parser = IncrementalParser::new(content.as_str());
+        let (documents, warnings) = (parser.documents(), parser.warnings());
         {
             let mut docs = self.documents.write().await;
             docs.insert(
@@ -220,11 +1612,15 @@ impl LanguageServer for Backend {
                     content: content.clone(),
                     version,
                     schemas,
+                    enums,
+                    parser,
                 },
             );
         }
 
-        let diagnostics = self.generate_diagnostics(&uri, &content).await;
+        let diagnostics = self
+            .diagnostics_for(&uri, &content, documents, warnings)
+            .await;
         self.client
             .publish_diagnostics(uri, diagnostics, Some(version))
             .await;
@@ -233,33 +1629,85 @@ impl LanguageServer for Backend {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri.clone();
         let version = params.text_document.version;
+        if params.content_changes.is_empty() {
+            return;
+        }
 
-        if let Some(change) = params.content_changes.first() {
-            let content = change.text.clone();
+        let (content, schemas, documents, warnings) = {
+            let mut docs = self.documents.write().await;
+            let (mut content, mut parser) = match docs.remove(&uri) {
+                Some(doc) => (doc.content, doc.parser),
+                None => (String::new(), IncrementalParser::new("")),
+            };
+            // Ranged changes re-parse only the lines they touch
+            for change in params.content_changes {
+                match change.range {
+                    Some(range) => {
+                        let start = Self::offset_at(&content, range.start);
+                        let end = Self::offset_at(&content, range.end).max(start);
+                        content.replace_range(start..end, &change.text);
+                        parser.edit(start, end, &change.text);
+                    }
+                    None => {
+                        parser = IncrementalParser::new(change.text.as_str());
+                        content = change.text;
+                    }
+                }
+            }
+            parser.reparse();
+
+            let (documents, warnings) = (parser.documents(), parser.warnings());
             let schemas = Self::extract_schemas(&content);
+            docs.insert(
+                uri.clone(),
+                Document {
+                    content: content.clone(),
+                    version,
+                    schemas: schemas.clone(),
+                    enums: Self::extract_enums(&content),
+                    parser,
+                },
+            );
+            (content, schemas, documents, warnings)
+        };
 
-            {
-                let mut docs = self.documents.write().await;
-                docs.insert(
-                    uri.clone(),
-                    Document {
-                        content: content.clone(),
-                        version,
-                        schemas,
-                    },
-                );
-            }
+        self.workspace.write().await.update(uri.clone(), schemas);
 
-            let diagnostics = self.generate_diagnostics(&uri, &content).await;
-            self.client
-                .publish_diagnostics(uri, diagnostics, Some(version))
-                .await;
-        }
+        let diagnostics = self
+            .diagnostics_for(&uri, &content, documents, warnings)
+            .await;
+        self.client
+            .publish_diagnostics(uri, diagnostics, Some(version))
+            .await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let mut docs = self.documents.write().await;
-        docs.remove(&params.text_document.uri);
+        let uri = params.text_document.uri;
+        self.documents.write().await.remove(&uri);
+
+        // Fall back to the saved file, which may differ from the closed buffer
+        match uri.to_file_path() {
+            Ok(path) if path.extension().is_some_and(|e| e == "tqn") => {
+                self.index_file(&path).await
+            }
+            _ => self.workspace.write().await.remove(&uri),
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            if path.extension().is_none_or(|e| e != "tqn") {
+                continue;
+            }
+            if change.typ == FileChangeType::DELETED {
+                self.workspace.write().await.remove(&change.uri);
+            } else {
+                self.index_file(&path).await;
+            }
+        }
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -293,7 +1741,10 @@ impl LanguageServer for Backend {
             }));
         }
 
-        if trimmed.starts_with("!use") {
+        // Past the directive, the schema name is described below
+        let on_use_target = trimmed.starts_with("!use ")
+            && position.character as usize > line.find("!use").unwrap_or(0) + "!use".len();
+        if trimmed.starts_with("!use") && !on_use_target {
             return Ok(Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -323,6 +1774,69 @@ impl LanguageServer for Backend {
             }));
         }
 
+        if trimmed.starts_with("!require") {
+            return Ok(Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: "**!require** - Pin the Tauq format version\n\n```tqn\n!require tauq >= 0.2\n```\n\nParsing fails with an upgrade message if the running version does not satisfy the constraint.".to_string(),
+                }),
+                range: None,
+            }));
+        }
+
+        if trimmed.starts_with("!enum") {
+            return Ok(Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: "**!enum** - Declare an enum type\n\n```tqn\n!enum Status active inactive\n!def User id name status:Status\n```\n\nFields annotated with the enum type get its variants as completions.".to_string(),
+                }),
+                range: None,
+            }));
+        }
+
+        // Hovering over an array key shows the schema name the formatter derives
+        if let Some(key) = trimmed.strip_suffix('[').map(str::trim)
+            && !key.is_empty()
+            && !key.contains(char::is_whitespace)
+        {
+            let derived = SingularisationConfig::default().schema_name(key);
+            return Ok(Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!(
+                        "**{}** - list\n\nRows of this list are named `{}` when formatted with `tauq format`.",
+                        key, derived
+                    ),
+                }),
+                range: None,
+            }));
+        }
+
+        // Over a value of a top-level schema row, describe its field
+        let depth = lines[..line_idx].iter().fold(0, |depth, line| {
+            Self::bracket_depth(depth, line.split('#').next().unwrap_or(line))
+        });
+        if depth == 0
+            && !trimmed.starts_with(['!', '#'])
+            && !trimmed.starts_with("---")
+            && let Some(name) = Self::active_schema_name(&lines, line_idx)
+        {
+            let workspace = self.workspace.read().await;
+            let definition = doc
+                .schemas
+                .iter()
+                .rev()
+                .find(|s| s.name == name)
+                .map(|s| (uri, s))
+                .or_else(|| workspace.find(name, uri));
+            if let Some((def_uri, schema)) = definition
+                && let Some(hover) =
+                    Self::field_hover(line, position.line, position.character, schema, def_uri)
+            {
+                return Ok(Some(hover));
+            }
+        }
+
         // Check if hovering over a schema name
         for schema in &doc.schemas {
             if trimmed.contains(&schema.name) {
@@ -342,6 +1856,26 @@ impl LanguageServer for Backend {
             }
         }
 
+        // Schemas shared from other workspace files
+        let workspace = self.workspace.read().await;
+        for (file, schema) in workspace.others(uri) {
+            if trimmed.contains(&schema.name) {
+                return Ok(Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: format!(
+                            "**Schema: {}**\n\nFields: `{}`\n\nDefined in `{}` at line {}",
+                            schema.name,
+                            schema.fields.join(", "),
+                            file.path().rsplit('/').next().unwrap_or(file.path()),
+                            schema.line + 1
+                        ),
+                    }),
+                    range: None,
+                }));
+            }
+        }
+
         Ok(None)
     }
 
@@ -363,6 +1897,15 @@ impl LanguageServer for Backend {
             current_line
         };
 
+        // Typed field values in data rows take precedence over generic constants
+        if let Some(doc) = doc {
+            let items =
+                Self::field_value_completions(&lines, line_idx, prefix, &doc.schemas, &doc.enums);
+            if !items.is_empty() {
+                return Ok(Some(CompletionResponse::Array(items)));
+            }
+        }
+
         let mut items = Vec::new();
 
         // Complete directives after !
@@ -375,6 +1918,13 @@ impl LanguageServer for Backend {
                     insert_text: Some("def ".to_string()),
                     ..Default::default()
                 },
+                CompletionItem {
+                    label: "!compose".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Define a schema joining the fields of others".to_string()),
+                    insert_text: Some("compose ".to_string()),
+                    ..Default::default()
+                },
                 CompletionItem {
                     label: "!use".to_string(),
                     kind: Some(CompletionItemKind::KEYWORD),
@@ -383,17 +1933,46 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 },
                 CompletionItem {
-                    label: "!schemas".to_string(),
+                    label: "!schemas".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Begin schema definition block".to_string()),
+                    insert_text: Some("schemas\n".to_string()),
+                    ..Default::default()
+                },
+                CompletionItem {
+                    label: "!import".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Import another file".to_string()),
+                    insert_text: Some("import \"".to_string()),
+                    ..Default::default()
+                },
+                CompletionItem {
+                    label: "!require".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Require a Tauq version".to_string()),
+                    documentation: Some(Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: "Fail with an upgrade message when the running Tauq version does not satisfy the semver constraint.\n\n```tqn\n!require tauq >= 0.2\n```".to_string(),
+                    })),
+                    insert_text: Some(format!(
+                        "require tauq >= {}.{}",
+                        env!("CARGO_PKG_VERSION_MAJOR"),
+                        env!("CARGO_PKG_VERSION_MINOR")
+                    )),
+                    ..Default::default()
+                },
+                CompletionItem {
+                    label: "!enum".to_string(),
                     kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Begin schema definition block".to_string()),
-                    insert_text: Some("schemas\n".to_string()),
+                    detail: Some("Declare an enum type for field annotations".to_string()),
+                    insert_text: Some("enum ".to_string()),
                     ..Default::default()
                 },
                 CompletionItem {
-                    label: "!import".to_string(),
+                    label: "!once".to_string(),
                     kind: Some(CompletionItemKind::KEYWORD),
-                    detail: Some("Import another file".to_string()),
-                    insert_text: Some("import \"".to_string()),
+                    detail: Some("Emit block only on first encounter (TQQ)".to_string()),
+                    insert_text: Some("once {\n\n}".to_string()),
                     ..Default::default()
                 },
                 CompletionItem {
@@ -403,6 +1982,20 @@ impl LanguageServer for Backend {
                     insert_text: Some("set ".to_string()),
                     ..Default::default()
                 },
+                CompletionItem {
+                    label: "!if".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Conditional block, closed by !end (TQQ)".to_string()),
+                    insert_text: Some("if ".to_string()),
+                    ..Default::default()
+                },
+                CompletionItem {
+                    label: "!for".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Repeat a block per value, closed by !end (TQQ)".to_string()),
+                    insert_text: Some("for ".to_string()),
+                    ..Default::default()
+                },
                 CompletionItem {
                     label: "!emit".to_string(),
                     kind: Some(CompletionItemKind::KEYWORD),
@@ -438,11 +2031,18 @@ impl LanguageServer for Backend {
                     insert_text: Some("read \"".to_string()),
                     ..Default::default()
                 },
+                CompletionItem {
+                    label: "!csv".to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("Import CSV file as schema rows (TQQ)".to_string()),
+                    insert_text: Some("csv \"".to_string()),
+                    ..Default::default()
+                },
             ]);
         }
 
         // Complete schema names after !use
-        if prefix.trim().starts_with("!use ")
+        if prefix.trim_start().starts_with("!use ")
             && let Some(doc) = doc
         {
             for schema in &doc.schemas {
@@ -453,6 +2053,22 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 });
             }
+            let workspace = self.workspace.read().await;
+            for (file, schema) in workspace.others(uri) {
+                if doc.schemas.iter().any(|s| s.name == schema.name) {
+                    continue;
+                }
+                items.push(CompletionItem {
+                    label: schema.name.clone(),
+                    kind: Some(CompletionItemKind::CLASS),
+                    detail: Some(format!(
+                        "Schema with {} fields from {}",
+                        schema.fields.len(),
+                        file.path().rsplit('/').next().unwrap_or(file.path())
+                    )),
+                    ..Default::default()
+                });
+            }
         }
 
         // Complete constants
@@ -529,6 +2145,180 @@ impl LanguageServer for Backend {
         Ok(None)
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+
+        let docs = self.documents.read().await;
+        let doc = match docs.get(uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        let range = params.range;
+        let actions: Vec<CodeActionOrCommand> = Self::find_inline_tables(&doc.content)
+            .iter()
+            .filter(|t| t.range.start <= range.end && range.start <= t.range.end)
+            .map(|t| {
+                CodeActionOrCommand::CodeAction(Self::extract_schema_action(uri, &doc.content, t))
+            })
+            .collect();
+
+        Ok((!actions.is_empty()).then_some(actions))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let docs = self.documents.read().await;
+        let doc = match docs.get(&params.text_document.uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        let plural = |n: usize, word: &str| match n {
+            1 => format!("1 {}", word),
+            n => format!("{} {}s", n, word),
+        };
+        let counts = Self::row_counts(&doc.content);
+        let lenses = doc
+            .schemas
+            .iter()
+            .filter_map(|schema| {
+                let rows = *counts.get(&schema.line)?;
+                let position = Position::new(schema.line, schema.character);
+                Some(CodeLens {
+                    range: Range::new(position, position),
+                    command: Some(Command {
+                        title: format!(
+                            "{} • {}",
+                            plural(rows, "row"),
+                            plural(schema.fields.len(), "field")
+                        ),
+                        command: String::new(),
+                        arguments: None,
+                    }),
+                    data: None,
+                })
+            })
+            .collect();
+
+        Ok(Some(lenses))
+    }
+
+    async fn code_lens_resolve(&self, lens: CodeLens) -> Result<CodeLens> {
+        // Lenses are complete when first returned
+        Ok(lens)
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let docs = self.documents.read().await;
+        let doc = match docs.get(&params.text_document.uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self::row_inlay_hints(
+            &doc.content,
+            &doc.schemas,
+            params.range,
+        )))
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let docs = self.documents.read().await;
+        let doc = match docs.get(uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        let lines: Vec<&str> = doc.content.lines().collect();
+        let line_idx = position.line as usize;
+        let line = lines.get(line_idx).copied().unwrap_or("");
+        let prefix: String = line.chars().take(position.character as usize).collect();
+
+        Ok(Self::row_signature(&lines, line_idx, &prefix, &doc.schemas))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let docs = self.documents.read().await;
+        let doc = match docs.get(&params.text_document.uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self::folding_ranges(&doc.content)))
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        let uri = &params.text_document.uri;
+
+        let docs = self.documents.read().await;
+        let doc = match docs.get(uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self::document_links(uri, &doc.content)))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let docs = self.documents.read().await;
+        let doc = match docs.get(&params.text_document.uri) {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        Ok(Some(DocumentSymbolResponse::Nested(
+            Self::document_symbols(&doc.content),
+        )))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let query = params.query.to_lowercase();
+        let docs = self.documents.read().await;
+
+        // Schemas from every open document, including those in `!schemas` blocks
+        let mut results = Vec::new();
+        for (uri, doc) in docs.iter() {
+            for symbol in Self::document_symbols(&doc.content) {
+                let (container, schemas) = match symbol.kind {
+                    SymbolKind::CLASS => (None, vec![symbol]),
+                    SymbolKind::NAMESPACE => {
+                        (Some(symbol.name), symbol.children.unwrap_or_default())
+                    }
+                    _ => continue,
+                };
+                for schema in schemas {
+                    if !schema.name.to_lowercase().contains(&query) {
+                        continue;
+                    }
+                    #[allow(deprecated)]
+                    results.push(SymbolInformation {
+                        name: schema.name,
+                        kind: SymbolKind::CLASS,
+                        tags: None,
+                        deprecated: None,
+                        location: Location {
+                            uri: uri.clone(),
+                            range: schema.selection_range,
+                        },
+                        container_name: container.clone(),
+                    });
+                }
+            }
+        }
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Some(results))
+    }
+
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         let uri = &params.text_document.uri;
 
@@ -579,59 +2369,7 @@ impl LanguageServer for Backend {
             None => return Ok(None),
         };
 
-        let mut tokens: Vec<SemanticToken> = Vec::new();
-        let mut prev_line = 0u32;
-        let mut prev_char = 0u32;
-
-        for (line_num, line) in doc.content.lines().enumerate() {
-            let line_num = line_num as u32;
-
-            // Highlight comments
-            if let Some(idx) = line.find('#') {
-                let delta_line = line_num - prev_line;
-                let delta_start = if delta_line == 0 {
-                    idx as u32 - prev_char
-                } else {
-                    idx as u32
-                };
-
-                tokens.push(SemanticToken {
-                    delta_line,
-                    delta_start,
-                    length: (line.len() - idx) as u32,
-                    token_type: 5, // COMMENT
-                    token_modifiers_bitset: 0,
-                });
-
-                prev_line = line_num;
-                prev_char = idx as u32;
-            }
-
-            // Highlight directives
-            let trimmed = line.trim();
-            if trimmed.starts_with('!') {
-                let start_char = line.find('!').unwrap_or(0) as u32;
-                let directive_end = trimmed.find(' ').unwrap_or(trimmed.len());
-
-                let delta_line = line_num - prev_line;
-                let delta_start = if delta_line == 0 {
-                    start_char - prev_char
-                } else {
-                    start_char
-                };
-
-                tokens.push(SemanticToken {
-                    delta_line,
-                    delta_start,
-                    length: directive_end as u32,
-                    token_type: 0, // KEYWORD
-                    token_modifiers_bitset: 0,
-                });
-
-                prev_line = line_num;
-                prev_char = start_char;
-            }
-        }
+        let tokens = Self::semantic_tokens(&doc.content);
 
         Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
             result_id: None,
@@ -648,3 +2386,932 @@ async fn main() {
     let (service, socket) = LspService::new(Backend::new);
     Server::new(stdin, stdout, socket).serve(service).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "!enum Status active inactive pending\n!def User id:int name status:Status admin:bool\n1 Alice active false\n";
+
+    fn completions_at(source: &str, line_idx: usize, prefix: &str) -> Vec<CompletionItem> {
+        let lines: Vec<&str> = source.lines().collect();
+        let schemas = Backend::extract_schemas(source);
+        let enums = Backend::extract_enums(source);
+        Backend::field_value_completions(&lines, line_idx, prefix, &schemas, &enums)
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_enum_completions_at_enum_column() {
+        let items = completions_at(SOURCE, 3, "2 \"Bob Smith\" ");
+        assert_eq!(labels(&items), vec!["active", "inactive", "pending"]);
+        assert!(
+            items
+                .iter()
+                .all(|i| i.kind == Some(CompletionItemKind::ENUM_MEMBER))
+        );
+
+        // Partially typed value in the same column
+        let items = completions_at(SOURCE, 3, "2 Bob ina");
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn test_no_enum_completions_in_other_columns() {
+        assert!(completions_at(SOURCE, 3, "2 ").is_empty());
+        let items = completions_at(SOURCE, 3, "2 Bob active ");
+        assert_eq!(labels(&items), vec!["true", "false"]);
+    }
+
+    #[test]
+    fn test_enum_completions_for_named_argument() {
+        let items = completions_at(SOURCE, 3, "id:2 status:");
+        assert_eq!(labels(&items), vec!["active", "inactive", "pending"]);
+    }
+
+    #[test]
+    fn test_numeric_field_snippet() {
+        let items = completions_at(SOURCE, 3, "");
+        assert_eq!(labels(&items), vec!["int"]);
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+    }
+
+    #[test]
+    fn test_no_completions_outside_schema() {
+        let source =
+            "!enum Status active inactive\n!def User id status:Status\n1 active\n---\nname x\n";
+        assert!(completions_at(source, 5, "status ").is_empty());
+        assert!(completions_at(source, 1, "!def User id ").is_empty());
+    }
+
+    #[test]
+    fn test_extends_prepends_parent_fields() {
+        let source = "!def Base id ts:int\n!def Click !extends Base ts url\n";
+        let schemas = Backend::extract_schemas(source);
+        assert_eq!(schemas[1].fields, vec!["id", "ts", "url"]);
+        assert_eq!(schemas[1].types, vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_compose_joins_part_fields() {
+        let source = "!def A x y:int\n!def B y z\n!compose C A B\n";
+        let schemas = Backend::extract_schemas(source);
+        assert_eq!(schemas[2].name, "C");
+        assert_eq!(schemas[2].fields, vec!["x", "y", "z"]);
+        assert_eq!(schemas[2].types, vec![None, Some("int".to_string()), None]);
+    }
+
+    fn outline(symbols: &[DocumentSymbol]) -> Vec<(String, SymbolKind, Vec<String>)> {
+        symbols
+            .iter()
+            .map(|s| {
+                let children = s.children.iter().flatten().map(|c| c.name.clone());
+                (s.name.clone(), s.kind, children.collect())
+            })
+            .collect()
+    }
+
+    async fn open(backend: &Backend, uri: &Url, text: &str) {
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(uri.clone(), "tauq".into(), 1, text.into()),
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_document_symbol_outline() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///config.tqn").unwrap();
+        let source = "service api\nport 8080 # http\ndatabase {\n  host localhost\n}\n\
+                      !def User id:int name\n1 Alice\n---\n\
+                      !def Admin !extends User level\n";
+        open(backend, &uri, source).await;
+
+        let response = backend
+            .document_symbol(DocumentSymbolParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap();
+        let Some(DocumentSymbolResponse::Nested(symbols)) = response else {
+            panic!("expected nested symbols");
+        };
+
+        let none = Vec::<String>::new;
+        assert_eq!(
+            outline(&symbols),
+            vec![
+                ("service".into(), SymbolKind::VARIABLE, none()),
+                ("port".into(), SymbolKind::VARIABLE, none()),
+                ("database".into(), SymbolKind::VARIABLE, none()),
+                (
+                    "User".into(),
+                    SymbolKind::CLASS,
+                    vec!["id".into(), "name".into()]
+                ),
+                ("Admin".into(), SymbolKind::CLASS, vec!["level".into()]),
+            ]
+        );
+        assert_eq!(symbols[1].detail.as_deref(), Some("8080"));
+        // The nested object's symbol spans through its closing brace
+        assert_eq!(symbols[2].range.end, Position::new(4, 1));
+        let user = &symbols[3];
+        assert_eq!(user.selection_range.start, Position::new(5, 5));
+        let id = &user.children.as_ref().unwrap()[0];
+        assert_eq!(id.detail.as_deref(), Some("int"));
+        assert_eq!(id.selection_range.end, Position::new(5, 12));
+    }
+
+    #[test]
+    fn test_schemas_block_symbols() {
+        let source = "!schemas\nUser id name\nOrder id total\n---\n!use User\n1 Alice\n";
+        let symbols = Backend::document_symbols(source);
+        assert_eq!(
+            outline(&symbols),
+            vec![(
+                "!schemas".into(),
+                SymbolKind::NAMESPACE,
+                vec!["User".into(), "Order".into()]
+            )]
+        );
+        assert_eq!(symbols[0].range.end.line, 3);
+        let order = &symbols[0].children.as_ref().unwrap()[1];
+        assert_eq!(
+            outline(std::slice::from_ref(order))[0].2,
+            vec!["id", "total"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbol_search() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let a = Url::parse("file:///a.tqn").unwrap();
+        let b = Url::parse("file:///b.tqn").unwrap();
+        open(backend, &a, "!def User id name\n1 Alice\n").await;
+        open(backend, &b, "!schemas\nUserRole id\nOrder id\n---\n").await;
+
+        let found = backend
+            .symbol(WorkspaceSymbolParams {
+                query: "user".into(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let names: Vec<_> = found
+            .iter()
+            .map(|s| {
+                (
+                    s.name.as_str(),
+                    s.location.uri.path(),
+                    s.container_name.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("User", "/a.tqn", None),
+                ("UserRole", "/b.tqn", Some("!schemas"))
+            ]
+        );
+    }
+
+    /// Apply non-overlapping edits, last first so earlier positions stay valid
+    fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+        let offset = |p: Position| {
+            let line_start: usize = text
+                .split_inclusive('\n')
+                .take(p.line as usize)
+                .map(str::len)
+                .sum();
+            let line = &text[line_start..];
+            line_start
+                + line
+                    .char_indices()
+                    .nth(p.character as usize)
+                    .map_or(line.len(), |(i, _)| i)
+        };
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+        let mut text = text.to_string();
+        for edit in edits {
+            let range = offset(edit.range.start)..offset(edit.range.end);
+            text.replace_range(range, &edit.new_text);
+        }
+        text
+    }
+
+    async fn extract_actions(source: &str, range: Range) -> Option<CodeActionResponse> {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///data.tqn").unwrap();
+        open(backend, &uri, source).await;
+        backend
+            .code_action(CodeActionParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                range,
+                context: Default::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+    }
+
+    fn applied(source: &str, action: &CodeActionOrCommand) -> String {
+        let CodeActionOrCommand::CodeAction(action) = action else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR_EXTRACT));
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let edits = changes.values().next().unwrap();
+        apply_edits(source, edits)
+    }
+
+    #[tokio::test]
+    async fn test_extract_schema_from_inline_objects() {
+        let source = "name demo\nusers [{ id 1 name Alice } { name \"Bob Smith\" id 2 }]\n";
+        let cursor = Range::new(Position::new(1, 10), Position::new(1, 10));
+        let actions = extract_actions(source, cursor).await.unwrap();
+        assert_eq!(actions.len(), 1);
+
+        let result = applied(source, &actions[0]);
+        assert_eq!(
+            result,
+            "!def User id name\n---\nname demo\nusers [!use User\n  1 Alice\n  2 \"Bob Smith\"\n]\n"
+        );
+        assert_eq!(
+            Parser::new(&result).parse().unwrap(),
+            Parser::new(source).parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_schema_next_to_existing_schemas() {
+        let source = "!def User id name\n1 Alice\n---\nconfig {\n  users [\n    { id 1 }\n    { id 2 }\n  ]\n}\n";
+        let cursor = Range::new(Position::new(5, 0), Position::new(5, 0));
+        let actions = extract_actions(source, cursor).await.unwrap();
+
+        // `User` is taken, and no separator is needed before an existing !def
+        let result = applied(source, &actions[0]);
+        assert!(
+            result.starts_with("!def User2 id\n!def User id name\n"),
+            "{}",
+            result
+        );
+        assert!(result.contains("  users [!use User2\n    1\n    2\n  ]\n"));
+        assert_eq!(
+            Parser::new(&result).parse().unwrap(),
+            Parser::new(source).parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_extract_for_mismatched_objects() {
+        let anywhere = Range::new(Position::new(0, 0), Position::new(9, 0));
+        let mismatched = "items [{ id 1 } { id 2 extra x }]\n";
+        assert!(extract_actions(mismatched, anywhere).await.is_none());
+        let single = "items [{ id 1 }]\n";
+        assert!(extract_actions(single, anywhere).await.is_none());
+        let nested = "items [{ id 1 tags [a] } { id 2 tags [b] }]\n";
+        assert!(extract_actions(nested, anywhere).await.is_none());
+
+        // Outside the cursor range
+        let source = "a 1\nusers [{ id 1 } { id 2 }]\n";
+        let first_line = Range::new(Position::new(0, 0), Position::new(0, 3));
+        assert!(extract_actions(source, first_line).await.is_none());
+    }
+
+    async fn lens_titles(source: &str) -> Vec<(u32, String)> {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///data.tqn").unwrap();
+        open(backend, &uri, source).await;
+        let lenses = backend
+            .code_lens(CodeLensParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        lenses
+            .into_iter()
+            .map(|l| (l.range.start.line, l.command.unwrap().title))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_code_lens_row_counts() {
+        let source = "!def User id name\n1 Alice\n2 Bob\n\n# comment\n3 Carol\n4 Dan\n5 Eve\n";
+        assert_eq!(
+            lens_titles(source).await,
+            vec![(0, "5 rows • 2 fields".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_code_lens_table_boundaries() {
+        let source = "!def A x\n1\n!def B x y\n1 2\n!use B\n3 {\n  k v\n}\n!use A\n2\n---\nname demo\n!def C id\n";
+        assert_eq!(
+            lens_titles(source).await,
+            vec![
+                (0, "1 row • 1 field".to_string()),
+                (2, "2 rows • 2 fields".to_string()),
+                (12, "0 rows • 1 field".to_string()),
+            ]
+        );
+    }
+
+    async fn hints(source: &str) -> Vec<(u32, u32, String)> {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///data.tqn").unwrap();
+        open(backend, &uri, source).await;
+        let hints = backend
+            .inlay_hint(InlayHintParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                range: Range::new(Position::new(0, 0), Position::new(100, 0)),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        hints
+            .into_iter()
+            .map(|h| {
+                assert_eq!(h.kind, Some(InlayHintKind::PARAMETER));
+                let InlayHintLabel::String(label) = h.label else {
+                    panic!("expected a string label");
+                };
+                (h.position.line, h.position.character, label)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints_label_row_values() {
+        let source =
+            "!def User id name role\n1 Alice admin\n2 \"Bob Smith\" user\n3 Carol guest # note\n";
+        let hints = hints(source).await;
+        assert_eq!(hints.len(), 9);
+        let labels: Vec<&str> = hints.iter().map(|(_, _, l)| l.as_str()).collect();
+        assert_eq!(labels, ["id", "name", "role"].repeat(3));
+        assert_eq!(
+            hints[3..6],
+            [
+                (2, 0, "id".to_string()),
+                (2, 2, "name".to_string()),
+                (2, 14, "role".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints_skip_non_rows() {
+        let source =
+            "!def User id name\n1 Alice\nid:2 name:Bob\n---\nname demo\n!use User\n3 Carol\n";
+        let hints = hints(source).await;
+        let lines: Vec<u32> = hints.iter().map(|(l, _, _)| *l).collect();
+        assert_eq!(lines, vec![1, 1, 6, 6]);
+    }
+
+    /// Signature label and the text of its active parameter
+    fn signature_at(source: &str, line_idx: usize, prefix: &str) -> Option<(String, String)> {
+        let lines: Vec<&str> = source.lines().collect();
+        let schemas = Backend::extract_schemas(source);
+        let help = Backend::row_signature(&lines, line_idx, prefix, &schemas)?;
+        let signature = &help.signatures[0];
+        let active = signature.active_parameter? as usize;
+        let ParameterLabel::LabelOffsets([start, end]) =
+            signature.parameters.as_ref()?[active].label
+        else {
+            panic!("expected offset labels");
+        };
+        let highlighted = signature
+            .label
+            .chars()
+            .skip(start as usize)
+            .take((end - start) as usize)
+            .collect();
+        Some((signature.label.clone(), highlighted))
+    }
+
+    #[test]
+    fn test_signature_help_highlights_current_field() {
+        let source = "!def User id:int name role active\n1 Alice admin true\n";
+        let active = |prefix| signature_at(source, 2, prefix).map(|(_, p)| p);
+        assert_eq!(
+            signature_at(source, 2, "2 Bob "),
+            Some((
+                "User(id:int, name, role, active)".to_string(),
+                "role".to_string()
+            ))
+        );
+        assert_eq!(active("2 \"Bob Sm").as_deref(), Some("name"));
+        assert_eq!(active("").as_deref(), Some("id:int"));
+        assert_eq!(active("id:2 active:").as_deref(), Some("active"));
+    }
+
+    #[test]
+    fn test_no_signature_outside_rows() {
+        let source = "!def User id name\n1 Alice\n---\nname demo\n";
+        assert_eq!(signature_at(source, 4, "name "), None);
+        assert_eq!(signature_at(source, 1, "1 Alice extra "), None);
+        assert_eq!(signature_at(source, 1, "# "), None);
+    }
+
+    #[tokio::test]
+    async fn test_signature_help_request() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///data.tqn").unwrap();
+        open(
+            backend,
+            &uri,
+            "!def User id name role active\n1 Alice admin true\n2 Bob \n",
+        )
+        .await;
+
+        let help = backend
+            .signature_help(SignatureHelpParams {
+                context: None,
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri),
+                    Position::new(2, 6),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "User(id, name, role, active)");
+        assert_eq!(signature.active_parameter, Some(2));
+        assert_eq!(
+            signature.parameters.as_ref().unwrap()[2].label,
+            ParameterLabel::LabelOffsets([15, 19])
+        );
+    }
+
+    fn undefined_schema_warnings(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == Some(DiagnosticSeverity::WARNING))
+            .map(|d| d.message.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_schema_from_other_document_is_defined() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let a = Url::parse("file:///schemas.tqn").unwrap();
+        let b = Url::parse("file:///users.tqn").unwrap();
+        let users = "!import \"schemas.tqn\"\n!use User\n1 Alice\n!use Order\n";
+
+        open(backend, &b, users).await;
+        let diagnostics = backend.generate_diagnostics(&b, users).await;
+        assert_eq!(undefined_schema_warnings(&diagnostics).len(), 2);
+
+        open(backend, &a, "!def User id name\n").await;
+        let diagnostics = backend.generate_diagnostics(&b, users).await;
+        assert_eq!(
+            undefined_schema_warnings(&diagnostics),
+            vec!["Schema 'Order' is not defined in this file or workspace"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lint_findings_are_information_diagnostics() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///lint.tqn").unwrap();
+        let source = "!def User id name\n!def Unused a\n!use User\n1 Alice\n";
+
+        let diagnostics = backend.generate_diagnostics(&uri, source).await;
+        let lint: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.source.as_deref() == Some("tauq-lint"))
+            .collect();
+        assert_eq!(lint.len(), 1);
+        assert_eq!(lint[0].severity, Some(DiagnosticSeverity::INFORMATION));
+        assert_eq!(
+            lint[0].code,
+            Some(NumberOrString::String("unused-schemas".to_string()))
+        );
+        assert_eq!(lint[0].range.start, Position::new(1, 5));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_files_indexed_on_initialized() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".cache")).unwrap();
+        let shared = dir.path().join("shared/schemas.tqn");
+        std::fs::write(&shared, "!def Product sku price\n").unwrap();
+        std::fs::write(dir.path().join(".cache/old.tqn"), "!def Stale x\n").unwrap();
+
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let root = Url::from_directory_path(dir.path()).unwrap();
+        backend
+            .initialize(InitializeParams {
+                workspace_folders: Some(vec![WorkspaceFolder {
+                    uri: root,
+                    name: "demo".into(),
+                }]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        backend.initialized(InitializedParams {}).await;
+
+        let main = Url::from_file_path(dir.path().join("main.tqn")).unwrap();
+        let source = "!use \n";
+        open(backend, &main, source).await;
+        let position = TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(main.clone()),
+            Position::new(0, 5),
+        );
+        let Some(CompletionResponse::Array(items)) = backend
+            .completion(CompletionParams {
+                text_document_position: position.clone(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .await
+            .unwrap()
+        else {
+            panic!("expected completions");
+        };
+        let schemas: Vec<_> = items
+            .iter()
+            .filter(|i| i.kind == Some(CompletionItemKind::CLASS))
+            .map(|i| (i.label.as_str(), i.detail.as_deref().unwrap_or("")))
+            .collect();
+        assert_eq!(
+            schemas,
+            vec![("Product", "Schema with 2 fields from schemas.tqn")]
+        );
+
+        // The file is removed from the index once deleted
+        let shared_uri = Url::from_file_path(&shared).unwrap();
+        backend
+            .did_change_watched_files(DidChangeWatchedFilesParams {
+                changes: vec![FileEvent::new(shared_uri, FileChangeType::DELETED)],
+            })
+            .await;
+        let diagnostics = backend.generate_diagnostics(&main, "!use Product\n").await;
+        assert_eq!(undefined_schema_warnings(&diagnostics).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_closed_document_falls_back_to_saved_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schemas.tqn");
+        std::fs::write(&path, "!def Saved x\n").unwrap();
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::from_file_path(&path).unwrap();
+        let main = Url::from_file_path(dir.path().join("main.tqn")).unwrap();
+        open(backend, &uri, "!def Unsaved y\n").await;
+
+        backend
+            .did_close(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier::new(uri.clone()),
+            })
+            .await;
+        assert!(!backend.documents.read().await.contains_key(&uri));
+        let diagnostics = backend.generate_diagnostics(&main, "!use Saved\n").await;
+        assert!(undefined_schema_warnings(&diagnostics).is_empty());
+        let diagnostics = backend.generate_diagnostics(&main, "!use Unsaved\n").await;
+        assert_eq!(undefined_schema_warnings(&diagnostics).len(), 1);
+
+        // Later changes on disk are indexed once the editor lets go
+        std::fs::write(&path, "!def Later z\n").unwrap();
+        backend
+            .did_change_watched_files(DidChangeWatchedFilesParams {
+                changes: vec![FileEvent::new(uri, FileChangeType::CHANGED)],
+            })
+            .await;
+        let diagnostics = backend.generate_diagnostics(&main, "!use Later\n").await;
+        assert!(undefined_schema_warnings(&diagnostics).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hover_shows_workspace_schema() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let a = Url::parse("file:///lib/schemas.tqn").unwrap();
+        let b = Url::parse("file:///users.tqn").unwrap();
+        open(backend, &a, "\n!def User id name\n").await;
+        open(backend, &b, "!use User\n1 Alice\n").await;
+
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(b),
+                    Position::new(0, 6),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown");
+        };
+        assert!(
+            markup.value.contains("Defined in `schemas.tqn` at line 2"),
+            "{}",
+            markup.value
+        );
+    }
+
+    async fn hover_text(backend: &Backend, uri: &Url, position: Position) -> Option<String> {
+        let hover = backend
+            .hover(HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri.clone()),
+                    position,
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()?;
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("expected markdown");
+        };
+        Some(markup.value)
+    }
+
+    #[tokio::test]
+    async fn test_hover_shows_row_field() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///users.tqn").unwrap();
+        let source = "!def User id name:string role\n1 Alice admin\n2 role:owner Bob\n1 Alice admin extra\n---\nname Alice\n";
+        open(backend, &uri, source).await;
+
+        let text = hover_text(backend, &uri, Position::new(1, 5))
+            .await
+            .unwrap();
+        assert_eq!(
+            text,
+            "**User.name** (field 2 of 3)\n\nType: string\n\nDefined in [`!def User id name:string role`](file:///users.tqn#L1)"
+        );
+        let text = hover_text(backend, &uri, Position::new(1, 9))
+            .await
+            .unwrap();
+        assert!(
+            text.starts_with("**User.role** (field 3 of 3)\n\nDefined"),
+            "{}",
+            text
+        );
+
+        // Named arguments do not count as positional values
+        let text = hover_text(backend, &uri, Position::new(2, 4))
+            .await
+            .unwrap();
+        assert!(text.starts_with("**User.role**"), "{}", text);
+        let text = hover_text(backend, &uri, Position::new(2, 14))
+            .await
+            .unwrap();
+        assert!(text.starts_with("**User.name**"), "{}", text);
+
+        // Extra values and rows after `---` have no field
+        assert!(
+            hover_text(backend, &uri, Position::new(3, 16))
+                .await
+                .is_none()
+        );
+        assert!(
+            hover_text(backend, &uri, Position::new(5, 7))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_row_field_of_workspace_schema() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let a = Url::parse("file:///lib/schemas.tqn").unwrap();
+        let b = Url::parse("file:///users.tqn").unwrap();
+        open(backend, &a, "\n!def User id:int name\n").await;
+        open(backend, &b, "!use User\n1 Alice\n").await;
+
+        let text = hover_text(backend, &b, Position::new(1, 0)).await.unwrap();
+        assert_eq!(
+            text,
+            "**User.id** (field 1 of 2)\n\nType: int\n\nDefined in [`!def User id:int name`](file:///lib/schemas.tqn#L2)"
+        );
+    }
+
+    fn folds(source: &str) -> Vec<(u32, u32)> {
+        Backend::folding_ranges(source)
+            .iter()
+            .map(|r| (r.start_line, r.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_folding_literals() {
+        let source = "config {\n  db {\n    host \"{not a brace\"\n  }\n  ports [80\n    443]\n  # } ignored\n}\ninline { a 1 }\n";
+        assert_eq!(folds(source), vec![(0, 6), (1, 2), (4, 5)]);
+    }
+
+    #[test]
+    fn test_folding_schema_blocks_and_tables() {
+        let source = "!schemas\nUser id name\nOrder id\n---\n!use User\n1 Alice\n2 Bob\n\n# end\n!def Tag name\nsolo\n!def Empty x\n---\nname demo\n";
+        let ranges = Backend::folding_ranges(source);
+        assert_eq!(folds(source), vec![(0, 3), (4, 6), (9, 10)]);
+        assert_eq!(ranges[0].kind, Some(FoldingRangeKind::Region));
+    }
+
+    #[test]
+    fn test_folding_table_with_multiline_row() {
+        let source = "!def User id tags\n1 [\n  a\n  b\n]\n2 []\n";
+        assert_eq!(folds(source), vec![(0, 5), (1, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_document_links_resolve_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("project/schemas")).unwrap();
+        std::fs::write(root.join("project/schemas/users.tqn"), "!def User id\n").unwrap();
+        std::fs::write(root.join("shared.tqn"), "").unwrap();
+        std::fs::write(root.join("project/gen.py"), "").unwrap();
+        let absolute = root.join("shared.tqn");
+
+        let main = Url::from_file_path(root.join("project/main.tqn")).unwrap();
+        let source = format!(
+            "!import \"schemas/users.tqn\"\n  !import \"../shared.tqn\" sha256:abc\n!json \"{}\"\n!read missing.txt\n!run python3 -u gen.py {{\n}}\n",
+            absolute.display()
+        );
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        open(backend, &main, &source).await;
+
+        let links = backend
+            .document_link(DocumentLinkParams {
+                text_document: TextDocumentIdentifier::new(main),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let file = |p: &std::path::Path| Some(Url::from_file_path(p).unwrap());
+        let targets: Vec<_> = links.iter().map(|l| l.target.clone()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                file(&root.join("project/schemas/users.tqn")),
+                file(&absolute),
+                file(&absolute),
+                None,
+                file(&root.join("project/gen.py")),
+            ]
+        );
+
+        // The range covers the path inside the quotes
+        assert_eq!(
+            links[0].range,
+            Range::new(Position::new(0, 9), Position::new(0, 26))
+        );
+        assert_eq!(
+            links[1].range,
+            Range::new(Position::new(1, 11), Position::new(1, 24))
+        );
+        assert_eq!(
+            links[3].tooltip.as_deref(),
+            Some("File not found: missing.txt")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_comma_separated_schema_rows() {
+        let source = "!def Record id:int,name,role\n1,Alice,admin\n2,\"Bob, Jr\",dev\n";
+        let schemas = Backend::extract_schemas(source);
+        assert_eq!(schemas[0].fields, vec!["id", "name", "role"]);
+        assert_eq!(schemas[0].types[0].as_deref(), Some("int"));
+
+        let symbols = Backend::document_symbols(source);
+        assert_eq!(outline(&symbols)[0].2, vec!["id", "name", "role"]);
+
+        let hints = hints(source).await;
+        assert_eq!(
+            hints[3..],
+            [
+                (2, 0, "id".to_string()),
+                (2, 2, "name".to_string()),
+                (2, 12, "role".to_string())
+            ]
+        );
+        let active = signature_at(source, 3, "3,Carol,").map(|(_, p)| p);
+        assert_eq!(active.as_deref(), Some("role"));
+    }
+
+    #[test]
+    fn test_semantic_tokens_from_lexer() {
+        let source = "# users\n!def User id name\n1 \"a # b\" # trailing\n";
+        let tokens: Vec<_> = Backend::semantic_tokens(source)
+            .iter()
+            .map(|t| (t.delta_line, t.delta_start, t.length, t.token_type))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (0, 0, 7, 5),  // # users
+                (1, 0, 4, 0),  // !def
+                (0, 5, 4, 1),  // User
+                (1, 0, 1, 4),  // 1
+                (0, 2, 7, 3),  // "a # b"
+                (0, 8, 10, 5), // # trailing
+            ]
+        );
+    }
+
+    async fn change(backend: &Backend, uri: &Url, version: i32, range: Option<Range>, text: &str) {
+        backend
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range,
+                    range_length: None,
+                    text: text.into(),
+                }],
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_incremental_changes() {
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let uri = Url::parse("file:///users.tqn").unwrap();
+        open(backend, &uri, "!def User id name\n1 Alice\n2 Bob\n").await;
+
+        let bob = Range::new(Position::new(2, 2), Position::new(2, 5));
+        change(backend, &uri, 2, Some(bob), "[Robert").await;
+        {
+            let docs = backend.documents.read().await;
+            let doc = &docs[&uri];
+            assert_eq!(doc.content, "!def User id name\n1 Alice\n2 [Robert\n");
+            assert_eq!(doc.parser.source(), doc.content);
+            assert!(doc.parser.documents()[0].is_err());
+        }
+
+        let open_bracket = Range::new(Position::new(2, 2), Position::new(2, 3));
+        change(backend, &uri, 3, Some(open_bracket), "").await;
+        change(
+            backend,
+            &uri,
+            4,
+            Some(Range::new(Position::new(3, 0), Position::new(3, 0))),
+            "3 Carol\n",
+        )
+        .await;
+        {
+            let docs = backend.documents.read().await;
+            let value = docs[&uri].parser.documents().remove(0).unwrap();
+            assert_eq!(value[1]["name"], "Robert");
+            assert_eq!(value[2]["name"], "Carol");
+        }
+
+        // A change without a range replaces the whole document
+        change(backend, &uri, 5, None, "name demo\n").await;
+        let docs = backend.documents.read().await;
+        assert_eq!(docs[&uri].parser.source(), "name demo\n");
+        assert!(docs[&uri].schemas.is_empty());
+    }
+
+    #[test]
+    fn test_offset_at_counts_utf16_units() {
+        let content = "a 😀 b\nsecond\n";
+        assert_eq!(Backend::offset_at(content, Position::new(0, 2)), 2);
+        // The emoji is two UTF-16 units and four bytes
+        assert_eq!(Backend::offset_at(content, Position::new(0, 4)), 6);
+        assert_eq!(Backend::offset_at(content, Position::new(0, 99)), 8);
+        assert_eq!(Backend::offset_at(content, Position::new(1, 3)), 12);
+        assert_eq!(
+            Backend::offset_at(content, Position::new(9, 0)),
+            content.len()
+        );
+    }
+}
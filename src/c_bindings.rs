@@ -1,3 +1,4 @@
+use crate::tauq::{Delimiter, Formatter, StreamingParser};
 use crate::{compile_tauq, compile_tauqq, format_to_tauq, minify_tauq_str};
 use std::cell::RefCell;
 use std::ffi::{CStr, CString};
@@ -207,6 +208,238 @@ pub unsafe extern "C" fn json_to_tauq_c(input: *const c_char) -> *mut c_char {
     }
 }
 
+/// Formatting options for `tauq_format_json_with_options`.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy)]
+pub struct tauq_format_options_t {
+    /// Row delimiter: `'s'` for spaces, `'c'` for commas
+    pub delimiter: u8,
+    /// Spaces per indentation level
+    pub indent_size: u32,
+    /// Single-line output
+    pub minify: bool,
+    /// Sort object keys for byte-identical output across runs
+    pub stable: bool,
+}
+
+impl Default for tauq_format_options_t {
+    fn default() -> Self {
+        Self {
+            delimiter: b's',
+            indent_size: 2,
+            minify: false,
+            stable: false,
+        }
+    }
+}
+
+/// Default formatting options, matching `json_to_tauq_c`.
+#[unsafe(no_mangle)]
+pub extern "C" fn tauq_format_options_default() -> tauq_format_options_t {
+    tauq_format_options_t::default()
+}
+
+/// Convert JSON string to Tauq string with formatting options.
+/// A null `opts` uses `tauq_format_options_default()`.
+/// Caller must free the result with `tauq_free_string`.
+///
+/// # Safety
+/// - `input` must be a valid pointer to a null-terminated UTF-8 string, or null.
+/// - `opts` must be null or point to a valid `tauq_format_options_t`.
+/// - If `input` is null, the function returns null and sets an error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tauq_format_json_with_options(
+    input: *const c_char,
+    opts: *const tauq_format_options_t,
+) -> *mut c_char {
+    if input.is_null() {
+        set_error("Input pointer is null".to_string());
+        return std::ptr::null_mut();
+    }
+
+    let opts = if opts.is_null() {
+        tauq_format_options_t::default()
+    } else {
+        unsafe { *opts }
+    };
+    let delimiter = match opts.delimiter {
+        b's' => Delimiter::Space,
+        b'c' => Delimiter::Comma,
+        other => {
+            set_error(format!(
+                "Invalid delimiter {:?}: expected 's' or 'c'",
+                other as char
+            ));
+            return std::ptr::null_mut();
+        }
+    };
+
+    let c_str = unsafe { CStr::from_ptr(input) };
+    let str_slice = match c_str.to_str() {
+        Ok(s) => s,
+        Err(e) => {
+            set_error(format!("Invalid UTF-8 in input: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+
+    let json_val: serde_json::Value = match serde_json::from_str(str_slice) {
+        Ok(v) => v,
+        Err(e) => {
+            set_error(format!("JSON parse error: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+
+    let mut formatter = Formatter::new()
+        .with_delimiter(delimiter)
+        .with_indent(opts.indent_size as usize)
+        .with_stable_output(opts.stable);
+    if opts.minify {
+        formatter = formatter.minified();
+    }
+
+    match CString::new(formatter.format(&json_val)) {
+        Ok(c) => c.into_raw(),
+        Err(e) => {
+            set_error(format!("Nul byte in output: {}", e));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Check whether a string is valid Tauq without producing any output.
+/// Returns `true` if it parses, `false` otherwise (details via `tauq_get_last_error`).
+///
+/// # Safety
+/// - `input` must be a valid pointer to a null-terminated UTF-8 string, or null.
+/// - If null is passed, the function returns false and sets an error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tauq_validate(input: *const c_char) -> bool {
+    if input.is_null() {
+        set_error("Input pointer is null".to_string());
+        return false;
+    }
+
+    let c_str = unsafe { CStr::from_ptr(input) };
+    let str_slice = match c_str.to_str() {
+        Ok(s) => s,
+        Err(e) => {
+            set_error(format!("Invalid UTF-8 in input: {}", e));
+            return false;
+        }
+    };
+
+    match compile_tauq(str_slice) {
+        Ok(_) => true,
+        Err(e) => {
+            set_error(e.to_string());
+            false
+        }
+    }
+}
+
+/// Opaque handle over a streaming parser, created by `tauq_parse_streaming_new`.
+pub struct TauqStreamHandle {
+    /// Parser borrowing from `_source`; declared first so it is dropped first.
+    /// `None` once the stream has ended or failed.
+    parser: Option<StreamingParser<'static>>,
+    /// Owned copy of the input the parser reads from.
+    _source: Box<str>,
+}
+
+/// Start parsing Tauq one record at a time.
+/// The input is copied, so it may be freed once this returns.
+/// Returns NULL on error. Free the handle with `tauq_parse_streaming_free`.
+///
+/// # Safety
+/// - `input` must be a valid pointer to a null-terminated UTF-8 string, or null.
+/// - If null is passed, the function returns null and sets an error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tauq_parse_streaming_new(input: *const c_char) -> *mut TauqStreamHandle {
+    if input.is_null() {
+        set_error("Input pointer is null".to_string());
+        return std::ptr::null_mut();
+    }
+
+    let c_str = unsafe { CStr::from_ptr(input) };
+    let source: Box<str> = match c_str.to_str() {
+        Ok(s) => s.into(),
+        Err(e) => {
+            set_error(format!("Invalid UTF-8 in input: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+
+    // SAFETY: the boxed text never moves or changes while the handle is
+    // alive, and `parser` is dropped before `_source`.
+    let text: &'static str = unsafe { &*(&*source as *const str) };
+    let handle = TauqStreamHandle {
+        parser: Some(StreamingParser::new(text)),
+        _source: source,
+    };
+    Box::into_raw(Box::new(handle))
+}
+
+/// Parse the next record and return it as a JSON string.
+/// Caller must free the result with `tauq_free_string`.
+///
+/// Returns NULL both at the end of the stream and on error. At the end the
+/// last error is cleared (`tauq_get_last_error(NULL, 0)` returns 0); on
+/// error it holds the message. Every call after either returns NULL.
+///
+/// # Safety
+/// - `handle` must be a pointer returned by `tauq_parse_streaming_new` that
+///   has not been freed, or null.
+/// - The handle must not be used from two threads at once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tauq_parse_streaming_next(handle: *mut TauqStreamHandle) -> *mut c_char {
+    if handle.is_null() {
+        set_error("Stream handle is null".to_string());
+        return std::ptr::null_mut();
+    }
+
+    let handle = unsafe { &mut *handle };
+    let Some(parser) = handle.parser.as_mut() else {
+        set_error(String::new());
+        return std::ptr::null_mut();
+    };
+
+    match parser.next_record() {
+        Some(Ok(json_val)) => match CString::new(json_val.to_string()) {
+            Ok(c) => c.into_raw(),
+            Err(e) => {
+                handle.parser = None;
+                set_error(format!("Nul byte in output JSON: {}", e));
+                std::ptr::null_mut()
+            }
+        },
+        Some(Err(e)) => {
+            handle.parser = None;
+            set_error(e.to_string());
+            std::ptr::null_mut()
+        }
+        None => {
+            handle.parser = None;
+            set_error(String::new());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Free a handle returned by `tauq_parse_streaming_new`.
+///
+/// # Safety
+/// - `handle` must be a pointer returned by `tauq_parse_streaming_new`, or null.
+/// - The handle must not have been freed already, and must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tauq_parse_streaming_free(handle: *mut TauqStreamHandle) {
+    if !handle.is_null() {
+        let _ = unsafe { Box::from_raw(handle) };
+    }
+}
+
 /// Free a string returned by tauq functions
 ///
 /// # Safety
@@ -11,12 +11,51 @@ pub struct Span {
     pub line: usize,
     /// 1-based column number
     pub column: usize,
+    /// 1-based line number of the end of the span
+    pub end_line: usize,
+    /// 1-based column just past the last character of the span (exclusive)
+    pub end_column: usize,
 }
 
 impl Span {
-    /// Create a new span
+    /// Create a single-character span; same as [`Span::point`]
     pub fn new(line: usize, column: usize) -> Self {
-        Self { line, column }
+        Self::point(line, column)
+    }
+
+    /// Create a span covering the single character at `line`, `column`
+    pub fn point(line: usize, column: usize) -> Self {
+        Self {
+            line,
+            column,
+            end_line: line,
+            end_column: column.saturating_add(1),
+        }
+    }
+
+    /// Create a span from `line`, `column` up to (not including)
+    /// `end_line`, `end_column`
+    pub fn range(line: usize, column: usize, end_line: usize, end_column: usize) -> Self {
+        Self {
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// Whether the span covers a single character
+    pub fn is_point(&self) -> bool {
+        self.end_line == self.line && self.end_column == self.column.saturating_add(1)
+    }
+
+    /// Move the span down by `lines` lines
+    pub fn shifted(self, lines: usize) -> Self {
+        Self {
+            line: self.line + lines,
+            end_line: self.end_line + lines,
+            ..self
+        }
     }
 }
 
@@ -102,6 +141,49 @@ impl std::fmt::Display for ParseError {
     }
 }
 
+/// Kind of non-fatal issue reported by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// `!use` of the schema that is already active
+    RedundantUse,
+    /// `!def` that declares no fields and is never used as a marker
+    EmptySchema,
+    /// Row that leaves trailing optional fields to their defaults or `null`
+    ShortRow,
+}
+
+/// Parser warning: valid input that is probably not what was meant
+#[derive(Debug, Clone)]
+pub struct ParseWarning {
+    /// What kind of issue this is
+    pub kind: WarningKind,
+    /// Warning message
+    pub message: String,
+    /// Location of the warning
+    pub span: Span,
+}
+
+impl ParseWarning {
+    /// Create a new parse warning
+    pub fn new(kind: WarningKind, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Warning at line {}, column {}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
 /// Interpreter error
 #[derive(Debug, Clone, Error)]
 pub struct InterpretError {
@@ -7,14 +7,19 @@
 // - Java_com_tauq_Tauq_formatJson(EnvUnowned, JClass, jstring) -> jstring
 // - Java_com_tauq_Tauq_toTbf(EnvUnowned, JClass, jstring) -> jbyteArray
 // - Java_com_tauq_Tauq_tbfToJson(EnvUnowned, JClass, jbyteArray) -> jstring
+// - Java_com_tauq_Tauq_streamingParserNew(EnvUnowned, JClass, jstring) -> jlong
+// - Java_com_tauq_Tauq_streamingParserNext(EnvUnowned, JClass, jlong) -> jstring
+// - Java_com_tauq_Tauq_streamingParserFree(EnvUnowned, JClass, jlong)
 
+use crate::tauq::StreamingParser;
 use crate::{compile_tauq, format_to_tauq};
 use jni::EnvUnowned;
 use jni::errors::ThrowRuntimeExAndDefault;
 use jni::jni_str;
 use jni::objects::{JByteArray, JClass, JString};
 use jni::strings::JNIString;
-use jni::sys::jstring;
+use jni::sys::{jlong, jstring};
+use std::thread::ThreadId;
 
 /// Class:     com_tauq_Tauq
 /// Method:    parseToJson
@@ -308,3 +313,152 @@ pub extern "system" fn Java_com_tauq_Tauq_tbfToTauq<'local>(
         })
         .resolve::<ThrowRuntimeExAndDefault>()
 }
+
+/// Streaming parser behind the `jlong` handle used by `TauqStreamingParser`.
+///
+/// The parser is not `Send` (its schema context is reference-counted), so the
+/// handle records the thread that created it and every call checks it.
+struct JavaStreamHandle {
+    /// Parser borrowing from `_source`; declared first so it is dropped first.
+    /// `None` once the stream has ended or failed.
+    parser: Option<StreamingParser<'static>>,
+    /// Owned copy of the input the parser reads from.
+    _source: Box<str>,
+    /// Thread that created the handle, the only one allowed to use it.
+    owner: ThreadId,
+}
+
+/// Borrow the handle behind `handle`, throwing `IllegalStateException` when
+/// it is 0 or used from a thread other than the one that created it.
+///
+/// # Safety
+///
+/// A non-zero `handle` must come from `streamingParserNew` and not have been freed.
+unsafe fn stream_handle<'h>(
+    env: &mut jni::Env<'_>,
+    handle: jlong,
+) -> Option<&'h mut JavaStreamHandle> {
+    if handle == 0 {
+        let _ = env.throw_new(
+            jni_str!("java/lang/IllegalStateException"),
+            jni_str!("Streaming parser handle is null or closed"),
+        );
+        return None;
+    }
+    let stream = unsafe { &mut *(handle as *mut JavaStreamHandle) };
+    if stream.owner != std::thread::current().id() {
+        let _ = env.throw_new(
+            jni_str!("java/lang/IllegalStateException"),
+            jni_str!("Streaming parser used from a thread other than the one that created it"),
+        );
+        return None;
+    }
+    Some(stream)
+}
+
+/// Class:     com_tauq_Tauq
+/// Method:    streamingParserNew
+/// Signature: (Ljava/lang/String;)J
+///
+/// Returns a handle that must be released with `streamingParserFree` and
+/// used only from the calling thread.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_tauq_Tauq_streamingParserNew<'local>(
+    mut unowned_env: EnvUnowned<'local>,
+    _class: JClass<'local>,
+    input: JString<'local>,
+) -> jlong {
+    unowned_env
+        .with_env(|env| -> jni::errors::Result<jlong> {
+            let input: String = input.mutf8_chars(env)?.into();
+            let source = input.into_boxed_str();
+
+            // SAFETY: the boxed text never moves or changes while the handle
+            // is alive, and `parser` is dropped before `_source`.
+            let text: &'static str = unsafe { &*(&*source as *const str) };
+            let handle = JavaStreamHandle {
+                parser: Some(StreamingParser::new(text)),
+                _source: source,
+                owner: std::thread::current().id(),
+            };
+            Ok(Box::into_raw(Box::new(handle)) as jlong)
+        })
+        .resolve::<ThrowRuntimeExAndDefault>()
+}
+
+/// Class:     com_tauq_Tauq
+/// Method:    streamingParserNext
+/// Signature: (J)Ljava/lang/String;
+///
+/// Returns the next record as JSON, or null at the end of the stream.
+///
+/// # Safety
+///
+/// `handle` must be 0 or a live handle from `streamingParserNew`.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_tauq_Tauq_streamingParserNext<'local>(
+    mut unowned_env: EnvUnowned<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) -> jstring {
+    unowned_env
+        .with_env(|env| -> jni::errors::Result<jstring> {
+            let Some(stream) = (unsafe { stream_handle(env, handle) }) else {
+                return Ok(std::ptr::null_mut());
+            };
+            let Some(parser) = stream.parser.as_mut() else {
+                return Ok(std::ptr::null_mut());
+            };
+
+            match parser.next_record() {
+                Some(Ok(json_val)) => match serde_json::to_string(&json_val) {
+                    Ok(s) => Ok(env.new_string(s)?.into_raw()),
+                    Err(e) => {
+                        stream.parser = None;
+                        let _ = env.throw_new(
+                            jni_str!("java/lang/RuntimeException"),
+                            JNIString::new(format!("JSON Serialization Error: {}", e)),
+                        );
+                        Ok(std::ptr::null_mut())
+                    }
+                },
+                Some(Err(e)) => {
+                    stream.parser = None;
+                    let _ = env.throw_new(
+                        jni_str!("java/lang/IllegalArgumentException"),
+                        JNIString::new(format!("Tauq Parse Error: {}", e)),
+                    );
+                    Ok(std::ptr::null_mut())
+                }
+                None => {
+                    stream.parser = None;
+                    Ok(std::ptr::null_mut())
+                }
+            }
+        })
+        .resolve::<ThrowRuntimeExAndDefault>()
+}
+
+/// Class:     com_tauq_Tauq
+/// Method:    streamingParserFree
+/// Signature: (J)V
+///
+/// # Safety
+///
+/// `handle` must be 0 or a live handle from `streamingParserNew`; it must
+/// not be used again afterwards.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_tauq_Tauq_streamingParserFree<'local>(
+    mut unowned_env: EnvUnowned<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    unowned_env
+        .with_env(|env| -> jni::errors::Result<()> {
+            if handle != 0 && unsafe { stream_handle(env, handle) }.is_some() {
+                drop(unsafe { Box::from_raw(handle as *mut JavaStreamHandle) });
+            }
+            Ok(())
+        })
+        .resolve::<ThrowRuntimeExAndDefault>()
+}
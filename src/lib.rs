@@ -22,6 +22,30 @@
 //! let json = compile_tauq(source).unwrap();
 //! assert_eq!(json["name"], "Alice");
 //! ```
+//!
+//! # Encode and decode
+//! ```
+//! use serde_json::json;
+//!
+//! let users = json!([
+//!     {"id": 1, "name": "Alice"},
+//!     {"id": 2, "name": "Bob"},
+//! ]);
+//!
+//! // Text: `to_string` / `from_str`
+//! let source = tauq::to_string(&users).unwrap();
+//! assert_eq!(source, "!def Record id name\n1 Alice\n2 Bob");
+//! let back: serde_json::Value = tauq::from_str(&source).unwrap();
+//! assert_eq!(back, users);
+//!
+//! // Single line: `to_string_minified`
+//! assert_eq!(tauq::to_string_minified(&users).unwrap(), "!def Record id name;1 Alice;2 Bob");
+//!
+//! // Bytes: `to_bytes` / `from_bytes`
+//! let bytes = tauq::to_bytes(&users).unwrap();
+//! let back: serde_json::Value = tauq::from_bytes(&bytes).unwrap();
+//! assert_eq!(back, users);
+//! ```
 
 /// Error types for Tauq
 pub mod error;
@@ -44,11 +68,22 @@ pub mod python_bindings;
 /// Iceberg table format integration for TBF
 pub mod tbf_iceberg;
 
-pub use error::TauqError;
-pub use serde_support::{from_bytes, from_file, from_str};
+/// The `serde_json` crate that parsed values use, so code generated by
+/// `tauq-derive` does not need its own dependency on it
+pub use serde_json;
+
+pub use error::{ParseWarning, TauqError, WarningKind};
+pub use serde_support::{
+    TauqSchema, from_bytes, from_file, from_reader, from_schema_row, from_str,
+    streaming_from_reader, to_bytes, to_string, to_string_minified, to_writer,
+};
 pub use tauq::Delimiter;
-pub use tauq::{Formatter, Lexer, Parser, StreamingParser};
-pub use tauq::{json_to_tauq, json_to_tauq_optimized, json_to_tauq_ultra, minify_tauq};
+pub use tauq::{Formatter, Lexer, Parser, StreamingParser, TomlFormatter};
+pub use tauq::{
+    csv_to_tauq, csv_to_value, json_to_tauq, json_to_tauq_annotated, json_to_tauq_optimized,
+    json_to_tauq_ultra, json_to_tauq_writer, minify_tauq, ndjson_to_tauq, ndjson_to_tauq_writer,
+    tauq_to_ndjson, to_multi_doc, to_toml,
+};
 
 /// Maximum input size (100 MB) to prevent DoS via memory exhaustion
 pub const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024;
@@ -56,6 +91,20 @@ pub const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024;
 /// Maximum nesting depth for recursive structures
 pub const MAX_NESTING_DEPTH: usize = 100;
 
+/// Reject sources larger than [`MAX_INPUT_SIZE`] to prevent DoS
+fn check_input_size(source: &str) -> Result<(), error::TauqError> {
+    if source.len() > MAX_INPUT_SIZE {
+        return Err(error::TauqError::Interpret(error::InterpretError::new(
+            format!(
+                "Input too large: {} bytes (max {} bytes)",
+                source.len(),
+                MAX_INPUT_SIZE
+            ),
+        )));
+    }
+    Ok(())
+}
+
 /// Parse Tauq source to JSON
 ///
 /// # Example
@@ -67,19 +116,135 @@ pub const MAX_NESTING_DEPTH: usize = 100;
 /// # Errors
 /// Returns `TauqError` if the source contains syntax errors.
 pub fn compile_tauq(source: &str) -> Result<serde_json::Value, error::TauqError> {
-    // Validate input size to prevent DoS
-    if source.len() > MAX_INPUT_SIZE {
-        return Err(error::TauqError::Interpret(error::InterpretError::new(
-            format!(
-                "Input too large: {} bytes (max {} bytes)",
-                source.len(),
-                MAX_INPUT_SIZE
-            ),
-        )));
-    }
-    let mut parser = tauq::Parser::new(source);
+    compile_tauq_verbose(source).map(|(value, _)| value)
+}
+
+/// Parse Tauq source to JSON, also returning the parser's warnings
+///
+/// Warnings flag input that parses but is probably a mistake, such as a
+/// `!use` of the schema that is already active.
+///
+/// # Example
+/// ```
+/// let source = "!def User id name\n!use User\n1 Alice";
+/// let (json, warnings) = tauq::compile_tauq_verbose(source).unwrap();
+/// assert_eq!(json["name"], "Alice");
+/// assert_eq!(warnings[0].kind, tauq::WarningKind::RedundantUse);
+/// ```
+///
+/// # Errors
+/// Returns `TauqError` if the source contains syntax errors.
+pub fn compile_tauq_verbose(
+    source: &str,
+) -> Result<(serde_json::Value, Vec<ParseWarning>), error::TauqError> {
+    check_input_size(source)?;
+    let mut parser = tauq::Parser::new(tauq::lexer::strip_bom(source));
     let result = parser.parse().map_err(error::TauqError::Parse)?;
-    Ok(result)
+    Ok((result, parser.take_warnings()))
+}
+
+/// Read and parse a Tauq file to JSON
+///
+/// `!import` paths resolve relative to the file's directory rather than the
+/// current working directory, and may not escape it. To allow imports from
+/// elsewhere, parse with [`Context::for_file`](tauq::parser::Context::for_file)
+/// and [`with_import_root`](tauq::parser::Context::with_import_root).
+///
+/// # Example
+/// ```no_run
+/// use std::path::Path;
+///
+/// let json = tauq::compile_tauq_from_file(Path::new("config/app.tqn")).unwrap();
+/// ```
+///
+/// # Errors
+/// Returns `TauqError::Io` if the file cannot be read, or `TauqError` if it
+/// contains syntax errors.
+pub fn compile_tauq_from_file(
+    path: &std::path::Path,
+) -> Result<serde_json::Value, error::TauqError> {
+    let source = std::fs::read_to_string(path).map_err(error::TauqError::Io)?;
+    check_input_size(&source)?;
+    let mut parser = tauq::Parser::new_with_context(
+        tauq::lexer::strip_bom(&source),
+        tauq::parser::Context::for_file(path),
+    );
+    parser.parse().map_err(error::TauqError::Parse)
+}
+
+/// Parse Tauq source to JSON, resolving `!import` paths relative to `base_dir`
+///
+/// Use this for sources that did not come from a file but still import
+/// others, e.g. templates kept in memory for a known project root. Imports
+/// may not escape `base_dir`.
+///
+/// # Example
+/// ```no_run
+/// use std::path::Path;
+///
+/// let source = "!import \"schemas/user.tqn\"\n!use User\n1 Alice\n";
+/// let json = tauq::compile_tauq_with_base_dir(source, Path::new("config")).unwrap();
+/// ```
+///
+/// # Errors
+/// Returns `TauqError` if the source or an imported file contains syntax
+/// errors, or an import cannot be read.
+pub fn compile_tauq_with_base_dir(
+    source: &str,
+    base_dir: &std::path::Path,
+) -> Result<serde_json::Value, error::TauqError> {
+    check_input_size(source)?;
+    let mut parser = tauq::Parser::new_with_context(
+        tauq::lexer::strip_bom(source),
+        tauq::parser::Context::with_base_dir(base_dir.to_path_buf()),
+    );
+    parser.parse().map_err(error::TauqError::Parse)
+}
+
+/// Parse a file of independent Tauq documents separated by `===` lines
+///
+/// Each document is parsed on its own, so schemas defined in one are not
+/// visible in the next. Blank documents are skipped, and error lines are
+/// reported relative to the whole file.
+///
+/// # Example
+/// ```
+/// let source = "!def User id name\n1 Alice\n===\nname staging\n";
+/// let docs = tauq::compile_tauq_multi(source).unwrap();
+/// assert_eq!(docs.len(), 2);
+/// assert_eq!(docs[1]["name"], "staging");
+/// ```
+///
+/// # Errors
+/// Returns `TauqError` for the first document that fails to parse.
+pub fn compile_tauq_multi(source: &str) -> Result<Vec<serde_json::Value>, error::TauqError> {
+    check_input_size(source)?;
+    let mut parser = tauq::Parser::new(tauq::lexer::strip_bom(source));
+    parser.parse_separated().map_err(error::TauqError::Parse)
+}
+
+/// Parse Tauq records asynchronously from a buffered reader.
+///
+/// Records are yielded as soon as each logical line is read, so large files and
+/// network streams never need to be held in memory.
+///
+/// # Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use futures::StreamExt;
+///
+/// let input: &[u8] = b"!def User id name\n1 Alice\n2 Bob\n";
+/// let users: Vec<_> = tauq::parse_async(input).collect().await;
+/// assert_eq!(users[0].as_ref().unwrap()["name"], "Alice");
+/// # });
+/// ```
+#[cfg(feature = "tokio")]
+pub fn parse_async<R: tokio::io::AsyncBufRead>(
+    reader: R,
+) -> impl futures::Stream<Item = Result<serde_json::Value, error::TauqError>> {
+    use futures::StreamExt;
+    tauq::AsyncStreamingParser::new(Box::pin(reader))
+        .map(|record| record.map_err(error::TauqError::Parse))
 }
 
 /// Execute TauqQ in safe mode (shell execution disabled) - **RECOMMENDED**
@@ -132,18 +297,43 @@ pub fn compile_tauqq(source: &str, safe_mode: bool) -> Result<serde_json::Value,
 /// * `source` - The TauqQ source code
 /// * `safe_mode` - If true, disables shell execution (!emit, !run, !pipe)
 pub fn process_tauqq(source: &str, safe_mode: bool) -> Result<String, error::TauqError> {
-    // Validate input size
-    if source.len() > MAX_INPUT_SIZE {
-        return Err(error::TauqError::Interpret(error::InterpretError::new(
-            format!(
-                "Input too large: {} bytes (max {} bytes)",
-                source.len(),
-                MAX_INPUT_SIZE
-            ),
-        )));
-    }
+    let config = tauq::tauqq::ProcessConfig {
+        base_dir: std::env::current_dir().ok(),
+        safe_mode,
+        ..Default::default()
+    };
+    process_tauqq_with_config(source, &config)
+}
+
+/// Process TauqQ directives, resolving `!import`, `!read`, `!json` and
+/// `!csv` paths relative to `base_dir` instead of the current directory
+///
+/// # Arguments
+/// * `source` - The TauqQ source code
+/// * `base_dir` - Directory that file paths are relative to, and may not escape
+/// * `safe_mode` - If true, disables shell execution and file directives
+pub fn process_tauqq_with_base_dir(
+    source: &str,
+    base_dir: &std::path::Path,
+    safe_mode: bool,
+) -> Result<String, error::TauqError> {
+    let config = tauq::tauqq::ProcessConfig {
+        base_dir: Some(base_dir.to_path_buf()),
+        safe_mode,
+        ..Default::default()
+    };
+    process_tauqq_with_config(source, &config)
+}
+
+/// Process TauqQ directives with explicit configuration (base directory,
+/// safe mode, `!log` verbosity, ...)
+pub fn process_tauqq_with_config(
+    source: &str,
+    config: &tauq::tauqq::ProcessConfig,
+) -> Result<String, error::TauqError> {
+    check_input_size(source)?;
     let mut vars = std::collections::HashMap::new();
-    tauq::tauqq::process(source, &mut vars, safe_mode)
+    tauq::tauqq::process_with_config(source, &mut vars, config)
         .map_err(|e| error::TauqError::Interpret(error::InterpretError::new(e)))
 }
 
@@ -161,38 +351,151 @@ pub fn minify_tauq_str(json: &serde_json::Value) -> String {
     tauq::minify_tauq(json)
 }
 
-/// Print an error with source code context
-pub fn print_error_with_source(source: &str, error: &error::TauqError) {
+/// Look up a value by RFC 6901 JSON Pointer, e.g. `/users/0/name`
+///
+/// `~1` and `~0` in a segment stand for `/` and `~`, and the empty pointer
+/// selects the whole value. Returns `None` when the pointer does not start
+/// with `/` or names something that does not exist.
+///
+/// # Example
+/// ```
+/// let value = serde_json::json!({"users": [{"name": "Alice"}]});
+/// assert_eq!(tauq::json_pointer(&value, "/users/0/name"), Some(&"Alice".into()));
+/// assert_eq!(tauq::json_pointer(&value, "users"), None);
+/// ```
+pub fn json_pointer<'a>(
+    value: &'a serde_json::Value,
+    pointer: &str,
+) -> Option<&'a serde_json::Value> {
+    value.pointer(pointer)
+}
+
+/// Options for printing diagnostics with source context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitOptions {
+    /// Emit ANSI color codes
+    pub color: bool,
+    /// Number of source lines to show before and after the underlined lines
+    pub context_lines: usize,
+}
+
+impl Default for EmitOptions {
+    /// Color when stderr is a terminal, no context lines
+    fn default() -> Self {
+        use std::io::IsTerminal;
+        Self {
+            color: std::io::stderr().is_terminal(),
+            context_lines: 0,
+        }
+    }
+}
+
+/// Render `label: message` followed by the source lines covered by `span`,
+/// each underlined with `^`
+fn render_diagnostic(
+    source: &str,
+    label: &str,
+    message: &str,
+    span: Option<error::Span>,
+    color: &str,
+    options: &EmitOptions,
+) -> String {
+    use std::fmt::Write;
+
+    let paint = |text: &str| {
+        if options.color {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.to_string()
+        }
+    };
+    let mut out = format!("{}: {}\n", paint(label), message);
+
+    let lines: Vec<&str> = source.lines().collect();
+    // Spans are 1-based
+    let Some(span) = span.filter(|s| s.line > 0 && s.line <= lines.len()) else {
+        return out;
+    };
+    // A span ending at column 1 stops at the end of the previous line
+    let mut last = span.end_line.clamp(span.line, lines.len());
+    if last > span.line && span.end_line <= lines.len() && span.end_column <= 1 {
+        last -= 1;
+    }
+    let first_shown = span.line.saturating_sub(options.context_lines).max(1);
+    let last_shown = (last + options.context_lines).min(lines.len());
+    let width = last_shown.to_string().len().max(2);
+    let gutter = " ".repeat(width + 1);
+
+    let _ = writeln!(out, "{}|", gutter);
+    for number in first_shown..=last_shown {
+        let line = lines[number - 1];
+        let _ = writeln!(out, "{:width$} | {}", number, line, width = width);
+        if number < span.line || number > last {
+            continue;
+        }
+        let line_len = line.chars().count();
+        let start = if number == span.line {
+            span.column.saturating_sub(1)
+        } else {
+            line_len - line.trim_start().chars().count()
+        };
+        let end = if number == span.end_line {
+            span.end_column.saturating_sub(1)
+        } else {
+            line_len
+        };
+        let carets = "^".repeat(end.saturating_sub(start).max(1));
+        let _ = writeln!(out, "{}| {}{}", gutter, " ".repeat(start), paint(&carets));
+    }
+    let _ = writeln!(out, "{}|", gutter);
+    out
+}
+
+/// Render an error with source code context, as printed by
+/// [`print_error_with_source`]
+pub fn format_error_with_source(
+    source: &str,
+    error: &error::TauqError,
+    options: &EmitOptions,
+) -> String {
     let span = match error {
         error::TauqError::Lex(e) => Some(e.span),
         error::TauqError::Parse(e) => Some(e.span),
         error::TauqError::Interpret(e) => e.span,
         error::TauqError::Io(_) => None,
     };
+    render_diagnostic(source, "Error", &error.to_string(), span, "1;31", options)
+}
 
-    if let Some(span) = span {
-        let lines: Vec<&str> = source.lines().collect();
-        // Spans are 1-based
-        if span.line > 0 && span.line <= lines.len() {
-            let line_idx = span.line - 1;
-            let line = lines[line_idx];
-
-            eprintln!("Error: {}", error);
-            eprintln!("   |");
-            eprintln!("{:2} | {}", span.line, line);
-
-            let mut pointer = String::new();
-            for _ in 0..span.column {
-                pointer.push(' ');
-            }
-            pointer.push('^');
-
-            eprintln!("   | {}", pointer);
-            eprintln!("   |");
-        } else {
-            eprintln!("Error: {}", error);
-        }
-    } else {
-        eprintln!("Error: {}", error);
-    }
+/// Print an error with source code context to stderr, using the default
+/// [`EmitOptions`]
+pub fn print_error_with_source(source: &str, error: &error::TauqError) {
+    print_error_with_source_with_options(source, error, &EmitOptions::default());
+}
+
+/// Print an error with source code context to stderr
+pub fn print_error_with_source_with_options(
+    source: &str,
+    error: &error::TauqError,
+    options: &EmitOptions,
+) {
+    eprint!("{}", format_error_with_source(source, error, options));
+}
+
+/// Render parser warnings with source code context, as printed by
+/// [`print_warnings_with_source`]
+pub fn format_warnings_with_source(
+    source: &str,
+    warnings: &[ParseWarning],
+    options: &EmitOptions,
+) -> String {
+    warnings
+        .iter()
+        .map(|w| render_diagnostic(source, "W", &w.to_string(), Some(w.span), "33", options))
+        .collect()
+}
+
+/// Print parser warnings with source code context to stderr
+pub fn print_warnings_with_source(source: &str, warnings: &[ParseWarning], options: &EmitOptions) {
+    eprint!("{}", format_warnings_with_source(source, warnings, options));
 }
@@ -26,12 +26,63 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 
 #[cfg(feature = "python-bindings")]
-use crate::{compile_tauq, compile_tauqq, format_to_tauq, minify_tauq_str};
+use crate::{TauqError, compile_tauq, compile_tauqq, format_to_tauq, minify_tauq_str};
 #[cfg(feature = "python-bindings")]
 use serde_json::Value as JsonValue;
 #[cfg(feature = "python-bindings")]
 use std::path::PathBuf;
 
+#[cfg(feature = "python-bindings")]
+pyo3::create_exception!(
+    tauq,
+    TauqException,
+    PyValueError,
+    "Base class for all Tauq errors; a ValueError subclass."
+);
+#[cfg(feature = "python-bindings")]
+pyo3::create_exception!(
+    tauq,
+    TauqLexError,
+    TauqException,
+    "Invalid token in Tauq source."
+);
+#[cfg(feature = "python-bindings")]
+pyo3::create_exception!(tauq, TauqParseError, TauqException, "Invalid Tauq syntax.");
+#[cfg(feature = "python-bindings")]
+pyo3::create_exception!(
+    tauq,
+    TauqInterpretError,
+    TauqException,
+    "Error while evaluating Tauq or TauqQ, or encoding/decoding TBF."
+);
+#[cfg(feature = "python-bindings")]
+pyo3::create_exception!(
+    tauq,
+    TauqIOError,
+    TauqException,
+    "File read or write failure."
+);
+
+/// Convert a Tauq error to the matching Python exception, with `line` and
+/// `column` attributes taken from its span (`None` when it has none)
+#[cfg(feature = "python-bindings")]
+fn tauq_error(err: TauqError) -> PyErr {
+    let message = err.to_string();
+    let (py_err, span) = match &err {
+        TauqError::Lex(e) => (TauqLexError::new_err(message), Some(e.span)),
+        TauqError::Parse(e) => (TauqParseError::new_err(message), Some(e.span)),
+        TauqError::Interpret(e) => (TauqInterpretError::new_err(message), e.span),
+        TauqError::Io(_) => (TauqIOError::new_err(message), None),
+    };
+    Python::attach(|py| {
+        let value = py_err.value(py);
+        // Setting attributes on a fresh exception instance cannot fail
+        let _ = value.setattr("line", span.map(|s| s.line));
+        let _ = value.setattr("column", span.map(|s| s.column));
+    });
+    py_err
+}
+
 /// Convert JSON Value to Python object
 #[cfg(feature = "python-bindings")]
 fn json_to_python(py: Python<'_>, value: &JsonValue) -> PyResult<Py<PyAny>> {
@@ -128,8 +179,7 @@ fn python_to_json(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<JsonValue>
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn loads(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
-    let json = compile_tauq(source)
-        .map_err(|e| PyValueError::new_err(format!("Tauq parse error: {}", e)))?;
+    let json = compile_tauq(source).map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -152,11 +202,9 @@ fn loads(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn load(py: Python<'_>, path: PathBuf) -> PyResult<Py<PyAny>> {
-    let source = std::fs::read_to_string(&path)
-        .map_err(|e| PyValueError::new_err(format!("File read error: {}", e)))?;
+    let source = std::fs::read_to_string(&path).map_err(|e| tauq_error(e.into()))?;
 
-    let json = compile_tauq(&source)
-        .map_err(|e| PyValueError::new_err(format!("Tauq parse error: {}", e)))?;
+    let json = compile_tauq(&source).map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -192,9 +240,8 @@ fn load(py: Python<'_>, path: PathBuf) -> PyResult<Py<PyAny>> {
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn exec_tauqq(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
-    let json =
-        compile_tauqq(source, true) // Safe mode by default - shell execution disabled
-            .map_err(|e| PyValueError::new_err(format!("TauqQ execution error: {}", e)))?;
+    let json = compile_tauqq(source, true) // Safe mode by default - shell execution disabled
+        .map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -208,9 +255,8 @@ fn exec_tauqq(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
 #[allow(clippy::unsafe_removed_from_name)]
 #[pyfunction]
 fn exec_tauqq_unsafe(py: Python<'_>, source: &str) -> PyResult<Py<PyAny>> {
-    let json =
-        compile_tauqq(source, false) // Shell execution enabled
-            .map_err(|e| PyValueError::new_err(format!("TauqQ execution error: {}", e)))?;
+    let json = compile_tauqq(source, false) // Shell execution enabled
+        .map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -265,8 +311,7 @@ fn dumps(py: Python<'_>, obj: Bound<'_, PyAny>) -> PyResult<String> {
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn minify(source: &str) -> PyResult<String> {
-    let json = compile_tauq(source)
-        .map_err(|e| PyValueError::new_err(format!("Tauq parse error: {}", e)))?;
+    let json = compile_tauq(source).map_err(tauq_error)?;
 
     Ok(minify_tauq_str(&json))
 }
@@ -290,8 +335,7 @@ fn dump(py: Python<'_>, obj: Bound<'_, PyAny>, path: PathBuf) -> PyResult<()> {
     let json = python_to_json(py, &obj)?;
     let tauq_str = format_to_tauq(&json);
 
-    std::fs::write(&path, tauq_str)
-        .map_err(|e| PyValueError::new_err(format!("Write error: {}", e)))?;
+    std::fs::write(&path, tauq_str).map_err(|e| tauq_error(e.into()))?;
 
     Ok(())
 }
@@ -313,8 +357,7 @@ fn tbf_dumps(py: Python<'_>, obj: Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
     use pyo3::types::PyBytes;
 
     let json = python_to_json(py, &obj)?;
-    let bytes = crate::tbf::encode_json(&json)
-        .map_err(|e| PyValueError::new_err(format!("TBF encode error: {}", e)))?;
+    let bytes = crate::tbf::encode_json(&json).map_err(tauq_error)?;
 
     Ok(PyBytes::new(py, &bytes).unbind().into_any())
 }
@@ -329,8 +372,7 @@ fn tbf_dumps(py: Python<'_>, obj: Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn tbf_loads(py: Python<'_>, data: &[u8]) -> PyResult<Py<PyAny>> {
-    let json = crate::tbf::decode(data)
-        .map_err(|e| PyValueError::new_err(format!("TBF decode error: {}", e)))?;
+    let json = crate::tbf::decode(data).map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -344,11 +386,9 @@ fn tbf_loads(py: Python<'_>, data: &[u8]) -> PyResult<Py<PyAny>> {
 #[pyfunction]
 fn tbf_dump(py: Python<'_>, obj: Bound<'_, PyAny>, path: PathBuf) -> PyResult<()> {
     let json = python_to_json(py, &obj)?;
-    let bytes = crate::tbf::encode_json(&json)
-        .map_err(|e| PyValueError::new_err(format!("TBF encode error: {}", e)))?;
+    let bytes = crate::tbf::encode_json(&json).map_err(tauq_error)?;
 
-    std::fs::write(&path, bytes)
-        .map_err(|e| PyValueError::new_err(format!("Write error: {}", e)))?;
+    std::fs::write(&path, bytes).map_err(|e| tauq_error(e.into()))?;
 
     Ok(())
 }
@@ -363,11 +403,9 @@ fn tbf_dump(py: Python<'_>, obj: Bound<'_, PyAny>, path: PathBuf) -> PyResult<()
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn tbf_load(py: Python<'_>, path: PathBuf) -> PyResult<Py<PyAny>> {
-    let bytes =
-        std::fs::read(&path).map_err(|e| PyValueError::new_err(format!("Read error: {}", e)))?;
+    let bytes = std::fs::read(&path).map_err(|e| tauq_error(e.into()))?;
 
-    let json = crate::tbf::decode(&bytes)
-        .map_err(|e| PyValueError::new_err(format!("TBF decode error: {}", e)))?;
+    let json = crate::tbf::decode(&bytes).map_err(tauq_error)?;
 
     json_to_python(py, &json)
 }
@@ -382,8 +420,7 @@ fn tbf_load(py: Python<'_>, path: PathBuf) -> PyResult<Py<PyAny>> {
 #[cfg(feature = "python-bindings")]
 #[pyfunction]
 fn tbf_to_tauq(data: &[u8]) -> PyResult<String> {
-    crate::tbf::decode_to_tauq(data)
-        .map_err(|e| PyValueError::new_err(format!("TBF decode error: {}", e)))
+    crate::tbf::decode_to_tauq(data).map_err(tauq_error)
 }
 
 /// Streaming Tauq parser that accepts chunks of text incrementally.
@@ -426,8 +463,7 @@ impl TauqStream {
         let mut record_count: usize = 0;
 
         for result in parser {
-            let val =
-                result.map_err(|e| PyValueError::new_err(format!("Stream parse error: {}", e)))?;
+            let val = result.map_err(|e| tauq_error(e.into()))?;
             // Only emit records beyond what we've already yielded.
             if record_count >= self.consumed {
                 list.append(json_to_python(py, &val)?)?;
@@ -451,8 +487,7 @@ impl TauqStream {
         let list = pyo3::types::PyList::empty(py);
 
         for (record_count, result) in parser.enumerate() {
-            let val =
-                result.map_err(|e| PyValueError::new_err(format!("Stream parse error: {}", e)))?;
+            let val = result.map_err(|e| tauq_error(e.into()))?;
             if record_count >= self.consumed {
                 list.append(json_to_python(py, &val)?)?;
             }
@@ -466,13 +501,265 @@ impl TauqStream {
     }
 }
 
+/// Iterator over the records of a Tauq source, one dict per schema row.
+///
+/// Returned by `streaming_loads` and by entering a `TauqFile`.
+#[cfg(feature = "python-bindings")]
+#[pyclass(unsendable)]
+struct TauqRecords {
+    /// Parser borrowing from `source`; declared first so it is dropped first.
+    parser: Option<crate::tauq::streaming::StreamingParser<'static>>,
+    /// Owned source text the parser reads from.
+    _source: Box<str>,
+}
+
+#[cfg(feature = "python-bindings")]
+impl TauqRecords {
+    fn from_source(source: String) -> Self {
+        let source = source.into_boxed_str();
+        // SAFETY: the boxed text never moves or changes while `self` is
+        // alive, and `parser` is dropped before `_source`.
+        let text: &'static str = unsafe { &*(&*source as *const str) };
+        Self {
+            parser: Some(crate::tauq::streaming::StreamingParser::new(text)),
+            _source: source,
+        }
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+#[pymethods]
+impl TauqRecords {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Parse and return the next record; raises `StopIteration` at the end
+    /// of the source and after the first parse error.
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        let Some(parser) = self.parser.as_mut() else {
+            return Ok(None);
+        };
+        match parser.next_record() {
+            Some(Ok(val)) => json_to_python(py, &val).map(Some),
+            Some(Err(e)) => {
+                self.parser = None;
+                Err(tauq_error(e.into()))
+            }
+            None => {
+                self.parser = None;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Parse Tauq from a string lazily, yielding one record at a time
+///
+/// # Arguments
+/// * `source` - Tauq source string
+///
+/// # Returns
+/// Iterator of Python dicts/values, one per record
+///
+/// # Example
+/// ```python
+/// import tauq
+///
+/// for user in tauq.streaming_loads("!def User id name\n1 Alice\n2 Bob"):
+///     print(user["name"])
+/// ```
+#[cfg(feature = "python-bindings")]
+#[pyfunction]
+fn streaming_loads(source: String) -> TauqRecords {
+    TauqRecords::from_source(source)
+}
+
+/// Context manager over a Tauq file that yields a `streaming_loads` iterator
+///
+/// # Example
+/// ```python
+/// import tauq
+///
+/// with tauq.TauqFile("users.tqn") as records:
+///     for user in records:
+///         print(user["name"])
+/// ```
+#[cfg(feature = "python-bindings")]
+#[pyclass]
+struct TauqFile {
+    path: PathBuf,
+}
+
+#[cfg(feature = "python-bindings")]
+#[pymethods]
+impl TauqFile {
+    #[new]
+    fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Read the file and return an iterator over its records.
+    fn __enter__(&self) -> PyResult<TauqRecords> {
+        let source = std::fs::read_to_string(&self.path).map_err(|e| tauq_error(e.into()))?;
+        Ok(TauqRecords::from_source(source))
+    }
+
+    /// Exceptions raised inside the `with` block are never suppressed.
+    fn __exit__(
+        &self,
+        _exc_type: Option<Py<PyAny>>,
+        _exc_value: Option<Py<PyAny>>,
+        _traceback: Option<Py<PyAny>>,
+    ) -> bool {
+        false
+    }
+}
+
+/// A `!def` schema built in Python, for writing schema tables directly
+///
+/// # Example
+/// ```python
+/// import tauq
+///
+/// users = tauq.Schema("User", ["id", "name"])
+/// print(users.dumps([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]))
+/// # !def User id name
+/// # 1 Alice
+/// # 2 Bob
+/// ```
+#[cfg(feature = "python-bindings")]
+#[pyclass]
+struct Schema {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    fields: Vec<String>,
+}
+
+#[cfg(feature = "python-bindings")]
+impl Schema {
+    /// Order a record's values by field; missing fields become `null`.
+    fn record_row(
+        &self,
+        py: Python<'_>,
+        values: &Bound<'_, PyAny>,
+        formatter: &crate::tauq::Formatter,
+    ) -> PyResult<String> {
+        let JsonValue::Object(mut record) = python_to_json(py, values)? else {
+            return Err(PyValueError::new_err(format!(
+                "Rows for schema '{}' must be dicts",
+                self.name
+            )));
+        };
+        if let Some(key) = record.keys().find(|key| !self.fields.contains(key)) {
+            return Err(PyValueError::new_err(format!(
+                "Field '{}' is not in schema '{}'",
+                key, self.name
+            )));
+        }
+        // Every value is written, trailing nulls included, since the
+        // header does not mark any field optional
+        let row: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let value = record.remove(field).unwrap_or(JsonValue::Null);
+                formatter.format_value_for_row(&value)
+            })
+            .collect();
+        Ok(row.join(formatter.value_sep()))
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+#[pymethods]
+impl Schema {
+    #[new]
+    fn new(name: String, fields: Vec<String>) -> PyResult<Self> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid schema name: {:?}",
+                name
+            )));
+        }
+        if let Some(field) = fields
+            .iter()
+            .find(|f| f.is_empty() || f.contains(char::is_whitespace))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid field name: {:?}",
+                field
+            )));
+        }
+        Ok(Self { name, fields })
+    }
+
+    /// Build a schema whose fields are the keys of the first record.
+    #[classmethod]
+    fn from_dicts(
+        _cls: &Bound<'_, pyo3::types::PyType>,
+        py: Python<'_>,
+        name: String,
+        records: Bound<'_, PyList>,
+    ) -> PyResult<Self> {
+        let first = records
+            .iter()
+            .next()
+            .ok_or_else(|| PyValueError::new_err("Cannot infer fields from an empty list"))?;
+        let JsonValue::Object(record) = python_to_json(py, &first)? else {
+            return Err(PyValueError::new_err("Records must be dicts"));
+        };
+        Self::new(name, record.keys().cloned().collect())
+    }
+
+    /// The `!def` line, without a trailing newline.
+    fn header(&self) -> String {
+        if self.fields.is_empty() {
+            format!("!def {}", self.name)
+        } else {
+            format!("!def {} {}", self.name, self.fields.join(" "))
+        }
+    }
+
+    /// Format one record as a row of this schema.
+    fn row(&self, py: Python<'_>, values: Bound<'_, PyAny>) -> PyResult<String> {
+        self.record_row(py, &values, &crate::tauq::Formatter::new())
+    }
+
+    /// The header followed by one row per record.
+    fn dumps(&self, py: Python<'_>, records: Bound<'_, PyList>) -> PyResult<String> {
+        let formatter = crate::tauq::Formatter::new();
+        let mut lines = vec![self.header()];
+        for record in records.iter() {
+            lines.push(self.record_row(py, &record, &formatter)?);
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Schema({:?}, {:?})", self.name, self.fields)
+    }
+}
+
 /// Python module definition
 #[cfg(feature = "python-bindings")]
 #[pymodule]
 fn tauq(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("TauqException", py.get_type::<TauqException>())?;
+    m.add("TauqLexError", py.get_type::<TauqLexError>())?;
+    m.add("TauqParseError", py.get_type::<TauqParseError>())?;
+    m.add("TauqInterpretError", py.get_type::<TauqInterpretError>())?;
+    m.add("TauqIOError", py.get_type::<TauqIOError>())?;
+
     m.add_class::<TauqStream>()?;
+    m.add_class::<TauqRecords>()?;
+    m.add_class::<TauqFile>()?;
+    m.add_class::<Schema>()?;
     m.add_function(wrap_pyfunction!(loads, m)?)?;
     m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(streaming_loads, m)?)?;
     m.add_function(wrap_pyfunction!(exec_tauqq, m)?)?;
     #[allow(clippy::unsafe_removed_from_name)]
     m.add_function(wrap_pyfunction!(exec_tauqq_unsafe, m)?)?;
@@ -487,6 +774,33 @@ fn tauq(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(tbf_load, m)?)?;
     m.add_function(wrap_pyfunction!(tbf_to_tauq, m)?)?;
 
+    m.add(
+        "__all__",
+        vec![
+            "TauqException",
+            "TauqLexError",
+            "TauqParseError",
+            "TauqInterpretError",
+            "TauqIOError",
+            "TauqStream",
+            "TauqRecords",
+            "TauqFile",
+            "Schema",
+            "loads",
+            "load",
+            "streaming_loads",
+            "exec_tauqq",
+            "exec_tauqq_unsafe",
+            "dumps",
+            "minify",
+            "dump",
+            "tbf_dumps",
+            "tbf_loads",
+            "tbf_dump",
+            "tbf_load",
+            "tbf_to_tauq",
+        ],
+    )?;
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     m.add(
         "__doc__",
@@ -12,10 +12,15 @@
 // }
 //
 // let config: Config = tauq::from_str(tauq_source)?;
+// let source = tauq::to_string(&config)?;
 // ```
 
-use crate::{TauqError, compile_tauq};
+use crate::tauq::{Formatter, PushParser, TauqSerializer};
+use crate::{MAX_INPUT_SIZE, TauqError, compile_tauq, compile_tauq_from_file};
+use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::io::{self, BufRead, Read};
 use std::path::Path;
 
 /// Deserialize Tauq from a string into a type T
@@ -53,7 +58,7 @@ pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, TauqError> {
 
 /// Deserialize Tauq from a file into a type T
 ///
-/// Supports !import directives.
+/// Supports !import directives, resolved relative to the file's directory.
 ///
 /// # Example
 ///
@@ -77,8 +82,7 @@ pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, TauqError> {
 /// let config: Config = from_file(Path::new("config.tqn")).unwrap();
 /// ```
 pub fn from_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, TauqError> {
-    let source = std::fs::read_to_string(path.as_ref()).map_err(TauqError::Io)?;
-    let json = compile_tauq(&source)?;
+    let json = compile_tauq_from_file(path.as_ref())?;
     serde_json::from_value(json).map_err(|e| {
         TauqError::Interpret(crate::error::InterpretError::new(format!(
             "Deserialization error: {}",
@@ -114,6 +118,264 @@ pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TauqError> {
     from_str(s)
 }
 
+/// Deserialize Tauq read from any [`io::Read`], such as stdin or a socket
+///
+/// The whole source is buffered before parsing; for record-by-record input
+/// use [`streaming_from_reader`].
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+/// use std::io::Cursor;
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     workers: u32,
+/// }
+///
+/// let config: Config = tauq::from_reader(Cursor::new(b"workers 8")).unwrap();
+/// assert_eq!(config.workers, 8);
+/// ```
+pub fn from_reader<T: DeserializeOwned, R: io::Read>(reader: R) -> Result<T, TauqError> {
+    from_str(&read_source(reader)?)
+}
+
+/// Parse Tauq read from any [`io::Read`] record by record, as
+/// [`StreamingParser`](crate::tauq::StreamingParser) does
+///
+/// Input is read a line at a time as records are requested, so only the
+/// current logical line is held in memory. Iteration ends after the first
+/// error.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// let input = Cursor::new(b"!def User id name\n1 Alice\n2 Bob\n");
+/// let names: Vec<_> = tauq::streaming_from_reader(input)
+///     .map(|record| record.unwrap()["name"].clone())
+///     .collect();
+/// assert_eq!(names, ["Alice", "Bob"]);
+/// ```
+pub fn streaming_from_reader<R: io::Read>(
+    reader: R,
+) -> impl Iterator<Item = Result<Value, TauqError>> {
+    ReaderRecords {
+        reader: io::BufReader::new(reader),
+        parser: PushParser::new(),
+        line: Vec::new(),
+        eof: false,
+        done: false,
+    }
+}
+
+/// Records of [`streaming_from_reader`], fed to a [`PushParser`] one line
+/// at a time
+struct ReaderRecords<R> {
+    reader: io::BufReader<R>,
+    parser: PushParser,
+    line: Vec<u8>,
+    /// Whether the reader is exhausted
+    eof: bool,
+    /// Whether an error ended the iteration
+    done: bool,
+}
+
+impl<R: io::Read> ReaderRecords<R> {
+    /// Hand the next line to the parser, or finish it at the end of input
+    fn read_line(&mut self) -> Result<(), TauqError> {
+        self.line.clear();
+        // A longer line is rejected by the parser
+        let limit = MAX_INPUT_SIZE as u64 + 1;
+        if (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', &mut self.line)?
+            == 0
+        {
+            self.eof = true;
+            self.parser.finish();
+            return Ok(());
+        }
+        let text = std::str::from_utf8(&self.line).map_err(|e| {
+            TauqError::Interpret(crate::error::InterpretError::new(format!(
+                "Invalid UTF-8: {}",
+                e
+            )))
+        })?;
+        self.parser.push(text);
+        Ok(())
+    }
+}
+
+impl<R: io::Read> Iterator for ReaderRecords<R> {
+    type Item = Result<Value, TauqError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            if let Some(record) = self.parser.next_record() {
+                self.done = record.is_err();
+                return Some(record.map_err(TauqError::Parse));
+            }
+            if self.eof {
+                return None;
+            }
+            if let Err(e) = self.read_line() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Read a whole Tauq source, failing once it exceeds [`MAX_INPUT_SIZE`]
+fn read_source<R: io::Read>(reader: R) -> Result<String, TauqError> {
+    let mut source = String::new();
+    reader
+        .take(MAX_INPUT_SIZE as u64 + 1)
+        .read_to_string(&mut source)?;
+    if source.len() > MAX_INPUT_SIZE {
+        return Err(TauqError::Interpret(crate::error::InterpretError::new(
+            format!("Input too large: exceeds {} bytes", MAX_INPUT_SIZE),
+        )));
+    }
+    Ok(source)
+}
+
+/// A struct whose fields form a Tauq `!def` schema
+///
+/// Implemented by `#[derive(TauqSchema)]` from the `tauq-derive` crate,
+/// which keeps the field list in sync with the struct definition and
+/// honours `#[serde(rename = "...")]`.
+pub trait TauqSchema: Sized {
+    /// Schema name used in the `!def` line
+    fn schema_name() -> &'static str;
+
+    /// Field names in declaration order
+    fn fields() -> &'static [&'static str];
+
+    /// The `!def` line for this schema, including its trailing newline
+    fn tauq_header() -> String {
+        format!(
+            "!def {} {}\n",
+            Self::schema_name(),
+            Self::fields().join(" ")
+        )
+    }
+
+    /// Build a value from one row, one value per field in schema order
+    fn from_tauq_row(values: &[Value]) -> Result<Self, TauqError>;
+}
+
+/// Deserialize a schema row given its field names and values
+///
+/// Backs the `from_tauq_row` generated by `#[derive(TauqSchema)]`.
+pub fn from_schema_row<T: DeserializeOwned>(
+    schema: &str,
+    fields: &[&str],
+    values: &[Value],
+) -> Result<T, TauqError> {
+    if values.len() != fields.len() {
+        return Err(TauqError::Interpret(crate::error::InterpretError::new(
+            format!(
+                "Row for schema '{}' has {} values, expected {}",
+                schema,
+                values.len(),
+                fields.len()
+            ),
+        )));
+    }
+    let row: serde_json::Map<String, Value> = fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| (field.to_string(), value.clone()))
+        .collect();
+    serde_json::from_value(Value::Object(row)).map_err(|e| {
+        TauqError::Interpret(crate::error::InterpretError::new(format!(
+            "Deserialization error: {}",
+            e
+        )))
+    })
+}
+
+/// Serialize a value as Tauq text
+///
+/// Sequences of structs with the same fields are written as `!def`
+/// schemas with one row per element.
+///
+/// # Example
+///
+/// ```
+/// use serde::Serialize;
+/// use tauq::to_string;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let users = vec![
+///     User { id: 1, name: "Alice".into() },
+///     User { id: 2, name: "Bob".into() },
+/// ];
+/// assert_eq!(to_string(&users).unwrap(), "!def Record id name\n1 Alice\n2 Bob");
+/// ```
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, TauqError> {
+    let mut serializer = TauqSerializer::new(Formatter::new());
+    let fragment = value.serialize(&mut serializer)?;
+    Ok(serializer.finish(fragment))
+}
+
+/// Serialize a value as single-line Tauq text
+///
+/// # Example
+///
+/// ```
+/// let value = serde_json::json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+/// let source = tauq::to_string_minified(&value).unwrap();
+/// assert!(!source.contains('\n'));
+/// assert_eq!(tauq::compile_tauq(&source).unwrap(), value);
+/// ```
+pub fn to_string_minified<T: Serialize + ?Sized>(value: &T) -> Result<String, TauqError> {
+    let mut serializer = TauqSerializer::new(Formatter::new().minified());
+    let fragment = value.serialize(&mut serializer)?;
+    Ok(serializer.finish(fragment))
+}
+
+/// Serialize a value as UTF-8 Tauq text; the inverse of [`from_bytes`]
+///
+/// # Example
+///
+/// ```
+/// let value = serde_json::json!({"name": "Alice", "age": 30});
+/// let bytes = tauq::to_bytes(&value).unwrap();
+/// let back: serde_json::Value = tauq::from_bytes(&bytes).unwrap();
+/// assert_eq!(back, value);
+/// ```
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TauqError> {
+    to_string(value).map(String::into_bytes)
+}
+
+/// Serialize a value as Tauq text into a writer
+///
+/// Top-level fields and table rows are written to `writer` one at a time,
+/// flushing after each, rather than building the document as a string.
+/// The output is identical to [`to_string`].
+pub fn to_writer<T: Serialize + ?Sized, W: io::Write>(
+    mut writer: W,
+    value: &T,
+) -> Result<(), TauqError> {
+    let mut serializer = TauqSerializer::new(Formatter::new());
+    let fragment = value.serialize(&mut serializer)?;
+    serializer.finish_to_writer(fragment, &mut writer)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -215,6 +477,30 @@ ports [8080 8081 8082]
         );
     }
 
+    #[test]
+    fn test_to_bytes_round_trip() {
+        #[derive(Serialize)]
+        struct Out {
+            workers: f64,
+            timeout: f64,
+        }
+
+        let out = Out {
+            workers: 8.0,
+            timeout: 30.0,
+        };
+        let expected = SimpleConfig {
+            workers: 8.0,
+            timeout: 30.0,
+        };
+        let config: SimpleConfig = from_bytes(&to_bytes(&out).unwrap()).unwrap();
+        assert_eq!(config, expected);
+
+        let minified = to_string_minified(&out).unwrap();
+        assert!(!minified.contains('\n'), "{}", minified);
+        assert_eq!(from_str::<SimpleConfig>(&minified).unwrap(), expected);
+    }
+
     #[test]
     fn test_deserialization_error() {
         use serde::Deserialize;
@@ -10,8 +10,18 @@
 // - Space (default): Most readable, good token efficiency
 // - Comma: Maximum token efficiency (matches TOON's density)
 
-use serde_json::Value;
-use std::collections::HashMap;
+use super::lexer::strip_bom;
+use crate::error::{ParseError, Span, TauqError};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write as _};
+use std::io;
+use std::sync::Arc;
+
+/// Callback naming a schema from its fields and the key of the array it
+/// came from (`None` for top-level tables), see [`Formatter::with_schema_namer`]
+pub type SchemaNamer = dyn Fn(&[String], Option<&str>) -> String + Send + Sync;
 
 /// Value delimiter type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -35,35 +45,232 @@ pub enum SchemaStrategy {
     Always,
 }
 
+/// Common irregular plurals resolved by default (plural, singular)
+const IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("people", "person"),
+    ("children", "child"),
+    ("men", "man"),
+    ("women", "woman"),
+    ("mice", "mouse"),
+    ("geese", "goose"),
+    ("feet", "foot"),
+    ("teeth", "tooth"),
+    ("data", "datum"),
+    ("indices", "index"),
+    ("matrices", "matrix"),
+    ("vertices", "vertex"),
+    ("criteria", "criterion"),
+    ("phenomena", "phenomenon"),
+    ("analyses", "analysis"),
+    ("crises", "crisis"),
+    ("theses", "thesis"),
+    ("media", "medium"),
+    ("statuses", "status"),
+    ("aliases", "alias"),
+];
+
+/// Controls how array keys are singularised into schema names
+/// (`users` → `User`, `people` → `Person`)
+#[derive(Debug, Clone, Default)]
+pub struct SingularisationConfig {
+    /// Overrides checked before any built-in rule (keys are matched case-insensitively)
+    pub custom_map: HashMap<String, String>,
+    /// Apply extra suffix rules (`-ches`/`-shes`/`-xes`/`-sses` → drop `es`, `-ves` → `f`)
+    pub aggressive: bool,
+}
+
+impl SingularisationConfig {
+    /// Default rules plus the built-in irregular dictionary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `plural` to `singular`, overriding built-in rules
+    pub fn with_override(mut self, plural: &str, singular: &str) -> Self {
+        self.custom_map
+            .insert(plural.to_lowercase(), singular.to_string());
+        self
+    }
+
+    /// Enable the extra suffix rules
+    pub fn aggressive(mut self) -> Self {
+        self.aggressive = true;
+        self
+    }
+
+    /// Singularise a word; for `snake_case`/`kebab-case` keys only the last
+    /// segment is singularised (`user_profiles` → `user_profile`)
+    pub fn singularize(&self, word: &str) -> String {
+        let split = word.rfind(['_', '-']).map_or(0, |i| i + 1);
+        let (head, last) = word.split_at(split);
+        format!("{}{}", head, self.singularize_word(last))
+    }
+
+    fn singularize_word(&self, s: &str) -> String {
+        let lower = s.to_lowercase();
+        if let Some(singular) = self.custom_map.get(&lower) {
+            return singular.clone();
+        }
+        if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(p, _)| *p == lower) {
+            return singular.to_string();
+        }
+
+        if self.aggressive {
+            if let Some(stem) = s.strip_suffix("ves") {
+                return format!("{}f", stem);
+            }
+            for suffix in ["sses", "ches", "shes", "xes", "zes"] {
+                if s.ends_with(suffix) {
+                    return s[..s.len() - 2].to_string();
+                }
+            }
+        }
+
+        if let Some(stripped) = s.strip_suffix("ies") {
+            format!("{}y", stripped)
+        } else if s.ends_with('s') && !s.ends_with("ss") && s.len() > 1 {
+            s[..s.len() - 1].to_string()
+        } else {
+            s.to_string()
+        }
+    }
+
+    /// Singularise and PascalCase a key into a schema name (`people` → `Person`)
+    pub fn schema_name(&self, key: &str) -> String {
+        let singular = self.singularize(key);
+        let mut result = String::new();
+        let mut cap_next = true;
+        for c in singular.chars() {
+            if c == '_' || c == '-' {
+                cap_next = true;
+            } else if cap_next {
+                result.push(c.to_ascii_uppercase());
+                cap_next = false;
+            } else {
+                result.push(c.to_ascii_lowercase());
+            }
+        }
+        result
+    }
+}
+
+/// A schema `json_to_tauq` would define for a document, with usage statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferredSchema {
+    /// Schema name as used in `!def`
+    pub name: String,
+    /// Field names in declaration order
+    pub fields: Vec<String>,
+    /// Type annotation inferred for each field (`int`, `float`, `bool`,
+    /// `string`, or `[Schema]` for nested tables); `None` if mixed or unknown
+    pub types: Vec<Option<String>>,
+    /// Number of rows seen with this shape
+    pub occurrences: usize,
+}
+
+impl InferredSchema {
+    /// Render as a `!def` line, optionally with type annotations
+    pub fn to_def(&self, annotate: bool) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .zip(&self.types)
+            .map(|(field, ty)| match ty {
+                Some(ty) if annotate => format!("{}:{}", field, ty),
+                _ => field.clone(),
+            })
+            .collect();
+        format!("!def {} {}", self.name, fields.join(" "))
+    }
+}
+
 /// Schema information collected during formatting
 #[derive(Clone, Debug)]
-struct SchemaInfo {
-    name: String,
-    fields: Vec<String>,
+pub struct SchemaInfo {
+    /// Schema name as used in `!def`
+    pub name: String,
+    /// Field names in the order first seen
+    pub fields: Vec<String>,
+    /// Fields that are `null` in some row, declared optional (`field?`)
+    pub nullable: HashSet<String>,
 }
 
 /// Collect and deduplicate schemas, returning name for each unique field set
-struct SchemaRegistry {
+///
+/// This is the naming logic `json_to_tauq` uses: a schema is named after
+/// the key its rows appear under (singularised, `users` → `User`), or
+/// inferred from its fields when there is no key. Field sets that only
+/// differ in order share a schema, unless
+/// [`with_strict_field_order`](Self::with_strict_field_order) is set.
+///
+/// # Example
+/// ```
+/// use tauq::tauq::{Delimiter, SchemaRegistry};
+///
+/// let mut registry = SchemaRegistry::new();
+/// let fields = vec!["id".to_string(), "name".to_string()];
+/// assert_eq!(registry.get_or_create(&fields, Some("users")), "User");
+/// assert_eq!(registry.definitions(Delimiter::Space), ["!def User id name"]);
+/// ```
+pub struct SchemaRegistry {
     /// Map from field signature to schema info
     schemas: HashMap<String, SchemaInfo>,
     /// Counter for unique naming
     name_counter: HashMap<String, usize>,
+    /// Rules for deriving names from array keys
+    singularisation: SingularisationConfig,
+    /// Caller-supplied naming, replacing the built-in heuristics
+    namer: Option<Arc<SchemaNamer>>,
+    /// Field name to rendered default literal, declared as `field=value`
+    pub(crate) defaults: HashMap<String, String>,
+    /// Field order is part of a schema's identity
+    strict_field_order: bool,
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SchemaRegistry {
-    fn new() -> Self {
+    /// An empty registry using the default singularisation rules
+    pub fn new() -> Self {
+        Self::with_singularisation(SingularisationConfig::default())
+    }
+
+    pub(crate) fn with_singularisation(config: SingularisationConfig) -> Self {
         Self {
             schemas: HashMap::new(),
             name_counter: HashMap::new(),
+            singularisation: config,
+            namer: None,
+            defaults: HashMap::new(),
+            strict_field_order: false,
         }
     }
 
-    /// Get or create a schema for the given fields, using context for naming
-    fn get_or_create(&mut self, fields: &[String], context: Option<&str>) -> String {
-        // Create deterministic signature from sorted fields (for deduplication only)
+    /// Register fields listed in a different order as a separate schema
+    /// instead of reusing the first one seen
+    pub fn with_strict_field_order(mut self, strict: bool) -> Self {
+        self.strict_field_order = strict;
+        self
+    }
+
+    /// Key of the schema for `fields`: their sorted names, or the names in
+    /// order when field order is strict
+    fn signature(&self, fields: &[String]) -> String {
+        if self.strict_field_order {
+            return fields.join(",");
+        }
         let mut sorted = fields.to_vec();
         sorted.sort();
-        let sig = sorted.join(",");
+        sorted.join(",")
+    }
+
+    /// Get or create a schema for the given fields, using context for naming
+    pub fn get_or_create(&mut self, fields: &[String], context: Option<&str>) -> String {
+        let sig = self.signature(fields);
 
         // Return existing schema if same shape
         if let Some(info) = self.schemas.get(&sig) {
@@ -71,7 +278,7 @@ impl SchemaRegistry {
         }
 
         // Generate name from context or fields
-        let base = Self::derive_name(fields, context);
+        let base = self.derive_name(fields, context);
         let name = self.unique_name(&base);
 
         self.schemas.insert(
@@ -79,11 +286,27 @@ impl SchemaRegistry {
             SchemaInfo {
                 name: name.clone(),
                 fields: fields.to_vec(), // Preserve original order!
+                nullable: HashSet::new(),
             },
         );
         name
     }
 
+    /// Declare `nullable` fields of the schema for `fields` optional
+    pub(crate) fn mark_nullable(&mut self, fields: &[String], nullable: HashSet<String>) {
+        let sig = self.signature(fields);
+        if let Some(info) = self.schemas.get_mut(&sig) {
+            info.nullable.extend(nullable);
+        }
+    }
+
+    /// Field order of the schema registered for this set of fields
+    pub(crate) fn declared_order(&self, fields: &[String]) -> Option<&[String]> {
+        self.schemas
+            .get(&self.signature(fields))
+            .map(|info| info.fields.as_slice())
+    }
+
     fn unique_name(&mut self, base: &str) -> String {
         let count = self.name_counter.entry(base.to_string()).or_insert(0);
         *count += 1;
@@ -94,10 +317,18 @@ impl SchemaRegistry {
         }
     }
 
-    fn derive_name(fields: &[String], context: Option<&str>) -> String {
+    fn derive_name(&self, fields: &[String], context: Option<&str>) -> String {
+        if let Some(namer) = &self.namer {
+            return namer(fields, context);
+        }
+
         // Use context if provided (singularize + PascalCase)
         if let Some(ctx) = context {
-            return Self::singularize(ctx);
+            return self.singularisation.schema_name(ctx);
+        }
+
+        if fields.is_empty() {
+            return "Marker".to_string();
         }
 
         // Infer from field patterns
@@ -127,33 +358,21 @@ impl SchemaRegistry {
         }
     }
 
-    fn singularize(s: &str) -> String {
-        let singular = if let Some(stripped) = s.strip_suffix("ies") {
-            format!("{}y", stripped)
-        } else if s.ends_with('s') && !s.ends_with("ss") && s.len() > 1 {
-            s[..s.len() - 1].to_string()
-        } else {
-            s.to_string()
-        };
+    /// Schemas registered so far, keyed by their sorted, comma-joined
+    /// field names (in field order when field order is strict)
+    pub fn schemas(&self) -> &HashMap<String, SchemaInfo> {
+        &self.schemas
+    }
 
-        // PascalCase
-        let mut result = String::new();
-        let mut cap_next = true;
-        for c in singular.chars() {
-            if c == '_' || c == '-' {
-                cap_next = true;
-            } else if cap_next {
-                result.push(c.to_ascii_uppercase());
-                cap_next = false;
-            } else {
-                result.push(c.to_ascii_lowercase());
-            }
-        }
-        result
+    /// All schema definitions as `!def` lines, sorted by name
+    pub fn definitions(&self, delimiter: Delimiter) -> Vec<String> {
+        self.definition_lines(delimiter, false)
     }
 
-    /// Get all schema definitions as !def lines
-    fn definitions(&self, delimiter: Delimiter) -> Vec<String> {
+    /// Get all schema definitions as `!def` lines, or as the lines of a
+    /// `!schemas` block (header plus one `Name fields...` line each) when
+    /// `block` is set. The caller emits the closing `---`.
+    pub(crate) fn definition_lines(&self, delimiter: Delimiter, block: bool) -> Vec<String> {
         let mut defs: Vec<_> = self.schemas.values().collect();
         defs.sort_by(|a, b| a.name.cmp(&b.name)); // Deterministic order
 
@@ -162,24 +381,70 @@ impl SchemaRegistry {
             Delimiter::Space => " ",
         };
 
-        defs.iter()
-            .map(|s| format!("!def {} {}", s.name, s.fields.join(field_sep)))
-            .collect()
+        let lines = defs.iter().map(|s| {
+            let fields = declared_fields(&s.fields, &s.nullable, &self.defaults);
+            format!("{} {}", s.name, fields.join(field_sep))
+        });
+        if block {
+            std::iter::once("!schemas".to_string())
+                .chain(lines)
+                .collect()
+        } else {
+            lines.map(|line| format!("!def {}", line)).collect()
+        }
     }
 
-    fn is_empty(&self) -> bool {
+    /// Number of distinct schema names (after merging)
+    pub fn len(&self) -> usize {
+        let names: std::collections::HashSet<&str> =
+            self.schemas.values().map(|s| s.name.as_str()).collect();
+        names.len()
+    }
+
+    /// Whether no schema has been registered
+    pub fn is_empty(&self) -> bool {
         self.schemas.is_empty()
     }
 }
 
+/// Character used for each indentation level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndentKind {
+    /// `indent_size` spaces per level
+    Space,
+    /// One tab per level
+    Tab,
+}
+
 /// Formatter for converting JSON values to Tauq syntax
 pub struct Formatter {
     delimiter: Delimiter,
     minify: bool,
     indent_size: usize,
+    indent_char: IndentKind,
     schema_strategy: SchemaStrategy,
+    version_tag: bool,
+    schemas_block_threshold: Option<usize>,
+    singularisation: SingularisationConfig,
+    max_inline_depth: usize,
+    heredoc_threshold: Option<usize>,
+    field_defaults: HashMap<String, Value>,
+    schema_threshold: usize,
+    schema_namer: Option<Arc<SchemaNamer>>,
+    stable_output: bool,
+    alignment: bool,
+    import_base_dir: Option<std::path::PathBuf>,
+    strict_field_order: bool,
 }
 
+/// `!require` line pinning output to the running major.minor version
+const VERSION_TAG: &str = concat!(
+    "!require tauq >= ",
+    env!("CARGO_PKG_VERSION_MAJOR"),
+    ".",
+    env!("CARGO_PKG_VERSION_MINOR")
+);
+
 impl Formatter {
     /// Create formatter with sensible defaults
     /// - Adaptive schema usage (uses !def when it saves tokens)
@@ -190,7 +455,20 @@ impl Formatter {
             delimiter: Delimiter::Space,
             minify: false,
             indent_size: 2,
+            indent_char: IndentKind::Space,
             schema_strategy: SchemaStrategy::Adaptive,
+            version_tag: false,
+            schemas_block_threshold: None,
+            singularisation: SingularisationConfig::default(),
+            max_inline_depth: usize::MAX,
+            heredoc_threshold: Some(80),
+            field_defaults: HashMap::new(),
+            schema_threshold: 2,
+            schema_namer: None,
+            stable_output: false,
+            alignment: false,
+            import_base_dir: None,
+            strict_field_order: false,
         }
     }
 
@@ -224,6 +502,120 @@ impl Formatter {
     /// Set custom indentation size
     pub fn with_indent(mut self, size: usize) -> Self {
         self.indent_size = size;
+        self.indent_char = IndentKind::Space;
+        self
+    }
+
+    /// Indent with one tab per level instead of spaces
+    pub fn with_tab_indent(mut self) -> Self {
+        self.indent_char = IndentKind::Tab;
+        self
+    }
+
+    /// Emit a leading `!require tauq >= X.Y` line pinning the format version
+    pub fn with_version_tag(mut self) -> Self {
+        self.version_tag = true;
+        self
+    }
+
+    /// Emit definitions as a single `!schemas` block once a document has at
+    /// least `n` schemas. Top-level tables keep `!def`, since their rows
+    /// follow the definition directly.
+    pub fn with_schemas_block_threshold(mut self, n: usize) -> Self {
+        self.schemas_block_threshold = Some(n);
+        self
+    }
+
+    /// Customise how array keys become schema names (`people` → `Person`)
+    pub fn with_singularisation(mut self, config: SingularisationConfig) -> Self {
+        self.singularisation = config;
+        self
+    }
+
+    /// Write objects nested deeper than `depth` levels on a single line
+    /// (`{ key val key2 val2 }`), bounding the indentation of deep documents.
+    /// Top-level fields are level 0; the default is unlimited.
+    pub fn with_max_inline_depth(mut self, depth: usize) -> Self {
+        self.max_inline_depth = depth;
+        self
+    }
+
+    /// Write field strings longer than `chars` characters, or spanning more
+    /// than two lines, as `<<<EOT` heredocs. The default is 80; heredocs are
+    /// never used in minified output or inside table rows.
+    pub fn with_heredoc_threshold(mut self, chars: usize) -> Self {
+        self.heredoc_threshold = Some(chars);
+        self
+    }
+
+    /// Always write strings as quoted literals
+    pub fn without_heredocs(mut self) -> Self {
+        self.heredoc_threshold = None;
+        self
+    }
+
+    /// Declare `field=value` in every schema with this field, leaving the
+    /// value out of rows that end with it. Only scalar defaults are used.
+    pub fn with_field_default(mut self, field: &str, value: Value) -> Self {
+        if !value.is_array() && !value.is_object() {
+            self.field_defaults.insert(field.to_string(), value);
+        }
+        self
+    }
+
+    /// Minimum number of objects an array needs before it is written as
+    /// schema rows. The default is 2; `1` also tabulates single-element
+    /// arrays and `usize::MAX` disables schema detection entirely.
+    pub fn with_schema_threshold(mut self, n: usize) -> Self {
+        self.schema_threshold = n;
+        self
+    }
+
+    /// Name schemas with `namer` instead of the built-in heuristics. It is
+    /// called with the schema's fields and the key of the array being
+    /// tabulated (`None` for top-level tables); clashing names get a
+    /// numeric suffix.
+    pub fn with_schema_namer(
+        mut self,
+        namer: impl Fn(&[String], Option<&str>) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.schema_namer = Some(Arc::new(namer));
+        self
+    }
+
+    /// Sort object keys alphabetically, so the output does not depend on the
+    /// order keys were inserted in. Schema fields follow the same order.
+    /// [`format_to_writer`](Self::format_to_writer) then copies the value
+    /// before writing it.
+    pub fn with_stable_output(mut self, stable: bool) -> Self {
+        self.stable_output = stable;
+        self
+    }
+
+    /// Pad schema row values so each column lines up across rows. Numbers
+    /// are right-aligned, everything else left-aligned; quoted strings are
+    /// measured without their quotes. Minified output is never aligned.
+    pub fn with_alignment(mut self, align: bool) -> Self {
+        self.alignment = align;
+        self
+    }
+
+    /// Treat field order as part of a schema's identity: arrays whose objects
+    /// list the same keys in different orders get separate `!def`s, each in
+    /// its objects' key order, and an array only becomes a table when every
+    /// object lists its keys in the same order. Only meaningful with
+    /// serde_json's `preserve_order` feature, which keeps keys in input order.
+    pub fn with_strict_field_order(mut self, strict: bool) -> Self {
+        self.strict_field_order = strict;
+        self
+    }
+
+    /// Resolve `!import` paths relative to `dir` when
+    /// [`format_source`](Self::format_source) parses its input. Imports may
+    /// reach outside `dir`, as they could when `tauq prettify` resolved them
+    /// from the working directory.
+    pub fn with_import_base_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.import_base_dir = Some(dir.into());
         self
     }
 
@@ -252,7 +644,7 @@ impl Formatter {
     }
 
     /// Get the value separator string based on delimiter
-    fn value_sep(&self) -> &'static str {
+    pub(crate) fn value_sep(&self) -> &'static str {
         match self.delimiter {
             Delimiter::Space => " ",
             Delimiter::Comma => ",",
@@ -261,67 +653,587 @@ impl Formatter {
 
     /// Format JSON value to Tauq syntax
     pub fn format(&self, value: &Value) -> String {
-        let mut registry = SchemaRegistry::new();
+        self.tagged(self.format_document(value))
+    }
+
+    /// Format JSON value to Tauq syntax, replaying comments recorded by
+    /// [`Parser::comments`](super::Parser::comments); see
+    /// [`json_to_tauq_annotated`]
+    pub fn format_annotated(&self, value: &Value, comments: &HashMap<usize, String>) -> String {
+        let formatted = self.format(value);
+        let mut pending: Vec<(usize, &String)> = comments.iter().map(|(&l, c)| (l, c)).collect();
+        pending.sort();
+        let mut pending = pending.into_iter().peekable();
+
+        let mut lines = Vec::new();
+        let mut heredoc: Option<&str> = None;
+        for content in formatted.lines() {
+            if heredoc.is_none() {
+                while let Some((_, text)) = pending.next_if(|(l, _)| *l <= lines.len() + 1) {
+                    lines.push(format!("#{}", text));
+                }
+            }
+            match heredoc {
+                Some(delimiter) if content == delimiter => heredoc = None,
+                Some(_) => {}
+                None => {
+                    heredoc = content
+                        .rsplit_once("<<<")
+                        .map(|(_, d)| d)
+                        .filter(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_alphanumeric()));
+                }
+            }
+            lines.push(content.to_string());
+        }
+        lines.extend(pending.map(|(_, text)| format!("#{}", text)));
+        lines.join("\n")
+    }
+
+    /// Parse Tauq source and format it again, keeping comments on lines of
+    /// their own. Imported files are resolved against the directory set by
+    /// [`with_import_base_dir`](Self::with_import_base_dir) and their data
+    /// is inlined into the output.
+    pub fn format_source(&self, source: &str) -> Result<String, ParseError> {
+        let source = strip_bom(source);
+        let mut parser = match &self.import_base_dir {
+            Some(dir) => super::Parser::new_with_context(
+                source,
+                super::parser::Context::for_dir(dir.clone()).unconfined(),
+            ),
+            None => super::Parser::new(source),
+        };
+        let value = parser.parse()?;
+        Ok(self.format_annotated(&value, parser.comments()))
+    }
+
+    /// Prefix a finished document with the version tag, if enabled
+    pub(crate) fn tagged(&self, body: String) -> String {
+        if self.version_tag {
+            let sep = if self.minify { ";" } else { "\n" };
+            format!("{VERSION_TAG}{sep}{body}")
+        } else {
+            body
+        }
+    }
+
+    /// Convert JSON value to TOML instead of Tauq; see [`TomlFormatter`]
+    pub fn to_toml(&self, value: &Value) -> String {
+        TomlFormatter::new().format(&self.stable(value))
+    }
+
+    /// `value` with sorted keys when stable output is enabled
+    fn stable<'a>(&self, value: &'a Value) -> Cow<'a, Value> {
+        if self.stable_output {
+            Cow::Owned(sorted_keys(value))
+        } else {
+            Cow::Borrowed(value)
+        }
+    }
+
+    /// Convert an array of flat, uniform objects to CSV with a header row.
+    ///
+    /// Fields are quoted per RFC 4180, and strings that would otherwise read
+    /// back as a number, boolean or empty (`null`) field are quoted too, so
+    /// [`csv_to_tauq`] restores the same value. Returns `None` for anything
+    /// else: non-arrays, empty arrays, rows with differing fields, or nested
+    /// objects and arrays.
+    pub fn to_csv(&self, value: &Value) -> Option<String> {
+        let value = self.stable(value);
+        let rows = value.as_array().filter(|rows| !rows.is_empty())?;
+        let header: Vec<&String> = rows[0].as_object()?.keys().collect();
+        if header.is_empty() {
+            return None;
+        }
+
+        let mut out = header
+            .iter()
+            .map(|k| csv_field(k))
+            .collect::<Vec<_>>()
+            .join(",");
+        for row in rows {
+            let obj = row.as_object().filter(|obj| obj.len() == header.len())?;
+            let mut fields = Vec::with_capacity(header.len());
+            for key in &header {
+                fields.push(match obj.get(key.as_str())? {
+                    Value::Null => String::new(),
+                    Value::Bool(b) => b.to_string(),
+                    Value::Number(n) => n.to_string(),
+                    Value::String(s) => csv_field(s),
+                    Value::Array(_) | Value::Object(_) => return None,
+                });
+            }
+            out.push('\n');
+            out.push_str(&fields.join(","));
+        }
+        Some(out)
+    }
+
+    /// Format JSON value to Tauq syntax, writing directly to `writer`.
+    ///
+    /// Schemas are still collected from the whole value first, but rows of
+    /// top-level tables, elements of top-level arrays and the fields of a
+    /// top-level object are written one at a time, so the document is never
+    /// held in memory. The writer is flushed after each one, so a reader on
+    /// the other end sees rows as they are produced. Output is identical to
+    /// [`format`](Self::format).
+    pub fn format_to_writer<W: io::Write>(&self, value: &Value, writer: &mut W) -> io::Result<()> {
+        self.write_tagged(writer, |out| self.write_document(value, out))
+    }
+
+    /// Write a document rendered by the serializer: the registry's
+    /// definitions, then `rows`, flushing after each one
+    pub(crate) fn write_rows<W: io::Write>(
+        &self,
+        registry: &SchemaRegistry,
+        rows: &[String],
+        writer: &mut W,
+    ) -> io::Result<()> {
         let sep = if self.minify { ";" } else { "\n" };
+        self.write_tagged(writer, |out| {
+            self.write_definitions(registry, out)?;
+            for (i, row) in rows.iter().enumerate() {
+                if i > 0 {
+                    out.write_str(sep)?;
+                }
+                out.write_str(row)?;
+                out.end_row()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Run `write` on `writer` after the version tag, if enabled, returning
+    /// the I/O error that stopped it
+    fn write_tagged<W: io::Write>(
+        &self,
+        writer: &mut W,
+        write: impl FnOnce(&mut dyn RowWrite) -> fmt::Result,
+    ) -> io::Result<()> {
+        let mut out = IoWriter {
+            inner: writer,
+            error: None,
+        };
+        let result = if self.version_tag {
+            let sep = if self.minify { ";" } else { "\n" };
+            write!(out, "{VERSION_TAG}{sep}").and_then(|_| write(&mut out))
+        } else {
+            write(&mut out)
+        };
+        if result.is_err() {
+            return Err(out
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error")));
+        }
+        out.inner.flush()
+    }
+
+    /// Convert NDJSON (one JSON value per line) to Tauq, writing to `writer`
+    ///
+    /// The input is read twice, one line at a time: first to check whether
+    /// every record is an object with the same fields, then to write them,
+    /// so only one record is held in memory. Uniform records become a
+    /// top-level table; anything else is written as an array with one
+    /// inline object per record. Blank lines are skipped. Output is identical
+    /// to [`format`](Self::format) on the array of records, except that
+    /// columns are never aligned.
+    ///
+    /// # Errors
+    /// Returns `TauqError::Parse` for a line that is not valid JSON, and
+    /// `TauqError::Io` if writing fails.
+    pub fn format_ndjson_to_writer<W: io::Write>(
+        &self,
+        ndjson: &str,
+        writer: &mut W,
+    ) -> Result<(), TauqError> {
+        let table = self.ndjson_table(ndjson)?;
+        let mut out = IoWriter {
+            inner: writer,
+            error: None,
+        };
+        if self.write_ndjson(ndjson, table, &mut out).is_err() {
+            return Err(out
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))
+                .into());
+        }
+        out.inner.flush()?;
+        Ok(())
+    }
+
+    /// Fields and nullable fields of the table the NDJSON records form, if
+    /// they are uniform enough for one
+    fn ndjson_table(&self, ndjson: &str) -> Result<Option<TableShape>, TauqError> {
+        let mut fields: Option<Vec<String>> = None;
+        let mut nullable = HashSet::new();
+        let mut uniform = self.uses_schemas();
+        let mut count = 0;
+        for record in ndjson_records(ndjson) {
+            let record = record?;
+            count += 1;
+            if !uniform {
+                continue;
+            }
+            let record = self.stable(&record);
+            let Some(obj) = record.as_object() else {
+                uniform = false;
+                continue;
+            };
+            match &fields {
+                None => {
+                    let keys: Vec<String> = obj.keys().cloned().collect();
+                    uniform = keys.iter().all(|k| self.is_valid_identifier(k));
+                    fields = Some(keys);
+                }
+                Some(keys) => {
+                    uniform = obj.len() == keys.len() && keys.iter().all(|k| obj.contains_key(k));
+                }
+            }
+            nullable.extend(
+                obj.iter()
+                    .filter(|(_, v)| v.is_null())
+                    .map(|(k, _)| k.clone()),
+            );
+        }
+        // Same rules as `top_level_table_fields`; a single record would read
+        // back as a bare object rather than a table
+        Ok(fields
+            .filter(|fields| {
+                uniform
+                    && count >= self.schema_threshold.max(2)
+                    && (!fields.is_empty() || !self.minify)
+            })
+            .map(|fields| (fields, nullable)))
+    }
+
+    fn write_ndjson(
+        &self,
+        ndjson: &str,
+        table: Option<TableShape>,
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
+        let sep = if self.minify { ";" } else { "\n" };
+        if self.version_tag {
+            write!(out, "{VERSION_TAG}{sep}")?;
+        }
+        // Every line parsed in the first pass
+        let records = ndjson_records(ndjson).filter_map(Result::ok);
+        let mut registry = self.new_registry();
+
+        if let Some((fields, nullable)) = table {
+            let schema_name = registry.get_or_create(&fields, None);
+            self.write_table_def(&fields, &nullable, &schema_name, out)?;
+            for record in records {
+                if let Some(obj) = self.stable(&record).as_object() {
+                    write!(out, "{}{}", sep, self.format_schema_row(obj, &fields))?;
+                    out.end_row()?;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut empty = true;
+        for record in records {
+            let element = self.format_heterogeneous_element(&self.stable(&record), &registry, 0);
+            match (empty, self.minify) {
+                (true, _) => write!(out, "[")?,
+                (false, true) => out.write_char(' ')?,
+                (false, false) => {}
+            }
+            if self.minify {
+                out.write_str(&element)?;
+            } else {
+                write!(out, "\n{}", element)?;
+            }
+            out.end_row()?;
+            empty = false;
+        }
+        match (empty, self.minify) {
+            (true, _) => out.write_str("[]"),
+            (false, true) => out.write_char(']'),
+            (false, false) => out.write_str("\n]"),
+        }
+    }
+
+    fn format_document(&self, value: &Value) -> String {
+        let mut out = String::new();
+        self.write_document(value, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    fn write_document(&self, value: &Value, out: &mut dyn RowWrite) -> fmt::Result {
+        let value = &*self.stable(value);
+        let mut registry = self.new_registry();
 
         // Check if this is a top-level array of uniform objects
         // Use schema syntax with implicit !use (rows follow !def directly)
         if let Value::Array(arr) = value {
-            if let Some(fields) = self.detect_uniform_objects(arr) {
-                let schema_name = registry.get_or_create(&fields, None);
-                return self.format_top_level_table(arr, &fields, &schema_name);
-            }
-            // Handle heterogeneous array at top level
-            return self.format_heterogeneous_array(arr, &registry, 0);
+            return match self.top_level_table_fields(arr) {
+                // A single row would read back as a bare object, so a
+                // one-element table is written as `[!use Name ...]`
+                Some(fields) if arr.len() == 1 => {
+                    let schema_name = registry.get_or_create(&fields, None);
+                    registry.mark_nullable(&fields, nullable_fields(arr, &fields));
+                    self.write_definitions(&registry, out)?;
+                    self.write_schema_array(arr, &schema_name, &fields, 1, out)
+                }
+                Some(fields) => {
+                    let schema_name = registry.get_or_create(&fields, None);
+                    let nullable = nullable_fields(arr, &fields);
+                    self.write_top_level_table(arr, &fields, &nullable, &schema_name, out)
+                }
+                // Handle heterogeneous array at top level
+                None => self.write_heterogeneous_array(arr, &registry, 0, out),
+            };
         }
 
         // For objects/other values: collect schemas from nested arrays first
-        self.collect_schemas(value, &mut registry, None);
-
-        // Format the body
-        let body = self.format_with_schemas(value, &registry, 0, None);
+        self.collect_schemas(value, &mut registry, None, 0);
+        self.write_definitions(&registry, out)?;
+
+        // Format the body, one top-level field at a time
+        let obj = match value {
+            Value::Object(obj) => obj,
+            // A lone bareword would read as a key without a value
+            Value::String(s) => return out.write_str(&self.quote_string(s)),
+            _ => return out.write_str(&self.format_with_schemas(value, &registry, 0, None)),
+        };
+        if obj.is_empty() {
+            // An empty document would read back as `[]`
+            return out.write_str("{}");
+        }
+        let sep = if self.minify { ";" } else { "\n" };
+        for (i, (key, val)) in obj.iter().enumerate() {
+            if i > 0 {
+                out.write_str(sep)?;
+            }
+            match val {
+                Value::Array(arr) => {
+                    write!(out, "{} ", self.format_key(key))?;
+                    self.write_array_with_schemas(arr, &registry, 1, Some(key), out)?;
+                }
+                _ => out.write_str(&self.format_field_with_schemas(key, val, &registry, 0))?,
+            }
+            out.end_row()?;
+        }
+        Ok(())
+    }
 
-        // If we have schemas, emit !def declarations, ---, then body
+    /// Write the registry's definitions followed by `---`, if any
+    fn write_definitions(&self, registry: &SchemaRegistry, out: &mut dyn RowWrite) -> fmt::Result {
         if registry.is_empty() {
-            body
+            return Ok(());
+        }
+        let sep = if self.minify { ";" } else { "\n" };
+        let block = self
+            .schemas_block_threshold
+            .is_some_and(|n| registry.len() >= n);
+        let defs = registry.definition_lines(self.delimiter, block).join(sep);
+        write!(out, "{}{sep}---{sep}", defs)
+    }
+
+    /// Prefix `body` with the registry's definitions and `---`, if any
+    pub(crate) fn with_definitions(&self, registry: &SchemaRegistry, body: String) -> String {
+        let mut out = String::new();
+        self.write_definitions(registry, &mut out)
+            .expect("writing to a String cannot fail");
+        out + &body
+    }
+
+    /// A fresh schema registry using this formatter's naming and defaults
+    pub(crate) fn new_registry(&self) -> SchemaRegistry {
+        let mut registry = SchemaRegistry::with_singularisation(self.singularisation.clone())
+            .with_strict_field_order(self.strict_field_order);
+        registry.namer = self.schema_namer.clone();
+        registry.defaults = self.rendered_defaults();
+        registry
+    }
+
+    /// Whether uniform arrays may be written as schema rows
+    pub(crate) fn uses_schemas(&self) -> bool {
+        self.schema_strategy != SchemaStrategy::Never
+    }
+
+    /// Minimum array length for schema rows (see `with_schema_threshold`)
+    pub(crate) fn schema_threshold(&self) -> usize {
+        self.schema_threshold
+    }
+
+    /// Whether output is minified (`;` separators, no indentation)
+    pub(crate) fn is_minified(&self) -> bool {
+        self.minify
+    }
+
+    /// Indentation for `depth` levels
+    pub(crate) fn indent(&self, depth: usize) -> String {
+        match self.indent_char {
+            IndentKind::Space => " ".repeat(depth * self.indent_size),
+            IndentKind::Tab => "\t".repeat(depth),
+        }
+    }
+
+    /// Infer the schemas this formatter would define for `value`, without
+    /// formatting any data. Results are sorted by name.
+    pub fn infer_schemas(&self, value: &Value) -> Vec<InferredSchema> {
+        let mut registry = self.new_registry();
+        let top_level = match value {
+            Value::Array(arr) => self.top_level_table_fields(arr),
+            _ => None,
+        };
+        if let Some(fields) = &top_level {
+            registry.get_or_create(fields, None);
         } else {
-            let defs = registry.definitions(self.delimiter).join(sep);
-            format!("{}{sep}---{sep}{body}", defs)
+            self.collect_schemas(value, &mut registry, None, 0);
+        }
+
+        let mut inferred: HashMap<String, SchemaStats> = HashMap::new();
+        match (value, top_level) {
+            (Value::Array(arr), Some(fields)) => {
+                self.observe_table(arr, &fields, None, &mut registry, &mut inferred);
+                for item in arr {
+                    self.observe_rows(item, &mut registry, &mut inferred, 0);
+                }
+            }
+            _ => self.observe_rows(value, &mut registry, &mut inferred, 0),
+        }
+
+        let mut schemas: Vec<InferredSchema> = inferred
+            .into_values()
+            .map(|stats| InferredSchema {
+                name: stats.name,
+                fields: stats.fields,
+                types: stats.evidence.into_iter().map(Option::flatten).collect(),
+                occurrences: stats.occurrences,
+            })
+            .collect();
+        schemas.sort_by(|a, b| a.name.cmp(&b.name));
+        schemas
+    }
+
+    /// Walk `value` the same way as [`collect_schemas`](Self::collect_schemas),
+    /// recording rows of every uniform array
+    fn observe_rows(
+        &self,
+        value: &Value,
+        registry: &mut SchemaRegistry,
+        inferred: &mut HashMap<String, SchemaStats>,
+        depth: usize,
+    ) {
+        match value {
+            Value::Object(_) if self.is_collapsed(depth) => {}
+            Value::Object(obj) => {
+                for (key, val) in obj {
+                    if let Value::Array(arr) = val
+                        && let Some(fields) = self.nested_table_fields(arr)
+                    {
+                        self.observe_table(arr, &fields, Some(key), registry, inferred);
+                    }
+                    self.observe_rows(val, registry, inferred, depth + 1);
+                }
+            }
+            Value::Array(arr) => {
+                for item in arr {
+                    self.observe_rows(item, registry, inferred, depth);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record one uniform array's rows against its schema
+    fn observe_table(
+        &self,
+        arr: &[Value],
+        fields: &[String],
+        context: Option<&str>,
+        registry: &mut SchemaRegistry,
+        inferred: &mut HashMap<String, SchemaStats>,
+    ) {
+        let name = registry.get_or_create(fields, context);
+        let stats = inferred.entry(name.clone()).or_insert_with(|| SchemaStats {
+            name,
+            fields: fields.to_vec(),
+            evidence: vec![None; fields.len()],
+            occurrences: 0,
+        });
+        stats.occurrences += arr.len();
+
+        for row in arr.iter().filter_map(|v| v.as_object()) {
+            for (slot, field) in stats.evidence.iter_mut().zip(fields) {
+                // Null or missing values say nothing about the type
+                if let Some(ty) = row
+                    .get(field)
+                    .and_then(|v| self.annotation(v, field, registry))
+                {
+                    unify_annotation(slot, ty);
+                }
+            }
+        }
+    }
+
+    /// Type annotation for a single field value
+    fn annotation(
+        &self,
+        value: &Value,
+        field: &str,
+        registry: &mut SchemaRegistry,
+    ) -> Option<String> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some("bool".to_string()),
+            Value::Number(n) if n.is_f64() => Some("float".to_string()),
+            Value::Number(_) => Some("int".to_string()),
+            Value::String(_) => Some("string".to_string()),
+            Value::Array(arr) => self
+                .nested_table_fields(arr)
+                .map(|fields| format!("[{}]", registry.get_or_create(&fields, Some(field)))),
+            Value::Object(_) => None,
         }
     }
 
     /// Collect schemas from nested arrays (first pass)
+    ///
+    /// `depth` is the nesting level of `value`; objects that will be
+    /// collapsed onto one line contribute no schemas.
     fn collect_schemas(
         &self,
         value: &Value,
         registry: &mut SchemaRegistry,
         _context: Option<&str>,
+        depth: usize,
     ) {
         match value {
+            Value::Object(_) if self.is_collapsed(depth) => {}
             Value::Object(obj) => {
                 for (key, val) in obj {
                     if let Value::Array(arr) = val {
-                        if let Some(fields) = self.detect_uniform_objects(arr) {
+                        if let Some(fields) = self.nested_table_fields(arr) {
                             registry.get_or_create(&fields, Some(key));
+                            registry.mark_nullable(&fields, nullable_fields(arr, &fields));
                         }
                         // Recurse into array elements
                         for item in arr {
-                            self.collect_schemas(item, registry, Some(key));
+                            self.collect_schemas(item, registry, Some(key), depth + 1);
                         }
                     } else {
-                        self.collect_schemas(val, registry, Some(key));
+                        self.collect_schemas(val, registry, Some(key), depth + 1);
                     }
                 }
             }
             Value::Array(arr) => {
                 for item in arr {
-                    self.collect_schemas(item, registry, _context);
+                    self.collect_schemas(item, registry, _context, depth);
                 }
             }
             _ => {}
         }
     }
 
+    /// Whether an object at nesting level `depth` is written on one line
+    fn is_collapsed(&self, depth: usize) -> bool {
+        !self.minify && depth > self.max_inline_depth
+    }
+
     /// Format value using collected schemas (second pass)
     fn format_with_schemas(
         &self,
@@ -365,13 +1277,14 @@ impl Formatter {
             Value::Array(arr) => {
                 self.format_array_with_schemas(arr, registry, depth + 1, Some(key))
             }
+            Value::String(s) => self.format_field_string(s),
             other => self.format_value_standard(other, depth),
         };
 
         if self.minify {
             format!("{} {}", formatted_key, formatted_value)
         } else {
-            let indent = " ".repeat(depth * self.indent_size);
+            let indent = self.indent(depth);
             format!("{}{} {}", indent, formatted_key, formatted_value)
         }
     }
@@ -386,6 +1299,9 @@ impl Formatter {
         if obj.is_empty() {
             return "{}".to_string();
         }
+        if self.is_collapsed(depth + 1) {
+            return self.format_object_single_line(obj);
+        }
 
         let mut fields = Vec::new();
         for (key, value) in obj {
@@ -395,7 +1311,7 @@ impl Formatter {
         if self.minify {
             format!("{{{}}}", fields.join(";"))
         } else {
-            let close_indent = " ".repeat(depth * self.indent_size);
+            let close_indent = self.indent(depth);
             format!("{{\n{}\n{}}}", fields.join("\n"), close_indent)
         }
     }
@@ -407,77 +1323,100 @@ impl Formatter {
         depth: usize,
         context: Option<&str>,
     ) -> String {
+        let mut out = String::new();
+        self.write_array_with_schemas(arr, registry, depth, context, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    fn write_array_with_schemas(
+        &self,
+        arr: &[Value],
+        registry: &SchemaRegistry,
+        depth: usize,
+        context: Option<&str>,
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
         if arr.is_empty() {
-            return "[]".to_string();
+            return out.write_str("[]");
         }
 
         // Check if this array has uniform objects with a schema
-        if let Some(fields) = self.detect_uniform_objects(arr) {
+        if let Some(fields) = self.nested_table_fields(arr) {
             // Find the schema for these fields
-            let mut sorted = fields.clone();
-            sorted.sort();
-            let sig = sorted.join(",");
-
-            if let Some(schema_info) = registry.schemas.get(&sig) {
+            if let Some(schema_info) = registry.schemas.get(&registry.signature(&fields)) {
                 // Use !use inside array with schema rows
-                return self.format_schema_array(
+                return self.write_schema_array(
                     arr,
                     &schema_info.name,
                     &schema_info.fields,
                     depth,
+                    out,
                 );
             }
         }
 
         // No schema - check if heterogeneous objects
         if arr.iter().all(|v| v.is_object()) {
-            return self.format_heterogeneous_array(arr, registry, depth);
+            return self.write_heterogeneous_array(arr, registry, depth, out);
         }
 
-        // Regular array of primitives/mixed
+        // Regular array of primitives/mixed; objects among them stay braced
         let elements: Vec<String> = arr
             .iter()
-            .map(|v| self.format_with_schemas(v, registry, depth, context))
+            .map(|v| match v {
+                Value::Object(_) => self.format_primitive(v),
+                _ => self.format_with_schemas(v, registry, depth, context),
+            })
             .collect();
-        format!("[{}]", elements.join(" "))
+        write!(out, "[{}]", elements.join(" "))
     }
 
-    /// Format heterogeneous array (objects with different shapes)
-    fn format_heterogeneous_array(
+    /// Write heterogeneous array (objects with different shapes)
+    fn write_heterogeneous_array(
         &self,
         arr: &[Value],
         registry: &SchemaRegistry,
         depth: usize,
-    ) -> String {
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
         if arr.is_empty() {
-            return "[]".to_string();
+            return out.write_str("[]");
         }
 
-        let mut elements = Vec::new();
-        for item in arr {
-            match item {
-                Value::Object(obj) => {
-                    // Format each object inline
-                    let obj_str = self.format_inline_object(obj, registry, depth + 1);
-                    elements.push(obj_str);
-                }
-                other => {
-                    elements.push(self.format_primitive(other));
+        let item_indent = self.indent(depth);
+        out.write_char('[')?;
+        for (i, item) in arr.iter().enumerate() {
+            let element = self.format_heterogeneous_element(item, registry, depth);
+            if self.minify {
+                if i > 0 {
+                    out.write_char(' ')?;
                 }
+                out.write_str(&element)?;
+            } else {
+                write!(out, "\n{}{}", item_indent, element)?;
             }
+            out.end_row()?;
         }
 
         if self.minify {
-            format!("[{}]", elements.join(" "))
+            out.write_char(']')
         } else {
-            let item_indent = " ".repeat(depth * self.indent_size);
-            let close_indent = " ".repeat((depth.saturating_sub(1)) * self.indent_size);
-            let items = elements
-                .iter()
-                .map(|e| format!("{}{}", item_indent, e))
-                .collect::<Vec<_>>()
-                .join("\n");
-            format!("[\n{}\n{}]", items, close_indent)
+            let close_indent = self.indent(depth.saturating_sub(1));
+            write!(out, "\n{}]", close_indent)
+        }
+    }
+
+    /// Format one element of a heterogeneous array; objects are inlined
+    fn format_heterogeneous_element(
+        &self,
+        item: &Value,
+        registry: &SchemaRegistry,
+        depth: usize,
+    ) -> String {
+        match item {
+            Value::Object(obj) => self.format_inline_object(obj, registry, depth + 1),
+            other => self.format_primitive(other),
         }
     }
 
@@ -504,77 +1443,165 @@ impl Formatter {
         format!("{{ {} }}", fields.join(" "))
     }
 
-    /// Format array of uniform objects using !use inside array
-    fn format_schema_array(
+    /// Write array of uniform objects using !use inside array
+    fn write_schema_array(
         &self,
         arr: &[Value],
         schema_name: &str,
         fields: &[String],
         depth: usize,
-    ) -> String {
-        let value_sep = self.value_sep();
-        let mut rows = Vec::new();
-
-        for item in arr {
-            if let Some(obj) = item.as_object() {
-                let values: Vec<String> = fields
-                    .iter()
-                    .filter_map(|key| obj.get(key))
-                    .map(|v| self.format_value_for_row(v))
-                    .collect();
-                rows.push(values.join(value_sep));
-            }
-        }
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
+        let rows = self.format_schema_rows(arr, fields);
 
         if self.minify {
-            format!("[!use {};{}]", schema_name, rows.join(";"))
+            write!(out, "[!use {}", schema_name)?;
+            for row in rows {
+                write!(out, ";{}", row)?;
+                out.end_row()?;
+            }
+            out.write_char(']')
         } else {
-            let row_indent = " ".repeat(depth * self.indent_size);
-            let close_indent = " ".repeat((depth - 1) * self.indent_size);
-            let rows_str = rows
-                .iter()
-                .map(|r| format!("{}{}", row_indent, r))
-                .collect::<Vec<_>>()
-                .join("\n");
-            format!(
-                "[\n{}!use {}\n{}\n{}]",
-                row_indent, schema_name, rows_str, close_indent
-            )
+            let row_indent = self.indent(depth);
+            let close_indent = self.indent(depth - 1);
+            write!(out, "[\n{}!use {}", row_indent, schema_name)?;
+            for row in rows {
+                write!(out, "\n{}{}", row_indent, row)?;
+                out.end_row()?;
+            }
+            write!(out, "\n{}]", close_indent)
         }
     }
 
-    /// Format top-level array of uniform objects using !def (implicit !use)
-    fn format_top_level_table(
+    /// Write top-level array of uniform objects using !def (implicit !use)
+    fn write_top_level_table(
         &self,
         arr: &[Value],
         fields: &[String],
+        nullable: &HashSet<String>,
         schema_name: &str,
-    ) -> String {
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
         let sep = if self.minify { ";" } else { "\n" };
-        let value_sep = self.value_sep();
-        let field_sep = value_sep; // Use same separator for schema fields
+        self.write_table_def(fields, nullable, schema_name, out)?;
+
+        // Write rows (implicit !use after !def)
+        for row in self.format_schema_rows(arr, fields) {
+            write!(out, "{}{}", sep, row)?;
+            out.end_row()?;
+        }
+        Ok(())
+    }
 
-        // Generate schema definition
-        let def_line = format!("!def {} {}", schema_name, fields.join(field_sep));
+    /// Write the `!def` line that starts a top-level table
+    fn write_table_def(
+        &self,
+        fields: &[String],
+        nullable: &HashSet<String>,
+        schema_name: &str,
+        out: &mut dyn RowWrite,
+    ) -> fmt::Result {
+        let field_sep = self.value_sep(); // Use same separator for schema fields
+        let defaults = self.rendered_defaults();
+        write!(out, "!def {}", schema_name)?;
+        let declared = declared_fields(fields, nullable, &defaults);
+        if !declared.is_empty() {
+            write!(out, " {}", declared.join(field_sep))?;
+        }
+        Ok(())
+    }
 
-        // Generate rows (implicit !use after !def)
-        let mut rows = Vec::new();
-        for item in arr {
-            if let Some(obj) = item.as_object() {
-                let values: Vec<String> = fields
-                    .iter()
-                    .filter_map(|key| obj.get(key))
-                    .map(|v| self.format_value_for_row(v))
-                    .collect();
-                rows.push(values.join(value_sep));
+    /// Format the object rows of a schema array, aligning columns when
+    /// enabled. Alignment needs every row's values before the first row is
+    /// written, since a later row can widen a column.
+    fn format_schema_rows(&self, arr: &[Value], fields: &[String]) -> Vec<String> {
+        let rows = arr.iter().filter_map(|item| item.as_object());
+        if !self.alignment || self.minify {
+            return rows
+                .map(|obj| self.format_schema_row(obj, fields))
+                .collect();
+        }
+
+        let cells: Vec<Vec<(String, &Value)>> = rows
+            .map(|obj| {
+                self.schema_row_values(obj, fields)
+                    .into_iter()
+                    .map(|v| (self.format_value_for_row(v), v))
+                    .collect()
+            })
+            .collect();
+        let mut widths = vec![0; fields.len()];
+        for row in &cells {
+            for (col, (text, _)) in row.iter().enumerate() {
+                widths[col] = widths[col].max(cell_width(text));
             }
         }
 
-        format!("{}{}{}", def_line, sep, rows.join(sep))
+        cells
+            .into_iter()
+            .map(|row| {
+                let last = row.len().saturating_sub(1);
+                row.into_iter()
+                    .enumerate()
+                    .map(|(col, (text, value))| {
+                        let pad = " ".repeat(widths[col].saturating_sub(cell_width(&text)));
+                        if value.is_number() {
+                            pad + &text
+                        } else if col == last {
+                            text
+                        } else {
+                            text + &pad
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(self.value_sep())
+            })
+            .collect()
+    }
+
+    /// Format one schema row. Trailing values equal to the field default
+    /// are left out, as are trailing `null`s in fields without a default
+    /// (every field that is `null` somewhere is declared optional). At least
+    /// one value is kept so the row is not mistaken for a blank line.
+    fn format_schema_row(&self, obj: &serde_json::Map<String, Value>, fields: &[String]) -> String {
+        self.schema_row_values(obj, fields)
+            .into_iter()
+            .map(|v| self.format_value_for_row(v))
+            .collect::<Vec<_>>()
+            .join(self.value_sep())
+    }
+
+    /// The values written for one schema row, after trailing omissions
+    fn schema_row_values<'v>(
+        &self,
+        obj: &'v serde_json::Map<String, Value>,
+        fields: &[String],
+    ) -> Vec<&'v Value> {
+        let mut values: Vec<&Value> = fields.iter().filter_map(|key| obj.get(key)).collect();
+        while values.len() > 1 {
+            let last = values[values.len() - 1];
+            let omittable = match self.field_defaults.get(&fields[values.len() - 1]) {
+                Some(default) => last == default,
+                None => last.is_null(),
+            };
+            if !omittable {
+                break;
+            }
+            values.pop();
+        }
+        values
+    }
+
+    /// Defaults as they appear after `=` in a `!def` line
+    pub(crate) fn rendered_defaults(&self) -> HashMap<String, String> {
+        self.field_defaults
+            .iter()
+            .map(|(field, value)| (field.clone(), self.format_value_for_row(value)))
+            .collect()
     }
 
     /// Format a value for use in a schema row (handles quoting based on delimiter)
-    fn format_value_for_row(&self, value: &Value) -> String {
+    pub(crate) fn format_value_for_row(&self, value: &Value) -> String {
         match value {
             Value::Null => "null".to_string(),
             Value::Bool(b) => b.to_string(),
@@ -637,30 +1664,63 @@ impl Formatter {
         if obj.is_empty() {
             return "{}".to_string();
         }
+        if self.is_collapsed(depth + 1) {
+            return self.format_object_single_line(obj);
+        }
 
         let mut fields = Vec::new();
         for (key, value) in obj {
             let formatted_key = self.format_key(key);
-            let formatted_value = self.format_value_standard(value, depth + 1);
+            let formatted_value = match value {
+                Value::String(s) => self.format_field_string(s),
+                other => self.format_value_standard(other, depth + 1),
+            };
             fields.push(format!("{} {}", formatted_key, formatted_value));
         }
 
         if self.minify {
             format!("{{{}}}", fields.join(";"))
         } else {
-            let indent = " ".repeat((depth + 1) * self.indent_size);
+            let indent = self.indent(depth + 1);
             let sep = format!("\n{}", indent);
             format!(
                 "{{\n{}{}\n{}}}",
                 indent,
                 fields.join(&sep),
-                " ".repeat(depth * self.indent_size)
+                self.indent(depth)
             )
         }
     }
 
-    /// Detect if array contains uniform objects suitable for schema
-    fn detect_uniform_objects(&self, arr: &[Value]) -> Option<Vec<String>> {
+    /// Format an object and everything inside it on one line: `{ key val key2 val2 }`
+    fn format_object_single_line(&self, obj: &serde_json::Map<String, Value>) -> String {
+        if obj.is_empty() {
+            return "{}".to_string();
+        }
+        let fields: Vec<String> = obj
+            .iter()
+            .map(|(k, v)| format!("{} {}", self.format_key(k), self.format_single_line(v)))
+            .collect();
+        format!("{{ {} }}", fields.join(" "))
+    }
+
+    fn format_single_line(&self, value: &Value) -> String {
+        match value {
+            Value::Object(obj) => self.format_object_single_line(obj),
+            Value::Array(arr) => {
+                let items: Vec<String> = arr.iter().map(|v| self.format_single_line(v)).collect();
+                format!("[{}]", items.join(" "))
+            }
+            other => self.format_value_standard(other, 0),
+        }
+    }
+
+    /// Detect if array contains uniform objects suitable for schema.
+    ///
+    /// Returns the shared field names, in first-row order, when `arr` would be
+    /// written as `!def` rows under this formatter's settings. An array of
+    /// empty objects gives an empty list, a zero-field marker schema.
+    pub fn detect_uniform_objects(&self, arr: &[Value]) -> Option<Vec<String>> {
         // Check schema strategy
         match self.schema_strategy {
             SchemaStrategy::Never => return None,
@@ -669,8 +1729,8 @@ impl Formatter {
             }
         }
 
-        if arr.len() < 2 {
-            return None; // Need at least 2 objects for schema to be beneficial
+        if arr.is_empty() || arr.len() < self.schema_threshold {
+            return None; // Too few objects for a schema to be worthwhile
         }
 
         // All elements must be objects
@@ -682,10 +1742,20 @@ impl Formatter {
         }
 
         // Extract keys from first object (preserve insertion order with preserve_order feature)
+        // An array of empty objects gives a zero-field schema
         let first_keys: Vec<String> = objects[0].keys().cloned().collect();
 
-        if first_keys.is_empty() {
-            return None; // Empty objects
+        if !first_keys.iter().all(|k| self.is_valid_identifier(k)) {
+            // Field names are barewords; `key?` and `key=...` would also
+            // declare a different field
+            return None;
+        }
+
+        if self.strict_field_order {
+            let same_order = objects[1..]
+                .iter()
+                .all(|obj| obj.keys().eq(first_keys.iter()));
+            return same_order.then_some(first_keys);
         }
 
         // Check all objects have exactly the same keys (order-independent check)
@@ -700,6 +1770,20 @@ impl Formatter {
         Some(first_keys)
     }
 
+    /// Fields of a uniform array written as schema rows inside a document.
+    /// Zero-field rows are blank lines, which only read back in a top-level
+    /// table, so arrays of empty objects are left inline here.
+    fn nested_table_fields(&self, arr: &[Value]) -> Option<Vec<String>> {
+        self.detect_uniform_objects(arr)
+            .filter(|fields| !fields.is_empty())
+    }
+
+    /// Fields of a top-level array written as a table, if any
+    fn top_level_table_fields(&self, arr: &[Value]) -> Option<Vec<String>> {
+        self.detect_uniform_objects(arr)
+            .filter(|fields| !fields.is_empty() || (arr.len() > 1 && !self.minify))
+    }
+
     /// Format primitive values (no nested structures)
     fn format_primitive(&self, value: &Value) -> String {
         match value {
@@ -734,28 +1818,51 @@ impl Formatter {
         }
     }
 
-    /// Format a key (always more conservative quoting for keys)
-    fn format_key(&self, s: &str) -> String {
-        if self.is_valid_identifier(s) {
-            s.to_string()
-        } else {
-            self.quote_string(s)
+    /// Format a key (always more conservative quoting for keys)
+    pub(crate) fn format_key(&self, s: &str) -> String {
+        if self.is_valid_identifier(s) {
+            s.to_string()
+        } else {
+            self.quote_string(s)
+        }
+    }
+
+    /// Format a string in `key value` position, where a heredoc may stand
+    /// on lines of its own
+    pub(crate) fn format_field_string(&self, s: &str) -> String {
+        if self.is_safe_bareword(s) {
+            s.to_string()
+        } else {
+            self.heredoc(s).unwrap_or_else(|| self.quote_string(s))
+        }
+    }
+
+    /// Heredoc form of a long or multi-line string, if the formatter allows
+    /// one. Strings with invisible characters other than newlines and tabs
+    /// stay quoted so those characters remain escaped.
+    fn heredoc(&self, s: &str) -> Option<String> {
+        let threshold = self.heredoc_threshold?;
+        if self.minify || (s.matches('\n').count() <= 1 && s.chars().count() <= threshold) {
+            return None;
+        }
+        if s.chars().any(|c| c != '\n' && c != '\t' && is_invisible(c)) {
+            return None;
         }
+
+        // The first delimiter that does not occur as a line of the content
+        let delimiter = std::iter::once("EOT".to_string())
+            .chain((1..).map(|n| format!("EOT{}", n)))
+            .find(|d| !s.lines().any(|line| line == d))?;
+        Some(format!("<<<{delimiter}\n{s}\n{delimiter}"))
     }
 
     /// Quote a string with proper escaping
-    fn quote_string(&self, s: &str) -> String {
-        let escaped = s
-            .replace('\\', "\\\\")
-            .replace('"', "\\\"")
-            .replace('\n', "\\n")
-            .replace('\r', "\\r")
-            .replace('\t', "\\t");
-        format!("\"{}\"", escaped)
+    pub(crate) fn quote_string(&self, s: &str) -> String {
+        quote(s)
     }
 
     /// Check if string is a valid identifier (for keys)
-    fn is_valid_identifier(&self, s: &str) -> bool {
+    pub(crate) fn is_valid_identifier(&self, s: &str) -> bool {
         if s.is_empty() {
             return false;
         }
@@ -777,7 +1884,7 @@ impl Formatter {
     }
 
     /// Check if string can be a bareword value (more permissive than identifiers)
-    fn is_safe_bareword(&self, s: &str) -> bool {
+    pub(crate) fn is_safe_bareword(&self, s: &str) -> bool {
         if s.is_empty() {
             return false;
         }
@@ -813,7 +1920,7 @@ impl Formatter {
     }
 
     /// Check if string is safe as bareword in a row (considers delimiter)
-    fn is_safe_bareword_for_row(&self, s: &str) -> bool {
+    pub(crate) fn is_safe_bareword_for_row(&self, s: &str) -> bool {
         if !self.is_safe_bareword(s) {
             return false;
         }
@@ -827,6 +1934,40 @@ impl Formatter {
     }
 }
 
+/// Adapts an `io::Write` to `fmt::Write`, keeping the first I/O error
+struct IoWriter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+impl<W: io::Write> RowWrite for IoWriter<'_, W> {
+    fn end_row(&mut self) -> fmt::Result {
+        self.inner.flush().map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Output of the document writers, told when each row is complete
+trait RowWrite: fmt::Write {
+    /// Called after each table row, array element or top-level field
+    fn end_row(&mut self) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl RowWrite for String {}
+
 impl Default for Formatter {
     fn default() -> Self {
         Self::new()
@@ -840,6 +1981,585 @@ pub fn json_to_tauq(value: &Value) -> String {
     Formatter::new().format(value)
 }
 
+/// Format each value as its own document, separated by `===` lines, for
+/// [`compile_tauq_multi`](crate::compile_tauq_multi) to read back
+pub fn to_multi_doc(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(json_to_tauq)
+        .collect::<Vec<_>>()
+        .join("\n===\n")
+}
+
+/// Format JSON value to Tauq like [`json_to_tauq`], replaying comments
+/// recorded by [`Parser::comments`](super::Parser::comments).
+///
+/// Comments are placed by line number: the comment recorded for line N
+/// becomes line N of the output, ahead of whatever would otherwise be there.
+/// Formatting a file that is already in canonical form therefore puts each
+/// comment back above the line it annotated. Comments never split a heredoc;
+/// those past the end of the output are appended.
+pub fn json_to_tauq_annotated(value: &Value, comments: &HashMap<usize, String>) -> String {
+    Formatter::new().format_annotated(value, comments)
+}
+
+/// Write JSON as Tauq to `writer` with the same output as [`json_to_tauq`],
+/// without building the document in memory
+pub fn json_to_tauq_writer(value: &Value, writer: &mut impl io::Write) -> io::Result<()> {
+    Formatter::new().format_to_writer(value, writer)
+}
+
+/// Quote `s` as a double-quoted string; the escapes are valid in both Tauq and TOML
+/// Copy of `value` with the keys of every object in sorted order
+fn sorted_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sorted_keys(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(sorted_keys).collect()),
+        _ => value.clone(),
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // Invisible characters would not survive hand-editing
+            c if is_invisible(c) => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Display width of a row value, not counting the quotes around a string
+fn cell_width(text: &str) -> usize {
+    let width = text.chars().count();
+    if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        width - 2
+    } else {
+        width
+    }
+}
+
+/// Control and zero-width/bidi formatting characters, written as `\uXXXX`
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00AD}'
+                | '\u{200B}'..='\u{200F}'
+                | '\u{2028}'..='\u{202E}'
+                | '\u{2060}'..='\u{2069}'
+                | '\u{FEFF}'
+        )
+}
+
+/// Fields of a top-level table and those that are `null` in some row
+type TableShape = (Vec<String>, HashSet<String>);
+
+/// Fields of a uniform array that are `null` in at least one row
+fn nullable_fields(arr: &[Value], fields: &[String]) -> HashSet<String> {
+    fields
+        .iter()
+        .filter(|field| {
+            arr.iter()
+                .any(|row| row.get(field.as_str()).is_some_and(Value::is_null))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Field names as written in `!def`: `field=default` for fields with a
+/// default, `field?` for other optional ones
+pub(crate) fn declared_fields(
+    fields: &[String],
+    nullable: &HashSet<String>,
+    defaults: &HashMap<String, String>,
+) -> Vec<String> {
+    fields
+        .iter()
+        .map(|f| {
+            if let Some(default) = defaults.get(f) {
+                format!("{}={}", f, default)
+            } else if nullable.contains(f) {
+                format!("{}?", f)
+            } else {
+                f.clone()
+            }
+        })
+        .collect()
+}
+
+/// Per-schema accumulator for [`Formatter::infer_schemas`]
+struct SchemaStats {
+    name: String,
+    fields: Vec<String>,
+    /// `None`: no evidence yet; `Some(None)`: conflicting types
+    evidence: Vec<Option<Option<String>>>,
+    occurrences: usize,
+}
+
+/// Merge an observed annotation into a field's evidence (`int` + `float` widen to `float`)
+fn unify_annotation(slot: &mut Option<Option<String>>, ty: String) {
+    let is_numeric = |t: &str| t == "int" || t == "float";
+    *slot = Some(match slot.take() {
+        None => Some(ty),
+        Some(Some(prev)) if prev == ty => Some(prev),
+        Some(Some(prev)) if is_numeric(&prev) && is_numeric(&ty) => Some("float".to_string()),
+        Some(_) => None,
+    });
+}
+
+/// JSON → TOML converter
+///
+/// Objects become `[section]` tables and arrays of objects become `[[section]]`
+/// array-of-tables. Values TOML cannot express are reported in `# warning:`
+/// comments: `null` is omitted and mixed-type arrays are written inline.
+/// A root that is not an object is wrapped under a `value` key.
+#[derive(Debug, Clone, Default)]
+pub struct TomlFormatter;
+
+impl TomlFormatter {
+    /// Create a TOML formatter
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Convert `value` to a TOML document
+    pub fn format(&self, value: &Value) -> String {
+        let mut out = String::new();
+        match value {
+            Value::Object(map) => self.write_table(&mut out, &mut Vec::new(), map),
+            other => {
+                let mut root = Map::new();
+                root.insert("value".to_string(), other.clone());
+                self.write_table(&mut out, &mut Vec::new(), &root);
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out
+    }
+
+    /// Write the body of the table at `path`: plain keys first, since any
+    /// key after a header belongs to that header's table
+    fn write_table(&self, out: &mut String, path: &mut Vec<String>, map: &Map<String, Value>) {
+        let mut tables = Vec::new();
+        let mut table_arrays = Vec::new();
+
+        for (key, value) in map {
+            match value {
+                Value::Object(obj) => tables.push((key, obj)),
+                Value::Array(arr) if !arr.is_empty() && arr.iter().all(Value::is_object) => {
+                    table_arrays.push((key, arr))
+                }
+                Value::Null => {
+                    out.push_str(&format!(
+                        "# warning: '{}' is null, which TOML cannot represent; omitted\n",
+                        key
+                    ));
+                }
+                _ => {
+                    let mut warnings = Vec::new();
+                    let rendered = self.inline_value(value, &mut warnings);
+                    for warning in warnings {
+                        out.push_str(&format!("# warning: '{}': {}\n", key, warning));
+                    }
+                    out.push_str(&format!("{} = {}\n", toml_key(key), rendered));
+                }
+            }
+        }
+
+        for (key, obj) in tables {
+            path.push(key.clone());
+            self.write_header(out, path, false);
+            self.write_table(out, path, obj);
+            path.pop();
+        }
+
+        for (key, arr) in table_arrays {
+            path.push(key.clone());
+            for row in arr.iter().filter_map(Value::as_object) {
+                self.write_header(out, path, true);
+                self.write_table(out, path, row);
+            }
+            path.pop();
+        }
+    }
+
+    fn write_header(&self, out: &mut String, path: &[String], array: bool) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let dotted = path
+            .iter()
+            .map(|k| toml_key(k))
+            .collect::<Vec<_>>()
+            .join(".");
+        if array {
+            out.push_str(&format!("[[{}]]\n", dotted));
+        } else {
+            out.push_str(&format!("[{}]\n", dotted));
+        }
+    }
+
+    /// Render a value in inline form, collecting anything that had to be
+    /// dropped or approximated into `warnings`
+    fn inline_value(&self, value: &Value, warnings: &mut Vec<String>) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) if n.is_u64() && n.as_i64().is_none() => {
+                warnings.push(format!(
+                    "{} exceeds TOML's 64-bit integer range; written as a string",
+                    n
+                ));
+                quote(&n.to_string())
+            }
+            Value::Number(n) => {
+                let text = n.to_string();
+                if n.is_f64() && !text.contains(['.', 'e', 'E']) {
+                    format!("{}.0", text)
+                } else {
+                    text
+                }
+            }
+            Value::String(s) => quote(s),
+            Value::Array(arr) => {
+                let kinds: HashSet<&str> =
+                    arr.iter().filter(|v| !v.is_null()).map(toml_kind).collect();
+                if kinds.len() > 1 {
+                    warnings.push("mixed-type array written inline".to_string());
+                }
+                if arr.iter().any(Value::is_null) {
+                    warnings.push("null array elements omitted".to_string());
+                }
+                let items: Vec<String> = arr
+                    .iter()
+                    .filter(|v| !v.is_null())
+                    .map(|v| self.inline_value(v, warnings))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Object(map) => {
+                let mut items = Vec::new();
+                for (key, v) in map {
+                    if v.is_null() {
+                        warnings.push(format!("null field '{}' omitted", key));
+                    } else {
+                        items.push(format!(
+                            "{} = {}",
+                            toml_key(key),
+                            self.inline_value(v, warnings)
+                        ));
+                    }
+                }
+                if items.is_empty() {
+                    return "{}".to_string();
+                }
+                format!("{{ {} }}", items.join(", "))
+            }
+        }
+    }
+}
+
+/// Bare TOML key when possible, quoted otherwise
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// Element kind used to detect heterogeneous arrays
+fn toml_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_u64() && n.as_i64().is_none() => "string",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "table",
+    }
+}
+
+/// Convert JSON to TOML; see [`TomlFormatter`]
+pub fn to_toml(value: &Value) -> String {
+    TomlFormatter::new().format(value)
+}
+
+/// Convert NDJSON (one JSON value per line) to Tauq
+///
+/// Records that are objects with the same fields become a schema table;
+/// otherwise each record is written as its own inline object. See
+/// [`Formatter::format_ndjson_to_writer`].
+///
+/// # Example
+///
+/// ```
+/// let ndjson = "{\"id\":1,\"name\":\"Alice\"}\n{\"id\":2,\"name\":\"Bob\"}\n";
+/// let tauq = tauq::ndjson_to_tauq(ndjson).unwrap();
+/// assert_eq!(tauq, "!def Record id name\n1 Alice\n2 Bob");
+/// assert_eq!(tauq::tauq_to_ndjson(&tauq).unwrap(), ndjson);
+/// ```
+///
+/// # Errors
+/// Returns `TauqError::Parse` for a line that is not valid JSON.
+pub fn ndjson_to_tauq(ndjson: &str) -> Result<String, TauqError> {
+    let mut out = Vec::new();
+    ndjson_to_tauq_writer(ndjson, &mut out)?;
+    Ok(String::from_utf8(out).expect("formatter output is UTF-8"))
+}
+
+/// Convert NDJSON to Tauq line by line, writing to `writer`; see
+/// [`Formatter::format_ndjson_to_writer`]
+pub fn ndjson_to_tauq_writer(ndjson: &str, writer: &mut impl io::Write) -> Result<(), TauqError> {
+    Formatter::new().format_ndjson_to_writer(ndjson, writer)
+}
+
+/// Convert Tauq to NDJSON
+///
+/// A document that is an array, such as a schema table, gives one line per
+/// element; any other document is a single line. Every line ends with a
+/// newline.
+///
+/// # Errors
+/// Returns the error from [`compile_tauq`](crate::compile_tauq) if the
+/// source does not parse.
+pub fn tauq_to_ndjson(source: &str) -> Result<String, TauqError> {
+    let value = crate::compile_tauq(source)?;
+    let records = match &value {
+        Value::Array(items) => items.as_slice(),
+        other => std::slice::from_ref(other),
+    };
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&record.to_string());
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// The records of an NDJSON source, skipping blank lines
+fn ndjson_records(ndjson: &str) -> impl Iterator<Item = Result<Value, TauqError>> + '_ {
+    strip_bom(ndjson)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                ParseError::new(
+                    format!("Invalid JSON on line {}: {}", i + 1, e),
+                    Span::new(i + 1, e.column().max(1)),
+                )
+                .into()
+            })
+        })
+}
+
+/// Parse RFC 4180 CSV into Tauq, one object per record.
+///
+/// See [`csv_to_value`] for how columns and fields are read.
+///
+/// # Errors
+/// Returns `TauqError::Parse` for malformed quoting, records with the wrong
+/// number of fields, or duplicate column names.
+pub fn csv_to_tauq(csv: &str, has_header: bool) -> Result<String, TauqError> {
+    Ok(Formatter::new().format(&csv_to_value(csv, has_header)?))
+}
+
+/// Parse RFC 4180 CSV into an array of objects, one per record.
+///
+/// With `has_header` the first record names the columns; otherwise they are
+/// named `col1`, `col2`, … Unquoted fields are typed: empty is `null`, and
+/// `true`/`false` and numbers that print back unchanged become booleans and
+/// numbers. Quoted fields are always strings.
+///
+/// # Errors
+/// Returns `TauqError::Parse` for malformed quoting, records with the wrong
+/// number of fields, or duplicate column names.
+pub fn csv_to_value(csv: &str, has_header: bool) -> Result<Value, TauqError> {
+    let mut records = parse_csv(csv)?.into_iter();
+
+    let header: Vec<String> = if has_header {
+        match records.next() {
+            Some((line, fields)) => {
+                let names: Vec<String> = fields.into_iter().map(|(text, _)| text).collect();
+                let mut seen = HashSet::new();
+                if let Some(dup) = names.iter().find(|n| !seen.insert(n.as_str())) {
+                    return Err(ParseError::new(
+                        format!("Duplicate CSV column '{}'", dup),
+                        Span::new(line, 1),
+                    )
+                    .into());
+                }
+                names
+            }
+            None => Vec::new(),
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut rows = Vec::new();
+    let mut width = has_header.then_some(header.len());
+    for (line, fields) in records {
+        let expected = *width.get_or_insert(fields.len());
+        if fields.len() != expected {
+            return Err(ParseError::new(
+                format!(
+                    "CSV record has {} fields, expected {}",
+                    fields.len(),
+                    expected
+                ),
+                Span::new(line, 1),
+            )
+            .into());
+        }
+        let row: Map<String, Value> = fields
+            .into_iter()
+            .enumerate()
+            .map(|(i, (text, quoted))| {
+                let key = header
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("col{}", i + 1));
+                (key, csv_value(text, quoted))
+            })
+            .collect();
+        rows.push(Value::Object(row));
+    }
+
+    Ok(Value::Array(rows))
+}
+
+/// A CSV record's starting line and its `(text, was_quoted)` fields
+type CsvRecord = (usize, Vec<(String, bool)>);
+
+/// Split CSV into records. Blank lines are skipped.
+fn parse_csv(csv: &str) -> Result<Vec<CsvRecord>, ParseError> {
+    let mut records = Vec::new();
+    let mut chars = csv.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = Vec::new();
+        loop {
+            let mut text = String::new();
+            let quoted = chars.peek() == Some(&'"');
+            if quoted {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            text.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            text.push(c);
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                "Unterminated quoted CSV field",
+                                Span::new(start_line, 1),
+                            ));
+                        }
+                    }
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if matches!(c, ',' | '\n' | '\r') {
+                        break;
+                    }
+                    if c == '"' {
+                        return Err(ParseError::new(
+                            "Unexpected '\"' inside an unquoted CSV field",
+                            Span::new(line, 1),
+                        ));
+                    }
+                    text.push(c);
+                    chars.next();
+                }
+            }
+            fields.push((text, quoted));
+
+            match chars.next() {
+                Some(',') => continue,
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some('\n') | Some('\r') | None => {}
+                Some(c) => {
+                    return Err(ParseError::new(
+                        format!("Unexpected '{}' after closing quote in CSV field", c),
+                        Span::new(line, 1),
+                    ));
+                }
+            }
+            line += 1;
+            break;
+        }
+        if !(fields.len() == 1 && fields[0] == (String::new(), false)) {
+            records.push((start_line, fields));
+        }
+    }
+    Ok(records)
+}
+
+/// Typed value of a CSV field (see [`csv_to_tauq`])
+fn csv_value(text: String, quoted: bool) -> Value {
+    if quoted {
+        return Value::String(text);
+    }
+    match text.as_str() {
+        "" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match serde_json::from_str::<serde_json::Number>(&text) {
+            Ok(n) if n.to_string() == text => Value::Number(n),
+            _ => Value::String(text),
+        },
+    }
+}
+
+/// Write a string as a CSV field, quoting it when RFC 4180 requires or when
+/// it would not read back as a string unquoted
+fn csv_field(s: &str) -> String {
+    let plain = !s.contains([',', '"', '\n', '\r'])
+        && matches!(csv_value(s.to_string(), false), Value::String(_));
+    if plain {
+        s.to_string()
+    } else {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    }
+}
+
 /// Format JSON value to minified Tauq
 pub fn minify_tauq(value: &Value) -> String {
     Formatter::new().minified().format(value)
@@ -1124,6 +2844,265 @@ mod tests {
         assert_eq!(second.get("role").unwrap(), "user");
     }
 
+    #[test]
+    fn test_identical_arrays_share_one_schema() {
+        let value = json!({
+            "users": [
+                {"id": 1, "name": "Alice"},
+                {"id": 2, "name": "Bob"}
+            ],
+            "admins": [
+                {"id": 3, "name": "Carol"},
+                {"id": 4, "name": "Dave"}
+            ]
+        });
+        let result = json_to_tauq(&value);
+
+        assert_eq!(result.matches("!def").count(), 1, "{}", result);
+        assert_eq!(result.matches("!use User").count(), 2, "{}", result);
+
+        let parsed = crate::compile_tauq(&result).unwrap();
+        assert_eq!(parsed["admins"][1]["name"], "Dave");
+    }
+
+    #[test]
+    fn test_schema_registry_definitions() {
+        let fields = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut registry = SchemaRegistry::new();
+        assert!(registry.is_empty());
+        assert_eq!(
+            registry.get_or_create(&fields(&["id", "name"]), Some("users")),
+            "User"
+        );
+        assert_eq!(
+            registry.get_or_create(&fields(&["sku", "price"]), Some("products")),
+            "Product"
+        );
+        assert_eq!(registry.get_or_create(&fields(&["x", "y"]), None), "Row");
+        // Same field set in another order reuses the schema
+        assert_eq!(
+            registry.get_or_create(&fields(&["name", "id"]), Some("admins")),
+            "User"
+        );
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(
+            registry.definitions(Delimiter::Space),
+            [
+                "!def Product sku price",
+                "!def Row x y",
+                "!def User id name"
+            ]
+        );
+        assert_eq!(
+            registry.definitions(Delimiter::Comma)[0],
+            "!def Product sku,price"
+        );
+        assert_eq!(
+            registry.schemas()["id,name"].fields,
+            fields(&["id", "name"])
+        );
+    }
+
+    #[test]
+    fn test_version_tag() {
+        let value = json!([
+            {"id": 1, "name": "Alice"},
+            {"id": 2, "name": "Bob"}
+        ]);
+        let result = Formatter::new().with_version_tag().format(&value);
+        assert!(result.starts_with("!require tauq >= "), "{}", result);
+
+        let minified = Formatter::new()
+            .with_version_tag()
+            .minified()
+            .format(&value);
+        let parsed = crate::compile_tauq(&minified).unwrap();
+        assert_eq!(parsed[1]["name"], "Bob");
+    }
+
+    #[test]
+    fn test_schemas_block_threshold() {
+        let value = json!({
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "tags": [{"label": "a"}, {"label": "b"}]
+        });
+
+        let flat = Formatter::new()
+            .with_schemas_block_threshold(3)
+            .format(&value);
+        assert!(flat.starts_with("!def "), "{}", flat);
+
+        let block = Formatter::new()
+            .with_schemas_block_threshold(2)
+            .format(&value);
+        assert!(
+            block.starts_with("!schemas\nTag label\nUser id name\n---\n"),
+            "{}",
+            block
+        );
+        assert!(!block.contains("!def"), "{}", block);
+    }
+
+    #[test]
+    fn test_singularisation_irregulars() {
+        let config = SingularisationConfig::default();
+        assert_eq!(config.schema_name("people"), "Person");
+        assert_eq!(config.schema_name("children"), "Child");
+        assert_eq!(config.schema_name("data"), "Datum");
+        assert_eq!(config.schema_name("indices"), "Index");
+        assert_eq!(config.schema_name("user_profiles"), "UserProfile");
+        assert_eq!(config.schema_name("categories"), "Category");
+        assert_eq!(config.schema_name("team_people"), "TeamPerson");
+    }
+
+    #[test]
+    fn test_singularisation_custom_and_aggressive() {
+        let config = SingularisationConfig::default().with_override("data", "record");
+        assert_eq!(config.schema_name("Data"), "Record");
+
+        let plain = SingularisationConfig::default();
+        let aggressive = SingularisationConfig::default().aggressive();
+        assert_eq!(plain.schema_name("boxes"), "Boxe");
+        assert_eq!(aggressive.schema_name("boxes"), "Box");
+        assert_eq!(aggressive.schema_name("addresses"), "Address");
+        assert_eq!(aggressive.schema_name("shelves"), "Shelf");
+        assert_eq!(aggressive.schema_name("users"), "User");
+    }
+
+    #[test]
+    fn test_formatter_with_singularisation() {
+        let value = json!({
+            "people": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]
+        });
+        let result = json_to_tauq(&value);
+        assert!(result.contains("!def Person id name"), "{}", result);
+
+        let config = SingularisationConfig::default().with_override("people", "member");
+        let result = Formatter::new().with_singularisation(config).format(&value);
+        assert!(result.contains("!def Member id name"), "{}", result);
+        assert!(result.contains("!use Member"), "{}", result);
+    }
+
+    #[test]
+    fn test_max_inline_depth() {
+        let value = json!({
+            "a": {
+                "b": {
+                    "c": {"d": {"e": 1, "f": [1, 2]}},
+                    "g": true
+                }
+            },
+            "name": "deep"
+        });
+
+        let result = Formatter::new().with_max_inline_depth(2).format(&value);
+        assert!(
+            result.contains("  b {\n    c { d { e 1 f [1 2] } }\n    g true\n  }"),
+            "{}",
+            result
+        );
+        assert_eq!(crate::compile_tauq(&result).unwrap(), value);
+
+        // Default keeps expanding every level
+        let expanded = json_to_tauq(&value);
+        assert!(expanded.contains("      d {\n"), "{}", expanded);
+        assert_eq!(crate::compile_tauq(&expanded).unwrap(), value);
+    }
+
+    #[test]
+    fn test_max_inline_depth_skips_schemas_in_collapsed_objects() {
+        let value = json!({
+            "outer": {
+                "inner": {
+                    "rows": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]
+                }
+            }
+        });
+
+        let result = Formatter::new().with_max_inline_depth(1).format(&value);
+        assert!(!result.contains("!def"), "{}", result);
+        assert!(
+            result.contains("inner { rows [{ id 1 name a } { id 2 name b }] }"),
+            "{}",
+            result
+        );
+        assert_eq!(crate::compile_tauq(&result).unwrap(), value);
+    }
+
+    #[test]
+    fn test_schema_threshold() {
+        let single = json!({"readings": [{"t": 1, "v": 20.5}]});
+        let default = Formatter::new().format(&single);
+        assert!(!default.contains("!def"), "{}", default);
+
+        let tabulated = Formatter::new().with_schema_threshold(1).format(&single);
+        assert!(tabulated.starts_with("!def Reading t v\n"), "{}", tabulated);
+        assert_eq!(crate::compile_tauq(&tabulated).unwrap(), single);
+
+        let top_level = json!([{"t": 1, "v": 20.5}]);
+        let result = Formatter::new()
+            .with_schema_threshold(1)
+            .with_delimiter(Delimiter::Comma)
+            .format(&top_level);
+        assert_eq!(result, "!def Row t,v\n---\n[\n  !use Row\n  1,20.5\n]");
+        assert_eq!(crate::compile_tauq(&result).unwrap(), top_level);
+
+        let table = json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+        let inline = Formatter::new()
+            .with_schema_threshold(usize::MAX)
+            .format(&table);
+        assert!(!inline.contains("!def"), "{}", inline);
+        assert!(inline.contains("{ id 1 name Alice }"), "{}", inline);
+        assert_eq!(crate::compile_tauq(&inline).unwrap(), table);
+    }
+
+    #[test]
+    fn test_schema_namer_replaces_heuristics() {
+        let value = json!({
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "orders": [{"sku": "a", "qty": 1}, {"sku": "b", "qty": 2}]
+        });
+        let prefixed = Formatter::new().with_schema_namer(|_, context| {
+            let base = SingularisationConfig::new().schema_name(context.unwrap_or("rows"));
+            format!("T{}", base)
+        });
+        let result = prefixed.format(&value);
+        assert!(result.contains("!def TUser id name"), "{}", result);
+        assert!(result.contains("!def TOrder sku qty"), "{}", result);
+        assert_eq!(crate::compile_tauq(&result).unwrap(), value);
+
+        // Clashing names are still made unique
+        let by_first_field = Formatter::new().with_schema_namer(|fields, _| {
+            let mut name = fields[0].to_uppercase();
+            name.truncate(1);
+            name
+        });
+        let table = json!([{"id": 1, "x": 1}, {"id": 2, "x": 2}]);
+        assert!(by_first_field.format(&table).starts_with("!def I id x\n"));
+        let nested = json!({"a": [{"k": 1}, {"k": 2}], "b": [{"k": 1, "v": 2}, {"k": 3, "v": 4}]});
+        let result = by_first_field.format(&nested);
+        assert!(result.contains("!def K k\n"), "{}", result);
+        assert!(result.contains("!def K2 k v\n"), "{}", result);
+        assert_eq!(crate::compile_tauq(&result).unwrap(), nested);
+    }
+
+    #[test]
+    fn test_formatter_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Formatter>();
+
+        let formatter =
+            std::sync::Arc::new(Formatter::new().with_schema_namer(|_, _| "Shared".into()));
+        let value = json!([{"a": 1}, {"a": 2}]);
+        let handle = {
+            let formatter = std::sync::Arc::clone(&formatter);
+            let value = value.clone();
+            std::thread::spawn(move || formatter.format(&value))
+        };
+        assert_eq!(handle.join().unwrap(), formatter.format(&value));
+    }
+
     #[test]
     fn test_array_value() {
         let value = json!({"tags": ["web", "api", "backend"]});
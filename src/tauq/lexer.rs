@@ -1,8 +1,96 @@
+//! Tokenizer for Tauq source.
+//!
+//! A UTF-8 byte-order mark (`U+FEFF`) at the start of the input, as written
+//! by some Windows editors, is skipped; token offsets are relative to the
+//! text after it.
+//!
+//! `Infinity`, `+Infinity`, `-Infinity` and `NaN` lex as non-finite
+//! [`Token::Float`]s. JSON has no such numbers, so they become the strings
+//! `"Infinity"`, `"-Infinity"` and `"NaN"` in parsed values. Other spellings
+//! Rust would accept (`inf`, `nan`) stay barewords.
+
 use super::token::{Location, SpannedToken, Token};
 use crate::error::{LexError, Span};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::iter::Peekable;
 use std::str::Chars;
 
+/// `source` without a leading UTF-8 byte-order mark
+pub(crate) fn strip_bom(source: &str) -> &str {
+    source.strip_prefix('\u{FEFF}').unwrap_or(source)
+}
+
+/// All tokens of `source`, in order
+///
+/// Comments are skipped and unterminated strings end the stream; use
+/// [`Lexer`] directly to inspect [`Lexer::lex_error`].
+///
+/// # Example
+/// ```
+/// use tauq::tauq::lex;
+/// use tauq::tauq::token::Token;
+///
+/// let tokens: Vec<Token> = lex("!def User id").into_iter().map(|t| t.token).collect();
+/// assert_eq!(tokens[0], Token::Directive("def".to_string()));
+/// assert_eq!(tokens.len(), 3);
+/// ```
+pub fn lex(source: &str) -> Vec<SpannedToken<'_>> {
+    Lexer::new(source).tokens().collect()
+}
+
+/// The non-finite float spelled by a bareword, if any
+fn special_float(word: &str) -> Option<f64> {
+    match word {
+        "Infinity" | "+Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => None,
+    }
+}
+
+/// Integer written with a `0x`, `0b` or `0o` prefix, optionally signed
+/// (`-0x10`); `None` for anything else, including values beyond `u64`
+fn radix_integer(word: &str) -> Option<Token<'static>> {
+    let (negative, unsigned) = match word.as_bytes().first()? {
+        b'-' => (true, &word[1..]),
+        b'+' => (false, &word[1..]),
+        _ => (false, word),
+    };
+    let radix = match unsigned.get(..2)? {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    let digits = &unsigned[2..];
+    // from_str_radix would accept a second sign after the prefix
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let magnitude = u64::from_str_radix(digits, radix).ok()?;
+    if negative {
+        let value = 0i64.checked_sub_unsigned(magnitude)?;
+        Some(Token::Integer(value))
+    } else {
+        Some(match i64::try_from(magnitude) {
+            Ok(value) => Token::Integer(value),
+            Err(_) => Token::UnsignedInteger(magnitude),
+        })
+    }
+}
+
+/// Literal spelling of a non-finite float, as [`Lexer`] reads it back
+pub(crate) fn special_float_name(n: f64) -> &'static str {
+    if n.is_nan() {
+        "NaN"
+    } else if n.is_sign_negative() {
+        "-Infinity"
+    } else {
+        "Infinity"
+    }
+}
+
 /// Lexer for tokenizing Tauq source code
 pub struct Lexer<'a> {
     input: &'a str,
@@ -15,11 +103,15 @@ pub struct Lexer<'a> {
     overflow_occurred: bool,
     /// Error recorded when an unterminated string literal is encountered
     pub lex_error: Option<crate::error::LexError>,
+    /// Text after the `#` of each comment on a line of its own, keyed by
+    /// 1-based line number
+    comment_map: HashMap<usize, String>,
 }
 
 impl<'a> Lexer<'a> {
     /// Create a new lexer for the given input
     pub fn new(input: &'a str) -> Self {
+        let input = strip_bom(input);
         Self {
             input,
             chars: input.chars().peekable(),
@@ -28,9 +120,16 @@ impl<'a> Lexer<'a> {
             column: 1,
             overflow_occurred: false,
             lex_error: None,
+            comment_map: HashMap::new(),
         }
     }
 
+    /// Comments seen so far that stand on a line of their own, keyed by
+    /// line number. Comments after code on the same line are not recorded.
+    pub fn comments(&self) -> &HashMap<usize, String> {
+        &self.comment_map
+    }
+
     /// Get current location
     fn location(&self) -> Location {
         Location::new(self.line, self.column, self.offset)
@@ -65,11 +164,12 @@ impl<'a> Lexer<'a> {
     }
 
     /// Get the next token from the input
-    pub fn next_token(&mut self) -> Option<SpannedToken> {
+    pub fn next_token(&mut self) -> Option<SpannedToken<'a>> {
         loop {
             self.skip_whitespace();
 
             let start = self.location();
+            let had_error = self.lex_error.is_some();
             let ch = self.advance()?;
 
             let token = match ch {
@@ -78,7 +178,11 @@ impl<'a> Lexer<'a> {
                 // consecutive commas).
                 ',' => continue,
                 '#' => {
-                    self.skip_comment();
+                    let line_start = self.input[..start.offset].rfind('\n').map_or(0, |i| i + 1);
+                    let text = self.skip_comment(start.offset + 1);
+                    if self.input[line_start..start.offset].trim().is_empty() {
+                        self.comment_map.insert(start.line, text.to_string());
+                    }
                     continue;
                 }
                 '!' => self.lex_directive(),
@@ -90,6 +194,7 @@ impl<'a> Lexer<'a> {
                 '[' => Token::LBracket,
                 ']' => Token::RBracket,
                 '"' => self.lex_string(),
+                '<' if self.at_heredoc() => self.lex_heredoc(start),
                 '-' => {
                     // Check for ---
                     let mut lookahead = self.chars.clone();
@@ -101,10 +206,29 @@ impl<'a> Lexer<'a> {
                         self.lex_bareword(ch)
                     }
                 }
+                '=' => {
+                    // Check for ===
+                    let mut lookahead = self.chars.clone();
+                    if lookahead.next() == Some('=') && lookahead.next() == Some('=') {
+                        self.advance(); // consume 2nd =
+                        self.advance(); // consume 3rd =
+                        Token::DocSeparator
+                    } else {
+                        self.lex_bareword(ch)
+                    }
+                }
                 _ => self.lex_bareword(ch),
             };
 
             let end = self.location();
+            // An error raised by this token underlines through its end
+            if !had_error
+                && let Some(err) = &mut self.lex_error
+                && err.span.is_point()
+            {
+                err.span.end_line = end.line;
+                err.span.end_column = end.column;
+            }
             return Some(SpannedToken { token, start, end });
         }
     }
@@ -119,16 +243,19 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn skip_comment(&mut self) {
+    /// Skip to the end of a comment whose text starts at byte `from`,
+    /// returning that text
+    fn skip_comment(&mut self, from: usize) -> &'a str {
         while let Some(&ch) = self.peek() {
             if ch == '\n' {
                 break;
             }
             self.advance();
         }
+        self.input[from..self.offset].trim_end_matches('\r')
     }
 
-    fn lex_directive(&mut self) -> Token {
+    fn lex_directive(&mut self) -> Token<'a> {
         let mut name = String::new();
         while let Some(&ch) = self.peek() {
             if ch.is_alphanumeric() || ch == '_' {
@@ -143,19 +270,25 @@ impl<'a> Lexer<'a> {
         Token::Directive(name)
     }
 
-    fn lex_string(&mut self) -> Token {
+    /// Lex a quoted string after its opening `"`. The text is borrowed from
+    /// the source until the first escape sequence, which forces a copy.
+    fn lex_string(&mut self) -> Token<'a> {
         let open_line = self.line;
         let open_column = self.column.saturating_sub(1); // position of the opening '"'
-        let mut s = String::new();
-        let mut closed = false;
+        let content_start = self.offset;
+        let mut content_end = None;
+        let mut owned: Option<String> = None;
         while let Some(&ch) = self.peek() {
             match ch {
                 '"' => {
+                    content_end = Some(self.offset);
                     self.advance();
-                    closed = true;
                     break;
                 }
                 '\\' => {
+                    let s = owned
+                        .get_or_insert_with(|| self.input[content_start..self.offset].to_string());
+                    let escape_span = Span::new(self.line, self.column);
                     self.advance(); // consume backslash
                     if let Some(escaped) = self.advance() {
                         match escaped {
@@ -164,6 +297,21 @@ impl<'a> Lexer<'a> {
                             'n' => s.push('\n'),
                             'r' => s.push('\r'),
                             't' => s.push('\t'),
+                            'u' => match self.lex_unicode_escape() {
+                                Ok(c) => s.push(c),
+                                Err(msg) => {
+                                    if self.lex_error.is_none() {
+                                        let span = Span::range(
+                                            escape_span.line,
+                                            escape_span.column,
+                                            self.line,
+                                            self.column,
+                                        );
+                                        self.lex_error = Some(LexError::new(msg, span));
+                                    }
+                                    s.push(char::REPLACEMENT_CHARACTER);
+                                }
+                            },
                             _ => {
                                 s.push('\\');
                                 s.push(escaped);
@@ -173,52 +321,198 @@ impl<'a> Lexer<'a> {
                 }
                 _ => {
                     // Safe: we just checked peek() returned Some
-                    if let Some(c) = self.advance() {
+                    if let Some(c) = self.advance()
+                        && let Some(s) = &mut owned
+                    {
                         s.push(c);
                     }
                 }
             }
         }
-        if !closed && self.lex_error.is_none() {
+        if content_end.is_none() && self.lex_error.is_none() {
             self.lex_error = Some(LexError::new(
                 "unterminated string literal",
                 Span::new(open_line, open_column),
             ));
         }
-        Token::String(s)
+        match owned {
+            Some(s) => Token::String(Cow::Owned(s)),
+            None => {
+                let end = content_end.unwrap_or(self.offset);
+                Token::String(Cow::Borrowed(&self.input[content_start..end]))
+            }
+        }
+    }
+
+    /// Check whether a `<` starts a heredoc opener (`<<<DELIM`)
+    fn at_heredoc(&self) -> bool {
+        let mut lookahead = self.chars.clone();
+        lookahead.next() == Some('<')
+            && lookahead.next() == Some('<')
+            && lookahead
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
     }
 
-    fn lex_bareword(&mut self, first: char) -> Token {
-        let mut s = String::from(first);
+    /// Lex a heredoc string: `<<<DELIM`, a newline, raw content lines, and a
+    /// closing line consisting of exactly `DELIM`. The content is taken
+    /// verbatim with no escape processing; the newline before the closing
+    /// delimiter is not part of it. A line equal to the delimiter always
+    /// ends the heredoc, so such a line cannot appear in the content.
+    fn lex_heredoc(&mut self, start: Location) -> Token<'a> {
+        let open_span = Span::new(start.line, start.column);
+        self.advance(); // consume 2nd <
+        self.advance(); // consume 3rd <
+
+        let mut delimiter = String::new();
+        while let Some(&ch) = self.peek() {
+            if ch.is_ascii_alphanumeric() || ch == '_' {
+                self.advance();
+                delimiter.push(ch);
+            } else {
+                break;
+            }
+        }
+        while matches!(self.peek(), Some(' ' | '\t' | '\r')) {
+            self.advance();
+        }
+        if self.advance() != Some('\n') {
+            if self.lex_error.is_none() {
+                self.lex_error = Some(LexError::new(
+                    format!(
+                        "heredoc opener <<<{} must be followed by a newline",
+                        delimiter
+                    ),
+                    open_span,
+                ));
+            }
+            return Token::String(Cow::Borrowed(""));
+        }
+
+        // The content is the source text of the lines before the closing one
+        let content_start = self.offset;
+        let mut content_end = content_start;
+        loop {
+            let line_start = self.offset;
+            while let Some(&ch) = self.peek() {
+                if ch == '\n' {
+                    break;
+                }
+                self.advance();
+            }
+            let line = &self.input[line_start..self.offset];
+            if line.strip_suffix('\r').unwrap_or(line) == delimiter {
+                return Token::String(Cow::Borrowed(&self.input[content_start..content_end]));
+            }
+            content_end = self.offset;
+            if self.advance().is_none() {
+                break;
+            }
+        }
+
+        if self.lex_error.is_none() {
+            self.lex_error = Some(LexError::new(
+                format!(
+                    "unterminated heredoc: expected a closing {} line",
+                    delimiter
+                ),
+                open_span,
+            ));
+        }
+        Token::String(Cow::Borrowed(&self.input[content_start..content_end]))
+    }
+
+    /// Decode the remainder of a `\u` escape: `XXXX` (with surrogate pairs
+    /// as `\uD83D\uDE00`) or `{X...}` with 1-6 hex digits
+    fn lex_unicode_escape(&mut self) -> Result<char, String> {
+        let code = if self.peek() == Some(&'{') {
+            self.advance();
+            let mut hex = String::new();
+            loop {
+                match self.advance() {
+                    Some('}') => break,
+                    Some(c) if c.is_ascii_hexdigit() && hex.len() < 6 => hex.push(c),
+                    _ => return Err("invalid \\u{...} escape: expected 1-6 hex digits".to_string()),
+                }
+            }
+            if hex.is_empty() {
+                return Err("invalid \\u{...} escape: expected 1-6 hex digits".to_string());
+            }
+            u32::from_str_radix(&hex, 16).map_err(|e| e.to_string())?
+        } else {
+            let high = self.lex_hex4()?;
+            if (0xD800..=0xDBFF).contains(&high) {
+                // A high surrogate must be followed by an escaped low surrogate
+                if self.advance() != Some('\\') || self.advance() != Some('u') {
+                    return Err(format!("lone surrogate \\u{:04X} in string", high));
+                }
+                let low = self.lex_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(format!("lone surrogate \\u{:04X} in string", high));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            } else {
+                high
+            }
+        };
+
+        if (0xD800..=0xDFFF).contains(&code) {
+            return Err(format!("lone surrogate \\u{:04X} in string", code));
+        }
+        char::from_u32(code).ok_or_else(|| format!("code point U+{:X} is above U+10FFFF", code))
+    }
+
+    fn lex_hex4(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = self
+                .advance()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| "invalid \\u escape: expected 4 hex digits".to_string())?;
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    fn lex_bareword(&mut self, first: char) -> Token<'a> {
+        let start = self.offset - first.len_utf8();
 
         while let Some(&ch) = self.peek() {
             // Stop at delimiters
             if ch.is_whitespace() || "{}[],:;\"#\n".contains(ch) {
                 break;
             }
-            // Safe: we just checked peek() returned Some
-            if let Some(c) = self.advance() {
-                s.push(c);
-            }
+            self.advance();
         }
+        let s = &self.input[start..self.offset];
 
         // Try to parse as number with precision fallback
         if let Ok(i) = s.parse::<i64>() {
             Token::Integer(i)
         } else if let Ok(u) = s.parse::<u64>() {
             Token::UnsignedInteger(u)
-        } else if let Ok(f) = s.parse::<f64>() {
+        } else if let Some(token) = radix_integer(s) {
+            token
+        } else if let Some(f) = s.parse::<f64>().ok().filter(|f| f.is_finite()) {
+            Token::Float(f)
+        } else if let Some(f) = special_float(s) {
             Token::Float(f)
         } else {
-            match s.as_str() {
+            match s {
                 "true" => Token::Bool(true),
                 "false" => Token::Bool(false),
                 "null" => Token::Null,
-                _ => Token::Ident(s),
+                _ => Token::Ident(Cow::Borrowed(s)),
             }
         }
     }
 
+    /// Iterate over the remaining tokens, calling [`Lexer::next_token`]
+    /// until the input is exhausted
+    pub fn tokens(mut self) -> impl Iterator<Item = SpannedToken<'a>> {
+        std::iter::from_fn(move || self.next_token())
+    }
+
     /// Get the source input (useful for error messages)
     pub fn source(&self) -> &'a str {
         self.input
@@ -231,7 +525,7 @@ mod tests {
     use crate::tauq::token::Token;
 
     /// Collect all tokens from an input string into a Vec.
-    fn lex_all(input: &str) -> Vec<Token> {
+    fn lex_all(input: &str) -> Vec<Token<'_>> {
         let mut lexer = Lexer::new(input);
         let mut tokens = Vec::new();
         while let Some(spanned) = lexer.next_token() {
@@ -241,7 +535,7 @@ mod tests {
     }
 
     /// Lex a single token and return it, panicking if the input is empty.
-    fn lex_one(input: &str) -> Token {
+    fn lex_one(input: &str) -> Token<'_> {
         let mut lexer = Lexer::new(input);
         lexer
             .next_token()
@@ -332,10 +626,21 @@ mod tests {
     #[test]
     fn test_triple_dash_followed_by_tokens() {
         let tokens = lex_all("--- foo");
+        assert_eq!(tokens, vec![Token::TripleDash, Token::Ident("foo".into())]);
+    }
+
+    #[test]
+    fn test_doc_separator_token() {
         assert_eq!(
-            tokens,
-            vec![Token::TripleDash, Token::Ident("foo".to_string())]
+            lex_all("===\nfoo"),
+            vec![
+                Token::DocSeparator,
+                Token::Newline,
+                Token::Ident("foo".into())
+            ]
         );
+        // Defaults such as `role=admin` stay barewords
+        assert_eq!(lex_one("role=admin"), Token::Ident("role=admin".into()));
     }
 
     #[test]
@@ -442,6 +747,46 @@ mod tests {
         assert_eq!(lex_one("-0.5"), Token::Float(-0.5));
     }
 
+    #[test]
+    fn test_radix_integers() {
+        assert_eq!(lex_one("0xFF"), Token::Integer(255));
+        assert_eq!(lex_one("0xff"), Token::Integer(255));
+        assert_eq!(lex_one("0b1010"), Token::Integer(10));
+        assert_eq!(lex_one("0o755"), Token::Integer(493));
+        assert_eq!(lex_one("-0x10"), Token::Integer(-16));
+        assert_eq!(lex_one("-0x8000000000000000"), Token::Integer(i64::MIN));
+        assert_eq!(
+            lex_one("0xFFFFFFFFFFFFFFFF"),
+            Token::UnsignedInteger(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_malformed_radix_integers_are_idents() {
+        for word in [
+            "0x",
+            "0b102",
+            "0o8",
+            "0xG1",
+            "0x-1",
+            "0X1F",
+            "0x1_000",
+            "0x10000000000000000",
+        ] {
+            assert_eq!(lex_one(word), Token::Ident(word.into()), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_special_floats() {
+        assert_eq!(lex_one("Infinity"), Token::Float(f64::INFINITY));
+        assert_eq!(lex_one("+Infinity"), Token::Float(f64::INFINITY));
+        assert_eq!(lex_one("-Infinity"), Token::Float(f64::NEG_INFINITY));
+        assert!(matches!(lex_one("NaN"), Token::Float(n) if n.is_nan()));
+        assert_eq!(lex_one("inf"), Token::Ident("inf".into()));
+        assert_eq!(lex_one("nan"), Token::Ident("nan".into()));
+    }
+
     // -----------------------------------------------------------------------
     // Bareword dispatch — booleans and null
     // -----------------------------------------------------------------------
@@ -467,18 +812,18 @@ mod tests {
 
     #[test]
     fn test_identifier_simple() {
-        assert_eq!(lex_one("foo"), Token::Ident("foo".to_string()));
+        assert_eq!(lex_one("foo"), Token::Ident("foo".into()));
     }
 
     #[test]
     fn test_identifier_with_underscores_and_digits() {
-        assert_eq!(lex_one("my_key_2"), Token::Ident("my_key_2".to_string()));
+        assert_eq!(lex_one("my_key_2"), Token::Ident("my_key_2".into()));
     }
 
     #[test]
     fn test_identifier_true_prefix_not_bool() {
         // "trueish" is not "true", so it must be an identifier.
-        assert_eq!(lex_one("trueish"), Token::Ident("trueish".to_string()));
+        assert_eq!(lex_one("trueish"), Token::Ident("trueish".into()));
     }
 
     // -----------------------------------------------------------------------
@@ -487,52 +832,87 @@ mod tests {
 
     #[test]
     fn test_string_empty() {
-        assert_eq!(lex_one(r#""""#), Token::String(String::new()));
+        assert_eq!(lex_one(r#""""#), Token::String("".into()));
     }
 
     #[test]
     fn test_string_plain() {
-        assert_eq!(lex_one(r#""hello""#), Token::String("hello".to_string()));
+        assert_eq!(lex_one(r#""hello""#), Token::String("hello".into()));
     }
 
     #[test]
     fn test_escape_newline() {
         // Input: "\n" (the two characters backslash and n inside quotes)
-        assert_eq!(lex_one(r#""\n""#), Token::String("\n".to_string()));
+        assert_eq!(lex_one(r#""\n""#), Token::String("\n".into()));
     }
 
     #[test]
     fn test_escape_carriage_return() {
-        assert_eq!(lex_one(r#""\r""#), Token::String("\r".to_string()));
+        assert_eq!(lex_one(r#""\r""#), Token::String("\r".into()));
     }
 
     #[test]
     fn test_escape_tab() {
-        assert_eq!(lex_one(r#""\t""#), Token::String("\t".to_string()));
+        assert_eq!(lex_one(r#""\t""#), Token::String("\t".into()));
     }
 
     #[test]
     fn test_escape_backslash() {
-        assert_eq!(lex_one(r#""\\""#), Token::String("\\".to_string()));
+        assert_eq!(lex_one(r#""\\""#), Token::String("\\".into()));
     }
 
     #[test]
     fn test_escape_double_quote() {
-        assert_eq!(lex_one(r#""\"""#), Token::String("\"".to_string()));
+        assert_eq!(lex_one(r#""\"""#), Token::String("\"".into()));
     }
 
     #[test]
     fn test_escape_unknown_sequence_preserved() {
         // An unrecognized escape like \x should produce the literal characters
         // backslash and 'x'.
-        assert_eq!(lex_one(r#""\x""#), Token::String("\\x".to_string()));
+        assert_eq!(lex_one(r#""\x""#), Token::String("\\x".into()));
     }
 
     #[test]
     fn test_string_all_escapes_combined() {
         // "\n\r\t\\\""  =>  newline, CR, tab, backslash, double-quote
         let expected = "\n\r\t\\\"".to_string();
-        assert_eq!(lex_one(r#""\n\r\t\\\"" "#), Token::String(expected));
+        assert_eq!(lex_one(r#""\n\r\t\\\"" "#), Token::String(expected.into()));
+    }
+
+    #[test]
+    fn test_escape_unicode_four_digits() {
+        assert_eq!(lex_one(r#""caf\u00e9""#), Token::String("café".into()));
+        assert_eq!(lex_one(r#""\u4E2D""#), Token::String("中".into()));
+    }
+
+    #[test]
+    fn test_escape_unicode_surrogate_pair() {
+        assert_eq!(lex_one(r#""\uD83D\uDE00""#), Token::String("😀".into()));
+    }
+
+    #[test]
+    fn test_escape_unicode_braced() {
+        assert_eq!(lex_one(r#""\u{1F600}""#), Token::String("😀".into()));
+        assert_eq!(lex_one(r#""\u{41}""#), Token::String("A".into()));
+        // \u{0} is a NUL character, not the end of the string
+        assert_eq!(lex_one(r#""a\u{0}b""#), Token::String("a\0b".into()));
+    }
+
+    #[test]
+    fn test_escape_unicode_invalid() {
+        for input in [
+            r#""\u{110000}""#,
+            r#""\uD800""#,
+            r#""\uDC00x""#,
+            r#""\u{D800}""#,
+            r#""\u12G4""#,
+            r#""\u{}""#,
+        ] {
+            let mut lexer = Lexer::new(input);
+            while lexer.next_token().is_some() {}
+            assert!(lexer.lex_error.is_some(), "expected error for {}", input);
+        }
     }
 
     // -----------------------------------------------------------------------
@@ -542,13 +922,13 @@ mod tests {
     #[test]
     fn test_utf8_string_content() {
         // Japanese characters (3 bytes each in UTF-8)
-        assert_eq!(lex_one(r#""日本語""#), Token::String("日本語".to_string()));
+        assert_eq!(lex_one(r#""日本語""#), Token::String("日本語".into()));
     }
 
     #[test]
     fn test_utf8_emoji_in_string() {
         // Emoji is 4 bytes in UTF-8
-        assert_eq!(lex_one(r#""🦀""#), Token::String("🦀".to_string()));
+        assert_eq!(lex_one(r#""🦀""#), Token::String("🦀".into()));
     }
 
     #[test]
@@ -568,7 +948,7 @@ mod tests {
         // tokens are produced correctly.
         let tokens = lex_all(r#""日本" 42"#);
         assert_eq!(tokens.len(), 2);
-        assert_eq!(tokens[0], Token::String("日本".to_string()));
+        assert_eq!(tokens[0], Token::String("日本".into()));
         assert_eq!(tokens[1], Token::Integer(42));
     }
 
@@ -609,9 +989,9 @@ mod tests {
         assert_eq!(
             tokens,
             vec![
-                Token::Ident("foo".to_string()),
+                Token::Ident("foo".into()),
                 Token::Newline,
-                Token::Ident("bar".to_string()),
+                Token::Ident("bar".into()),
             ]
         );
     }
@@ -636,4 +1016,82 @@ mod tests {
         // After consuming 3 bytes the end offset should be >= 3.
         assert!(spanned.end.offset >= 3);
     }
+
+    #[test]
+    fn test_heredoc_raw_content() {
+        let tokens = lex_all("sql <<<SQL\nSELECT \"a\\n\"\n  FROM t\nSQL\nnext 1");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("sql".into()),
+                Token::String("SELECT \"a\\n\"\n  FROM t".into()),
+                Token::Newline,
+                Token::Ident("next".into()),
+                Token::Integer(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heredoc_delimiter_must_match_whole_line() {
+        let tokens = lex_all("<<<EOT\nEOT is here\n EOT\nEOT");
+        assert_eq!(tokens, vec![Token::String("EOT is here\n EOT".into())]);
+    }
+
+    #[test]
+    fn test_heredoc_errors() {
+        let mut lexer = Lexer::new("<<<EOT\nno end");
+        while lexer.next_token().is_some() {}
+        assert!(
+            lexer
+                .lex_error
+                .unwrap()
+                .message
+                .contains("unterminated heredoc")
+        );
+
+        let mut lexer = Lexer::new("<<<EOT trailing\nEOT");
+        while lexer.next_token().is_some() {}
+        assert!(lexer.lex_error.is_some());
+    }
+
+    #[test]
+    fn test_double_angle_is_not_heredoc() {
+        let tokens = lex_all("<<x");
+        assert_eq!(tokens, vec![Token::Ident("<<x".into())]);
+    }
+
+    #[test]
+    fn test_lex_returns_tokens_in_order() {
+        let tokens: Vec<Token> = lex("!def User id name")
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Directive("def".into()),
+                Token::Ident("User".into()),
+                Token::Ident("id".into()),
+                Token::Ident("name".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens_iterator_and_display() {
+        let tokens: Vec<String> = Lexer::new("a 1 # note\nb")
+            .tokens()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                "1:1-1:2 Ident(\"a\")",
+                "1:3-1:4 Integer(1)",
+                "1:11-2:1 Newline",
+                "2:1-2:2 Ident(\"b\")",
+            ]
+        );
+    }
 }
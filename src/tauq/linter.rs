@@ -0,0 +1,581 @@
+// Tauq Linter
+//
+// Semantic checks for documents that parse: schemas that are never used or
+// are referenced above their definition, keys set twice in one object, and
+// rows with more values than their schema has fields (the extra values
+// silently start another row). The checks walk the token stream, since the
+// parsed value no longer records where schemas and keys came from. Parser
+// warnings are passed through under rule names of their own.
+
+use super::lexer::Lexer;
+use super::token::{SpannedToken, Token};
+use crate::error::{ParseWarning, Span, WarningKind};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Every lint rule, as accepted by [`Linter::with_rules`]
+pub const LINT_RULES: &[&str] = &[
+    "unused-schemas",
+    "use-before-def",
+    "shadow-keys",
+    "long-rows",
+    "redundant-use",
+    "empty-schema",
+    "short-row",
+];
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    /// Probably a mistake
+    Warning,
+    /// Often intentional; passed through from the parser
+    Info,
+}
+
+/// One lint finding
+#[derive(Debug, Clone)]
+pub struct LintDiagnostic {
+    /// Name of the rule that produced it, one of [`LINT_RULES`]
+    pub rule: &'static str,
+    /// How serious the finding is
+    pub severity: LintSeverity,
+    /// Description of the problem
+    pub message: String,
+    /// Location of the problem
+    pub span: Span,
+}
+
+impl std::fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.severity {
+            LintSeverity::Warning => "warning",
+            LintSeverity::Info => "info",
+        };
+        write!(
+            f,
+            "{}[{}] at line {}, column {}: {}",
+            level, self.rule, self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+/// Runs semantic lint rules over a parsed document
+///
+/// # Example
+///
+/// ```
+/// use tauq::tauq::Linter;
+///
+/// let source = "!def User id name\n!def Unused a b\n!use User\n1 Alice\n";
+/// let mut parser = tauq::Parser::new(source);
+/// let value = parser.parse().unwrap();
+/// let diagnostics = Linter::new().lint(&value, parser.take_warnings(), source);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].rule, "unused-schemas");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Linter {
+    rules: HashSet<&'static str>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// Create a linter with every rule enabled
+    pub fn new() -> Self {
+        Self {
+            rules: LINT_RULES.iter().copied().collect(),
+        }
+    }
+
+    /// Create a linter with only the named rules enabled; `all` enables
+    /// every rule. Fails on a name that is not in [`LINT_RULES`].
+    pub fn with_rules<'r>(rules: impl IntoIterator<Item = &'r str>) -> Result<Self, String> {
+        let mut enabled = HashSet::new();
+        for rule in rules {
+            if rule == "all" {
+                enabled.extend(LINT_RULES.iter().copied());
+                continue;
+            }
+            match LINT_RULES.iter().find(|known| **known == rule) {
+                Some(known) => {
+                    enabled.insert(*known);
+                }
+                None => {
+                    return Err(format!(
+                        "Unknown lint rule '{}' (expected all, {})",
+                        rule,
+                        LINT_RULES.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(Self { rules: enabled })
+    }
+
+    /// Lint `source`, which parsed to `value` with `warnings`
+    ///
+    /// Diagnostics are sorted by position. `value` is used to report which
+    /// value a shadowed key ends up with.
+    pub fn lint(
+        &self,
+        value: &Value,
+        warnings: Vec<ParseWarning>,
+        source: &str,
+    ) -> Vec<LintDiagnostic> {
+        let mut diagnostics = Walker::new(source, value).run();
+        diagnostics.extend(warnings.into_iter().map(|warning| LintDiagnostic {
+            rule: match warning.kind {
+                WarningKind::RedundantUse => "redundant-use",
+                WarningKind::EmptySchema => "empty-schema",
+                WarningKind::ShortRow => "short-row",
+            },
+            severity: LintSeverity::Info,
+            message: warning.message,
+            span: warning.span,
+        }));
+        diagnostics.retain(|d| self.rules.contains(d.rule));
+        diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
+        diagnostics
+    }
+}
+
+/// A schema seen in the file
+struct SchemaInfo {
+    fields: Vec<String>,
+    span: Span,
+    used: bool,
+}
+
+/// An object, array or the top level of the document
+struct Scope {
+    object: bool,
+    /// JSON pointer of the object in the parsed value, when known
+    path: Option<String>,
+    /// Line each key was first set on
+    keys: HashMap<String, usize>,
+    pending_key: Option<String>,
+    /// Schema whose rows this scope holds
+    schema: Option<String>,
+    row_values: usize,
+    /// Values past the schema's last field in the current row
+    extra: Option<Span>,
+}
+
+impl Scope {
+    fn new(object: bool, path: Option<String>) -> Self {
+        Self {
+            object,
+            path,
+            keys: HashMap::new(),
+            pending_key: None,
+            schema: None,
+            row_values: 0,
+            extra: None,
+        }
+    }
+}
+
+struct Walker<'a, 'v> {
+    tokens: Vec<SpannedToken<'a>>,
+    pos: usize,
+    value: &'v Value,
+    scopes: Vec<Scope>,
+    schemas: HashMap<String, SchemaInfo>,
+    /// Schema names in definition order
+    order: Vec<String>,
+    /// `!use`, `!extends`, `!compose` and type annotation references
+    references: Vec<(String, Span)>,
+    /// Whether the newline being visited is a row of a zero-field schema
+    blank_row: bool,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl<'a, 'v> Walker<'a, 'v> {
+    fn new(source: &'a str, value: &'v Value) -> Self {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token() {
+            tokens.push(token);
+        }
+        Self {
+            tokens,
+            pos: 0,
+            value,
+            scopes: vec![Scope::new(true, Some(String::new()))],
+            schemas: HashMap::new(),
+            order: Vec::new(),
+            references: Vec::new(),
+            blank_row: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<LintDiagnostic> {
+        while let Some(st) = self.tokens.get(self.pos) {
+            let token = st.token.clone();
+            let span = st.span();
+            self.pos += 1;
+            // As in the parser, a newline followed by another newline is
+            // an empty row, and so is one at the end of input after a
+            // blank line
+            let after_newline = self.pos >= 2 && self.tokens[self.pos - 2].token == Token::Newline;
+            self.blank_row = token == Token::Newline
+                && match self.peek() {
+                    Some(Token::Newline) => true,
+                    None => after_newline,
+                    _ => false,
+                };
+            self.visit(token, span);
+        }
+        self.blank_row = false;
+        while !self.scopes.is_empty() {
+            self.end_row();
+            self.scopes.pop();
+        }
+        self.resolve();
+        self.diagnostics
+    }
+
+    fn scope(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            .expect("top-level scope is never popped")
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos).map(|st| &st.token)
+    }
+
+    fn visit(&mut self, token: Token<'a>, span: Span) {
+        match token {
+            Token::Directive(d) => {
+                if self.scopes.len() == 1 {
+                    // A directive ends the top-level object being built
+                    let top = self.scope();
+                    top.keys.clear();
+                    top.pending_key = None;
+                }
+                self.directive(&d);
+            }
+            Token::Newline | Token::Semi => {
+                if self.scope().schema.is_some() {
+                    self.end_row();
+                } else {
+                    self.scope().pending_key = None;
+                }
+            }
+            Token::TripleDash | Token::DocSeparator => {
+                if self.scopes.len() == 1 {
+                    self.end_row();
+                    *self.scope() = Scope::new(true, Some(String::new()));
+                }
+            }
+            Token::LBrace | Token::LBracket => {
+                let object = token == Token::LBrace;
+                let scope = self.scope();
+                let in_row = scope.schema.is_some();
+                let path = match (&scope.path, scope.pending_key.take()) {
+                    (Some(path), Some(key)) if object && !in_row => {
+                        Some(format!("{}/{}", path, pointer_escape(&key)))
+                    }
+                    _ => None,
+                };
+                if in_row {
+                    self.row_value(span);
+                }
+                self.scopes.push(Scope::new(object, path));
+            }
+            Token::RBrace | Token::RBracket => {
+                if self.scopes.len() > 1 {
+                    self.end_row();
+                    self.scopes.pop();
+                }
+            }
+            Token::Colon => {}
+            Token::Ident(word) | Token::String(word) => {
+                let scope = self.scope();
+                if scope.schema.is_some() {
+                    self.row_value(span);
+                } else if scope.object && scope.pending_key.is_none() {
+                    self.key(word.into_owned(), span);
+                } else {
+                    scope.pending_key = None;
+                }
+            }
+            _ => {
+                if self.scope().schema.is_some() {
+                    self.row_value(span);
+                } else {
+                    self.scope().pending_key = None;
+                }
+            }
+        }
+    }
+
+    fn directive(&mut self, name: &str) {
+        match name {
+            "def" => {
+                if let Some((name, span)) = self.take_ident() {
+                    self.definition(name.clone(), span);
+                    self.activate(name);
+                }
+            }
+            "compose" => {
+                if let Some((name, span)) = self.take_ident() {
+                    let mut fields = Vec::new();
+                    while let Some((part, part_span)) = self.take_ident() {
+                        if let Some(info) = self.schemas.get(&part) {
+                            fields.extend(info.fields.iter().cloned());
+                        }
+                        self.references.push((part, part_span));
+                    }
+                    self.define(name.clone(), fields, span);
+                    self.activate(name);
+                }
+            }
+            "use" => {
+                if let Some((name, span)) = self.take_ident() {
+                    self.references.push((name.clone(), span));
+                    self.activate(name);
+                }
+            }
+            "schemas" | "models" => {
+                while let Some(token) = self.peek() {
+                    match token {
+                        Token::TripleDash => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Token::Ident(_) => {
+                            if let Some((name, span)) = self.take_ident() {
+                                self.definition(name, span);
+                            }
+                        }
+                        _ => self.pos += 1,
+                    }
+                }
+            }
+            _ => {
+                // Arguments of other directives are not keys or values
+                while self.peek().is_some_and(|t| *t != Token::Newline) {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Read `[!extends Parent] field field:Type ...` after a schema name
+    fn definition(&mut self, name: String, span: Span) {
+        let mut fields = Vec::new();
+        if matches!(self.peek(), Some(Token::Directive(d)) if d == "extends") {
+            self.pos += 1;
+            if let Some((parent, parent_span)) = self.take_ident() {
+                if let Some(info) = self.schemas.get(&parent) {
+                    fields.extend(info.fields.iter().cloned());
+                }
+                self.references.push((parent, parent_span));
+            }
+        }
+        while let Some((word, _)) = self.take_ident() {
+            let field = word
+                .split(['=', '?'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+            let mut default_follows = word.ends_with('=');
+            if self.peek() == Some(&Token::Colon) {
+                self.pos += 1;
+                let list = self.peek() == Some(&Token::LBracket);
+                if list {
+                    self.pos += 1;
+                }
+                match self.peek() {
+                    Some(Token::Null) => self.pos += 1,
+                    Some(Token::Ident(_)) => {
+                        if let Some((ty, ty_span)) = self.take_ident() {
+                            default_follows |= ty.ends_with('=');
+                            let base = ty.split('=').next().unwrap_or_default().to_string();
+                            self.references.push((base, ty_span));
+                        }
+                    }
+                    _ => {}
+                }
+                if list && self.peek() == Some(&Token::RBracket) {
+                    self.pos += 1;
+                }
+            }
+            if default_follows && self.peek().is_some() {
+                self.pos += 1;
+            }
+        }
+        self.define(name, fields, span);
+    }
+
+    fn define(&mut self, name: String, fields: Vec<String>, span: Span) {
+        match self.schemas.get_mut(&name) {
+            Some(info) => info.fields = fields,
+            None => {
+                self.order.push(name.clone());
+                self.schemas.insert(
+                    name,
+                    SchemaInfo {
+                        fields,
+                        span,
+                        used: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Make `name` the schema of the rows that follow in this scope
+    fn activate(&mut self, name: String) {
+        self.end_row();
+        self.scope().schema = Some(name);
+    }
+
+    fn take_ident(&mut self) -> Option<(String, Span)> {
+        let st = self.tokens.get(self.pos)?;
+        let Token::Ident(word) = &st.token else {
+            return None;
+        };
+        let ident = (word.to_string(), st.span());
+        self.pos += 1;
+        Some(ident)
+    }
+
+    fn key(&mut self, key: String, span: Span) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("top-level scope is never popped");
+        if let Some(first) = scope.keys.get(&key) {
+            let mut message = format!(
+                "Key '{}' is already set on line {}; only the last value is kept",
+                key, first
+            );
+            let kept = scope
+                .path
+                .as_ref()
+                .and_then(|path| {
+                    self.value
+                        .pointer(&format!("{}/{}", path, pointer_escape(&key)))
+                })
+                .filter(|v| !v.is_object() && !v.is_array());
+            if let Some(kept) = kept {
+                message.push_str(&format!(" ({})", kept));
+            }
+            self.diagnostics.push(LintDiagnostic {
+                rule: "shadow-keys",
+                severity: LintSeverity::Warning,
+                message,
+                span,
+            });
+        } else {
+            scope.keys.insert(key.clone(), span.line);
+        }
+        scope.pending_key = Some(key);
+    }
+
+    fn row_value(&mut self, span: Span) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("top-level scope is never popped");
+        let Some(width) = scope.schema.as_ref().map(|name| {
+            self.schemas
+                .get(name)
+                .map_or(usize::MAX, |s| s.fields.len())
+        }) else {
+            return;
+        };
+        scope.row_values += 1;
+        if scope.row_values > width {
+            scope.extra = Some(match scope.extra {
+                Some(extra) => {
+                    Span::range(extra.line, extra.column, span.end_line, span.end_column)
+                }
+                None => span,
+            });
+        }
+    }
+
+    fn end_row(&mut self) {
+        let blank_row = self.blank_row;
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("top-level scope is never popped");
+        let Some(name) = scope.schema.clone() else {
+            return;
+        };
+        let values = std::mem::take(&mut scope.row_values);
+        let extra = scope.extra.take();
+        let Some(info) = self.schemas.get_mut(&name) else {
+            return;
+        };
+        if values > 0 || (info.fields.is_empty() && blank_row) {
+            info.used = true;
+        }
+        if let Some(span) = extra {
+            self.diagnostics.push(LintDiagnostic {
+                rule: "long-rows",
+                severity: LintSeverity::Warning,
+                message: format!(
+                    "Row has {} values but schema '{}' has {} fields; the extra values start another row",
+                    values,
+                    name,
+                    info.fields.len()
+                ),
+                span,
+            });
+        }
+    }
+
+    /// Mark referenced schemas used and report unused or late definitions
+    fn resolve(&mut self) {
+        for (name, span) in &self.references {
+            // Undefined names are builtin types, imports or parse errors
+            let Some(info) = self.schemas.get_mut(name) else {
+                continue;
+            };
+            info.used = true;
+            if (span.line, span.column) < (info.span.line, info.span.column) {
+                self.diagnostics.push(LintDiagnostic {
+                    rule: "use-before-def",
+                    severity: LintSeverity::Warning,
+                    message: format!(
+                        "Schema '{}' is referenced before its definition on line {}",
+                        name, info.span.line
+                    ),
+                    span: *span,
+                });
+            }
+        }
+        for name in &self.order {
+            let info = &self.schemas[name];
+            if !info.used {
+                self.diagnostics.push(LintDiagnostic {
+                    rule: "unused-schemas",
+                    severity: LintSeverity::Warning,
+                    message: format!("Schema '{}' is defined but never used", name),
+                    span: info.span,
+                });
+            }
+        }
+    }
+}
+
+/// Escape a key for use in a JSON pointer
+fn pointer_escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -2,8 +2,16 @@
 pub mod formatter;
 /// Lexer for tokenizing Tauq source
 pub mod lexer;
+/// Semantic lint rules for Tauq source
+pub mod linter;
 /// Parser for Tauq source
 pub mod parser;
+/// Event-driven SAX-style parser
+pub mod sax;
+/// JSON Schema, TypeScript and Rust export of `!def` schemas
+pub mod schema_export;
+/// Serde serializer writing Tauq text
+pub mod serializer;
 /// Streaming parser for efficient row-by-row processing
 pub mod streaming;
 /// Legacy Tauq Query module (deprecated)
@@ -12,9 +20,22 @@ pub mod tauqq;
 pub mod token;
 
 pub use formatter::{
-    Delimiter, Formatter, SchemaStrategy, json_to_tauq, json_to_tauq_no_schemas,
-    json_to_tauq_optimized, json_to_tauq_ultra, minify_tauq,
+    Delimiter, Formatter, InferredSchema, SchemaInfo, SchemaNamer, SchemaRegistry, SchemaStrategy,
+    SingularisationConfig, TomlFormatter, csv_to_tauq, csv_to_value, json_to_tauq,
+    json_to_tauq_annotated, json_to_tauq_no_schemas, json_to_tauq_optimized, json_to_tauq_ultra,
+    json_to_tauq_writer, minify_tauq, ndjson_to_tauq, ndjson_to_tauq_writer, tauq_to_ndjson,
+    to_multi_doc, to_toml,
 };
-pub use lexer::Lexer;
-pub use parser::Parser;
-pub use streaming::StreamingParser;
+pub use lexer::{Lexer, lex};
+pub use linter::{LINT_RULES, LintDiagnostic, LintSeverity, Linter};
+#[cfg(feature = "lsp")]
+pub use parser::IncrementalParser;
+pub use parser::{ParseMode, Parser, SchemaDefinition};
+pub use sax::{CollectingHandler, TauqHandler, TauqValue, parse_sax};
+pub use schema_export::{
+    tauq_schemas_to_json_schema, tauq_schemas_to_rust, tauq_schemas_to_typescript,
+};
+pub use serializer::TauqSerializer;
+#[cfg(feature = "tokio")]
+pub use streaming::AsyncStreamingParser;
+pub use streaming::{PushParser, StreamingParser};
@@ -1,6 +1,6 @@
-use super::lexer::Lexer;
+use super::lexer::{Lexer, special_float_name};
 use super::token::{Location, SpannedToken, Token};
-use crate::error::{ParseError, Span};
+use crate::error::{ParseError, ParseWarning, Span, WarningKind};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::path::Path;
@@ -8,18 +8,197 @@ use std::path::Path;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::rc::Rc;
+#[cfg(feature = "lsp")]
+use std::sync::Arc;
 
 /// Field definition in a schema
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldDef {
     /// Name of the field
     pub name: String,
     /// Type definition for the field
     pub type_def: TypeDef,
+    /// Declared with a trailing `?`; rows may omit it and get `null`
+    pub optional: bool,
+    /// Declared with `=value`; rows may omit it and get this value
+    pub default: Option<Value>,
+}
+
+impl FieldDef {
+    /// A required field with no default
+    pub fn new(name: impl Into<String>, type_def: TypeDef) -> Self {
+        Self {
+            name: name.into(),
+            type_def,
+            optional: false,
+            default: None,
+        }
+    }
+
+    /// Build a field from its name as written in `!def`, where a trailing
+    /// `?` marks it optional (`role?`)
+    pub fn declared(name: &str, type_def: TypeDef) -> Self {
+        match name.strip_suffix('?') {
+            Some(base) if !base.is_empty() => Self::new(base, type_def).with_optional(true),
+            _ => Self::new(name, type_def),
+        }
+    }
+
+    /// Set whether rows may omit this field
+    pub fn with_optional(mut self, optional: bool) -> Self {
+        self.optional = optional;
+        self
+    }
+
+    /// Set the value used when a row omits this field
+    pub fn with_default(mut self, default: Option<Value>) -> Self {
+        self.default = default;
+        self
+    }
+}
+
+/// Fields of a schema extending a parent: the parent's fields first, with
+/// any the child redeclares replaced in place, then the child's new fields
+pub(crate) fn inherit_fields(inherited: Vec<FieldDef>, own: Vec<FieldDef>) -> Vec<FieldDef> {
+    let parent_len = inherited.len();
+    let mut fields = inherited;
+    for field in own {
+        match fields[..parent_len]
+            .iter_mut()
+            .find(|f| f.name == field.name)
+        {
+            Some(shadowed) => *shadowed = field,
+            None => fields.push(field),
+        }
+    }
+    fields
+}
+
+/// Fields of `!compose name parts...`: the union of the parts' fields in
+/// order, where a field in several parts keeps its first occurrence
+pub(crate) fn compose_fields(
+    name: &str,
+    parts: &[String],
+    lookup: impl Fn(&str) -> Option<Vec<FieldDef>>,
+) -> Result<Vec<FieldDef>, String> {
+    if parts.len() < 2 {
+        return Err(format!("!compose {} requires at least two schemas", name));
+    }
+    let mut fields: Vec<FieldDef> = Vec::new();
+    for part in parts {
+        // Parts are flattened when the schema is composed, so the only
+        // possible cycle is a schema naming itself
+        if part == name {
+            return Err(format!(
+                "Circular composition: schema '{}' cannot compose itself",
+                name
+            ));
+        }
+        let part_fields = lookup(part)
+            .ok_or_else(|| format!("!compose references undefined schema '{}'", part))?;
+        for field in part_fields {
+            if !fields.iter().any(|f| f.name == field.name) {
+                fields.push(field);
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// Whether `value` fits a scalar type annotation under `!strict`. Other
+/// type names refer to schemas and enums, which are not checked.
+fn matches_scalar_type(type_name: &str, value: &Value) -> bool {
+    match type_name {
+        "int" => value.as_number().is_some_and(|n| {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }),
+        // Non-finite floats parse to their names
+        "float" => {
+            value.is_number()
+                || value
+                    .as_str()
+                    .is_some_and(|s| matches!(s, "Infinity" | "-Infinity" | "NaN"))
+        }
+        "bool" => value.is_boolean(),
+        "string" => value.is_string(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Build a schema field from its name token and parsed type, taking the
+/// `=default` from the name (`port=8080`) or from a scalar type name
+/// (`port:int=8080`). When nothing follows the `=`, the default is `next`
+/// (as in `host="local host"`); the flag reports that it was used.
+pub(crate) fn resolve_field_def(
+    word: &str,
+    type_def: TypeDef,
+    next: Option<&Token>,
+) -> Result<(FieldDef, bool), String> {
+    let (name, mut default_src) = match word.split_once('=') {
+        Some((name, src)) => (name, Some(src.to_string())),
+        None => (word, None),
+    };
+    let type_def = match type_def {
+        TypeDef::Object(t) if t.contains('=') => {
+            let (base, src) = t.split_once('=').unwrap_or((&t, ""));
+            default_src = Some(src.to_string());
+            TypeDef::Object(base.to_string())
+        }
+        other => other,
+    };
+
+    let field = FieldDef::declared(name, type_def);
+    match default_src.as_deref() {
+        None => Ok((field, false)),
+        Some("") => match next.and_then(scalar_token_value) {
+            Some(value) => Ok((field.with_default(Some(value)), true)),
+            None => Err(format!("Expected default value for field '{}'", name)),
+        },
+        Some(src) => Ok((field.with_default(Some(parse_default(name, src)?)), false)),
+    }
+}
+
+/// JSON value of a float literal; non-finite floats, which JSON numbers
+/// cannot hold, become `"Infinity"`, `"-Infinity"` or `"NaN"`
+pub(crate) fn float_value(n: f64) -> Value {
+    match serde_json::Number::from_f64(n) {
+        Some(n) => Value::Number(n),
+        None => Value::String(special_float_name(n).to_string()),
+    }
+}
+
+/// Value of a single scalar token, as allowed for field defaults
+pub(crate) fn scalar_token_value(token: &Token) -> Option<Value> {
+    match token {
+        Token::String(s) | Token::Ident(s) => Some(Value::String(s.to_string())),
+        Token::Integer(n) => Some(Value::from(*n)),
+        Token::UnsignedInteger(n) => Some(Value::from(*n)),
+        Token::Float(n) => Some(float_value(*n)),
+        Token::Bool(b) => Some(Value::Bool(*b)),
+        Token::Null => Some(Value::Null),
+        _ => None,
+    }
+}
+
+/// Parse the text after `=` in a field definition. Defaults are limited to
+/// one scalar literal: number, bool, null, bareword or quoted string.
+pub(crate) fn parse_default(field: &str, src: &str) -> Result<Value, String> {
+    let mut lexer = Lexer::new(src);
+    let value = lexer
+        .next_token()
+        .and_then(|st| scalar_token_value(&st.token));
+    match value {
+        Some(value) if lexer.next_token().is_none() && lexer.lex_error.is_none() => Ok(value),
+        _ => Err(format!(
+            "Default for field '{}' must be a scalar literal, got '{}'",
+            field, src
+        )),
+    }
 }
 
 /// Type definition for schema fields
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeDef {
     /// scalar value (number, string, bool, null)
     Scalar,
@@ -29,18 +208,151 @@ pub enum TypeDef {
     List(String),
 }
 
+/// Ordered schema fields with a name index for O(1) named-argument lookup
+#[derive(Debug, Clone, Default)]
+pub struct FieldSet {
+    /// Fields in declaration order (used for positional values)
+    pub ordered: Vec<FieldDef>,
+    /// Field name to position in `ordered`
+    pub index: HashMap<String, usize>,
+}
+
+impl FieldSet {
+    /// Build a field set from fields in declaration order
+    pub fn new(ordered: Vec<FieldDef>) -> Self {
+        let mut index = HashMap::with_capacity(ordered.len());
+        for (i, field) in ordered.iter().enumerate() {
+            // First declaration wins, matching positional lookup semantics
+            index.entry(field.name.clone()).or_insert(i);
+        }
+        Self { ordered, index }
+    }
+
+    /// Look up a field by name
+    pub fn get(&self, name: &str) -> Option<&FieldDef> {
+        self.index.get(name).map(|&i| &self.ordered[i])
+    }
+
+    /// Number of fields in the schema
+    pub fn len(&self) -> usize {
+        self.ordered.len()
+    }
+
+    /// Whether the schema has no fields
+    pub fn is_empty(&self) -> bool {
+        self.ordered.is_empty()
+    }
+}
+
+impl From<Vec<FieldDef>> for FieldSet {
+    fn from(ordered: Vec<FieldDef>) -> Self {
+        Self::new(ordered)
+    }
+}
+
 /// Maximum total number of imports allowed to prevent DoS
 const MAX_TOTAL_IMPORTS: usize = 100;
 
+/// Maximum warnings kept by one parser; later ones are dropped
+pub(crate) const MAX_WARNINGS: usize = 1000;
+
+/// Under `!strict`, the error for a row value that does not fit its
+/// field's scalar type annotation
+pub(crate) fn strict_type_error(field: &FieldDef, value: &Value) -> Option<String> {
+    match &field.type_def {
+        TypeDef::Object(type_name) if !matches_scalar_type(type_name, value) => Some(format!(
+            "Field '{}' expects {}, got {}",
+            field.name, type_name, value
+        )),
+        _ => None,
+    }
+}
+
+/// Error for a row of schema `shape` that left out a required `field`
+pub(crate) fn missing_field_error(field: &FieldDef, shape: &str) -> String {
+    format!(
+        "Missing value for required field '{}' in schema '{}'",
+        field.name, shape
+    )
+}
+
+/// Error for `===` in a parser that reads a single document
+pub(crate) const DOC_SEPARATOR_ERROR: &str = "Unexpected '===' - separate documents must be parsed with compile_tauq_multi or Parser::parse_multi";
+
+/// Warning for a row that gave only the first `supplied` values of schema
+/// `shape`, when every field it left out is optional or has a default
+pub(crate) fn short_row_warning(
+    shape: &str,
+    fields: &FieldSet,
+    supplied: usize,
+    span: Span,
+) -> Option<ParseWarning> {
+    let omitted = fields.ordered.get(supplied..).unwrap_or_default();
+    if omitted.is_empty() || !omitted.iter().all(|f| f.optional || f.default.is_some()) {
+        return None;
+    }
+    let names: Vec<&str> = omitted.iter().map(|f| f.name.as_str()).collect();
+    Some(ParseWarning::new(
+        WarningKind::ShortRow,
+        format!(
+            "Row has {} of {} values for schema '{}'; {} left to defaults",
+            supplied,
+            fields.len(),
+            shape,
+            names.join(", ")
+        ),
+        span,
+    ))
+}
+
+/// Warning for schema `name`, declared with no fields at `span` and never
+/// used as a marker
+pub(crate) fn empty_schema_warning(name: &str, span: Span) -> ParseWarning {
+    ParseWarning::new(
+        WarningKind::EmptySchema,
+        format!("Schema '{}' declares no fields", name),
+        span,
+    )
+}
+
+/// Error for a non-blank row under schema `name`, which has no fields
+pub(crate) fn marker_row_error(name: &str) -> String {
+    format!(
+        "Schema '{}' has no fields; its rows must be blank lines",
+        name
+    )
+}
+
+/// A `!def` (or `!schemas` entry) as declared, with the file it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaDefinition {
+    /// Schema name
+    pub name: String,
+    /// Fields in declaration order, including inherited ones
+    pub fields: Vec<FieldDef>,
+    /// Canonical path of the imported file that declared it; `None` for the
+    /// source handed to the parser
+    pub source: Option<std::path::PathBuf>,
+}
+
 /// Parser context holding schema definitions
 #[derive(Clone)]
 pub struct Context {
     /// Map of schema names to field definitions
-    pub shapes: Rc<RefCell<HashMap<String, Vec<FieldDef>>>>,
+    pub shapes: Rc<RefCell<HashMap<String, Rc<FieldSet>>>>,
+    /// Every schema definition in declaration order, redefinitions included
+    pub definitions: Rc<RefCell<Vec<SchemaDefinition>>>,
     /// Base directory for resolving relative imports
     pub base_dir: Option<std::path::PathBuf>,
+    /// Directory that imports, nested ones included, may not escape;
+    /// `None` confines each import to its importer's `base_dir`
+    pub import_root: Option<std::path::PathBuf>,
     /// Set of already-imported file paths (prevents circular/diamond imports)
     pub imported_files: Rc<RefCell<HashSet<std::path::PathBuf>>>,
+    /// Canonical paths of imports currently being parsed (cycle detection)
+    pub visited_imports: Rc<RefCell<HashSet<std::path::PathBuf>>>,
+    /// Active import chain in order, used to report cycles
+    pub import_chain: Rc<RefCell<Vec<std::path::PathBuf>>>,
     /// Total import count (prevents DoS via many flat imports)
     pub import_count: Rc<RefCell<usize>>,
 }
@@ -50,19 +362,71 @@ impl Context {
     pub fn new() -> Self {
         Self {
             shapes: Rc::new(RefCell::new(HashMap::new())),
+            definitions: Rc::new(RefCell::new(Vec::new())),
             base_dir: None,
+            import_root: None,
             imported_files: Rc::new(RefCell::new(HashSet::new())),
+            visited_imports: Rc::new(RefCell::new(HashSet::new())),
+            import_chain: Rc::new(RefCell::new(Vec::new())),
             import_count: Rc::new(RefCell::new(0)),
         }
     }
 
-    /// Create a context with a base directory for imports
+    /// Create a context whose schemas are already defined, as if by `!def`
+    pub fn with_schemas(schemas: HashMap<String, Vec<FieldDef>>) -> Self {
+        let context = Self::new();
+        context.shapes.borrow_mut().extend(
+            schemas
+                .into_iter()
+                .map(|(name, fields)| (name, Rc::new(FieldSet::new(fields)))),
+        );
+        context
+    }
+
+    /// Create a context with a base directory for imports, which they may
+    /// not escape
     pub fn with_base_dir(base_dir: std::path::PathBuf) -> Self {
         Self {
-            shapes: Rc::new(RefCell::new(HashMap::new())),
+            import_root: Some(base_dir.clone()),
             base_dir: Some(base_dir),
-            imported_files: Rc::new(RefCell::new(HashSet::new())),
-            import_count: Rc::new(RefCell::new(0)),
+            ..Self::new()
+        }
+    }
+
+    /// Create a context for parsing the file at `path`: imports resolve
+    /// relative to the file's directory, and may not escape it
+    pub fn for_file(path: &std::path::Path) -> Self {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => std::path::PathBuf::from("."),
+        };
+        Self::for_dir(dir)
+    }
+
+    /// Like [`Context::for_file`], for a file in `dir`
+    pub(crate) fn for_dir(dir: std::path::PathBuf) -> Self {
+        Self {
+            base_dir: Some(dir),
+            ..Self::new()
+        }
+    }
+
+    /// Let imports, nested ones included, reach anywhere under `root`
+    /// rather than only their importer's directory
+    /// (`!import "../schemas/user.tqn"`)
+    pub fn with_import_root(mut self, root: std::path::PathBuf) -> Self {
+        self.import_root = Some(root);
+        self
+    }
+
+    /// Let imports reach anywhere on the filesystem holding `base_dir`, for
+    /// sources the user chose, such as a file named on the command line
+    pub fn unconfined(self) -> Self {
+        let dir = self.base_dir.clone().unwrap_or_else(|| ".".into());
+        let absolute = std::path::absolute(&dir).unwrap_or(dir);
+        match absolute.ancestors().last() {
+            Some(root) => self.with_import_root(root.to_path_buf()),
+            None => self,
         }
     }
 }
@@ -76,15 +440,126 @@ impl Default for Context {
 /// Maximum nesting depth to prevent stack overflow from deeply nested structures
 const MAX_NESTING_DEPTH: usize = 100;
 
+/// Check a `!require` constraint such as `tauq >= 1.0` against the running version
+///
+/// The leading `tauq` is optional; the remainder is a semver requirement.
+pub fn check_version_requirement(spec: &str) -> Result<(), String> {
+    let spec = spec.trim();
+    let constraint = match spec.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("tauq") => spec[4..].trim(),
+        _ => spec,
+    };
+    if constraint.is_empty() {
+        return Err("!require needs a version constraint, e.g. '!require tauq >= 0.2'".to_string());
+    }
+
+    let req = semver::VersionReq::parse(constraint).map_err(|e| {
+        format!(
+            "Invalid version constraint '{}' in !require: {}",
+            constraint, e
+        )
+    })?;
+    let current = semver::Version::parse(env!("CARGO_PKG_VERSION"))
+        .map_err(|e| format!("Invalid crate version: {}", e))?;
+
+    if req.matches(&current) {
+        Ok(())
+    } else {
+        Err(format!(
+            "This file requires Tauq {} but you have {}. Please upgrade.",
+            constraint, current
+        ))
+    }
+}
+
+/// Text from byte `start` of `source` to the end of its line, without a
+/// trailing comment: the argument of a directive like `!require` that is not
+/// read as tokens
+pub(crate) fn rest_of_line(source: &str, start: usize) -> &str {
+    let rest = &source[start..];
+    let end = rest.find(['\n', ';', '#']).unwrap_or(rest.len());
+    rest[..end].trim()
+}
+
+/// How top-level `---` and `===` separators are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// `---` only clears the active schema; the file is one document and
+    /// `===` is an error
+    #[default]
+    SingleDocument,
+    /// `---` also ends the current document and starts a new one that keeps
+    /// the schema definitions so far; `===` starts an independent one that
+    /// sees none of them
+    MultiDocument,
+}
+
 /// Parser for Tauq source code
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
-    current_token: Option<SpannedToken>,
-    peek_token: Option<SpannedToken>,
+    current_token: Option<SpannedToken<'a>>,
+    peek_token: Option<SpannedToken<'a>>,
     context: Context,
     active_shape: Option<String>,
+    mode: ParseMode,
     /// Current nesting depth for recursion protection
     nesting_depth: usize,
+    /// Non-fatal issues found so far
+    warnings: Vec<ParseWarning>,
+    /// Check values against scalar type annotations (`!strict`)
+    strict: bool,
+    /// Whether top-level `---` ends a document in multi-document mode;
+    /// cleared by [`parse_separated`](Self::parse_separated)
+    dash_ends_document: bool,
+    /// End of the newline token just consumed, when the current token is
+    /// the first on its line
+    line_start: Option<Location>,
+    /// End of the last token consumed
+    prev_end: Option<Location>,
+    /// Zero-field schemas not yet used, with the span of their `!def`;
+    /// reported as [`WarningKind::EmptySchema`] at the end of the document
+    unused_markers: Vec<(String, Span)>,
+}
+
+/// What one top-level step of a document produced
+#[derive(Debug, Clone)]
+pub(crate) enum Step {
+    /// A directive was handled
+    Directive,
+    /// `key value` entries, merged into the document's current object
+    Entries(Map<String, Value>),
+    /// A schema row
+    Row(Value),
+    /// A bare value such as a list
+    Value(Value),
+    /// `---`
+    Separator,
+    /// `---` or `===` in multi-document mode
+    DocumentEnd,
+    /// Newline or `;`
+    Skip,
+}
+
+impl Step {
+    /// Add the step's output to a document's values and pending object
+    pub(crate) fn apply(self, result: &mut Vec<Value>, pending_map: &mut Map<String, Value>) {
+        match self {
+            Step::Directive => {
+                if !pending_map.is_empty() {
+                    result.push(Value::Object(std::mem::take(pending_map)));
+                }
+            }
+            Step::Entries(map) => pending_map.extend(map),
+            Step::Row(row) => {
+                if !pending_map.is_empty() {
+                    result.push(Value::Object(std::mem::take(pending_map)));
+                }
+                result.push(row);
+            }
+            Step::Value(value) => result.push(value),
+            Step::Separator | Step::DocumentEnd | Step::Skip => {}
+        }
+    }
 }
 
 impl<'a> Parser<'a> {
@@ -104,15 +579,212 @@ impl<'a> Parser<'a> {
             peek_token,
             context,
             active_shape: None,
+            mode: ParseMode::SingleDocument,
+            dash_ends_document: true,
             nesting_depth: 0,
+            warnings: Vec::new(),
+            strict: false,
+            line_start: None,
+            prev_end: None,
+            unused_markers: Vec::new(),
+        }
+    }
+
+    /// Create a parser that starts with `schemas` already defined
+    ///
+    /// Snippets that share a set of definitions can then `!use` them
+    /// without repeating the `!def` lines; see
+    /// [`extract_schemas`](Self::extract_schemas). The schemas are not
+    /// active until a `!use`, and are not listed by
+    /// [`schema_definitions`](Self::schema_definitions).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tauq::Parser;
+    ///
+    /// let schemas = Parser::extract_schemas("!def User id name\n").unwrap();
+    /// let value = Parser::new_with_schemas("!use User\n1 Alice\n", schemas)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(value, serde_json::json!({"id": 1, "name": "Alice"}));
+    /// ```
+    pub fn new_with_schemas(source: &'a str, schemas: HashMap<String, Vec<FieldDef>>) -> Self {
+        Self::new_with_context(source, Context::with_schemas(schemas))
+    }
+
+    /// Read only the schema header of `source`: the directives before the
+    /// first line that is not a directive, or the first `---` other than
+    /// the one closing a `!schemas` block
+    ///
+    /// Imports in the header are followed. Rows and values after it are
+    /// neither parsed nor validated.
+    pub fn extract_schemas(source: &str) -> Result<HashMap<String, Vec<FieldDef>>, ParseError> {
+        let mut parser = Parser::new(source);
+        while let Some(st) = &parser.current_token {
+            match &st.token {
+                Token::Newline | Token::Semi => parser.advance(),
+                Token::Directive(d) => {
+                    let d = d.clone();
+                    parser.advance();
+                    if d == "schemas" || d == "models" {
+                        parser.handle_schemas_block()?;
+                    } else {
+                        parser.handle_directive(&d)?;
+                    }
+                }
+                _ => break,
+            }
+        }
+        if let Some(lex_err) = parser.lexer.lex_error.take() {
+            return Err(ParseError::new(lex_err.message, lex_err.span));
+        }
+        Ok(parser.schemas())
+    }
+
+    /// Parse `source` as a single value: a scalar, `[list]` or `{ object }`
+    ///
+    /// Unlike [`parse`](Self::parse), `key value` is not read as an object
+    /// entry, and anything but blank lines after the value is an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use tauq::Parser;
+    ///
+    /// let value = Parser::parse_single_value("[1 2 3]").unwrap();
+    /// assert_eq!(value, serde_json::json!([1, 2, 3]));
+    /// assert!(Parser::parse_single_value("1 2").is_err());
+    /// ```
+    pub fn parse_single_value(source: &str) -> Result<Value, ParseError> {
+        let mut parser = Parser::new(source);
+        let result = parser.parse_lone_value();
+        // A lexer error (e.g. unterminated string) explains the failure better
+        if let Some(lex_err) = parser.lexer.lex_error.take() {
+            return Err(ParseError::new(lex_err.message, lex_err.span));
+        }
+        result
+    }
+
+    fn parse_lone_value(&mut self) -> Result<Value, ParseError> {
+        while self.check(&Token::Newline) {
+            self.advance();
+        }
+        let Some(value) = self.parse_value()? else {
+            return Err(match &self.current_token {
+                Some(st) => self.make_error(format!("Expected a value, found {:?}", st.token)),
+                None => self.make_error("Expected a value, found end of input"),
+            });
+        };
+        while self.check(&Token::Newline) {
+            self.advance();
+        }
+        match &self.current_token {
+            Some(st) => Err(self.make_error(format!("Unexpected {:?} after value", st.token))),
+            None => Ok(value),
+        }
+    }
+
+    /// Set how top-level `---` separators are handled
+    pub fn with_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Comments read so far that stand on a line of their own, keyed by
+    /// line number; see [`json_to_tauq_annotated`](super::json_to_tauq_annotated)
+    pub fn comments(&self) -> &HashMap<usize, String> {
+        self.lexer.comments()
+    }
+
+    /// Reject row values that do not match their field's scalar type
+    /// (`int`, `float`, `bool`, `string`, `null`), as `!strict` does
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Schemas defined so far, including those from imports, by name
+    pub fn schemas(&self) -> HashMap<String, Vec<FieldDef>> {
+        self.context
+            .shapes
+            .borrow()
+            .iter()
+            .map(|(name, fields)| (name.clone(), fields.ordered.clone()))
+            .collect()
+    }
+
+    /// Every schema definition seen so far in declaration order, with the
+    /// imported file each came from
+    pub fn schema_definitions(&self) -> Vec<SchemaDefinition> {
+        self.context.definitions.borrow().clone()
+    }
+
+    /// The schemas and import state shared with imported files
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Take the warnings collected so far, leaving none behind
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn warn(&mut self, warning: ParseWarning) {
+        if self.warnings.len() < MAX_WARNINGS {
+            self.warnings.push(warning);
         }
     }
 
     fn advance(&mut self) {
+        self.line_start = match &self.current_token {
+            Some(st) if st.token == Token::Newline => Some(st.end),
+            _ => None,
+        };
+        if let Some(st) = &self.current_token {
+            self.prev_end = Some(st.end);
+        }
         self.current_token = self.peek_token.take();
         self.peek_token = self.lexer.next_token();
     }
 
+    /// Name of the active schema, when it has no fields
+    fn active_marker(&self) -> Option<&str> {
+        self.active_shape.as_deref().filter(|name| {
+            self.context
+                .shapes
+                .borrow()
+                .get(*name)
+                .is_some_and(|set| set.is_empty())
+        })
+    }
+
+    /// Whether the current newline is followed by an empty row of a
+    /// zero-field schema: a blank line, or the end of the input after a
+    /// blank line
+    fn at_empty_row(&self) -> bool {
+        let blank = match self.peek_token.as_ref().map(|st| &st.token) {
+            Some(Token::Newline) => true,
+            // The newline ending the last line is not a row of its own
+            None => self.line_start.is_some(),
+            _ => false,
+        };
+        blank && self.active_marker().is_some()
+    }
+
+    /// Note that schema `name` is in use, so its `!def` is not reported
+    /// as empty
+    fn mark_used(&mut self, name: &str) {
+        self.unused_markers.retain(|(marker, _)| marker != name);
+    }
+
+    /// Warn about zero-field schemas that were never used
+    fn report_unused_markers(&mut self) {
+        for (name, span) in std::mem::take(&mut self.unused_markers) {
+            self.warn(empty_schema_warning(&name, span));
+        }
+    }
+
     fn current_location(&self) -> Location {
         self.current_token
             .as_ref()
@@ -121,12 +793,28 @@ impl<'a> Parser<'a> {
     }
 
     fn make_error(&self, msg: impl Into<String>) -> ParseError {
-        let loc = self.current_location();
-        ParseError::new(msg, Span::new(loc.line, loc.column))
+        self.make_error_at(msg, self.current_location())
     }
 
+    /// Error at `loc`, underlining the current token when it starts there
     fn make_error_at(&self, msg: impl Into<String>, loc: Location) -> ParseError {
-        ParseError::new(msg, Span::new(loc.line, loc.column))
+        let span = match &self.current_token {
+            Some(t) if t.start == loc => t.span(),
+            _ => Span::point(loc.line, loc.column),
+        };
+        ParseError::new(msg, span)
+    }
+
+    /// Error spanning a row from `first`, the span of its first token, to
+    /// the end of the last token consumed
+    fn row_error(&self, msg: impl Into<String>, first: Option<Span>) -> ParseError {
+        match (first, self.prev_end) {
+            (Some(first), Some(end)) => ParseError::new(
+                msg,
+                Span::range(first.line, first.column, end.line, end.column),
+            ),
+            _ => self.make_error(msg),
+        }
     }
 
     /// Check if current token matches the given token type
@@ -138,79 +826,79 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse the source into a JSON Value
+    ///
+    /// In [`ParseMode::MultiDocument`] the result is an array of documents.
     pub fn parse(&mut self) -> Result<Value, ParseError> {
+        match self.mode {
+            ParseMode::SingleDocument => Ok(self.parse_document()?.unwrap_or(Value::Array(vec![]))),
+            ParseMode::MultiDocument => self.parse_multi().map(Value::Array),
+        }
+    }
+
+    /// Parse a stream of `---`- or `===`-separated documents
+    ///
+    /// Schema definitions stay available to documents after a `---`, but
+    /// each document starts with no active schema. After `===` a document
+    /// starts with no schemas at all. Empty documents are skipped.
+    pub fn parse_multi(&mut self) -> Result<Vec<Value>, ParseError> {
+        self.parse_documents().into_iter().collect()
+    }
+
+    /// Parse a stream of `===`-separated documents, as
+    /// [`compile_tauq_multi`](crate::compile_tauq_multi) does
+    ///
+    /// Unlike [`parse_multi`](Self::parse_multi), `---` keeps its
+    /// single-document meaning and only clears the active schema.
+    pub fn parse_separated(&mut self) -> Result<Vec<Value>, ParseError> {
+        self.dash_ends_document = false;
+        self.parse_multi()
+    }
+
+    /// Parse every document as [`parse_multi`](Self::parse_multi) splits
+    /// them, recovering after errors
+    ///
+    /// A failing document yields its error and parsing resumes at the next
+    /// separator, so each document is validated independently.
+    pub fn parse_documents(&mut self) -> Vec<Result<Value, ParseError>> {
+        self.mode = ParseMode::MultiDocument;
+        let mut documents = Vec::new();
+        while self.current_token.is_some() {
+            match self.parse_document() {
+                Ok(Some(doc)) => documents.push(Ok(doc)),
+                Ok(None) => {}
+                Err(e) => {
+                    documents.push(Err(e));
+                    self.recover_to_document_end();
+                }
+            }
+        }
+        documents
+    }
+
+    /// Skip tokens up to the next document separator after a failed document
+    fn recover_to_document_end(&mut self) {
+        self.nesting_depth = 0;
+        self.active_shape = None;
+        while let Some(st) = &self.current_token {
+            if matches!(st.token, Token::DocSeparator)
+                || (st.token == Token::TripleDash && self.dash_ends_document)
+            {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// Parse one document; returns `None` if it contained no values
+    fn parse_document(&mut self) -> Result<Option<Value>, ParseError> {
         let mut result = Vec::new();
         let mut pending_map = Map::new();
 
-        while let Some(st) = &self.current_token {
-            match &st.token {
-                Token::Directive(d) => {
-                    if !pending_map.is_empty() {
-                        result.push(Value::Object(pending_map));
-                        pending_map = Map::new();
-                    }
-                    let d_str = d.clone();
-                    self.advance();
-                    if d_str == "schemas" || d_str == "models" {
-                        self.handle_schemas_block()?;
-                    } else {
-                        self.handle_directive(&d_str)?;
-                    }
-                }
-                Token::Newline | Token::Semi => self.advance(),
-                Token::TripleDash => {
-                    // --- clears the active schema (ends implicit !use scope)
-                    self.active_shape = None;
-                    self.advance();
-                }
-                Token::RBrace => {
-                    let loc = st.start;
-                    return Err(
-                        self.make_error_at("Unexpected '}' at top level - mismatched braces", loc)
-                    );
-                }
-                Token::RBracket => {
-                    let loc = st.start;
-                    return Err(self
-                        .make_error_at("Unexpected ']' at top level - mismatched brackets", loc));
-                }
-                _ => {
-                    if self.active_shape.is_some() {
-                        if !pending_map.is_empty() {
-                            result.push(Value::Object(pending_map));
-                            pending_map = Map::new();
-                        }
-                        if let Some(row) = self.parse_row()? {
-                            result.push(row);
-                        } else {
-                            // parse_row() returned None without consuming tokens
-                            // (e.g., empty schema with no fields). Advance to prevent
-                            // an infinite loop.
-                            self.advance();
-                        }
-                    } else {
-                        // Try to parse as map entry
-                        if let Some(val) = self.parse_map_entry()? {
-                            if let Value::Object(map) = val {
-                                for (k, v) in map {
-                                    pending_map.insert(k, v);
-                                }
-                            }
-                        } else if let Some(val) = self.parse_value()? {
-                            result.push(val);
-                        } else {
-                            let loc = self.current_location();
-                            let token_desc = self
-                                .current_token
-                                .as_ref()
-                                .map(|t| format!("{:?}", t.token))
-                                .unwrap_or_else(|| "EOF".to_string());
-                            return Err(self
-                                .make_error_at(format!("Unexpected token: {}", token_desc), loc));
-                        }
-                    }
-                }
+        while let Some(step) = self.parse_step()? {
+            if matches!(step, Step::DocumentEnd) {
+                break;
             }
+            step.apply(&mut result, &mut pending_map);
         }
 
         if !pending_map.is_empty() {
@@ -219,20 +907,120 @@ impl<'a> Parser<'a> {
 
         // Surface any lexer errors (e.g. unterminated string literal) that were
         // deferred during tokenisation.
-        if let Some(lex_err) = &self.lexer.lex_error {
-            return Err(ParseError::new(
-                lex_err.message.clone(),
-                Span::new(lex_err.span.line, lex_err.span.column),
-            ));
+        if let Some(lex_err) = self.lexer.lex_error.take() {
+            return Err(ParseError::new(lex_err.message, lex_err.span));
         }
 
-        if result.len() == 1 {
-            Ok(result.remove(0))
-        } else {
-            Ok(Value::Array(result))
+        match result.len() {
+            0 if self.mode == ParseMode::MultiDocument => Ok(None),
+            1 => Ok(Some(result.remove(0))),
+            _ => Ok(Some(Value::Array(result))),
         }
     }
 
+    /// Parse the next top-level directive, row, entry or value; `None` at
+    /// the end of the input
+    pub(crate) fn parse_step(&mut self) -> Result<Option<Step>, ParseError> {
+        let Some(st) = &self.current_token else {
+            self.report_unused_markers();
+            return Ok(None);
+        };
+        let step = match &st.token {
+            Token::Directive(d) => {
+                let d_str = d.clone();
+                self.advance();
+                if d_str == "schemas" || d_str == "models" {
+                    self.handle_schemas_block()?;
+                } else {
+                    self.handle_directive(&d_str)?;
+                }
+                Step::Directive
+            }
+            Token::Newline if self.at_empty_row() => {
+                // Rows of a zero-field schema are empty lines
+                if let Some(name) = self.active_marker().map(str::to_string) {
+                    self.mark_used(&name);
+                }
+                self.advance();
+                Step::Row(Value::Object(Map::new()))
+            }
+            Token::Newline | Token::Semi => {
+                self.advance();
+                Step::Skip
+            }
+            Token::TripleDash => {
+                // --- clears the active schema (ends implicit !use scope)
+                self.active_shape = None;
+                self.advance();
+                if self.mode == ParseMode::MultiDocument && self.dash_ends_document {
+                    Step::DocumentEnd
+                } else {
+                    Step::Separator
+                }
+            }
+            Token::DocSeparator if self.mode == ParseMode::MultiDocument => {
+                // A new document starts with no schemas
+                self.report_unused_markers();
+                self.context.shapes.borrow_mut().clear();
+                self.context.imported_files.borrow_mut().clear();
+                self.active_shape = None;
+                self.advance();
+                Step::DocumentEnd
+            }
+            Token::DocSeparator => {
+                let loc = st.start;
+                return Err(self.make_error_at(DOC_SEPARATOR_ERROR, loc));
+            }
+            Token::RBrace => {
+                let loc = st.start;
+                return Err(
+                    self.make_error_at("Unexpected '}' at top level - mismatched braces", loc)
+                );
+            }
+            Token::RBracket => {
+                let loc = st.start;
+                return Err(
+                    self.make_error_at("Unexpected ']' at top level - mismatched brackets", loc)
+                );
+            }
+            _ => {
+                if let Some(name) = self.active_marker() {
+                    let msg = marker_row_error(name);
+                    return Err(self.make_error(msg));
+                }
+                if self.active_shape.is_some() {
+                    if let Some(row) = self.parse_row()? {
+                        Step::Row(row)
+                    } else {
+                        // parse_row() returned None without consuming tokens.
+                        // Advance to prevent an infinite loop.
+                        self.advance();
+                        Step::Skip
+                    }
+                } else if let Some(val) = self.parse_map_entry()? {
+                    // Try to parse as map entry
+                    match val {
+                        Value::Object(map) => Step::Entries(map),
+                        _ => Step::Skip,
+                    }
+                } else if let Some(val) = self.parse_value()? {
+                    Step::Value(val)
+                } else {
+                    let loc = self.current_location();
+                    let token_desc = self
+                        .current_token
+                        .as_ref()
+                        .map(|t| format!("{:?}", t.token))
+                        .unwrap_or_else(|| "EOF".to_string());
+                    return Err(
+                        self.make_error_at(format!("Unexpected token: {}", token_desc), loc)
+                    );
+                }
+            }
+        };
+        Ok(Some(step))
+    }
+
     fn handle_schemas_block(&mut self) -> Result<(), ParseError> {
         loop {
             match &self.current_token {
@@ -242,19 +1030,18 @@ impl<'a> Parser<'a> {
                         break;
                     }
                     Token::Ident(shape_name) => {
-                        let shape_name = shape_name.clone();
+                        let shape_name = shape_name.to_string();
                         self.advance();
 
+                        let inherited = self.parse_extends(&shape_name)?;
                         let mut fields = Vec::new();
                         // Parse fields until newline or EOF or TripleDash
                         while let Some(st2) = &self.current_token {
                             match &st2.token {
-                                Token::Ident(name) => {
-                                    let name = name.clone();
+                                Token::Ident(word) => {
+                                    let word = word.clone();
                                     self.advance();
-
-                                    let type_def = self.parse_type_annotation()?;
-                                    fields.push(FieldDef { name, type_def });
+                                    fields.push(self.parse_field_def(&word)?);
                                 }
                                 Token::Newline | Token::Semi => {
                                     self.advance();
@@ -269,7 +1056,8 @@ impl<'a> Parser<'a> {
                                 }
                             }
                         }
-                        self.context.shapes.borrow_mut().insert(shape_name, fields);
+                        let fields = inherit_fields(inherited, fields);
+                        self.define_shape(shape_name, fields);
                     }
                     Token::Newline | Token::Semi => {
                         self.advance();
@@ -288,6 +1076,56 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    /// Parse an optional `!extends Parent` after a schema name, returning
+    /// the parent's fields in declaration order
+    fn parse_extends(&mut self, shape_name: &str) -> Result<Vec<FieldDef>, ParseError> {
+        if !matches!(&self.current_token, Some(st) if st.token == Token::Directive("extends".to_string()))
+        {
+            return Ok(Vec::new());
+        }
+        self.advance(); // Skip !extends
+
+        let Some(Token::Ident(parent)) = self.current_token.as_ref().map(|st| st.token.clone())
+        else {
+            return Err(self.make_error("!extends requires a parent schema name"));
+        };
+        // Parents are flattened when the child is defined, so the only
+        // possible cycle is a schema naming itself
+        if parent == shape_name {
+            return Err(self.make_error(format!(
+                "Circular inheritance: schema '{}' cannot extend itself",
+                shape_name
+            )));
+        }
+        let fields = match self.context.shapes.borrow().get(parent.as_ref()) {
+            Some(set) => set.ordered.clone(),
+            None => {
+                return Err(
+                    self.make_error(format!("!extends references undefined schema '{}'", parent))
+                );
+            }
+        };
+        self.advance();
+        Ok(fields)
+    }
+
+    /// Parse the rest of a schema field after its name token `word`:
+    /// `name[?][:Type][=default]`
+    fn parse_field_def(&mut self, word: &str) -> Result<FieldDef, ParseError> {
+        let type_def = if word.contains('=') {
+            TypeDef::Scalar
+        } else {
+            self.parse_type_annotation()?
+        };
+        let next = self.current_token.as_ref().map(|st| &st.token);
+        let (field, consumed) =
+            resolve_field_def(word, type_def, next).map_err(|msg| self.make_error(msg))?;
+        if consumed {
+            self.advance();
+        }
+        Ok(field)
+    }
+
     /// Parse optional type annotation (:Type or :[Type])
     fn parse_type_annotation(&mut self) -> Result<TypeDef, ParseError> {
         if !self.check(&Token::Colon) {
@@ -301,7 +1139,7 @@ impl<'a> Parser<'a> {
             if let Some(st) = &self.current_token
                 && let Token::Ident(inner) = &st.token
             {
-                let t = TypeDef::List(inner.clone());
+                let t = TypeDef::List(inner.to_string());
                 self.advance();
                 if self.check(&Token::RBracket) {
                     self.advance();
@@ -313,11 +1151,17 @@ impl<'a> Parser<'a> {
             return Err(self.make_error("Expected type name in list type"));
         }
 
+        // `null` lexes as a literal rather than a type name
+        if self.check(&Token::Null) {
+            self.advance();
+            return Ok(TypeDef::Object("null".to_string()));
+        }
+
         // Object type
         if let Some(st) = &self.current_token
             && let Token::Ident(t) = &st.token
         {
-            let t_def = TypeDef::Object(t.clone());
+            let t_def = TypeDef::Object(t.to_string());
             self.advance();
             return Ok(t_def);
         }
@@ -342,27 +1186,28 @@ impl<'a> Parser<'a> {
                 }
             }
             "def" => {
-                // !def Name field1 field2:Type
+                // !def Name [!extends Parent] field1 field2:Type
                 if let Some(st) = self.current_token.clone() {
-                    if let Token::Ident(shape_name) = st.token {
+                    if let Token::Ident(shape_name) = st.token.clone() {
+                        let shape_name = shape_name.into_owned();
                         self.advance();
+                        let inherited = self.parse_extends(&shape_name)?;
                         let mut fields = Vec::new();
 
                         while let Some(st_curr) = &self.current_token {
-                            let name = match &st_curr.token {
-                                Token::Ident(n) => n.clone(),
+                            let word = match &st_curr.token {
+                                Token::Ident(n) => n.to_string(),
                                 Token::Newline | Token::Semi => break,
                                 _ => break,
                             };
                             self.advance();
-
-                            let type_def = self.parse_type_annotation()?;
-                            fields.push(FieldDef { name, type_def });
+                            fields.push(self.parse_field_def(&word)?);
+                        }
+                        let fields = inherit_fields(inherited, fields);
+                        if fields.is_empty() {
+                            self.unused_markers.push((shape_name.clone(), st.span()));
                         }
-                        self.context
-                            .shapes
-                            .borrow_mut()
-                            .insert(shape_name.clone(), fields);
+                        self.define_shape(shape_name.clone(), fields);
                         self.active_shape = Some(shape_name);
                     } else {
                         return Err(self.make_error("!def requires a schema name"));
@@ -371,15 +1216,54 @@ impl<'a> Parser<'a> {
                     return Err(self.make_error("!def requires a schema name"));
                 }
             }
+            "compose" => {
+                // !compose Name Schema1 Schema2 ...
+                let Some(Token::Ident(shape_name)) =
+                    self.current_token.as_ref().map(|st| st.token.clone())
+                else {
+                    return Err(self.make_error("!compose requires a schema name"));
+                };
+                let shape_name = shape_name.into_owned();
+                self.advance();
+                let mut parts = Vec::new();
+                while let Some(Token::Ident(part)) =
+                    self.current_token.as_ref().map(|st| st.token.clone())
+                {
+                    parts.push(part.into_owned());
+                    self.advance();
+                }
+                let fields = compose_fields(&shape_name, &parts, |part| {
+                    self.context
+                        .shapes
+                        .borrow()
+                        .get(part)
+                        .map(|set| set.ordered.clone())
+                })
+                .map_err(|msg| self.make_error(msg))?;
+                self.define_shape(shape_name.clone(), fields);
+                self.active_shape = Some(shape_name);
+            }
             "use" => {
                 if let Some(st) = self.current_token.clone() {
-                    if let Token::Ident(shape_name) = st.token {
+                    if let Token::Ident(shape_name) = st.token.clone() {
+                        let shape_name = shape_name.into_owned();
                         if !self.context.shapes.borrow().contains_key(&shape_name) {
                             return Err(self.make_error(format!(
                                 "!use references undefined schema '{}'",
                                 shape_name
                             )));
                         }
+                        if self.active_shape.as_deref() == Some(shape_name.as_str()) {
+                            self.warn(ParseWarning::new(
+                                WarningKind::RedundantUse,
+                                format!(
+                                    "Schema '{}' is already active; !use is redundant",
+                                    shape_name
+                                ),
+                                st.span(),
+                            ));
+                        }
+                        self.mark_used(&shape_name);
                         self.active_shape = Some(shape_name);
                         self.advance();
                     } else {
@@ -389,6 +1273,23 @@ impl<'a> Parser<'a> {
                     return Err(self.make_error("!use requires a schema name"));
                 }
             }
+            "strict" => self.strict = true,
+            "require" => {
+                let loc = self.current_location();
+                let spec = self.take_rest_of_line();
+                check_version_requirement(&spec).map_err(|msg| self.make_error_at(msg, loc))?;
+            }
+            "enum" => {
+                // Enum declarations (`!enum Status active inactive`) are
+                // documentation for tooling; values are not validated here
+                if !matches!(
+                    self.current_token.as_ref().map(|t| &t.token),
+                    Some(Token::Ident(_))
+                ) {
+                    return Err(self.make_error("!enum requires a type name"));
+                }
+                self.take_rest_of_line();
+            }
             _ => {
                 // Unknown directive - skip but warn
                 // In a production system, this might be a warning or error
@@ -397,6 +1298,39 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    fn define_shape(&mut self, name: String, fields: Vec<FieldDef>) {
+        let source = self.context.import_chain.borrow().last().cloned();
+        self.context
+            .definitions
+            .borrow_mut()
+            .push(SchemaDefinition {
+                name: name.clone(),
+                fields: fields.clone(),
+                source,
+            });
+        self.context
+            .shapes
+            .borrow_mut()
+            .insert(name, Rc::new(FieldSet::new(fields)));
+    }
+
+    /// Consume the remaining tokens on the current line and return their raw source text
+    fn take_rest_of_line(&mut self) -> String {
+        let start = match &self.current_token {
+            Some(st) if !matches!(st.token, Token::Newline | Token::Semi) => st.start.offset,
+            _ => return String::new(),
+        };
+        let text = rest_of_line(self.lexer.source(), start).to_string();
+
+        while let Some(st) = &self.current_token {
+            if matches!(st.token, Token::Newline | Token::Semi) {
+                break;
+            }
+            self.advance();
+        }
+        text
+    }
+
     fn handle_import(&mut self, path: &str) -> Result<(), ParseError> {
         // Check total import count to prevent DoS via many flat imports
         {
@@ -422,11 +1356,16 @@ impl<'a> Parser<'a> {
             self.make_error(format!("Cannot resolve import path '{}': {}", path, e))
         })?;
 
-        if let Some(base) = &self.context.base_dir {
-            let base_canonical = base
+        let root = self
+            .context
+            .import_root
+            .as_ref()
+            .or(self.context.base_dir.as_ref());
+        if let Some(root) = root {
+            let root_canonical = root
                 .canonicalize()
                 .map_err(|e| self.make_error(format!("Cannot resolve base directory: {}", e)))?;
-            if !canonical.starts_with(&base_canonical) {
+            if !canonical.starts_with(&root_canonical) {
                 return Err(self.make_error(format!(
                     "Import path '{}' escapes base directory (path traversal blocked)",
                     path
@@ -434,7 +1373,19 @@ impl<'a> Parser<'a> {
             }
         }
 
-        // Check for circular/diamond imports using visited set
+        // A file that is still being parsed higher up the chain is a cycle
+        if self.context.visited_imports.borrow().contains(&canonical) {
+            let chain = self.context.import_chain.borrow();
+            let start = chain.iter().position(|p| *p == canonical).unwrap_or(0);
+            let cycle: Vec<String> = chain[start..]
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(self.make_error(format!("Circular import detected: {}", cycle.join(" → "))));
+        }
+
+        // Skip files already imported via another branch (diamond imports)
         {
             let mut imported = self.context.imported_files.borrow_mut();
             if imported.contains(&canonical) {
@@ -452,10 +1403,23 @@ impl<'a> Parser<'a> {
         let mut import_context = self.context.clone();
         import_context.base_dir = canonical.parent().map(|p| p.to_path_buf());
 
-        let mut parser = Parser::new_with_context(&content, import_context);
-        parser
-            .parse()
-            .map_err(|e| self.make_error(format!("Error in imported file '{}': {}", path, e)))?;
+        self.context
+            .visited_imports
+            .borrow_mut()
+            .insert(canonical.clone());
+        self.context
+            .import_chain
+            .borrow_mut()
+            .push(canonical.clone());
+
+        let mut parser =
+            Parser::new_with_context(&content, import_context).with_strict(self.strict);
+        let result = parser.parse();
+
+        self.context.import_chain.borrow_mut().pop();
+        self.context.visited_imports.borrow_mut().remove(&canonical);
+
+        result.map_err(|e| self.make_error(format!("Error in imported file '{}': {}", path, e)))?;
 
         Ok(())
     }
@@ -468,13 +1432,15 @@ impl<'a> Parser<'a> {
         };
 
         let fields = if let Some(f) = self.context.shapes.borrow().get(&shape_name) {
-            f.clone()
+            Rc::clone(f)
         } else {
             return Ok(None);
         };
 
         let mut obj = Map::new();
         let mut field_idx = 0;
+        let mut named = false;
+        let row_span = self.current_token.as_ref().map(SpannedToken::span);
 
         loop {
             if self.current_token.is_none() {
@@ -507,11 +1473,13 @@ impl<'a> Parser<'a> {
                 if let Some(st) = self.current_token.clone()
                     && let Token::Ident(key) = st.token
                 {
+                    let key = key.into_owned();
                     self.advance(); // consume key
                     self.advance(); // consume colon
+                    named = true;
 
-                    if let Some(field) = fields.iter().find(|f| f.name == key) {
-                        if let Some(val) = self.parse_typed_value(&field.type_def)? {
+                    if let Some(field) = fields.get(&key) {
+                        if let Some(val) = self.parse_field_value(field)? {
                             obj.insert(key, val);
                         } else {
                             return Err(self
@@ -524,8 +1492,8 @@ impl<'a> Parser<'a> {
                     }
                 }
             } else if field_idx < fields.len() {
-                let field = &fields[field_idx];
-                if let Some(val) = self.parse_typed_value(&field.type_def)? {
+                let field = &fields.ordered[field_idx];
+                if let Some(val) = self.parse_field_value(field)? {
                     obj.insert(field.name.clone(), val);
                     field_idx += 1;
                 } else {
@@ -546,10 +1514,43 @@ impl<'a> Parser<'a> {
         }
 
         if obj.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(Value::Object(obj)))
+            return Ok(None);
         }
+
+        // Fields the row did not supply: defaults and nulls fill optional
+        // ones, required ones are an error
+        for field in &fields.ordered {
+            if obj.contains_key(&field.name) {
+                continue;
+            }
+            if let Some(default) = &field.default {
+                obj.insert(field.name.clone(), default.clone());
+            } else if field.optional {
+                obj.insert(field.name.clone(), Value::Null);
+            } else {
+                return Err(self.row_error(missing_field_error(field, &shape_name), row_span));
+            }
+        }
+        if !named
+            && let Some(span) = row_span
+            && let Some(warning) = short_row_warning(&shape_name, &fields, field_idx, span)
+        {
+            self.warn(warning);
+        }
+        Ok(Some(Value::Object(obj)))
+    }
+
+    /// Parse a row value for `field`, checking its type in strict mode
+    fn parse_field_value(&mut self, field: &FieldDef) -> Result<Option<Value>, ParseError> {
+        let loc = self.current_location();
+        let value = self.parse_typed_value(&field.type_def)?;
+        if self.strict
+            && let Some(value) = &value
+            && let Some(msg) = strict_type_error(field, value)
+        {
+            return Err(self.make_error_at(msg, loc));
+        }
+        Ok(value)
     }
 
     fn parse_typed_value(&mut self, type_def: &TypeDef) -> Result<Option<Value>, ParseError> {
@@ -572,7 +1573,8 @@ impl<'a> Parser<'a> {
                     self.active_shape = old_shape;
                     Ok(res)
                 } else {
-                    Ok(None)
+                    // Scalar annotations (`:int`, enum names) carry no structure
+                    self.parse_value()
                 }
             }
             TypeDef::List(type_name) => {
@@ -640,9 +1642,20 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_map_entry(&mut self) -> Result<Option<Value>, ParseError> {
-        if let Some(st) = self.current_token.clone()
-            && let Token::Ident(key) = st.token
-        {
+        let key = match self.current_token.as_ref().map(|st| &st.token) {
+            Some(Token::Ident(key)) => Some(key.to_string()),
+            // A quoted key needs a value after it; alone it is a value itself
+            Some(Token::String(key))
+                if !matches!(
+                    self.peek_token.as_ref().map(|st| &st.token),
+                    None | Some(Token::Newline | Token::Semi | Token::RBrace | Token::RBracket)
+                ) =>
+            {
+                Some(key.to_string())
+            }
+            _ => None,
+        };
+        if let Some(key) = key {
             self.advance();
 
             // Optional colon
@@ -662,15 +1675,13 @@ impl<'a> Parser<'a> {
     fn parse_value(&mut self) -> Result<Option<Value>, ParseError> {
         let val = if let Some(st) = &self.current_token {
             match &st.token {
-                Token::String(s) => Some(Value::String(s.clone())),
+                Token::String(s) => Some(Value::String(s.to_string())),
                 Token::Integer(n) => Some(Value::Number(serde_json::Number::from(*n))),
                 Token::UnsignedInteger(n) => Some(Value::Number(serde_json::Number::from(*n))),
-                Token::Float(n) => Some(Value::Number(
-                    serde_json::Number::from_f64(*n).unwrap_or(serde_json::Number::from(0)),
-                )),
+                Token::Float(n) => Some(float_value(*n)),
                 Token::Bool(b) => Some(Value::Bool(*b)),
                 Token::Null => Some(Value::Null),
-                Token::Ident(s) => Some(Value::String(s.clone())),
+                Token::Ident(s) => Some(Value::String(s.to_string())),
                 Token::LBracket => return self.parse_list(),
                 Token::LBrace => return self.parse_object(),
                 _ => None,
@@ -728,7 +1739,7 @@ impl<'a> Parser<'a> {
                         if let Some(st2) = &self.current_token
                             && let Token::Ident(shape_name) = &st2.token
                         {
-                            let shape_name = shape_name.clone();
+                            let shape_name = shape_name.to_string();
                             if !self.context.shapes.borrow().contains_key(&shape_name) {
                                 return Err(self.make_error(format!(
                                     "!use references undefined schema '{}' in array",
@@ -793,9 +1804,9 @@ impl<'a> Parser<'a> {
                     _ => {
                         // Expect Key Value
                         let key = if let Token::Ident(k) = &st.token {
-                            k.clone()
+                            k.to_string()
                         } else if let Token::String(k) = &st.token {
-                            k.clone()
+                            k.to_string()
                         } else {
                             return Err(self.make_error(format!(
                                 "Expected key in object, got {:?}",
@@ -823,3 +1834,449 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+/// Schema state at the start of a line, owned so parses can be kept
+/// across threads
+#[cfg(feature = "lsp")]
+#[derive(Debug, Default, PartialEq)]
+struct ParseState {
+    shapes: HashMap<String, Vec<FieldDef>>,
+    definitions: Vec<SchemaDefinition>,
+    imported_files: HashSet<std::path::PathBuf>,
+    import_count: usize,
+    active_shape: Option<String>,
+    strict: bool,
+    /// Zero-field schemas not yet used, with absolute line numbers
+    unused_markers: Vec<(String, Span)>,
+}
+
+#[cfg(feature = "lsp")]
+impl ParseState {
+    /// State of `parser`, which started `lines` lines into the source
+    fn capture(parser: &Parser, lines: usize) -> Self {
+        let context = &parser.context;
+        Self {
+            shapes: context
+                .shapes
+                .borrow()
+                .iter()
+                .map(|(name, fields)| (name.clone(), fields.ordered.clone()))
+                .collect(),
+            definitions: context.definitions.borrow().clone(),
+            imported_files: context.imported_files.borrow().clone(),
+            import_count: *context.import_count.borrow(),
+            active_shape: parser.active_shape.clone(),
+            strict: parser.strict,
+            unused_markers: parser
+                .unused_markers
+                .iter()
+                .map(|(name, span)| (name.clone(), wrapping_shift(*span, lines)))
+                .collect(),
+        }
+    }
+
+    fn context(&self) -> Context {
+        let context = Context::with_schemas(self.shapes.clone());
+        *context.definitions.borrow_mut() = self.definitions.clone();
+        *context.imported_files.borrow_mut() = self.imported_files.clone();
+        *context.import_count.borrow_mut() = self.import_count;
+        context
+    }
+}
+
+/// Output of a segment, in source order
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone)]
+enum Item {
+    Step(Step),
+    /// Parse error; the rest of its document was skipped. One `at_end` of
+    /// the input is reported at 1:1 and does not move with its segment.
+    Error {
+        error: ParseError,
+        at_end: bool,
+    },
+    /// Lexer error, reported when its document ends
+    LexError(ParseError),
+}
+
+/// Lines parsed together: a top-level line, plus the following lines when a
+/// value, string or `!schemas` block runs on
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone)]
+struct Segment {
+    /// Byte offset of the first line
+    offset: usize,
+    /// 1-based number of the first line
+    line: usize,
+    /// State before the segment
+    state: Arc<ParseState>,
+    items: Vec<Item>,
+    warnings: Vec<ParseWarning>,
+}
+
+#[cfg(feature = "lsp")]
+impl Segment {
+    fn new(offset: usize, line: usize, state: Arc<ParseState>) -> Self {
+        Self {
+            offset,
+            line,
+            state,
+            items: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Move the segment `bytes` and `lines` down (or up, when negative).
+    /// Warnings before `from_line`, about unused schemas defined above the
+    /// moved lines, stay put.
+    fn shifted(mut self, bytes: isize, lines: isize, from_line: usize) -> Self {
+        let shift = |span: &mut Span| {
+            span.line = span.line.saturating_add_signed(lines);
+            span.end_line = span.end_line.saturating_add_signed(lines);
+        };
+        self.offset = self.offset.saturating_add_signed(bytes);
+        self.line = self.line.saturating_add_signed(lines);
+        for item in &mut self.items {
+            match item {
+                Item::Error { at_end: true, .. } | Item::Step(_) => {}
+                Item::Error { error, .. } | Item::LexError(error) => shift(&mut error.span),
+            }
+        }
+        for warning in &mut self.warnings {
+            if warning.span.line >= from_line {
+                shift(&mut warning.span);
+            }
+        }
+        self
+    }
+}
+
+/// `span` moved `lines` down, wrapping: a zero-field schema defined before
+/// a segment run is moved up out of the run's lines and back again
+#[cfg(feature = "lsp")]
+fn wrapping_shift(span: Span, lines: usize) -> Span {
+    Span {
+        line: span.line.wrapping_add(lines),
+        end_line: span.end_line.wrapping_add(lines),
+        ..span
+    }
+}
+
+/// Where [`parse_segments`] stopped
+#[cfg(feature = "lsp")]
+struct SegmentRun {
+    /// Index of the old segment the parse caught up with
+    resync: Option<usize>,
+    /// 1-based line where the parse stopped; past the last line at the end
+    end_line: usize,
+    /// State at the end of the input, when the parse got there
+    end_state: Option<Arc<ParseState>>,
+}
+
+/// Parse `source` from the line starting at `offset`, appending segments to
+/// `out`. Stops early at a line start at or after `min_offset` where an
+/// `old` segment, moved by `delta` bytes, starts with the same state.
+#[cfg(feature = "lsp")]
+fn parse_segments(
+    source: &str,
+    (offset, line, state): (usize, usize, Arc<ParseState>),
+    out: &mut Vec<Segment>,
+    old: &[Segment],
+    delta: isize,
+    min_offset: usize,
+) -> SegmentRun {
+    let text = &source[offset..];
+    // The lexer skips a byte-order mark, so its offsets start after it
+    let base = offset + text.len() - super::lexer::strip_bom(text).len();
+    let lines = line - 1;
+    let mut parser = Parser::new_with_context(text, state.context())
+        .with_mode(ParseMode::MultiDocument)
+        .with_strict(state.strict);
+    parser.active_shape = state.active_shape.clone();
+    parser.unused_markers = state
+        .unused_markers
+        .iter()
+        .map(|(name, span)| (name.clone(), wrapping_shift(*span, lines.wrapping_neg())))
+        .collect();
+
+    let mut segment = Segment::new(offset, line, state);
+    let mut dirty = false;
+    // Lexer error from a token past the current segment
+    let mut carry: Option<ParseError> = None;
+    let mut candidate = 0;
+    loop {
+        let markers = parser.unused_markers.len();
+        match parser.parse_step() {
+            Ok(None) => break,
+            Ok(Some(Step::Skip)) => {}
+            Ok(Some(step)) => {
+                dirty |= matches!(step, Step::Directive | Step::Separator | Step::DocumentEnd);
+                segment.items.push(Item::Step(step));
+            }
+            Err(mut error) => {
+                // At the end of the input errors are reported at 1:1
+                let at_end = parser.current_token.is_none() && error.span == Span::point(1, 1);
+                if !at_end {
+                    error.span = error.span.shifted(lines);
+                }
+                segment.items.push(Item::Error { error, at_end });
+                parser.recover_to_document_end();
+                dirty = true;
+            }
+        }
+        dirty |= parser.unused_markers.len() != markers;
+        segment
+            .warnings
+            .extend(parser.take_warnings().into_iter().map(|mut w| {
+                w.span = wrapping_shift(w.span, lines);
+                w
+            }));
+        if let Some(e) = parser.lexer.lex_error.take() {
+            let span = e.span.shifted(lines);
+            carry = carry.or(Some(ParseError::new(e.message, span)));
+        }
+
+        let (Some(start), Some(_)) = (parser.line_start, &parser.current_token) else {
+            continue;
+        };
+        let next_offset = base + start.offset;
+        let next_line = start.line + lines;
+        if let Some(e) = carry.take_if(|e| e.span.line < next_line) {
+            segment.items.push(Item::LexError(e));
+        }
+        let state = if std::mem::take(&mut dirty) {
+            Arc::new(ParseState::capture(&parser, lines))
+        } else {
+            Arc::clone(&segment.state)
+        };
+        out.push(std::mem::replace(
+            &mut segment,
+            Segment::new(next_offset, next_line, state),
+        ));
+
+        if next_offset < min_offset {
+            continue;
+        }
+        // The old segment starting here, if any, and whether its parse can
+        // be reused; a lexer error still carried belongs to it
+        let old_offset = next_offset.saturating_add_signed(-delta);
+        candidate += old[candidate..].partition_point(|s| s.offset < old_offset);
+        if let Some(seg) = old.get(candidate)
+            && seg.offset == old_offset
+            && (Arc::ptr_eq(&seg.state, &segment.state) || seg.state == segment.state)
+        {
+            return SegmentRun {
+                resync: Some(candidate),
+                end_line: next_line,
+                end_state: None,
+            };
+        }
+    }
+
+    // Zero-field schemas never used are reported at the end of the input
+    segment
+        .warnings
+        .extend(parser.take_warnings().into_iter().map(|mut w| {
+            w.span = wrapping_shift(w.span, lines);
+            w
+        }));
+    segment.items.extend(carry.map(Item::LexError));
+    out.push(segment);
+    SegmentRun {
+        resync: None,
+        end_line: line + text.lines().count(),
+        end_state: Some(Arc::new(ParseState::capture(&parser, lines))),
+    }
+}
+
+/// Finish a document of [`IncrementalParser::documents`]
+#[cfg(feature = "lsp")]
+fn finish_document(
+    documents: &mut Vec<Result<Value, ParseError>>,
+    result: &mut Vec<Value>,
+    pending_map: &mut Map<String, Value>,
+    lex_error: &mut Option<ParseError>,
+) {
+    if !pending_map.is_empty() {
+        result.push(Value::Object(std::mem::take(pending_map)));
+    }
+    if let Some(e) = lex_error.take() {
+        documents.push(Err(e));
+        result.clear();
+        return;
+    }
+    match result.len() {
+        0 => {}
+        1 => documents.extend(result.pop().map(Ok)),
+        _ => documents.push(Ok(Value::Array(std::mem::take(result)))),
+    }
+}
+
+/// Parser that keeps its results between edits and re-parses only the
+/// lines an edit touches
+///
+/// Parse state is recorded at the start of every top-level line. After
+/// [`edit`](Self::edit)s, [`reparse`](Self::reparse) resumes from the line
+/// before the first change and stops at the first line after the last
+/// change whose schema state matches the previous parse; later lines keep
+/// their results. Editing a row of a large schema table re-parses a couple
+/// of lines, while changing a `!def` re-parses everything after it.
+///
+/// Results match [`Parser::parse_documents`] on the current source, except
+/// that a lexer error (such as a bad `\u` escape) fails only the document
+/// holding the bad token.
+///
+/// # Example
+/// ```
+/// use tauq::tauq::IncrementalParser;
+///
+/// let mut parser = IncrementalParser::new("!def User id name\n1 Alice\n2 Bob\n");
+/// parser.edit(20, 25, "Alicia");
+/// assert_eq!(parser.reparse(), 2);
+/// let docs = parser.documents();
+/// assert_eq!(docs[0].as_ref().unwrap()[0]["name"], "Alicia");
+/// ```
+#[cfg(feature = "lsp")]
+#[derive(Debug, Clone)]
+pub struct IncrementalParser {
+    source: String,
+    segments: Vec<Segment>,
+    end_state: Arc<ParseState>,
+    edits: Vec<(usize, usize, String)>,
+}
+
+#[cfg(feature = "lsp")]
+impl IncrementalParser {
+    /// Parse `source` in full
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let mut segments = Vec::new();
+        let run = parse_segments(
+            &source,
+            (0, 1, Arc::default()),
+            &mut segments,
+            &[],
+            0,
+            usize::MAX,
+        );
+        Self {
+            source,
+            segments,
+            end_state: run.end_state.unwrap_or_default(),
+            edits: Vec::new(),
+        }
+    }
+
+    /// The source with every edit applied by [`reparse`](Self::reparse)
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Queue replacing bytes `start..end` with `replacement`
+    ///
+    /// Offsets refer to the source after the edits queued before this one,
+    /// as with LSP content changes.
+    pub fn edit(&mut self, start: usize, end: usize, replacement: &str) {
+        self.edits.push((start, end, replacement.to_string()));
+    }
+
+    /// Apply the queued edits and re-parse the lines they affect, returning
+    /// how many lines were parsed again
+    ///
+    /// # Panics
+    /// If an edit range is out of bounds or not on character boundaries.
+    pub fn reparse(&mut self) -> usize {
+        if self.edits.is_empty() {
+            return 0;
+        }
+        let old_len = self.source.len();
+        // First changed byte, and the length of the unchanged tail
+        let (mut first, mut tail) = (usize::MAX, usize::MAX);
+        for (start, end, replacement) in std::mem::take(&mut self.edits) {
+            first = first.min(start);
+            tail = tail.min(self.source.len() - end);
+            self.source.replace_range(start..end, &replacement);
+        }
+        let delta = self.source.len() as isize - old_len as isize;
+        let changed_end = self.source.len() - tail;
+
+        // Start a line early: a line's last token looks ahead into the next
+        let restart = self
+            .segments
+            .partition_point(|s| s.offset <= first)
+            .saturating_sub(2);
+        let old = self.segments.split_off(restart);
+        let start = old.first().map_or((0, 1, Arc::default()), |s| {
+            (s.offset, s.line, Arc::clone(&s.state))
+        });
+        let line = start.1;
+        let run = parse_segments(
+            &self.source,
+            start,
+            &mut self.segments,
+            &old,
+            delta,
+            changed_end,
+        );
+        match run.resync {
+            Some(index) => {
+                let from_line = old[index].line;
+                let lines = run.end_line as isize - from_line as isize;
+                self.segments.extend(
+                    old.into_iter()
+                        .skip(index)
+                        .map(|s| s.shifted(delta, lines, from_line)),
+                );
+            }
+            None => self.end_state = run.end_state.unwrap_or_default(),
+        }
+        run.end_line - line
+    }
+
+    /// Every document, as [`Parser::parse_documents`] returns them
+    pub fn documents(&self) -> Vec<Result<Value, ParseError>> {
+        let mut documents = Vec::new();
+        let mut result = Vec::new();
+        let mut pending_map = Map::new();
+        let mut lex_error = None;
+        for item in self.segments.iter().flat_map(|s| &s.items) {
+            match item {
+                Item::Step(Step::DocumentEnd) => finish_document(
+                    &mut documents,
+                    &mut result,
+                    &mut pending_map,
+                    &mut lex_error,
+                ),
+                Item::Step(step) => step.clone().apply(&mut result, &mut pending_map),
+                Item::Error { error, .. } => {
+                    documents.push(Err(error.clone()));
+                    result.clear();
+                    pending_map.clear();
+                }
+                Item::LexError(e) => {
+                    lex_error.get_or_insert_with(|| e.clone());
+                }
+            }
+        }
+        finish_document(
+            &mut documents,
+            &mut result,
+            &mut pending_map,
+            &mut lex_error,
+        );
+        documents
+    }
+
+    /// Warnings for the whole source
+    pub fn warnings(&self) -> Vec<ParseWarning> {
+        self.segments
+            .iter()
+            .flat_map(|s| s.warnings.iter().cloned())
+            .collect()
+    }
+
+    /// Schemas defined by the end of the source, by name
+    pub fn schemas(&self) -> HashMap<String, Vec<FieldDef>> {
+        self.end_state.shapes.clone()
+    }
+}
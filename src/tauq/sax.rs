@@ -0,0 +1,881 @@
+// Tauq SAX Parser
+//
+// Event-driven API for parsing Tauq without materialising rows.
+// Handlers receive schema definitions, row boundaries, and field values
+// that borrow from the source wherever possible.
+
+use super::lexer::Lexer;
+use super::parser::{
+    DOC_SEPARATOR_ERROR, FieldDef, TypeDef, check_version_requirement, compose_fields, float_value,
+    inherit_fields, marker_row_error, missing_field_error, resolve_field_def, rest_of_line,
+    strict_type_error,
+};
+use super::token::{Location, SpannedToken, Token};
+use crate::error::{ParseError, Span};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Maximum nesting depth to prevent stack overflow from deeply nested structures
+const MAX_NESTING_DEPTH: usize = 100;
+
+/// A value passed to [`TauqHandler`] callbacks, borrowing from the source where possible
+#[derive(Debug, Clone, PartialEq)]
+pub enum TauqValue<'a> {
+    /// Null literal
+    Null,
+    /// Boolean literal
+    Bool(bool),
+    /// Signed integer literal
+    Integer(i64),
+    /// Unsigned integer literal (for values > i64::MAX)
+    UnsignedInteger(u64),
+    /// Floating point literal
+    Float(f64),
+    /// String or bareword (borrowed unless the literal contained escapes)
+    Str(Cow<'a, str>),
+    /// List of values
+    Array(Vec<TauqValue<'a>>),
+    /// Object entries in source order
+    Object(Vec<(Cow<'a, str>, TauqValue<'a>)>),
+}
+
+impl TauqValue<'_> {
+    /// Convert to an owned JSON value
+    pub fn to_json(&self) -> Value {
+        match self {
+            TauqValue::Null => Value::Null,
+            TauqValue::Bool(b) => Value::Bool(*b),
+            TauqValue::Integer(n) => Value::Number((*n).into()),
+            TauqValue::UnsignedInteger(n) => Value::Number((*n).into()),
+            TauqValue::Float(n) => float_value(*n),
+            TauqValue::Str(s) => Value::String(s.to_string()),
+            TauqValue::Array(items) => Value::Array(items.iter().map(|v| v.to_json()).collect()),
+            TauqValue::Object(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Callbacks for event-driven Tauq parsing
+///
+/// All methods have empty default implementations so handlers only need to
+/// implement the events they care about.
+pub trait TauqHandler {
+    /// A schema was defined via `!def` or a `!schemas` block
+    fn on_schema_def(&mut self, _name: &str, _fields: &[&str]) {}
+    /// A schema row begins
+    fn on_row_start(&mut self, _schema: &str) {}
+    /// A field value within the current row
+    fn on_field(&mut self, _name: &str, _value: TauqValue<'_>) {}
+    /// The current schema row ends
+    fn on_row_end(&mut self) {}
+    /// A top-level key-value entry outside any schema
+    fn on_kv(&mut self, _key: &str, _value: TauqValue<'_>) {}
+}
+
+/// Parse Tauq source, reporting structure to `handler` instead of building values
+///
+/// # Example
+/// ```
+/// use tauq::tauq::sax::{TauqHandler, TauqValue, parse_sax};
+///
+/// struct RowCounter(usize);
+/// impl TauqHandler for RowCounter {
+///     fn on_row_end(&mut self) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// let mut counter = RowCounter(0);
+/// parse_sax("!def User id name\n1 Alice\n2 Bob", &mut counter).unwrap();
+/// assert_eq!(counter.0, 2);
+/// ```
+pub fn parse_sax<H: TauqHandler>(source: &str, handler: &mut H) -> Result<(), ParseError> {
+    SaxParser::new(source).run(handler)
+}
+
+/// Handler that rebuilds JSON values, matching [`Parser`](super::Parser) output
+#[derive(Debug, Default)]
+pub struct CollectingHandler {
+    values: Vec<Value>,
+    pending: Map<String, Value>,
+    row: Option<Map<String, Value>>,
+}
+
+impl CollectingHandler {
+    /// Create an empty collecting handler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consume the handler and return the collected top-level values
+    pub fn into_values(mut self) -> Vec<Value> {
+        self.flush_pending();
+        self.values
+    }
+
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            self.values
+                .push(Value::Object(std::mem::take(&mut self.pending)));
+        }
+    }
+}
+
+impl TauqHandler for CollectingHandler {
+    fn on_schema_def(&mut self, _name: &str, _fields: &[&str]) {
+        self.flush_pending();
+    }
+
+    fn on_row_start(&mut self, _schema: &str) {
+        self.flush_pending();
+        self.row = Some(Map::new());
+    }
+
+    fn on_field(&mut self, name: &str, value: TauqValue<'_>) {
+        if let Some(row) = &mut self.row {
+            row.insert(name.to_string(), value.to_json());
+        }
+    }
+
+    fn on_row_end(&mut self) {
+        if let Some(row) = self.row.take() {
+            self.values.push(Value::Object(row));
+        }
+    }
+
+    fn on_kv(&mut self, key: &str, value: TauqValue<'_>) {
+        self.pending.insert(key.to_string(), value.to_json());
+    }
+}
+
+/// A field default (always a scalar) as a handler value
+fn scalar_value(value: &Value) -> TauqValue<'static> {
+    match value {
+        Value::Bool(b) => TauqValue::Bool(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => TauqValue::Integer(i),
+            (None, Some(u)) => TauqValue::UnsignedInteger(u),
+            _ => TauqValue::Float(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => TauqValue::Str(Cow::Owned(s.clone())),
+        _ => TauqValue::Null,
+    }
+}
+
+struct SaxParser<'a> {
+    source: &'a str,
+    lexer: Lexer<'a>,
+    current_token: Option<SpannedToken<'a>>,
+    peek_token: Option<SpannedToken<'a>>,
+    shapes: HashMap<String, Vec<FieldDef>>,
+    active_shape: Option<String>,
+    nesting_depth: usize,
+    /// Check values against scalar type annotations (`!strict`)
+    strict: bool,
+    /// Whether the token before the current one was a newline
+    after_newline: bool,
+    /// End of the last token consumed
+    prev_end: Option<Location>,
+}
+
+impl<'a> SaxParser<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut lexer = Lexer::new(source);
+        let current_token = lexer.next_token();
+        let peek_token = lexer.next_token();
+        Self {
+            source,
+            lexer,
+            current_token,
+            peek_token,
+            shapes: HashMap::new(),
+            active_shape: None,
+            nesting_depth: 0,
+            strict: false,
+            after_newline: false,
+            prev_end: None,
+        }
+    }
+
+    fn advance(&mut self) {
+        self.after_newline = matches!(self.current(), Some(Token::Newline));
+        if let Some(st) = &self.current_token {
+            self.prev_end = Some(st.end);
+        }
+        self.current_token = self.peek_token.take();
+        self.peek_token = self.lexer.next_token();
+    }
+
+    fn current(&self) -> Option<&Token<'a>> {
+        self.current_token.as_ref().map(|t| &t.token)
+    }
+
+    /// Name of the active schema, when it has no fields
+    fn active_marker(&self) -> Option<&str> {
+        self.active_shape.as_deref().filter(|name| {
+            self.shapes
+                .get(*name)
+                .is_some_and(|fields| fields.is_empty())
+        })
+    }
+
+    /// Whether the current newline is followed by an empty row of a
+    /// zero-field schema: a blank line, or the end of the input after a
+    /// blank line
+    fn at_empty_row(&self) -> bool {
+        let blank = match self.peek_token.as_ref().map(|st| &st.token) {
+            Some(Token::Newline) => true,
+            // The newline ending the last line is not a row of its own
+            None => self.after_newline,
+            _ => false,
+        };
+        blank && self.active_marker().is_some()
+    }
+
+    fn make_error(&self, msg: impl Into<String>) -> ParseError {
+        let span = match &self.current_token {
+            Some(t) => t.span(),
+            None => Span::point(1, 1),
+        };
+        ParseError::new(msg, span)
+    }
+
+    /// Error spanning a row from `first`, the span of its first token, to
+    /// the end of the last token consumed
+    fn row_error(&self, msg: impl Into<String>, first: Option<Span>) -> ParseError {
+        match (first, self.prev_end) {
+            (Some(first), Some(end)) => ParseError::new(
+                msg,
+                Span::range(first.line, first.column, end.line, end.column),
+            ),
+            _ => self.make_error(msg),
+        }
+    }
+
+    fn run<H: TauqHandler>(mut self, handler: &mut H) -> Result<(), ParseError> {
+        while let Some(token) = self.current() {
+            match token {
+                Token::Directive(d) => {
+                    let d = d.clone();
+                    self.advance();
+                    match d.as_str() {
+                        "def" => self.handle_def(handler)?,
+                        "compose" => self.handle_compose(handler)?,
+                        "use" => self.handle_use()?,
+                        "schemas" | "models" => self.handle_schemas_block(handler)?,
+                        "strict" => self.strict = true,
+                        "require" => self.handle_require()?,
+                        _ => self.skip_line(),
+                    }
+                }
+                Token::Newline if self.at_empty_row() => {
+                    // Rows of a zero-field schema are empty lines
+                    if let Some(shape) = &self.active_shape {
+                        handler.on_row_start(shape);
+                        handler.on_row_end();
+                    }
+                    self.advance();
+                }
+                Token::Newline | Token::Semi => self.advance(),
+                Token::TripleDash => {
+                    self.active_shape = None;
+                    self.advance();
+                }
+                Token::DocSeparator => return Err(self.make_error(DOC_SEPARATOR_ERROR)),
+                Token::RBrace => {
+                    return Err(self.make_error("Unexpected '}' at top level - mismatched braces"));
+                }
+                Token::RBracket => {
+                    return Err(
+                        self.make_error("Unexpected ']' at top level - mismatched brackets")
+                    );
+                }
+                _ => {
+                    if let Some(name) = self.active_marker() {
+                        return Err(self.make_error(marker_row_error(name)));
+                    }
+                    if let Some(shape) = self.active_shape.clone() {
+                        if !self.parse_row(&shape, handler)? {
+                            self.advance();
+                        }
+                    } else {
+                        self.parse_kv(handler)?;
+                    }
+                }
+            }
+        }
+
+        if let Some(lex_err) = &self.lexer.lex_error {
+            return Err(ParseError::new(lex_err.message.clone(), lex_err.span));
+        }
+        Ok(())
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(token) = self.current() {
+            if matches!(token, Token::Newline | Token::Semi) {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// Check `!require tauq >= 1.0` against the running version
+    fn handle_require(&mut self) -> Result<(), ParseError> {
+        let spec = match &self.current_token {
+            Some(st) if !matches!(st.token, Token::Newline | Token::Semi) => {
+                rest_of_line(self.source, st.start.offset)
+            }
+            _ => "",
+        };
+        check_version_requirement(spec).map_err(|msg| self.make_error(msg))?;
+        self.skip_line();
+        Ok(())
+    }
+
+    /// Fields inherited through an optional `!extends Parent`
+    fn parse_extends(&mut self, name: &str) -> Result<Vec<FieldDef>, ParseError> {
+        if !matches!(self.current(), Some(Token::Directive(d)) if d == "extends") {
+            return Ok(Vec::new());
+        }
+        self.advance();
+        let Some(Token::Ident(parent)) = self.current() else {
+            return Err(self.make_error("!extends requires a parent schema name"));
+        };
+        if parent == name {
+            return Err(self.make_error(format!(
+                "Circular inheritance: schema '{}' cannot extend itself",
+                name
+            )));
+        }
+        let Some(fields) = self.shapes.get(parent.as_ref()) else {
+            return Err(
+                self.make_error(format!("!extends references undefined schema '{}'", parent))
+            );
+        };
+        let fields = fields.clone();
+        self.advance();
+        Ok(fields)
+    }
+
+    /// Parse `Name field field:Type ...` and register it
+    fn parse_schema_fields<H: TauqHandler>(
+        &mut self,
+        name: String,
+        handler: &mut H,
+    ) -> Result<(), ParseError> {
+        let inherited = self.parse_extends(&name)?;
+        let mut fields = Vec::new();
+        while let Some(Token::Ident(field)) = self.current() {
+            let field = field.to_string();
+            self.advance();
+            let type_def = if field.contains('=') {
+                TypeDef::Scalar
+            } else {
+                self.parse_type_annotation()?
+            };
+            let (def, consumed) = resolve_field_def(&field, type_def, self.current())
+                .map_err(|msg| self.make_error(msg))?;
+            if consumed {
+                self.advance();
+            }
+            fields.push(def);
+        }
+        let fields = inherit_fields(inherited, fields);
+
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        handler.on_schema_def(&name, &names);
+        self.shapes.insert(name, fields);
+        Ok(())
+    }
+
+    fn handle_def<H: TauqHandler>(&mut self, handler: &mut H) -> Result<(), ParseError> {
+        let Some(Token::Ident(name)) = self.current() else {
+            return Err(self.make_error("!def requires a schema name"));
+        };
+        let name = name.to_string();
+        self.advance();
+        self.parse_schema_fields(name.clone(), handler)?;
+        self.active_shape = Some(name);
+        Ok(())
+    }
+
+    fn handle_compose<H: TauqHandler>(&mut self, handler: &mut H) -> Result<(), ParseError> {
+        let Some(Token::Ident(name)) = self.current() else {
+            return Err(self.make_error("!compose requires a schema name"));
+        };
+        let name = name.to_string();
+        self.advance();
+        let mut parts = Vec::new();
+        while let Some(Token::Ident(part)) = self.current() {
+            parts.push(part.to_string());
+            self.advance();
+        }
+        let fields = compose_fields(&name, &parts, |part| self.shapes.get(part).cloned())
+            .map_err(|msg| self.make_error(msg))?;
+
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        handler.on_schema_def(&name, &names);
+        self.shapes.insert(name.clone(), fields);
+        self.active_shape = Some(name);
+        Ok(())
+    }
+
+    fn handle_use(&mut self) -> Result<(), ParseError> {
+        let Some(Token::Ident(name)) = self.current() else {
+            return Err(self.make_error("!use requires a schema name"));
+        };
+        if !self.shapes.contains_key(name.as_ref()) {
+            return Err(self.make_error(format!("!use references undefined schema '{}'", name)));
+        }
+        self.active_shape = Some(name.to_string());
+        self.advance();
+        Ok(())
+    }
+
+    fn handle_schemas_block<H: TauqHandler>(&mut self, handler: &mut H) -> Result<(), ParseError> {
+        loop {
+            match self.current() {
+                Some(Token::TripleDash) => {
+                    self.advance();
+                    return Ok(());
+                }
+                Some(Token::Ident(name)) => {
+                    let name = name.to_string();
+                    self.advance();
+                    self.parse_schema_fields(name, handler)?;
+                    if !matches!(self.current(), Some(Token::TripleDash) | None) {
+                        self.advance();
+                    }
+                }
+                Some(Token::Newline | Token::Semi) => self.advance(),
+                Some(_) => {
+                    return Err(self.make_error("Expected schema name or '---' in schema block"));
+                }
+                None => return Err(self.make_error("Unterminated schema block - expected '---'")),
+            }
+        }
+    }
+
+    fn parse_type_annotation(&mut self) -> Result<TypeDef, ParseError> {
+        if !matches!(self.current(), Some(Token::Colon)) {
+            return Ok(TypeDef::Scalar);
+        }
+        self.advance();
+
+        if matches!(self.current(), Some(Token::LBracket)) {
+            self.advance();
+            let Some(Token::Ident(inner)) = self.current() else {
+                return Err(self.make_error("Expected type name in list type"));
+            };
+            let t = TypeDef::List(inner.to_string());
+            self.advance();
+            if !matches!(self.current(), Some(Token::RBracket)) {
+                return Err(self.make_error("Expected ']' after list type"));
+            }
+            self.advance();
+            return Ok(t);
+        }
+
+        // `null` lexes as a literal rather than a type name
+        if matches!(self.current(), Some(Token::Null)) {
+            self.advance();
+            return Ok(TypeDef::Object("null".to_string()));
+        }
+
+        if let Some(Token::Ident(t)) = self.current() {
+            let t = TypeDef::Object(t.to_string());
+            self.advance();
+            return Ok(t);
+        }
+        Ok(TypeDef::Scalar)
+    }
+
+    /// Emit one schema row; returns false if no tokens were consumed
+    fn parse_row<H: TauqHandler>(
+        &mut self,
+        shape: &str,
+        handler: &mut H,
+    ) -> Result<bool, ParseError> {
+        if self.shapes.get(shape).is_none_or(|f| f.is_empty()) {
+            return Ok(false);
+        }
+
+        handler.on_row_start(shape);
+        self.emit_row_fields(shape, &mut |name, value| handler.on_field(name, value))?;
+        handler.on_row_end();
+        Ok(true)
+    }
+
+    /// Parse row values for `shape`, calling `emit` for each field
+    fn emit_row_fields(
+        &mut self,
+        shape: &str,
+        emit: &mut dyn FnMut(&str, TauqValue<'a>),
+    ) -> Result<(), ParseError> {
+        let mut field_idx = 0;
+        let mut named_keys = Vec::new();
+        let row_span = self.current_token.as_ref().map(SpannedToken::span);
+        loop {
+            match self.current() {
+                None
+                | Some(
+                    Token::Newline
+                    | Token::Semi
+                    | Token::RBrace
+                    | Token::RBracket
+                    | Token::Directive(_),
+                ) => break,
+                _ => {}
+            }
+
+            let named = match (self.current(), self.peek_token.as_ref().map(|t| &t.token)) {
+                (Some(Token::Ident(key)), Some(Token::Colon)) => Some(key.clone()),
+                _ => None,
+            };
+
+            if let Some(key) = named {
+                self.advance();
+                self.advance();
+                let field = self
+                    .shapes
+                    .get(shape)
+                    .and_then(|fields| fields.iter().find(|f| f.name == key))
+                    .cloned();
+                let value = self.parse_field_value(field.as_ref())?;
+                emit(&key, value);
+                named_keys.push(key);
+            } else {
+                let field = self
+                    .shapes
+                    .get(shape)
+                    .and_then(|f| f.get(field_idx))
+                    .cloned();
+                let Some(field) = field else {
+                    // Extra tokens belong to the next row
+                    break;
+                };
+                let value = self.parse_field_value(Some(&field))?;
+                emit(&field.name, value);
+                field_idx += 1;
+            }
+        }
+
+        // Fields the row left out: defaults and nulls fill optional ones,
+        // required ones are an error
+        if let Some(fields) = self.shapes.get(shape) {
+            let omitted = fields.iter().skip(field_idx);
+            for field in omitted.filter(|f| !named_keys.iter().any(|k| *k == f.name)) {
+                match &field.default {
+                    Some(default) => emit(&field.name, scalar_value(default)),
+                    None if field.optional => emit(&field.name, TauqValue::Null),
+                    None => {
+                        return Err(self.row_error(missing_field_error(field, shape), row_span));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a row value for `field` (untyped when the schema lacks it),
+    /// checking its type in strict mode
+    fn parse_field_value(&mut self, field: Option<&FieldDef>) -> Result<TauqValue<'a>, ParseError> {
+        let span = self.current_token.as_ref().map(SpannedToken::span);
+        let type_def = field.map_or(TypeDef::Scalar, |f| f.type_def.clone());
+        let value = self.parse_typed_value(&type_def)?;
+        if self.strict
+            && let Some(field) = field
+            && let Some(msg) = strict_type_error(field, &value.to_json())
+        {
+            return Err(ParseError::new(msg, span.unwrap_or(Span::point(1, 1))));
+        }
+        Ok(value)
+    }
+
+    fn parse_typed_value(&mut self, type_def: &TypeDef) -> Result<TauqValue<'a>, ParseError> {
+        match type_def {
+            TypeDef::Object(shape) if matches!(self.current(), Some(Token::LBrace)) => {
+                self.advance();
+                let mut entries = Vec::new();
+                self.emit_row_fields(shape, &mut |name, value| {
+                    entries.push((Cow::Owned(name.to_string()), value))
+                })?;
+                if !matches!(self.current(), Some(Token::RBrace)) {
+                    return Err(self.make_error("Expected '}' for typed object"));
+                }
+                self.advance();
+                Ok(TauqValue::Object(entries))
+            }
+            TypeDef::List(shape) if matches!(self.current(), Some(Token::LBracket)) => {
+                self.advance();
+                let mut items = Vec::new();
+                loop {
+                    match self.current() {
+                        Some(Token::RBracket) => {
+                            self.advance();
+                            break;
+                        }
+                        Some(Token::Newline | Token::Semi) => self.advance(),
+                        None => return Err(self.make_error("Unclosed list: expected ']'")),
+                        _ => {
+                            let braced = matches!(self.current(), Some(Token::LBrace));
+                            if braced {
+                                self.advance();
+                            }
+                            let mut entries = Vec::new();
+                            self.emit_row_fields(shape, &mut |name, value| {
+                                entries.push((Cow::Owned(name.to_string()), value))
+                            })?;
+                            if braced {
+                                if !matches!(self.current(), Some(Token::RBrace)) {
+                                    return Err(
+                                        self.make_error("Expected '}' for item in typed list")
+                                    );
+                                }
+                                self.advance();
+                            } else if entries.is_empty() {
+                                self.advance();
+                                continue;
+                            }
+                            items.push(TauqValue::Object(entries));
+                        }
+                    }
+                }
+                Ok(TauqValue::Array(items))
+            }
+            _ => self
+                .parse_value()?
+                .ok_or_else(|| self.make_error("Expected value")),
+        }
+    }
+
+    fn parse_kv<H: TauqHandler>(&mut self, handler: &mut H) -> Result<(), ParseError> {
+        let Some(st) = self.current_token.clone() else {
+            return Ok(());
+        };
+        if let Token::Ident(_) | Token::String(_) = st.token {
+            let key = self.text_of(&st);
+            self.advance();
+            if matches!(self.current(), Some(Token::Colon)) {
+                self.advance();
+            }
+            let value = self
+                .parse_value()?
+                .ok_or_else(|| self.make_error("Expected value for key"))?;
+            handler.on_kv(&key, value);
+            Ok(())
+        } else {
+            Err(self.make_error(format!("Unexpected token: {:?}", st.token)))
+        }
+    }
+
+    /// Text of a string or bareword token, borrowed from the source when unescaped
+    fn text_of(&self, st: &SpannedToken<'a>) -> Cow<'a, str> {
+        match &st.token {
+            Token::Ident(s) | Token::String(s) => s.clone(),
+            _ => Cow::Borrowed(&self.source[st.start.offset..st.end.offset]),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Option<TauqValue<'a>>, ParseError> {
+        let Some(st) = self.current_token.clone() else {
+            return Ok(None);
+        };
+        let value = match &st.token {
+            Token::String(_) | Token::Ident(_) => TauqValue::Str(self.text_of(&st)),
+            Token::Integer(n) => TauqValue::Integer(*n),
+            Token::UnsignedInteger(n) => TauqValue::UnsignedInteger(*n),
+            Token::Float(n) => TauqValue::Float(*n),
+            Token::Bool(b) => TauqValue::Bool(*b),
+            Token::Null => TauqValue::Null,
+            Token::LBracket => return self.parse_list().map(Some),
+            Token::LBrace => return self.parse_object().map(Some),
+            _ => return Ok(None),
+        };
+        self.advance();
+        Ok(Some(value))
+    }
+
+    fn enter(&mut self) -> Result<(), ParseError> {
+        if self.nesting_depth >= MAX_NESTING_DEPTH {
+            return Err(self.make_error(format!(
+                "Maximum nesting depth ({}) exceeded - structure too deeply nested",
+                MAX_NESTING_DEPTH
+            )));
+        }
+        self.nesting_depth += 1;
+        Ok(())
+    }
+
+    fn parse_list(&mut self) -> Result<TauqValue<'a>, ParseError> {
+        self.enter()?;
+        self.advance(); // Skip [
+        let mut items = Vec::new();
+        loop {
+            match self.current() {
+                Some(Token::RBracket) => {
+                    self.advance();
+                    self.nesting_depth -= 1;
+                    return Ok(TauqValue::Array(items));
+                }
+                Some(Token::Newline | Token::Semi | Token::Colon) => self.advance(),
+                Some(Token::RBrace) => {
+                    return Err(self.make_error("Unexpected '}' in list - mismatched brackets"));
+                }
+                Some(Token::Directive(d)) if d == "use" => {
+                    self.advance();
+                    let Some(Token::Ident(shape)) = self.current() else {
+                        return Err(self.make_error("!use in array requires a schema name"));
+                    };
+                    let shape = shape.to_string();
+                    if !self.shapes.contains_key(&shape) {
+                        return Err(self.make_error(format!(
+                            "!use references undefined schema '{}' in array",
+                            shape
+                        )));
+                    }
+                    self.advance();
+                    while !matches!(self.current(), Some(Token::RBracket) | None) {
+                        if matches!(self.current(), Some(Token::Newline | Token::Semi)) {
+                            self.advance();
+                            continue;
+                        }
+                        let mut entries = Vec::new();
+                        self.emit_row_fields(&shape, &mut |name, value| {
+                            entries.push((Cow::Owned(name.to_string()), value))
+                        })?;
+                        if entries.is_empty() {
+                            self.advance();
+                        } else {
+                            items.push(TauqValue::Object(entries));
+                        }
+                    }
+                }
+                None => return Err(self.make_error("Unclosed list: expected ']'")),
+                _ => match self.parse_value()? {
+                    Some(v) => items.push(v),
+                    None => return Err(self.make_error("Expected value in list or ']'")),
+                },
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<TauqValue<'a>, ParseError> {
+        self.enter()?;
+        self.advance(); // Skip {
+        let mut entries = Vec::new();
+        loop {
+            let Some(st) = self.current_token.clone() else {
+                return Err(self.make_error("Unclosed object: expected '}'"));
+            };
+            match &st.token {
+                Token::RBrace => {
+                    self.advance();
+                    self.nesting_depth -= 1;
+                    return Ok(TauqValue::Object(entries));
+                }
+                Token::Newline | Token::Semi => self.advance(),
+                Token::Ident(_) | Token::String(_) => {
+                    let key = self.text_of(&st);
+                    self.advance();
+                    if matches!(self.current(), Some(Token::Colon)) {
+                        self.advance();
+                    }
+                    match self.parse_value()? {
+                        Some(v) => entries.push((key, v)),
+                        None => return Err(self.make_error("Expected value for key")),
+                    }
+                }
+                other => {
+                    return Err(self.make_error(format!("Expected key in object, got {:?}", other)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(source: &str) -> Vec<Value> {
+        let mut handler = CollectingHandler::new();
+        parse_sax(source, &mut handler).unwrap();
+        handler.into_values()
+    }
+
+    #[test]
+    fn test_sax_rows_match_parser() {
+        let source =
+            "!def User id name email\n1 Alice alice@example.com\n2 \"Bob Smith\" bob@example.com";
+        let values = collect(source);
+        let expected = crate::compile_tauq(source).unwrap();
+        assert_eq!(Value::Array(values), expected);
+    }
+
+    #[test]
+    fn test_sax_key_values_and_nested() {
+        let source = "!def Item sku qty\n---\norder ORD-1\ncustomer { name Alice tier gold }\nitems [\n  !use Item\n  A1 2\n  B2 5\n]";
+        let values = collect(source);
+        let expected = crate::compile_tauq(source).unwrap();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0], expected);
+    }
+
+    #[test]
+    fn test_sax_typed_fields_and_named_args() {
+        let source = "!def Addr city zip\n!def User id addr:Addr\n!use User\n1 { Paris 75001 }\naddr:{ Rome 00100 } id:2";
+        let values = collect(source);
+        assert_eq!(Value::Array(values), crate::compile_tauq(source).unwrap());
+    }
+
+    #[test]
+    fn test_sax_borrows_unescaped_strings() {
+        struct Borrowed(Vec<bool>);
+        impl TauqHandler for Borrowed {
+            fn on_field(&mut self, _name: &str, value: TauqValue<'_>) {
+                self.0
+                    .push(matches!(value, TauqValue::Str(Cow::Borrowed(_))));
+            }
+        }
+
+        let mut handler = Borrowed(Vec::new());
+        parse_sax("!def T a b c\nbare \"quoted\" \"esc\\n\"", &mut handler).unwrap();
+        assert_eq!(handler.0, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_sax_schema_events() {
+        #[derive(Default)]
+        struct Schemas(Vec<(String, Vec<String>)>);
+        impl TauqHandler for Schemas {
+            fn on_schema_def(&mut self, name: &str, fields: &[&str]) {
+                self.0.push((
+                    name.to_string(),
+                    fields.iter().map(|f| f.to_string()).collect(),
+                ));
+            }
+        }
+
+        let mut handler = Schemas::default();
+        parse_sax("!schemas\nUser id name\nTag label\n---\n", &mut handler).unwrap();
+        assert_eq!(handler.0.len(), 2);
+        assert_eq!(handler.0[0].0, "User");
+        assert_eq!(handler.0[0].1, vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_sax_errors() {
+        let mut handler = CollectingHandler::new();
+        assert!(parse_sax("!use Missing\n1 2", &mut handler).is_err());
+        assert!(parse_sax("name \"unterminated", &mut handler).is_err());
+        assert!(parse_sax("}", &mut handler).is_err());
+    }
+}
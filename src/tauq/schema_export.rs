@@ -0,0 +1,358 @@
+// Tauq Schema Export
+//
+// Converts `!def` schemas into a JSON Schema (draft 2020-12) document with
+// one `$defs` entry per schema, TypeScript interfaces or Rust structs. Scalar
+// annotations (`:int`, `:float`, `:bool`, `:string`, `:null`) become the
+// target's types, schema names become references, and untyped fields are
+// left unconstrained.
+
+use super::parser::{FieldDef, TypeDef};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// JSON Schema dialect of the generated document
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Build a JSON Schema document describing `shapes`
+///
+/// Each schema becomes an object schema under `$defs`, sorted by name.
+/// Fields that are neither optional (`role?`) nor defaulted (`port=80`) are
+/// required; optional fields also accept `null`, which is what rows that
+/// omit them parse to.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use tauq::tauq::parser::{FieldDef, TypeDef};
+/// use tauq::tauq::tauq_schemas_to_json_schema;
+///
+/// let mut shapes = HashMap::new();
+/// shapes.insert(
+///     "User".to_string(),
+///     vec![
+///         FieldDef::declared("id", TypeDef::Object("int".to_string())),
+///         FieldDef::declared("name", TypeDef::Scalar),
+///     ],
+/// );
+/// let schema = tauq_schemas_to_json_schema(&shapes);
+/// assert_eq!(schema["$defs"]["User"]["properties"]["id"]["type"], "integer");
+/// assert_eq!(schema["$defs"]["User"]["required"][1], "name");
+/// ```
+pub fn tauq_schemas_to_json_schema(shapes: &HashMap<String, Vec<FieldDef>>) -> Value {
+    let mut names: Vec<&String> = shapes.keys().collect();
+    names.sort();
+
+    let mut defs = Map::new();
+    for name in names {
+        defs.insert(name.clone(), object_schema(&shapes[name], shapes));
+    }
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "$defs": defs,
+    })
+}
+
+/// Build TypeScript interface declarations for `shapes`
+///
+/// Each schema becomes an exported interface, sorted by name. Untyped
+/// fields and unknown type names are `unknown`, and optional fields
+/// (`role?`) are declared optional and nullable. Interfaces may refer to
+/// ones declared later in the output.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use tauq::tauq::parser::{FieldDef, TypeDef};
+/// use tauq::tauq::tauq_schemas_to_typescript;
+///
+/// let mut shapes = HashMap::new();
+/// shapes.insert(
+///     "User".to_string(),
+///     vec![
+///         FieldDef::declared("id", TypeDef::Object("int".to_string())),
+///         FieldDef::declared("role", TypeDef::Scalar),
+///     ],
+/// );
+/// assert_eq!(
+///     tauq_schemas_to_typescript(&shapes),
+///     "export interface User {\n  id: number;\n  role: unknown;\n}\n"
+/// );
+/// ```
+pub fn tauq_schemas_to_typescript(shapes: &HashMap<String, Vec<FieldDef>>) -> String {
+    let mut names: Vec<&String> = shapes.keys().collect();
+    names.sort();
+
+    let mut interfaces = Vec::new();
+    for name in names {
+        let mut interface = format!("export interface {} {{\n", name);
+        for field in &shapes[name] {
+            let ty = match &field.type_def {
+                TypeDef::Scalar => "unknown".to_string(),
+                TypeDef::Object(ty) => typescript_type(ty, shapes),
+                TypeDef::List(ty) => format!("{}[]", typescript_type(ty, shapes)),
+            };
+            let (marker, ty) = match ty.as_str() {
+                _ if !field.optional => ("", ty),
+                "unknown" | "null" => ("?", ty),
+                _ => ("?", format!("{} | null", ty)),
+            };
+            interface.push_str(&format!(
+                "  {}{}: {};\n",
+                typescript_key(&field.name),
+                marker,
+                ty
+            ));
+        }
+        interface.push_str("}\n");
+        interfaces.push(interface);
+    }
+    interfaces.join("\n")
+}
+
+/// TypeScript type for a type name, mirroring [`type_schema`]
+fn typescript_type(name: &str, shapes: &HashMap<String, Vec<FieldDef>>) -> String {
+    match name {
+        "int" | "float" => "number".to_string(),
+        "bool" => "boolean".to_string(),
+        "string" => "string".to_string(),
+        "null" => "null".to_string(),
+        _ if shapes.contains_key(name) => name.to_string(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// A property name, quoted unless it is a plain identifier
+fn typescript_key(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if plain {
+        name.to_string()
+    } else {
+        Value::from(name).to_string()
+    }
+}
+
+/// Build serde-compatible Rust structs for `shapes`
+///
+/// Each schema becomes a `pub struct` deriving `Debug`, `Clone` and serde's
+/// `Serialize` and `Deserialize`, sorted by name. Untyped fields and unknown
+/// type names are `serde_json::Value`, optional fields are `Option`s, and
+/// field names that are not Rust identifiers are renamed with
+/// `#[serde(rename = "...")]`. Each struct also gets a `tauq_def()` returning
+/// its `!def` line.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use tauq::tauq::parser::{FieldDef, TypeDef};
+/// use tauq::tauq::tauq_schemas_to_rust;
+///
+/// let mut shapes = HashMap::new();
+/// shapes.insert(
+///     "User".to_string(),
+///     vec![FieldDef::declared("id", TypeDef::Object("int".to_string()))],
+/// );
+/// let rust = tauq_schemas_to_rust(&shapes);
+/// assert!(rust.contains("pub struct User {\n    pub id: i64,\n}"));
+/// assert!(rust.contains(r#"pub fn tauq_def() -> &'static str {
+///         "!def User id:int"
+///     }"#));
+/// ```
+pub fn tauq_schemas_to_rust(shapes: &HashMap<String, Vec<FieldDef>>) -> String {
+    let mut names: Vec<&String> = shapes.keys().collect();
+    names.sort();
+
+    let mut structs = Vec::new();
+    for name in names {
+        let fields = &shapes[name];
+        let ident = rust_ident(name);
+        let mut code = format!(
+            "#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]\npub struct {} {{\n",
+            ident
+        );
+        for field in fields {
+            let mut ty = match &field.type_def {
+                TypeDef::Scalar => "serde_json::Value".to_string(),
+                TypeDef::Object(ty) => {
+                    let inner = rust_type(ty, shapes);
+                    // A struct that contains itself, directly or through
+                    // others, needs indirection to have a size
+                    if shapes.contains_key(ty) && reaches(ty, name, shapes, &mut Vec::new()) {
+                        format!("Box<{}>", inner)
+                    } else {
+                        inner
+                    }
+                }
+                TypeDef::List(ty) => format!("Vec<{}>", rust_type(ty, shapes)),
+            };
+            if field.optional && ty != "serde_json::Value" {
+                ty = format!("Option<{}>", ty);
+            }
+            let field_ident = rust_ident(&field.name);
+            if field_ident.trim_start_matches("r#") != field.name {
+                code.push_str(&format!("    #[serde(rename = {:?})]\n", field.name));
+            }
+            code.push_str(&format!("    pub {}: {},\n", field_ident, ty));
+        }
+        code.push_str("}\n\n");
+
+        let mut def = format!("!def {}", name);
+        for field in fields {
+            def.push(' ');
+            def.push_str(&field_spec(field));
+        }
+        code.push_str(&format!(
+            "impl {} {{\n    /// The `!def` line this struct was generated from\n    pub fn tauq_def() -> &'static str {{\n        {:?}\n    }}\n}}\n",
+            ident, def
+        ));
+        structs.push(code);
+    }
+    structs.join("\n")
+}
+
+/// Rust type for a type name, mirroring [`type_schema`]
+fn rust_type(name: &str, shapes: &HashMap<String, Vec<FieldDef>>) -> String {
+    match name {
+        "int" => "i64".to_string(),
+        "float" => "f64".to_string(),
+        "bool" => "bool".to_string(),
+        "string" => "String".to_string(),
+        "null" => "()".to_string(),
+        _ if shapes.contains_key(name) => rust_ident(name),
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+/// Whether schema `from` contains `target` through non-list fields
+fn reaches<'a>(
+    from: &'a str,
+    target: &str,
+    shapes: &'a HashMap<String, Vec<FieldDef>>,
+    seen: &mut Vec<&'a str>,
+) -> bool {
+    if from == target {
+        return true;
+    }
+    if seen.contains(&from) {
+        return false;
+    }
+    seen.push(from);
+    shapes.get(from).is_some_and(|fields| {
+        fields.iter().any(|field| match &field.type_def {
+            TypeDef::Object(ty) => reaches(ty, target, shapes, seen),
+            _ => false,
+        })
+    })
+}
+
+/// A Rust identifier for a schema or field name
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override",
+        "priv", "try", "typeof", "unsized", "virtual", "yield",
+    ];
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if matches!(ident.as_str(), "self" | "Self" | "super" | "crate") {
+        // Keywords that cannot be raw identifiers
+        ident.push('_');
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
+
+/// A field as it would be written in `!def`, e.g. `role?`, `tags:[Tag]`
+pub fn field_spec(field: &FieldDef) -> String {
+    let mut spec = field.name.clone();
+    if field.optional {
+        spec.push('?');
+    }
+    match &field.type_def {
+        TypeDef::Scalar => {}
+        TypeDef::Object(name) => spec.push_str(&format!(":{}", name)),
+        TypeDef::List(name) => spec.push_str(&format!(":[{}]", name)),
+    }
+    if let Some(default) = &field.default {
+        spec.push_str(&format!("={}", default));
+    }
+    spec
+}
+
+fn object_schema(fields: &[FieldDef], shapes: &HashMap<String, Vec<FieldDef>>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in fields {
+        let mut schema = match &field.type_def {
+            TypeDef::Scalar => json!({}),
+            TypeDef::Object(name) => type_schema(name, shapes),
+            TypeDef::List(name) => json!({"type": "array", "items": type_schema(name, shapes)}),
+        };
+        if field.optional {
+            schema = nullable(schema);
+        } else if field.default.is_none() {
+            required.push(Value::from(field.name.clone()));
+        }
+        if let (Some(default), Value::Object(map)) = (&field.default, &mut schema) {
+            map.insert("default".to_string(), default.clone());
+        }
+        properties.insert(field.name.clone(), schema);
+    }
+    let mut schema = json!({"type": "object", "properties": properties});
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+/// Schema for a type name: a scalar type, a reference to another schema,
+/// or no constraint for names that are neither (such as `!enum` types)
+fn type_schema(name: &str, shapes: &HashMap<String, Vec<FieldDef>>) -> Value {
+    match name {
+        "int" => json!({"type": "integer"}),
+        "float" => json!({"type": "number"}),
+        "bool" => json!({"type": "boolean"}),
+        "string" => json!({"type": "string"}),
+        "null" => json!({"type": "null"}),
+        _ if shapes.contains_key(name) => json!({"$ref": format!("#/$defs/{}", name)}),
+        _ => json!({}),
+    }
+}
+
+/// Widen a field schema to also accept `null`
+fn nullable(mut schema: Value) -> Value {
+    if schema.as_object().is_some_and(Map::is_empty) {
+        return schema;
+    }
+    match schema
+        .get("type")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    {
+        Some(ty) if ty == "null" => schema,
+        Some(ty) => {
+            schema["type"] = json!([ty, "null"]);
+            schema
+        }
+        None => json!({"anyOf": [schema, {"type": "null"}]}),
+    }
+}
@@ -0,0 +1,1009 @@
+// Tauq Serializer
+//
+// serde::Serializer that writes Tauq text with the settings of a Formatter,
+// without going through serde_json::Value. Structs and scalars are rendered
+// as soon as they are serialized; sequence elements are held until the end
+// of the sequence so uniform structs can be written as schema rows.
+
+use super::formatter::{Formatter, SchemaRegistry, declared_fields};
+use super::lexer::special_float_name;
+use crate::error::TauqError;
+use serde::ser::{self, Serialize};
+use std::collections::HashSet;
+use std::io;
+
+/// A serialized value, as produced by [`TauqSerializer`]
+pub struct Fragment(Repr);
+
+enum Repr {
+    /// Text already rendered for its position in the document
+    Rendered(String),
+    /// Top-level lines of the document, the fields of an object or the
+    /// rows of a table, kept apart so they can be written one at a time
+    Rows(Vec<String>),
+    /// Parts of sequence elements, rendered when the sequence ends
+    Null,
+    Literal(String),
+    Str(String),
+    List(Vec<Repr>),
+    Object(Vec<(String, Repr)>),
+}
+
+/// Where the value being serialized sits in the document
+#[derive(Clone)]
+struct Position {
+    /// Indentation level of the line the value starts on
+    depth: usize,
+    /// The value is the whole document
+    root: bool,
+    /// Inside a sequence element, where values are kept for later
+    element: bool,
+    /// Key of the field holding the value, used to name schemas
+    key: Option<String>,
+}
+
+/// Serializer producing Tauq text
+///
+/// ```
+/// use serde::Serialize;
+/// use tauq::tauq::{Formatter, TauqSerializer};
+///
+/// #[derive(Serialize)]
+/// struct Point { x: i32, y: i32 }
+///
+/// let mut ser = TauqSerializer::new(Formatter::new());
+/// let fragment = Point { x: 1, y: 2 }.serialize(&mut ser).unwrap();
+/// assert_eq!(ser.finish(fragment), "x 1\ny 2");
+/// ```
+pub struct TauqSerializer {
+    formatter: Formatter,
+    registry: SchemaRegistry,
+    pos: Position,
+}
+
+impl TauqSerializer {
+    /// Create a serializer writing with the given formatter settings
+    pub fn new(formatter: Formatter) -> Self {
+        let registry = formatter.new_registry();
+        Self {
+            formatter,
+            registry,
+            pos: Position {
+                depth: 0,
+                root: true,
+                element: false,
+                key: None,
+            },
+        }
+    }
+
+    /// Complete the document for the fragment serialized at the root,
+    /// adding the schema definitions it uses
+    pub fn finish(self, fragment: Fragment) -> String {
+        let body = match fragment.0 {
+            Repr::Rendered(text) => text,
+            other => self.render_primitive(&other),
+        };
+        self.formatter
+            .tagged(self.formatter.with_definitions(&self.registry, body))
+    }
+
+    /// Write the document for the fragment serialized at the root to
+    /// `writer`, like [`finish`](Self::finish) but without assembling it
+    /// into a string first. The writer is flushed after each top-level
+    /// field or table row.
+    pub fn finish_to_writer<W: io::Write>(
+        self,
+        fragment: Fragment,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let rows = match fragment.0 {
+            Repr::Rows(rows) => rows,
+            Repr::Rendered(text) => vec![text],
+            other => vec![self.render_primitive(&other)],
+        };
+        self.formatter.write_rows(&self.registry, &rows, writer)
+    }
+
+    fn sep(&self) -> &'static str {
+        if self.formatter.is_minified() {
+            ";"
+        } else {
+            "\n"
+        }
+    }
+
+    fn indent(&self, depth: usize) -> String {
+        self.formatter.indent(depth)
+    }
+
+    /// Move to the value of field `key`, returning the position to restore
+    fn enter_field(&mut self, key: &str) -> Position {
+        let saved = self.pos.clone();
+        if !self.pos.element {
+            self.pos.depth = if self.pos.root { 0 } else { self.pos.depth + 1 };
+        }
+        self.pos.root = false;
+        self.pos.key = Some(key.to_string());
+        saved
+    }
+
+    /// Move to a sequence element, returning the position to restore
+    fn enter_element(&mut self) -> Position {
+        let saved = self.pos.clone();
+        self.pos.root = false;
+        self.pos.element = true;
+        saved
+    }
+
+    fn scalar(&self, repr: Repr) -> Fragment {
+        if self.pos.element {
+            return Fragment(repr);
+        }
+        let text = match repr {
+            // A lone bareword would read as a key without a value
+            Repr::Str(s) if self.pos.root => self.formatter.quote_string(&s),
+            Repr::Str(s) => self.formatter.format_field_string(&s),
+            other => self.render_primitive(&other),
+        };
+        Fragment(Repr::Rendered(text))
+    }
+
+    fn end_object(&self, entries: Vec<(String, Repr)>) -> Fragment {
+        if self.pos.element {
+            return Fragment(Repr::Object(entries));
+        }
+
+        let minify = self.formatter.is_minified();
+        let field_depth = if self.pos.root { 0 } else { self.pos.depth + 1 };
+        let fields: Vec<String> = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Repr::Rendered(text) => text,
+                    other => self.render_primitive(&other),
+                };
+                let indent = if minify {
+                    String::new()
+                } else {
+                    self.indent(field_depth)
+                };
+                format!("{}{} {}", indent, self.formatter.format_key(&key), value)
+            })
+            .collect();
+
+        let text = if fields.is_empty() {
+            "{}".to_string()
+        } else if self.pos.root {
+            return Fragment(Repr::Rows(fields));
+        } else if minify {
+            format!("{{{}}}", fields.join(";"))
+        } else {
+            format!(
+                "{{\n{}\n{}}}",
+                fields.join("\n"),
+                self.indent(self.pos.depth)
+            )
+        };
+        Fragment(Repr::Rendered(text))
+    }
+
+    fn end_seq(&mut self, items: Vec<Repr>) -> Fragment {
+        if self.pos.element {
+            return Fragment(Repr::List(items));
+        }
+        if items.is_empty() {
+            return Fragment(Repr::Rendered("[]".to_string()));
+        }
+
+        let text = match self.uniform_fields(&items) {
+            // A lone row would read back as an object, so it stays bracketed
+            Some(fields) if self.pos.root && items.len() > 1 => {
+                return Fragment(Repr::Rows(self.render_table(&items, &fields)));
+            }
+            Some(fields) => self.render_schema_array(&items, &fields),
+            None if self.pos.root || items.iter().all(|item| matches!(item, Repr::Object(_))) => {
+                self.render_object_list(&items)
+            }
+            None => {
+                let elements: Vec<String> = items
+                    .iter()
+                    .map(|item| self.render_primitive(item))
+                    .collect();
+                format!("[{}]", elements.join(" "))
+            }
+        };
+        Fragment(Repr::Rendered(text))
+    }
+
+    /// Keys shared by every element, when the sequence can be schema rows
+    fn uniform_fields(&self, items: &[Repr]) -> Option<Vec<String>> {
+        if !self.formatter.uses_schemas()
+            || items.is_empty()
+            || items.len() < self.formatter.schema_threshold()
+        {
+            return None;
+        }
+        let Repr::Object(first) = &items[0] else {
+            return None;
+        };
+        let fields: Vec<String> = first.iter().map(|(k, _)| k.clone()).collect();
+        if fields.is_empty() || !fields.iter().all(|k| self.formatter.is_valid_identifier(k)) {
+            return None;
+        }
+
+        let field_set: HashSet<&String> = fields.iter().collect();
+        let uniform = items.iter().all(|item| match item {
+            Repr::Object(entries) => {
+                entries.len() == fields.len()
+                    && entries.iter().map(|(k, _)| k).collect::<HashSet<_>>() == field_set
+            }
+            _ => false,
+        });
+        uniform.then_some(fields)
+    }
+
+    /// Fields that are `null` in some element
+    fn nullable(items: &[Repr], fields: &[String]) -> HashSet<String> {
+        fields
+            .iter()
+            .filter(|field| {
+                items
+                    .iter()
+                    .any(|item| matches!(field_value(item, field), Some(Repr::Null)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Top-level table: `!def` followed directly by rows
+    fn render_table(&self, items: &[Repr], fields: &[String]) -> Vec<String> {
+        // Only the rows follow from this definition, so it is named with
+        // a scratch registry and not emitted again by `finish`
+        let mut names = self.formatter.new_registry();
+        let name = names.get_or_create(fields, None);
+        let nullable = Self::nullable(items, fields);
+        let declared = declared_fields(fields, &nullable, &names.defaults);
+
+        let value_sep = self.formatter.value_sep();
+        let mut lines = vec![format!("!def {} {}", name, declared.join(value_sep))];
+        lines.extend(items.iter().map(|item| self.render_row(item, fields)));
+        lines
+    }
+
+    /// Nested uniform sequence: `[ !use Name rows... ]`
+    fn render_schema_array(&mut self, items: &[Repr], fields: &[String]) -> String {
+        let name = self.registry.get_or_create(fields, self.pos.key.as_deref());
+        self.registry
+            .mark_nullable(fields, Self::nullable(items, fields));
+        let order = self
+            .registry
+            .declared_order(fields)
+            .map(<[String]>::to_vec)
+            .unwrap_or_else(|| fields.to_vec());
+
+        let rows: Vec<String> = items
+            .iter()
+            .map(|item| self.render_row(item, &order))
+            .collect();
+        if self.formatter.is_minified() {
+            format!("[!use {};{}]", name, rows.join(";"))
+        } else {
+            let row_indent = self.indent(self.pos.depth + 1);
+            let rows: Vec<String> = rows
+                .iter()
+                .map(|row| format!("{}{}", row_indent, row))
+                .collect();
+            format!(
+                "[\n{}!use {}\n{}\n{}]",
+                row_indent,
+                name,
+                rows.join("\n"),
+                self.indent(self.pos.depth)
+            )
+        }
+    }
+
+    /// Sequence of differently shaped objects (or any top-level sequence
+    /// that is not a table), one element per line
+    fn render_object_list(&self, items: &[Repr]) -> String {
+        let elements: Vec<String> = items
+            .iter()
+            .map(|item| match item {
+                Repr::Object(entries) if entries.is_empty() => "{}".to_string(),
+                Repr::Object(entries) => {
+                    let fields: Vec<String> = entries
+                        .iter()
+                        .map(|(k, v)| {
+                            format!(
+                                "{} {}",
+                                self.formatter.format_key(k),
+                                self.render_primitive(v)
+                            )
+                        })
+                        .collect();
+                    format!("{{ {} }}", fields.join(" "))
+                }
+                other => self.render_primitive(other),
+            })
+            .collect();
+
+        if self.formatter.is_minified() {
+            return format!("[{}]", elements.join(" "));
+        }
+        let item_indent = self.indent(self.item_depth());
+        let close_indent = if self.pos.root {
+            String::new()
+        } else {
+            self.indent(self.pos.depth)
+        };
+        let elements: Vec<String> = elements
+            .iter()
+            .map(|e| format!("{}{}", item_indent, e))
+            .collect();
+        format!("[\n{}\n{}]", elements.join("\n"), close_indent)
+    }
+
+    /// Indentation level of the lines inside a multi-line sequence
+    fn item_depth(&self) -> usize {
+        if self.pos.root { 0 } else { self.pos.depth + 1 }
+    }
+
+    /// One schema row, leaving out trailing values the `!def` line supplies
+    fn render_row(&self, item: &Repr, fields: &[String]) -> String {
+        let mut values: Vec<&Repr> = fields.iter().filter_map(|f| field_value(item, f)).collect();
+        while values.len() > 1 {
+            let last = values[values.len() - 1];
+            let omittable = match self.registry.defaults.get(&fields[values.len() - 1]) {
+                Some(default) => self.render_cell(last) == *default,
+                None => matches!(last, Repr::Null),
+            };
+            if !omittable {
+                break;
+            }
+            values.pop();
+        }
+        values
+            .into_iter()
+            .map(|v| self.render_cell(v))
+            .collect::<Vec<_>>()
+            .join(self.formatter.value_sep())
+    }
+
+    /// A value inside a schema row
+    fn render_cell(&self, repr: &Repr) -> String {
+        match repr {
+            Repr::Str(s) if self.formatter.is_safe_bareword_for_row(s) => s.clone(),
+            Repr::Str(s) => self.formatter.quote_string(s),
+            Repr::List(items) => {
+                let items: Vec<String> = items.iter().map(|v| self.render_cell(v)).collect();
+                format!("[{}]", items.join(" "))
+            }
+            Repr::Object(entries) => {
+                let fields: Vec<String> = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        format!("{} {}", self.formatter.format_key(k), self.render_cell(v))
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join(" "))
+            }
+            other => self.render_primitive(other),
+        }
+    }
+
+    /// A value written on a single line outside a schema row
+    fn render_primitive(&self, repr: &Repr) -> String {
+        match repr {
+            Repr::Rendered(text) | Repr::Literal(text) => text.clone(),
+            Repr::Rows(rows) => rows.join(self.sep()),
+            Repr::Null => "null".to_string(),
+            Repr::Str(s) if self.formatter.is_safe_bareword(s) => s.clone(),
+            Repr::Str(s) => self.formatter.quote_string(s),
+            Repr::List(items) => {
+                let items: Vec<String> = items.iter().map(|v| self.render_primitive(v)).collect();
+                format!("[{}]", items.join(" "))
+            }
+            Repr::Object(entries) => {
+                let fields: Vec<String> = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        format!(
+                            "{} {}",
+                            self.formatter.format_key(k),
+                            self.render_primitive(v)
+                        )
+                    })
+                    .collect();
+                if self.formatter.is_minified() {
+                    format!("{{{}}}", fields.join(";"))
+                } else {
+                    format!("{{ {} }}", fields.join(" "))
+                }
+            }
+        }
+    }
+}
+
+/// Value of `field` in an object element
+fn field_value<'r>(item: &'r Repr, field: &str) -> Option<&'r Repr> {
+    match item {
+        Repr::Object(entries) => entries.iter().find(|(k, _)| k == field).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut TauqSerializer {
+    type Ok = Fragment;
+    type Error = TauqError;
+    type SerializeSeq = TauqSeqSerializer<'a>;
+    type SerializeTuple = TauqSeqSerializer<'a>;
+    type SerializeTupleStruct = TauqSeqSerializer<'a>;
+    type SerializeTupleVariant = TauqVariantSerializer<'a>;
+    type SerializeMap = TauqMapSerializer<'a>;
+    type SerializeStruct = TauqMapSerializer<'a>;
+    type SerializeStructVariant = TauqVariantSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Literal(v.to_string())))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Fragment, TauqError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Fragment, TauqError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Fragment, TauqError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Literal(v.to_string())))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Fragment, TauqError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Fragment, TauqError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Fragment, TauqError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Literal(v.to_string())))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Fragment, TauqError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Fragment, TauqError> {
+        let repr = match serde_json::Number::from_f64(v) {
+            Some(n) => Repr::Literal(n.to_string()),
+            None => Repr::Literal(special_float_name(v).to_string()),
+        };
+        Ok(self.scalar(repr))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Str(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Str(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Fragment, TauqError> {
+        use ser::SerializeSeq;
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Null))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Fragment, TauqError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Fragment, TauqError> {
+        Ok(self.scalar(Repr::Null))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Fragment, TauqError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Fragment, TauqError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Fragment, TauqError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Fragment, TauqError> {
+        // Externally tagged, as in serde_json: `{ Variant value }`
+        let saved = self.enter_field(variant);
+        let inner = value.serialize(&mut *self);
+        self.pos = saved;
+        Ok(self.end_object(vec![(variant.to_string(), inner?.0)]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<TauqSeqSerializer<'a>, TauqError> {
+        Ok(TauqSeqSerializer {
+            ser: self,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<TauqSeqSerializer<'a>, TauqError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<TauqSeqSerializer<'a>, TauqError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<TauqVariantSerializer<'a>, TauqError> {
+        let outer = self.enter_field(variant);
+        Ok(TauqVariantSerializer {
+            ser: self,
+            variant,
+            outer,
+            items: Vec::with_capacity(len),
+            entries: Vec::new(),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<TauqMapSerializer<'a>, TauqError> {
+        Ok(TauqMapSerializer {
+            ser: self,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<TauqMapSerializer<'a>, TauqError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<TauqVariantSerializer<'a>, TauqError> {
+        let outer = self.enter_field(variant);
+        Ok(TauqVariantSerializer {
+            ser: self,
+            variant,
+            outer,
+            items: Vec::new(),
+            entries: Vec::with_capacity(len),
+        })
+    }
+}
+
+/// Sequence serializer; writes uniform structs as a `!def`/`!use` table
+pub struct TauqSeqSerializer<'a> {
+    ser: &'a mut TauqSerializer,
+    items: Vec<Repr>,
+}
+
+impl TauqSeqSerializer<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        let saved = self.ser.enter_element();
+        let item = value.serialize(&mut *self.ser);
+        self.ser.pos = saved;
+        self.items.push(item?.0);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for TauqSeqSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Fragment, TauqError> {
+        Ok(self.ser.end_seq(self.items))
+    }
+}
+
+impl ser::SerializeTuple for TauqSeqSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Fragment, TauqError> {
+        Ok(self.ser.end_seq(self.items))
+    }
+}
+
+impl ser::SerializeTupleStruct for TauqSeqSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Fragment, TauqError> {
+        Ok(self.ser.end_seq(self.items))
+    }
+}
+
+/// Map and struct serializer
+pub struct TauqMapSerializer<'a> {
+    ser: &'a mut TauqSerializer,
+    entries: Vec<(String, Repr)>,
+    key: Option<String>,
+}
+
+impl TauqMapSerializer<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), TauqError> {
+        let saved = self.ser.enter_field(&key);
+        let value = value.serialize(&mut *self.ser);
+        self.ser.pos = saved;
+        self.entries.push((key, value?.0));
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for TauqMapSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), TauqError> {
+        self.key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        let key = self.key.take().ok_or_else(|| {
+            <TauqError as ser::Error>::custom("serialize_value called before serialize_key")
+        })?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Fragment, TauqError> {
+        Ok(self.ser.end_object(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for TauqMapSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TauqError> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Fragment, TauqError> {
+        Ok(self.ser.end_object(self.entries))
+    }
+}
+
+/// Tuple and struct variant serializer, writing `{ Variant ... }`
+pub struct TauqVariantSerializer<'a> {
+    ser: &'a mut TauqSerializer,
+    variant: &'static str,
+    /// Position of the enclosing `{ Variant ... }` object
+    outer: Position,
+    items: Vec<Repr>,
+    entries: Vec<(String, Repr)>,
+}
+
+impl TauqVariantSerializer<'_> {
+    fn wrap(self, inner: Fragment) -> Fragment {
+        self.ser.pos = self.outer;
+        self.ser
+            .end_object(vec![(self.variant.to_string(), inner.0)])
+    }
+}
+
+impl ser::SerializeTupleVariant for TauqVariantSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TauqError> {
+        let saved = self.ser.enter_element();
+        let item = value.serialize(&mut *self.ser);
+        self.ser.pos = saved;
+        self.items.push(item?.0);
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Fragment, TauqError> {
+        let items = std::mem::take(&mut self.items);
+        let inner = self.ser.end_seq(items);
+        Ok(self.wrap(inner))
+    }
+}
+
+impl ser::SerializeStructVariant for TauqVariantSerializer<'_> {
+    type Ok = Fragment;
+    type Error = TauqError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TauqError> {
+        let saved = self.ser.enter_field(key);
+        let value = value.serialize(&mut *self.ser);
+        self.ser.pos = saved;
+        self.entries.push((key.to_string(), value?.0));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Fragment, TauqError> {
+        let entries = std::mem::take(&mut self.entries);
+        let inner = self.ser.end_object(entries);
+        Ok(self.wrap(inner))
+    }
+}
+
+/// Serializer for map keys, which must be strings (or scalars written as strings)
+struct MapKeySerializer;
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = TauqError;
+    type SerializeSeq = ser::Impossible<String, TauqError>;
+    type SerializeTuple = ser::Impossible<String, TauqError>;
+    type SerializeTupleStruct = ser::Impossible<String, TauqError>;
+    type SerializeTupleVariant = ser::Impossible<String, TauqError>;
+    type SerializeMap = ser::Impossible<String, TauqError>;
+    type SerializeStruct = ser::Impossible<String, TauqError>;
+    type SerializeStructVariant = ser::Impossible<String, TauqError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, TauqError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, TauqError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, TauqError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, TauqError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, TauqError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, TauqError> {
+        Err(key_must_be_a_string())
+    }
+}
+
+fn key_must_be_a_string() -> TauqError {
+    ser::Error::custom("map key must be a string")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn to_tauq<T: Serialize>(value: &T, formatter: Formatter) -> String {
+        let mut ser = TauqSerializer::new(formatter);
+        let fragment = value.serialize(&mut ser).unwrap();
+        ser.finish(fragment)
+    }
+
+    #[test]
+    fn test_matches_formatter_output() {
+        let values = [
+            json!({"name": "demo", "tags": ["a", "b c"], "nested": {"x": 1, "y": [1, 2]}}),
+            json!({"users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": null}]}),
+            json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]),
+            json!([{"id": 1}, {"name": "x"}]),
+            json!({"items": [{"a": 1}, {"b": "two words"}], "empty": [], "obj": {}}),
+            json!([1, "two", null, 3.5]),
+            json!([{"id": 1, "name": "Alice"}]),
+            json!({"users": [{"id": 1, "name": "Alice"}]}),
+        ];
+        for value in &values {
+            for formatter in [
+                Formatter::new(),
+                Formatter::new().minified(),
+                Formatter::new().with_comma_delimiter(),
+                Formatter::new().with_schema_threshold(1),
+                Formatter::new().with_schema_threshold(usize::MAX),
+            ] {
+                let expected = formatter.format(value);
+                assert_eq!(to_tauq(value, formatter), expected, "{}", value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_map_keys_must_be_strings() {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(vec![1], 2);
+        let mut ser = TauqSerializer::new(Formatter::new());
+        assert!(map.serialize(&mut ser).is_err());
+    }
+}
@@ -4,10 +4,17 @@
 // Memory-efficient for large datasets - only one record in memory at a time.
 
 use super::lexer::Lexer;
-use super::parser::{Context, FieldDef, TypeDef};
+use super::parser::{
+    Context, DOC_SEPARATOR_ERROR, FieldDef, FieldSet, MAX_WARNINGS, TypeDef,
+    check_version_requirement, compose_fields, empty_schema_warning, float_value, inherit_fields,
+    marker_row_error, missing_field_error, resolve_field_def, rest_of_line, short_row_warning,
+    strict_type_error,
+};
 use super::token::{Location, SpannedToken, Token};
-use crate::error::{ParseError, Span};
+use crate::error::{ParseError, ParseWarning, Span, WarningKind};
 use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::rc::Rc;
 
 /// Maximum nesting depth to prevent stack overflow from deeply nested structures
 const MAX_NESTING_DEPTH: usize = 100;
@@ -29,37 +36,139 @@ const MAX_NESTING_DEPTH: usize = 100;
 /// }
 /// ```
 pub struct StreamingParser<'a> {
-    lexer: Lexer<'a>,
-    current_token: Option<SpannedToken>,
-    peek_token: Option<SpannedToken>,
+    source: TokenSource<'a>,
+    current_token: Option<SpannedToken<'a>>,
+    peek_token: Option<SpannedToken<'a>>,
     context: Context,
     active_shape: Option<String>,
     pending_kv: Map<String, Value>,
     finished: bool,
+    input_closed: bool,
     nesting_depth: usize,
+    warnings: Vec<ParseWarning>,
+    /// Whether `===` starts a new document instead of being an error
+    multi_document: bool,
+    /// Check values against scalar type annotations (`!strict`)
+    strict: bool,
+    /// Whether the token before the current one was a newline
+    after_newline: bool,
+    /// End of the last token consumed
+    prev_end: Option<Location>,
+    /// Zero-field schemas not yet used, with the span of their `!def`
+    unused_markers: Vec<(String, Span)>,
+}
+
+/// Where the parser pulls its tokens from.
+enum TokenSource<'a> {
+    /// Lex directly from an in-memory source.
+    Lexer(Lexer<'a>),
+    /// Tokens handed over line by line (see [`PushParser`]).
+    Fed(std::collections::VecDeque<SpannedToken<'a>>),
+}
+
+impl<'a> TokenSource<'a> {
+    fn next_token(&mut self) -> Option<SpannedToken<'a>> {
+        match self {
+            TokenSource::Lexer(lexer) => lexer.next_token(),
+            TokenSource::Fed(queue) => queue.pop_front(),
+        }
+    }
 }
 
 impl<'a> StreamingParser<'a> {
     /// Create a new streaming parser
     pub fn new(source: &'a str) -> Self {
-        let mut lexer = Lexer::new(source);
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
+        Self::with_source(TokenSource::Lexer(Lexer::new(source)), true)
+    }
+
+    /// Create a streaming parser for `===`-separated documents
+    ///
+    /// Records from every document are yielded in order. Each `===` forgets
+    /// the schemas defined so far, so documents cannot see each other's
+    /// definitions.
+    pub fn new_multi(source: &'a str) -> Self {
+        let mut parser = Self::new(source);
+        parser.multi_document = true;
+        parser
+    }
+
+    /// Create a streaming parser that starts with `schemas` already
+    /// defined, as [`Parser::new_with_schemas`](super::Parser::new_with_schemas)
+    /// does
+    pub fn new_with_schemas(source: &'a str, schemas: HashMap<String, Vec<FieldDef>>) -> Self {
+        let mut parser = Self::new(source);
+        parser.context = Context::with_schemas(schemas);
+        parser
+    }
+
+    fn with_source(mut source: TokenSource<'a>, input_closed: bool) -> Self {
+        let current_token = source.next_token();
+        let peek_token = source.next_token();
         Self {
-            lexer,
+            source,
             current_token,
             peek_token,
             context: Context::new(),
             active_shape: None,
             pending_kv: Map::new(),
             finished: false,
+            input_closed,
             nesting_depth: 0,
+            warnings: Vec::new(),
+            multi_document: false,
+            strict: false,
+            after_newline: false,
+            prev_end: None,
+            unused_markers: Vec::new(),
+        }
+    }
+
+    /// Reject row values that do not match their field's scalar type
+    /// (`int`, `float`, `bool`, `string`, `null`), as `!strict` does
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Take the warnings found since the last call.
+    ///
+    /// Warnings are collected as records are parsed, so calling this between
+    /// [`next_record`](Self::next_record) calls attributes them to the
+    /// records just read.
+    pub fn drain_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Schemas defined so far, including those from imports, by name
+    pub fn schemas(&self) -> HashMap<String, Vec<FieldDef>> {
+        self.context
+            .shapes
+            .borrow()
+            .iter()
+            .map(|(name, fields)| (name.clone(), fields.ordered.clone()))
+            .collect()
+    }
+
+    fn warn(&mut self, warning: ParseWarning) {
+        if self.warnings.len() < MAX_WARNINGS {
+            self.warnings.push(warning);
         }
     }
 
     /// Get the next record from the stream.
     /// Returns `None` when the stream is exhausted.
     pub fn next_record(&mut self) -> Option<Result<Value, ParseError>> {
+        let start = self.current_token.as_ref().map(|t| t.start);
+        let record = self.read_record();
+        // Skip the offending token if an error consumed nothing, so iterating
+        // past errors always terminates
+        if matches!(record, Some(Err(_))) && self.current_token.as_ref().map(|t| t.start) == start {
+            self.advance();
+        }
+        record
+    }
+
+    fn read_record(&mut self) -> Option<Result<Value, ParseError>> {
         if self.finished {
             return None;
         }
@@ -68,7 +177,12 @@ impl<'a> StreamingParser<'a> {
             let st = match &self.current_token {
                 Some(st) => st.clone(),
                 None => {
+                    // More tokens may still be fed in; keep the pending state.
+                    if !self.input_closed {
+                        return None;
+                    }
                     self.finished = true;
+                    self.report_unused_markers();
                     // Flush any pending key-value pairs
                     if !self.pending_kv.is_empty() {
                         let result = Value::Object(std::mem::take(&mut self.pending_kv));
@@ -105,6 +219,40 @@ impl<'a> StreamingParser<'a> {
                     self.active_shape = None;
                     self.advance();
                 }
+                Token::DocSeparator => {
+                    if !self.pending_kv.is_empty() {
+                        let result = Value::Object(std::mem::take(&mut self.pending_kv));
+                        return Some(Ok(result));
+                    }
+                    if !self.multi_document {
+                        let loc = st.start;
+                        return Some(Err(self.make_error_at(DOC_SEPARATOR_ERROR, loc)));
+                    }
+                    // A new document starts with no schemas
+                    self.report_unused_markers();
+                    self.context.shapes.borrow_mut().clear();
+                    self.active_shape = None;
+                    self.advance();
+                }
+                Token::Newline if self.active_marker().is_some() => {
+                    // Rows of a zero-field schema are empty lines: a newline
+                    // followed by a blank line, or by the end of the input
+                    // when it ends a blank line itself
+                    let empty_row = match self.peek_token.as_ref().map(|st| &st.token) {
+                        // Wait for the next line to be fed
+                        None if !self.input_closed => return None,
+                        None => self.after_newline,
+                        Some(Token::Newline) => true,
+                        Some(_) => false,
+                    };
+                    if empty_row && let Some(name) = self.active_marker().map(str::to_string) {
+                        self.mark_used(&name);
+                    }
+                    self.advance();
+                    if empty_row {
+                        return Some(Ok(Value::Object(Map::new())));
+                    }
+                }
                 Token::Newline | Token::Semi => {
                     self.advance();
                 }
@@ -121,6 +269,11 @@ impl<'a> StreamingParser<'a> {
                     ));
                 }
                 _ => {
+                    if let Some(name) = self.active_marker() {
+                        let err = self.make_error(marker_row_error(name));
+                        self.take_rest_of_line();
+                        return Some(Err(err));
+                    }
                     if self.active_shape.is_some() {
                         // Flush pending before row
                         if !self.pending_kv.is_empty() {
@@ -132,8 +285,7 @@ impl<'a> StreamingParser<'a> {
                             Ok(Some(row)) => return Some(Ok(row)),
                             Ok(None) => {
                                 // parse_row() returned None without consuming
-                                // tokens (e.g., empty schema). Advance to
-                                // prevent an infinite loop.
+                                // tokens. Advance to prevent an infinite loop.
                                 self.advance();
                             }
                             Err(e) => return Some(Err(e)),
@@ -156,8 +308,39 @@ impl<'a> StreamingParser<'a> {
     }
 
     fn advance(&mut self) {
+        self.after_newline = matches!(
+            self.current_token.as_ref().map(|st| &st.token),
+            Some(Token::Newline)
+        );
+        if let Some(st) = &self.current_token {
+            self.prev_end = Some(st.end);
+        }
         self.current_token = self.peek_token.take();
-        self.peek_token = self.lexer.next_token();
+        self.peek_token = self.source.next_token();
+    }
+
+    /// Name of the active schema, when it has no fields
+    fn active_marker(&self) -> Option<&str> {
+        self.active_shape.as_deref().filter(|name| {
+            self.context
+                .shapes
+                .borrow()
+                .get(*name)
+                .is_some_and(|set| set.is_empty())
+        })
+    }
+
+    /// Note that schema `name` is in use, so its `!def` is not reported
+    /// as empty
+    fn mark_used(&mut self, name: &str) {
+        self.unused_markers.retain(|(marker, _)| marker != name);
+    }
+
+    /// Warn about zero-field schemas that were never used
+    fn report_unused_markers(&mut self) {
+        for (name, span) in std::mem::take(&mut self.unused_markers) {
+            self.warn(empty_schema_warning(&name, span));
+        }
     }
 
     fn current_location(&self) -> Location {
@@ -168,59 +351,168 @@ impl<'a> StreamingParser<'a> {
     }
 
     fn make_error(&self, msg: impl Into<String>) -> ParseError {
-        let loc = self.current_location();
-        ParseError::new(msg, Span::new(loc.line, loc.column))
+        self.make_error_at(msg, self.current_location())
     }
 
+    /// Error at `loc`, underlining the current token when it starts there
     fn make_error_at(&self, msg: impl Into<String>, loc: Location) -> ParseError {
-        ParseError::new(msg, Span::new(loc.line, loc.column))
+        let span = match &self.current_token {
+            Some(t) if t.start == loc => t.span(),
+            _ => Span::point(loc.line, loc.column),
+        };
+        ParseError::new(msg, span)
+    }
+
+    /// Error spanning a row from `first`, the span of its first token, to
+    /// `end`, the end of its last one
+    fn row_error(
+        &self,
+        msg: impl Into<String>,
+        first: Option<Span>,
+        end: Option<Location>,
+    ) -> ParseError {
+        match (first, end) {
+            (Some(first), Some(end)) => ParseError::new(
+                msg,
+                Span::range(first.line, first.column, end.line, end.column),
+            ),
+            _ => self.make_error(msg),
+        }
     }
 
     fn handle_directive(&mut self, name: &str) -> Result<(), ParseError> {
         match name {
             "def" => {
                 if let Some(st) = self.current_token.clone()
-                    && let Token::Ident(shape_name) = st.token
+                    && let Token::Ident(shape_name) = st.token.clone()
                 {
+                    let shape_name = shape_name.into_owned();
                     self.advance();
+                    let mut inherited = Vec::new();
+                    if matches!(&self.current_token, Some(st) if st.token == Token::Directive("extends".to_string()))
+                    {
+                        self.advance();
+                        let parent = match self.current_token.as_ref().map(|st| &st.token) {
+                            Some(Token::Ident(parent)) => parent.to_string(),
+                            _ => {
+                                return Err(
+                                    self.make_error("!extends requires a parent schema name")
+                                );
+                            }
+                        };
+                        if parent == shape_name {
+                            return Err(self.make_error(format!(
+                                "Circular inheritance: schema '{}' cannot extend itself",
+                                shape_name
+                            )));
+                        }
+                        inherited = match self.context.shapes.borrow().get(&parent) {
+                            Some(set) => set.ordered.clone(),
+                            None => {
+                                return Err(self.make_error(format!(
+                                    "!extends references undefined schema '{}'",
+                                    parent
+                                )));
+                            }
+                        };
+                        self.advance();
+                    }
                     let mut fields = Vec::new();
 
                     while let Some(st_curr) = &self.current_token {
                         let field_name = match &st_curr.token {
-                            Token::Ident(n) => n.clone(),
+                            Token::Ident(n) => n.to_string(),
                             Token::Newline | Token::Semi => break,
                             _ => break,
                         };
                         self.advance();
 
-                        let type_def = self.parse_type_annotation()?;
-                        fields.push(FieldDef {
-                            name: field_name,
-                            type_def,
-                        });
+                        let type_def = if field_name.contains('=') {
+                            TypeDef::Scalar
+                        } else {
+                            self.parse_type_annotation()?
+                        };
+                        let next = self.current_token.as_ref().map(|st| &st.token);
+                        let (field, consumed) = resolve_field_def(&field_name, type_def, next)
+                            .map_err(|msg| self.make_error(msg))?;
+                        if consumed {
+                            self.advance();
+                        }
+                        fields.push(field);
+                    }
+                    let fields = inherit_fields(inherited, fields);
+                    if fields.is_empty() {
+                        self.unused_markers.push((shape_name.clone(), st.span()));
                     }
 
                     self.context
                         .shapes
                         .borrow_mut()
-                        .insert(shape_name.clone(), fields);
+                        .insert(shape_name.clone(), Rc::new(FieldSet::new(fields)));
                     self.active_shape = Some(shape_name);
                 }
             }
+            "compose" => {
+                let Some(Token::Ident(shape_name)) =
+                    self.current_token.as_ref().map(|st| st.token.clone())
+                else {
+                    return Err(self.make_error("!compose requires a schema name"));
+                };
+                let shape_name = shape_name.into_owned();
+                self.advance();
+                let mut parts = Vec::new();
+                while let Some(Token::Ident(part)) =
+                    self.current_token.as_ref().map(|st| st.token.clone())
+                {
+                    parts.push(part.into_owned());
+                    self.advance();
+                }
+                let fields = compose_fields(&shape_name, &parts, |part| {
+                    self.context
+                        .shapes
+                        .borrow()
+                        .get(part)
+                        .map(|set| set.ordered.clone())
+                })
+                .map_err(|msg| self.make_error(msg))?;
+                self.context
+                    .shapes
+                    .borrow_mut()
+                    .insert(shape_name.clone(), Rc::new(FieldSet::new(fields)));
+                self.active_shape = Some(shape_name);
+            }
             "use" => {
                 if let Some(st) = self.current_token.clone()
-                    && let Token::Ident(shape_name) = st.token
+                    && let Token::Ident(shape_name) = st.token.clone()
                 {
+                    let shape_name = shape_name.into_owned();
                     if !self.context.shapes.borrow().contains_key(&shape_name) {
                         return Err(self.make_error(format!(
                             "!use references undefined schema '{}'",
                             shape_name
                         )));
                     }
+                    if self.active_shape.as_deref() == Some(shape_name.as_str()) {
+                        self.warn(ParseWarning::new(
+                            WarningKind::RedundantUse,
+                            format!(
+                                "Schema '{}' is already active; !use is redundant",
+                                shape_name
+                            ),
+                            st.span(),
+                        ));
+                    }
+                    self.mark_used(&shape_name);
                     self.active_shape = Some(shape_name);
                     self.advance();
                 }
             }
+            "strict" => self.strict = true,
+            "require" => {
+                let loc = self.current_location();
+                let spec = self.take_rest_of_line();
+                check_version_requirement(&spec).map_err(|msg| self.make_error_at(msg, loc))?;
+            }
             _ => {
                 // Skip unknown directives in streaming mode
                 while let Some(st) = &self.current_token {
@@ -234,6 +526,29 @@ impl<'a> StreamingParser<'a> {
         Ok(())
     }
 
+    /// Consume the rest of the current line and return its text
+    fn take_rest_of_line(&mut self) -> String {
+        let text = match (&self.source, &self.current_token) {
+            (_, Some(st)) if matches!(st.token, Token::Newline | Token::Semi) => String::new(),
+            (TokenSource::Lexer(lexer), Some(st)) => {
+                rest_of_line(lexer.source(), st.start.offset).to_string()
+            }
+            // LineLexer hands over the rest of a `!require` line as one token
+            (TokenSource::Fed(_), Some(st)) => match &st.token {
+                Token::String(text) => text.to_string(),
+                _ => String::new(),
+            },
+            (_, None) => String::new(),
+        };
+        while let Some(st) = &self.current_token {
+            if matches!(st.token, Token::Newline | Token::Semi) {
+                break;
+            }
+            self.advance();
+        }
+        text
+    }
+
     fn parse_type_annotation(&mut self) -> Result<TypeDef, ParseError> {
         if !matches!(
             self.current_token.as_ref().map(|t| &t.token),
@@ -252,7 +567,7 @@ impl<'a> StreamingParser<'a> {
             if let Some(st) = &self.current_token
                 && let Token::Ident(inner) = &st.token
             {
-                let t = TypeDef::List(inner.clone());
+                let t = TypeDef::List(inner.to_string());
                 self.advance();
                 if matches!(
                     self.current_token.as_ref().map(|t| &t.token),
@@ -265,11 +580,20 @@ impl<'a> StreamingParser<'a> {
             return Err(self.make_error("Expected type name in list type"));
         }
 
+        // `null` lexes as a literal rather than a type name
+        if matches!(
+            self.current_token.as_ref().map(|t| &t.token),
+            Some(Token::Null)
+        ) {
+            self.advance();
+            return Ok(TypeDef::Object("null".to_string()));
+        }
+
         // Object type
         if let Some(st) = &self.current_token
             && let Token::Ident(t) = &st.token
         {
-            let t_def = TypeDef::Object(t.clone());
+            let t_def = TypeDef::Object(t.to_string());
             self.advance();
             return Ok(t_def);
         }
@@ -284,12 +608,13 @@ impl<'a> StreamingParser<'a> {
         };
 
         let fields = match self.context.shapes.borrow().get(&shape_name) {
-            Some(f) => f.clone(),
+            Some(f) => Rc::clone(f),
             None => return Ok(None),
         };
 
         let mut obj = Map::new();
         let mut field_idx = 0;
+        let row_span = self.current_token.as_ref().map(SpannedToken::span);
 
         loop {
             if self.current_token.is_none() {
@@ -310,12 +635,19 @@ impl<'a> StreamingParser<'a> {
                 continue;
             }
 
-            let field = &fields[field_idx];
+            let field = &fields.ordered[field_idx];
+            let loc = self.current_location();
             let value = self.parse_value(&field.type_def)?;
+            if self.strict
+                && let Some(msg) = strict_type_error(field, &value)
+            {
+                return Err(self.make_error_at(msg, loc));
+            }
             obj.insert(field.name.clone(), value);
             field_idx += 1;
         }
 
+        let row_end = self.prev_end;
         // Skip newline/semi
         if let Some(st) = &self.current_token
             && matches!(st.token, Token::Newline | Token::Semi)
@@ -324,10 +656,29 @@ impl<'a> StreamingParser<'a> {
         }
 
         if obj.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(Value::Object(obj)))
+            return Ok(None);
+        }
+        // Fields the row did not supply: defaults and nulls fill optional
+        // ones, required ones are an error
+        for field in &fields.ordered[field_idx..] {
+            if let Some(default) = &field.default {
+                obj.insert(field.name.clone(), default.clone());
+            } else if field.optional {
+                obj.insert(field.name.clone(), Value::Null);
+            } else {
+                return Err(self.row_error(
+                    missing_field_error(field, &shape_name),
+                    row_span,
+                    row_end,
+                ));
+            }
         }
+        if let Some(span) = row_span
+            && let Some(warning) = short_row_warning(&shape_name, &fields, field_idx, span)
+        {
+            self.warn(warning);
+        }
+        Ok(Some(Value::Object(obj)))
     }
 
     fn parse_value(&mut self, _type_def: &TypeDef) -> Result<Value, ParseError> {
@@ -346,18 +697,17 @@ impl<'a> StreamingParser<'a> {
                 Value::Number(serde_json::Number::from(*n))
             }
             Token::Float(n) => {
+                let value = float_value(*n);
                 self.advance();
-                Value::Number(
-                    serde_json::Number::from_f64(*n).unwrap_or(serde_json::Number::from(0)),
-                )
+                value
             }
             Token::String(s) => {
                 self.advance();
-                Value::String(s.clone())
+                Value::String(s.to_string())
             }
             Token::Ident(s) => {
                 self.advance();
-                Value::String(s.clone())
+                Value::String(s.to_string())
             }
             Token::Bool(b) => {
                 self.advance();
@@ -443,12 +793,12 @@ impl<'a> StreamingParser<'a> {
             let key = match &self.current_token {
                 Some(st) => match &st.token {
                     Token::Ident(k) => {
-                        let k = k.clone();
+                        let k = k.to_string();
                         self.advance();
                         k
                     }
                     Token::String(k) => {
-                        let k = k.clone();
+                        let k = k.to_string();
                         self.advance();
                         k
                     }
@@ -470,12 +820,12 @@ impl<'a> StreamingParser<'a> {
         let key = match &self.current_token {
             Some(st) => match &st.token {
                 Token::Ident(k) => {
-                    let k = k.clone();
+                    let k = k.to_string();
                     self.advance();
                     k
                 }
                 Token::String(k) => {
-                    let k = k.clone();
+                    let k = k.to_string();
                     self.advance();
                     k
                 }
@@ -489,6 +839,242 @@ impl<'a> StreamingParser<'a> {
     }
 }
 
+impl StreamingParser<'static> {
+    /// Create a parser whose tokens are supplied with [`feed`](Self::feed).
+    fn fed() -> Self {
+        Self::with_source(TokenSource::Fed(std::collections::VecDeque::new()), false)
+    }
+
+    /// Append the tokens of one or more complete logical lines.
+    fn feed(&mut self, tokens: Vec<SpannedToken<'static>>) {
+        if let TokenSource::Fed(queue) = &mut self.source {
+            queue.extend(tokens);
+        }
+        if self.current_token.is_none() {
+            self.current_token = self.source.next_token();
+        }
+        if self.peek_token.is_none() {
+            self.peek_token = self.source.next_token();
+        }
+    }
+
+    /// Signal that no more tokens will be fed.
+    fn close_input(&mut self) {
+        self.input_closed = true;
+    }
+}
+
+/// Lexes fed text one logical line at a time, keeping track of how much
+/// input came before so token locations and errors point into the whole
+/// stream rather than the current line.
+#[derive(Default)]
+struct LineLexer {
+    /// Lines and bytes lexed so far
+    line: usize,
+    offset: usize,
+}
+
+impl LineLexer {
+    /// Lex `text`, a run of complete physical lines (or the tail of the
+    /// input). Returns `Ok(None)` without consuming anything if `more` input
+    /// may follow and the text ends inside a bracket, string or heredoc.
+    fn lex(
+        &mut self,
+        text: &str,
+        more: bool,
+    ) -> Result<Option<Vec<SpannedToken<'static>>>, ParseError> {
+        let mut lexer = Lexer::new(text);
+        let mut tokens = Vec::new();
+        let mut depth = 0isize;
+        while let Some(mut st) = lexer.next_token() {
+            match st.token {
+                Token::LBracket | Token::LBrace => depth += 1,
+                Token::RBracket | Token::RBrace => depth -= 1,
+                _ => {}
+            }
+            st.start = self.shift(st.start);
+            st.end = self.shift(st.end);
+            tokens.push(st.into_owned());
+        }
+        let unterminated = lexer
+            .lex_error
+            .as_ref()
+            .is_some_and(|e| e.message.starts_with("unterminated"));
+        if more && (depth > 0 || unterminated) {
+            return Ok(None);
+        }
+
+        self.join_require_arguments(&mut tokens, text);
+
+        let lex_error = lexer
+            .lex_error
+            .map(|e| ParseError::new(e.message, e.span.shifted(self.line)));
+        self.line += text.matches('\n').count();
+        self.offset += text.len();
+        match lex_error {
+            Some(err) => Err(err),
+            None => Ok(Some(tokens)),
+        }
+    }
+
+    /// Replace the tokens after each `!require` with one string token
+    /// holding the line's text, which the parser cannot recover once `text`
+    /// is gone (`1.10` lexes as the float `1.1`)
+    fn join_require_arguments(&self, tokens: &mut Vec<SpannedToken<'static>>, text: &str) {
+        let mut i = 0;
+        while i < tokens.len() {
+            if matches!(&tokens[i].token, Token::Directive(d) if d == "require") {
+                let start = i + 1;
+                let end = tokens[start..]
+                    .iter()
+                    .position(|st| matches!(st.token, Token::Newline | Token::Semi))
+                    .map_or(tokens.len(), |n| start + n);
+                if end > start {
+                    let spec = rest_of_line(text, tokens[start].start.offset - self.offset);
+                    let joined = SpannedToken {
+                        token: Token::String(spec.to_string().into()),
+                        start: tokens[start].start,
+                        end: tokens[end - 1].end,
+                    };
+                    tokens.splice(start..end, [joined]);
+                }
+            }
+            i += 1;
+        }
+    }
+
+    fn shift(&self, loc: Location) -> Location {
+        Location::new(loc.line + self.line, loc.column, loc.offset + self.offset)
+    }
+
+    /// Error for input that cannot be lexed, placed on the next line
+    fn error(&self, message: String) -> ParseError {
+        ParseError::new(message, Span::new(self.line + 1, 1))
+    }
+}
+
+/// Streaming parser fed with text chunks as they arrive, for input that is
+/// not available as one string or an async reader (WebSocket messages,
+/// callbacks, WASM).
+///
+/// Chunks may split lines, strings or brackets anywhere; complete logical
+/// lines are lexed as soon as they arrive and the rest is buffered. Call
+/// [`next_record`](Self::next_record) after each [`push`](Self::push) until
+/// it returns `None`, then [`finish`](Self::finish) at the end of the input
+/// and drain the remaining records.
+///
+/// # Example
+/// ```
+/// use tauq::tauq::streaming::PushParser;
+///
+/// let mut parser = PushParser::new();
+/// parser.push("!def User id name\n1 Al");
+/// parser.push("ice\n2 Bo");
+/// assert_eq!(parser.next_record().unwrap().unwrap()["name"], "Alice");
+/// assert!(parser.next_record().is_none()); // Bob's line is incomplete
+/// parser.push("b\n");
+/// parser.finish();
+/// assert_eq!(parser.next_record().unwrap().unwrap()["name"], "Bob");
+/// assert!(parser.next_record().is_none());
+/// ```
+pub struct PushParser {
+    parser: StreamingParser<'static>,
+    lines: LineLexer,
+    /// Text after the last logical line handed to the parser
+    buffer: String,
+    /// Input error, reported once the records before it are drained
+    error: Option<ParseError>,
+}
+
+impl PushParser {
+    /// Create a parser waiting for its first chunk
+    pub fn new() -> Self {
+        Self {
+            parser: StreamingParser::fed(),
+            lines: LineLexer::default(),
+            buffer: String::new(),
+            error: None,
+        }
+    }
+
+    /// Append a chunk of input. Ignored after [`finish`](Self::finish) or an
+    /// input error.
+    pub fn push(&mut self, chunk: &str) {
+        if self.parser.input_closed || self.error.is_some() {
+            return;
+        }
+        let mut rest = chunk;
+        while let Some(i) = rest.find('\n') {
+            self.buffer.push_str(&rest[..=i]);
+            rest = &rest[i + 1..];
+            self.lex_buffered(true);
+        }
+        self.buffer.push_str(rest);
+        if self.buffer.len() > crate::MAX_INPUT_SIZE {
+            self.buffer.clear();
+            self.error = Some(self.lines.error(format!(
+                "Logical line too large: exceeds {} bytes",
+                crate::MAX_INPUT_SIZE
+            )));
+        }
+    }
+
+    /// Reject row values that do not match their field's scalar type, as
+    /// [`StreamingParser::with_strict`] does
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.parser.strict = strict;
+        self
+    }
+
+    /// Signal the end of the input, flushing any unterminated last line
+    pub fn finish(&mut self) {
+        if self.error.is_none() {
+            self.lex_buffered(false);
+        }
+        self.parser.close_input();
+    }
+
+    /// The next complete record, or `None` if more input is needed (or the
+    /// stream has ended). An input error ends the stream after the records
+    /// before it.
+    pub fn next_record(&mut self) -> Option<Result<Value, ParseError>> {
+        if let Some(record) = self.parser.next_record() {
+            return Some(record);
+        }
+        let err = self.error.take()?;
+        self.parser.finished = true;
+        Some(Err(err))
+    }
+
+    /// Take the warnings found since the last call
+    pub fn drain_warnings(&mut self) -> Vec<ParseWarning> {
+        self.parser.drain_warnings()
+    }
+
+    /// Schemas defined by the input read so far, by name
+    pub fn schemas(&self) -> HashMap<String, Vec<FieldDef>> {
+        self.parser.schemas()
+    }
+
+    fn lex_buffered(&mut self, more: bool) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        match self.lines.lex(&self.buffer, more) {
+            Ok(None) => return,
+            Ok(Some(tokens)) => self.parser.feed(tokens),
+            Err(e) => self.error = Some(e),
+        }
+        self.buffer.clear();
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Iterator adapter for StreamingParser
 impl<'a> Iterator for StreamingParser<'a> {
     type Item = Result<Value, ParseError>;
@@ -498,6 +1084,139 @@ impl<'a> Iterator for StreamingParser<'a> {
     }
 }
 
+/// Asynchronous streaming parser over a [`tokio::io::AsyncBufRead`].
+///
+/// Input is buffered one logical line at a time - a physical line, extended
+/// while a bracket, string or heredoc is still open - then lexed and handed to
+/// the same record logic as [`StreamingParser`]. Schemas defined earlier in the
+/// stream remain active across records.
+///
+/// # Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use futures::StreamExt;
+/// use tauq::tauq::streaming::AsyncStreamingParser;
+///
+/// let input: &[u8] = b"!def User id name\n1 Alice\n2 Bob\n";
+/// let records: Vec<_> = AsyncStreamingParser::new(input).collect().await;
+/// assert_eq!(records.len(), 2);
+/// assert_eq!(records[1].as_ref().unwrap()["name"], "Bob");
+/// # });
+/// ```
+#[cfg(feature = "tokio")]
+pub struct AsyncStreamingParser<R> {
+    reader: R,
+    parser: StreamingParser<'static>,
+    buffer: Vec<u8>,
+    lines: LineLexer,
+    eof: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncBufRead + Unpin> AsyncStreamingParser<R> {
+    /// Create a new async streaming parser
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            parser: StreamingParser::fed(),
+            buffer: Vec::new(),
+            lines: LineLexer::default(),
+            eof: false,
+        }
+    }
+
+    /// Read until a complete logical line is buffered and return its tokens.
+    /// Returns `None` once the reader is exhausted.
+    fn poll_logical_line(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<Option<Vec<SpannedToken<'static>>>, ParseError>> {
+        use std::task::Poll;
+
+        loop {
+            if !self.eof {
+                let available = match std::pin::Pin::new(&mut self.reader).poll_fill_buf(cx) {
+                    Poll::Ready(Ok(available)) => available,
+                    Poll::Ready(Err(e)) => {
+                        return Poll::Ready(Err(self.lines.error(format!("I/O error: {}", e))));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                };
+                if available.is_empty() {
+                    self.eof = true;
+                } else {
+                    let (len, newline) = match available.iter().position(|&b| b == b'\n') {
+                        Some(i) => (i + 1, true),
+                        None => (available.len(), false),
+                    };
+                    self.buffer.extend_from_slice(&available[..len]);
+                    std::pin::Pin::new(&mut self.reader).consume(len);
+                    if self.buffer.len() > crate::MAX_INPUT_SIZE {
+                        return Poll::Ready(Err(self.lines.error(format!(
+                            "Logical line too large: exceeds {} bytes",
+                            crate::MAX_INPUT_SIZE
+                        ))));
+                    }
+                    if !newline {
+                        continue;
+                    }
+                }
+            }
+
+            if self.buffer.is_empty() {
+                return Poll::Ready(Ok(None));
+            }
+            if let Some(tokens) = self.lex_buffered()? {
+                return Poll::Ready(Ok(Some(tokens)));
+            }
+        }
+    }
+
+    /// Lex the buffered text, or return `Ok(None)` if it ends inside an open
+    /// construct and more input is available.
+    fn lex_buffered(&mut self) -> Result<Option<Vec<SpannedToken<'static>>>, ParseError> {
+        let text = std::str::from_utf8(&self.buffer)
+            .map_err(|e| self.lines.error(format!("Invalid UTF-8 in input: {}", e)))?;
+        let lexed = self.lines.lex(text, !self.eof);
+        if !matches!(lexed, Ok(None)) {
+            self.buffer.clear();
+        }
+        lexed
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncBufRead + Unpin> futures::Stream for AsyncStreamingParser<R> {
+    type Item = Result<Value, ParseError>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use std::task::Poll;
+
+        let this = &mut *self;
+        loop {
+            if let Some(record) = this.parser.next_record() {
+                return Poll::Ready(Some(record));
+            }
+            if this.parser.finished {
+                return Poll::Ready(None);
+            }
+            match this.poll_logical_line(cx) {
+                Poll::Ready(Ok(Some(tokens))) => this.parser.feed(tokens),
+                Poll::Ready(Ok(None)) => this.parser.close_input(),
+                Poll::Ready(Err(e)) => {
+                    // Input-level errors (I/O, encoding, lexing) end the stream.
+                    this.parser.finished = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,11 +1,19 @@
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use sha2::{Digest, Sha256};
 
 use super::Parser;
+use super::lexer::{Lexer, strip_bom};
+use super::token::{SpannedToken, Token};
 
 /// Maximum input size (100 MB) to prevent DoS
 const MAX_INPUT_SIZE: usize = 100 * 1024 * 1024;
@@ -24,13 +32,393 @@ const ALLOWED_COMMANDS: &[&str] = &[
     "true", "false", "test", "expr",
 ];
 
+/// Maximum nesting depth of `!if` blocks (matches the import depth limit)
+const MAX_CONDITIONAL_DEPTH: usize = 50;
+
+/// Maximum nesting depth of `!for` loops
+const MAX_LOOP_DEPTH: usize = 10;
+
 /// Configuration for TauqQ processing
-#[derive(Default)]
+///
+/// Build one from [`Default`], with struct update syntax or the `with_*`
+/// methods; a config holds no state of its own, so it can be reused across
+/// runs.
+#[derive(Debug, Clone, Default)]
 pub struct ProcessConfig {
     /// Base directory for resolving relative paths (security boundary)
     pub base_dir: Option<std::path::PathBuf>,
     /// Safe mode disables all shell execution and file I/O
     pub safe_mode: bool,
+    /// Time limit for `!emit`, `!run` and `!pipe` commands; `!timeout`
+    /// overrides it for the rest of the file or block
+    pub timeout: Option<Duration>,
+    /// Print `!log` messages to stderr; when false they are discarded
+    pub verbose: bool,
+    /// Programs `!emit`, `!run` and `!pipe` may start, matched against the
+    /// command name as written. `None` falls back to the built-in
+    /// `ALLOWED_COMMANDS` list; `Some` narrows that list further and
+    /// cannot admit a program outside it.
+    pub command_allowlist: Option<HashSet<String>>,
+}
+
+impl ProcessConfig {
+    /// Resolve relative paths against `dir`, which they may not escape
+    pub fn with_base_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+
+    /// Disable shell execution and file I/O
+    pub fn with_safe_mode(mut self, safe_mode: bool) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Limit how long each command may run; `None` for no limit
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Print `!log` messages to stderr
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Only allow commands whose name is in `commands` and in the built-in
+    /// allowlist
+    pub fn with_command_allowlist<I, S>(mut self, commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.command_allowlist = Some(commands.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+/// State of one processing run, shared with every file it imports
+#[derive(Default)]
+struct Run {
+    /// Canonical paths of the imports being processed (cycle detection)
+    visited: HashSet<String>,
+    /// Content hashes of the `!once` blocks emitted so far
+    once_seen: HashSet<u64>,
+}
+
+/// Options for [`pack`]
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// Drop lines that hold only a comment
+    pub strip_comments: bool,
+    /// Fail when two files define a schema with the same name but different
+    /// fields, instead of prefixing the later one with its file name
+    pub no_prefix: bool,
+}
+
+/// Schema definitions collected while packing
+#[derive(Debug, Default)]
+struct PackState {
+    options: PackOptions,
+    /// Packed schema names and their field text, in definition order
+    schemas: Vec<(String, String)>,
+    /// Current packed name for each schema name as written in the sources
+    aliases: HashMap<String, String>,
+    /// File stem of the file being packed, used to prefix conflicting names
+    file_stem: String,
+    /// Canonical paths of the files being packed, entry first
+    chain: Vec<std::path::PathBuf>,
+}
+
+impl PackState {
+    fn alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// Rewrite schema references in a field list to their packed names
+    fn resolve_fields(&self, fields: &str) -> String {
+        let mut words = Vec::new();
+        let mut after_extends = false;
+        for word in fields.split_whitespace() {
+            let rewritten = if after_extends {
+                self.alias(word).to_string()
+            } else if let Some((field, ty)) = word.split_once(':') {
+                let inner = ty.trim_start_matches('[').trim_end_matches(']');
+                format!("{}:{}", field, ty.replacen(inner, self.alias(inner), 1))
+            } else {
+                word.to_string()
+            };
+            after_extends = word == "!extends";
+            words.push(rewritten);
+        }
+        words.join(" ")
+    }
+
+    /// Register a schema definition and return the name it is packed under
+    fn define(&mut self, name: &str, fields: &str) -> Result<String, String> {
+        let fields = self.resolve_fields(fields);
+        let existing = |packed: &str| {
+            self.schemas
+                .iter()
+                .find(|(n, _)| n == packed)
+                .map(|(_, f)| f.clone())
+        };
+        let packed = match existing(name) {
+            None => name.to_string(),
+            Some(f) if f == fields => name.to_string(),
+            Some(_) if self.options.no_prefix => {
+                return Err(format!(
+                    "Schema '{}' in '{}' conflicts with an earlier definition",
+                    name, self.file_stem
+                ));
+            }
+            Some(_) => {
+                let prefixed = format!("{}_{}", self.file_stem, name);
+                match existing(&prefixed) {
+                    Some(f) if f != fields => {
+                        return Err(format!(
+                            "Cannot resolve conflicting definitions of schema '{}'",
+                            name
+                        ));
+                    }
+                    _ => prefixed,
+                }
+            }
+        };
+        if existing(&packed).is_none() {
+            self.schemas.push((packed.clone(), fields));
+        }
+        self.aliases.insert(name.to_string(), packed.clone());
+        Ok(packed)
+    }
+
+    /// Pack the file at the canonical `path`, checking its SHA-256 digest
+    /// against `checksum` when given
+    fn pack_file(&mut self, path: &Path, checksum: Option<&str>) -> Result<String, String> {
+        if self.chain.iter().any(|p| p == path) {
+            return Err(format!("Circular import detected: {}", path.display()));
+        }
+        if self.chain.len() > 50 {
+            return Err("Maximum import depth (50) exceeded".to_string());
+        }
+        let content = secure_read_file(&path.to_string_lossy(), &None)?;
+        if let Some(expected) = checksum {
+            let actual = sha256_hex(content.as_bytes());
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(format!(
+                    "Integrity check failed for '{}': expected {}, got {}",
+                    path.display(),
+                    expected,
+                    actual
+                ));
+            }
+        }
+
+        let parent_stem = std::mem::replace(&mut self.file_stem, stem_prefix(path));
+        self.chain.push(path.to_path_buf());
+        let packed = self.pack_source(strip_bom(&content), path);
+        self.chain.pop();
+        self.file_stem = parent_stem;
+        packed
+    }
+
+    /// Rewrite the source of the file at `path`. Imports are inlined, each
+    /// `!def` becomes a `!use` of its packed name and schema references are
+    /// renamed; all other text, strings and heredocs included, is copied as
+    /// written.
+    fn pack_source(&mut self, source: &str, path: &Path) -> Result<String, String> {
+        let mut lexer = Lexer::new(source);
+        let tokens: Vec<SpannedToken> = std::iter::from_fn(|| lexer.next_token()).collect();
+        if let Some(e) = &lexer.lex_error {
+            return Err(format!("{}: {}", path.display(), e));
+        }
+        let dir = path.parent().unwrap_or(Path::new("."));
+        // End offset of the last token on a line, or of the line's start
+        let line_start = |i: usize| if i == 0 { 0 } else { tokens[i - 1].end.offset };
+        let at_line_start = |i: usize| i == 0 || tokens[i - 1].token == Token::Newline;
+        // Index of the `Newline` or `;` that ends the statement holding token `i`
+        let statement_end = |i: usize| {
+            (i..tokens.len())
+                .find(|&j| matches!(tokens[j].token, Token::Newline | Token::Semi))
+                .unwrap_or(tokens.len())
+        };
+
+        let mut out = String::new();
+        // Source up to this offset has been written to `out`
+        let mut copied = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let name = match &token.token {
+                Token::Newline
+                    if self.options.strip_comments
+                        && at_line_start(i)
+                        && source[line_start(i)..token.start.offset]
+                            .trim_start()
+                            .starts_with('#') =>
+                {
+                    out.push_str(&source[copied..line_start(i)]);
+                    copied = token.end.offset;
+                    i += 1;
+                    continue;
+                }
+                Token::Directive(name) => name.as_str(),
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let end = statement_end(i);
+            match name {
+                "import" => {
+                    let target = match tokens.get(i + 1).map(|t| &t.token) {
+                        Some(Token::String(target) | Token::Ident(target)) if i + 1 < end => target,
+                        _ => return Err("!import requires a quoted path string".to_string()),
+                    };
+                    let arg_end = tokens.get(end).map_or(source.len(), |t| t.start.offset);
+                    let (_, checksum) =
+                        split_import_checksum(source[tokens[i + 1].start.offset..arg_end].trim());
+                    let resolved = dir
+                        .join(&**target)
+                        .canonicalize()
+                        .map_err(|e| format!("Cannot resolve import path '{}': {}", target, e))?;
+
+                    out.push_str(source[copied..token.start.offset].trim_end_matches([' ', '\t']));
+                    let inlined = self.pack_file(&resolved, checksum)?;
+                    out.push_str(&inlined);
+                    if !inlined.is_empty() && !inlined.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    copied = tokens.get(end).map_or(source.len(), |t| t.end.offset);
+                    i = end + 1;
+                }
+                "def" if i + 1 < end && matches!(tokens[i + 1].token, Token::Ident(_)) => {
+                    let name = &source[tokens[i + 1].start.offset..tokens[i + 1].end.offset];
+                    let fields = &source[tokens[i + 1].end.offset..tokens[end - 1].end.offset];
+                    let packed = self.define(name, fields.trim())?;
+                    out.push_str(&source[copied..token.start.offset]);
+                    out.push_str("!use ");
+                    out.push_str(&packed);
+                    copied = tokens[end - 1].end.offset;
+                    i = end;
+                }
+                "use" | "compose" => {
+                    // `!compose Name A B` defines Name in place from A and B
+                    let first = if name == "use" { i + 1 } else { i + 2 };
+                    for t in tokens.get(first..end).unwrap_or_default() {
+                        if let Token::Ident(part) = &t.token {
+                            out.push_str(&source[copied..t.start.offset]);
+                            out.push_str(self.alias(part));
+                            copied = t.end.offset;
+                        }
+                    }
+                    if name == "compose"
+                        && let Some(Token::Ident(composed)) = tokens.get(i + 1).map(|t| &t.token)
+                    {
+                        self.aliases.remove(&**composed);
+                    }
+                    i = end;
+                }
+                "schemas" | "models" => {
+                    let Some(close) =
+                        (i..tokens.len()).find(|&j| tokens[j].token == Token::TripleDash)
+                    else {
+                        return Err("Unterminated schema block - expected '---'".to_string());
+                    };
+                    let mut line = i + 1;
+                    while line < close {
+                        let line_end = statement_end(line).min(close);
+                        if let Token::Ident(_) = tokens[line].token {
+                            let name = &source[tokens[line].start.offset..tokens[line].end.offset];
+                            let fields =
+                                &source[tokens[line].end.offset..tokens[line_end - 1].end.offset];
+                            self.define(name, fields.trim())?;
+                        }
+                        line = line_end + 1;
+                    }
+
+                    out.push_str(source[copied..token.start.offset].trim_end_matches([' ', '\t']));
+                    let after = match tokens.get(close + 1) {
+                        Some(t) if t.token == Token::Newline => close + 1,
+                        _ => close,
+                    };
+                    copied = tokens[after].end.offset;
+                    i = after + 1;
+                }
+                _ => i += 1,
+            }
+        }
+
+        // A comment on the last line, with no newline after it
+        let tail = line_start(tokens.len());
+        if self.options.strip_comments
+            && at_line_start(tokens.len())
+            && copied <= tail
+            && source[tail..].trim_start().starts_with('#')
+        {
+            out.push_str(&source[copied..tail]);
+        } else {
+            out.push_str(&source[copied..]);
+        }
+        Ok(out)
+    }
+
+    /// The collected schemas as a `!schemas ... ---` block followed by `body`
+    fn finish(self, body: &str) -> String {
+        if self.schemas.is_empty() {
+            return body.to_string();
+        }
+        let mut packed = String::from("!schemas\n");
+        for (name, fields) in &self.schemas {
+            packed.push_str(name);
+            if !fields.is_empty() {
+                packed.push(' ');
+                packed.push_str(fields);
+            }
+            packed.push('\n');
+        }
+        packed.push_str("---\n");
+        packed.push_str(body);
+        packed
+    }
+}
+
+/// A file stem usable as a schema name prefix
+fn stem_prefix(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut prefix: String = stem
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    if !prefix.starts_with(char::is_alphabetic) {
+        prefix.insert(0, 'F');
+    }
+    prefix
+}
+
+/// Inline every `!import` of `entry`, recursively, into one self-contained
+/// source
+///
+/// Imports resolve relative to the importing file, as when parsing it, and
+/// may reach outside its directory. Schema definitions from all files are
+/// merged into a single `!schemas` block at the top; each `!def` is replaced
+/// by a `!use` of the packed name. The source is rewritten token by token,
+/// so directive-like text in strings and heredocs is left alone, and no
+/// other directive is evaluated.
+pub fn pack(entry: &Path, options: PackOptions) -> Result<String, String> {
+    let canonical = entry
+        .canonicalize()
+        .map_err(|e| format!("Cannot resolve path '{}': {}", entry.display(), e))?;
+    let mut state = PackState {
+        options,
+        ..Default::default()
+    };
+    let body = state.pack_file(&canonical, None)?;
+    Ok(state.finish(&body))
 }
 
 /// Process TauqQ directives (!pipe, !emit) and return canonical Tauq source.
@@ -42,9 +430,16 @@ pub fn process(
     let config = ProcessConfig {
         base_dir: std::env::current_dir().ok(),
         safe_mode,
+        ..Default::default()
     };
-    let mut visited = HashSet::new();
-    process_internal(input, vars, &config, 0, &mut visited)
+    process_internal(
+        input,
+        vars,
+        &mut Vec::new(),
+        &config,
+        0,
+        &mut Run::default(),
+    )
 }
 
 /// Process with explicit configuration
@@ -53,8 +448,25 @@ pub fn process_with_config(
     vars: &mut HashMap<String, String>,
     config: &ProcessConfig,
 ) -> Result<String, String> {
-    let mut visited = HashSet::new();
-    process_internal(input, vars, config, 0, &mut visited)
+    process_internal(input, vars, &mut Vec::new(), config, 0, &mut Run::default())
+}
+
+/// Hex-encoded SHA-256 digest, as used by `!import "file" sha256:...`
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Split an optional trailing `sha256:<hex>` checksum off an `!import` argument
+fn split_import_checksum(arg: &str) -> (&str, Option<&str>) {
+    if let Some((path, last)) = arg.rsplit_once(char::is_whitespace)
+        && let Some(hex) = last.strip_prefix("sha256:")
+    {
+        return (path.trim(), Some(hex));
+    }
+    (arg, None)
 }
 
 /// Securely open and read a file, preventing TOCTOU race conditions
@@ -177,68 +589,224 @@ fn validate_path(
     Ok(canonical)
 }
 
+/// Values shadowed by the `!local` bindings of one scope, restored when it ends
+type Scope = HashMap<String, Option<String>>;
+
+/// Bind `name` in the innermost scope, or globally outside any scope
+fn set_local(vars: &mut HashMap<String, String>, scopes: &mut [Scope], name: &str, value: String) {
+    if let Some(scope) = scopes.last_mut() {
+        scope
+            .entry(name.to_string())
+            .or_insert_with(|| vars.get(name).cloned());
+    }
+    vars.insert(name.to_string(), value);
+}
+
+/// End the innermost scope, restoring the values its bindings shadowed
+fn end_scope(vars: &mut HashMap<String, String>, scopes: &mut Vec<Scope>) {
+    for (name, previous) in scopes.pop().unwrap_or_default() {
+        match previous {
+            Some(value) => vars.insert(name, value),
+            None => vars.remove(&name),
+        };
+    }
+}
+
+/// `vars` holds the innermost binding of every variable, with the global
+/// scope at the bottom; `scopes` records what each open `!for` iteration or
+/// `!scope` block has shadowed.
 fn process_internal(
     input: &str,
     vars: &mut HashMap<String, String>,
+    scopes: &mut Vec<Scope>,
     config: &ProcessConfig,
     depth: usize,
-    visited: &mut HashSet<String>,
+    run: &mut Run,
 ) -> Result<String, String> {
     if depth > 50 {
         return Err("Maximum import depth (50) exceeded".to_string());
     }
+    // `!timeout` overrides the limit for the rest of this input only
+    let mut config = Cow::Borrowed(config);
 
     let mut output = String::new();
-    let mut lines = input.lines().peekable();
+    let mut lines = strip_bom(input).lines().peekable();
+    let mut conditionals: Vec<IfState> = Vec::new();
+    // `!scope` blocks opened by this input, which must also close them
+    let outer_scopes = scopes.len();
 
     while let Some(line) = lines.next() {
         let trimmed = line.trim();
 
-        if trimmed.starts_with("!set ") {
-            let parts: Vec<&str> = trimmed
-                .strip_prefix("!set ")
-                .unwrap()
-                .splitn(2, ' ')
-                .collect();
+        if let Some(spec) = trimmed
+            .strip_prefix("!for")
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            // The body is consumed even in a false branch so its !end is not
+            // mistaken for the end of the enclosing !if
+            let body = collect_loop_body(&mut lines)?;
+            if branch_active(&conditionals) {
+                let (name, values) = parse_for_spec(spec, vars, config.safe_mode)?;
+                for value in values {
+                    // Each iteration gets a fresh scope for the loop
+                    // variable and the body's `!local` bindings
+                    scopes.push(Scope::new());
+                    let expanded = substitute_loop_var(&body, &name, &value);
+                    set_local(vars, scopes, &name, value);
+                    let processed =
+                        process_internal(&expanded, vars, scopes, &config, depth + 1, run);
+                    end_scope(vars, scopes);
+                    output.push_str(&processed?);
+                }
+            }
+            continue;
+        }
+        if conditional_directive(trimmed, &mut conditionals, vars, &config)? {
+            continue;
+        }
+        if !branch_active(&conditionals) {
+            // Lines in a false branch are discarded
+            continue;
+        }
+
+        if let Some(spec) = trimmed.strip_prefix("!require ") {
+            super::parser::check_version_requirement(spec)?;
+        } else if let Some(secs) = trimmed.strip_prefix("!timeout ") {
+            // No commands run in safe mode, so there is nothing to limit
+            if !config.safe_mode {
+                let secs = secs.trim();
+                let secs: u64 = secs
+                    .parse()
+                    .map_err(|_| format!("!timeout expects a number of seconds, got '{}'", secs))?;
+                config.to_mut().timeout = (secs > 0).then(|| Duration::from_secs(secs));
+            }
+        } else if let Some(count) = trimmed.strip_prefix("!assert_count ") {
+            let count = count.trim();
+            let expected: usize = count
+                .parse()
+                .map_err(|_| format!("!assert_count expects a number, got '{}'", count))?;
+            let rows = output.lines().filter(|l| is_data_line(l)).count();
+            if rows != expected {
+                return Err(format!(
+                    "Assertion failed: expected {} rows, found {}",
+                    expected, rows
+                ));
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("!assert ") {
+            let (condition, message) = split_assert(rest)?;
+            if !eval_condition(&condition, vars, &config)? {
+                return Err(format!("Assertion failed: {}", message));
+            }
+        } else if trimmed == "!log" || trimmed.starts_with("!log ") {
+            // Diagnostics go to stderr and never reach the data stream
+            let message = interpolate(trimmed["!log".len()..].trim(), vars, config.safe_mode)?;
+            if config.verbose {
+                eprintln!("{}", message.trim_matches('"'));
+            }
+        } else if let Some(rest) = trimmed
+            .strip_prefix("!set ")
+            .or_else(|| trimmed.strip_prefix("!local "))
+        {
+            let parts: Vec<&str> = rest.splitn(2, ' ').collect();
             if parts.len() == 2 {
                 let key = parts[0].trim();
-                let val = parts[1].trim().trim_matches('"'); // Strip quotes if present
-                vars.insert(key.to_string(), val.to_string());
+                let raw = parts[1].trim();
+                let val = if let Some(env_ref) = raw.strip_prefix("?env:") {
+                    if config.safe_mode {
+                        return Err("?env: values are disabled in safe mode".to_string());
+                    }
+                    read_env(env_ref)?
+                } else {
+                    raw.trim_matches('"').to_string() // Strip quotes if present
+                };
+                if trimmed.starts_with("!local ") {
+                    set_local(vars, scopes, key, val);
+                } else {
+                    // Assigns the innermost binding: a `!local` if one is in
+                    // scope, otherwise the global variable
+                    vars.insert(key.to_string(), val);
+                }
+            }
+        } else if trimmed == "!scope" {
+            scopes.push(Scope::new());
+        } else if trimmed == "!endscope" {
+            if scopes.len() == outer_scopes {
+                return Err("!endscope without a matching !scope".to_string());
             }
+            end_scope(vars, scopes);
         } else if trimmed.starts_with("!import ") {
             if config.safe_mode {
                 return Err("!import directive is disabled in safe mode".to_string());
             }
-            let path_str = trimmed
+            let import_arg = trimmed
                 .strip_prefix("!import ")
                 .ok_or_else(|| "Invalid !import directive".to_string())?
                 .trim();
+            let (path_str, expected_checksum) = split_import_checksum(import_arg);
             let clean_path = path_str.trim_matches('"');
 
             // Validate path for circular import detection
             let validated_path = validate_path(clean_path, &config.base_dir)?;
             let abs_path = validated_path.to_string_lossy().into_owned();
 
-            if visited.contains(&abs_path) {
+            if run.visited.contains(&abs_path) {
                 return Err(format!("Circular import detected: {}", abs_path));
             }
 
-            visited.insert(abs_path.clone());
+            run.visited.insert(abs_path.clone());
 
             // Use secure file reading to prevent TOCTOU
             let content = secure_read_file(clean_path, &config.base_dir)?;
 
+            if let Some(expected) = expected_checksum {
+                let actual = sha256_hex(content.as_bytes());
+                if !actual.eq_ignore_ascii_case(expected) {
+                    return Err(format!(
+                        "Integrity check failed for '{}': expected {}, got {}",
+                        clean_path, expected, actual
+                    ));
+                }
+            }
+
             // Recursive process with same vars, update base_dir to imported file's directory
             let import_config = ProcessConfig {
                 base_dir: validated_path.parent().map(|p| p.to_path_buf()),
-                safe_mode: config.safe_mode,
+                ..config.as_ref().clone()
             };
             let processed_import =
-                process_internal(&content, vars, &import_config, depth + 1, visited)?;
+                process_internal(&content, vars, scopes, &import_config, depth + 1, run)?;
             output.push_str(&processed_import);
             output.push('\n');
 
-            visited.remove(&abs_path);
+            run.visited.remove(&abs_path);
+        } else if let Some(rest) = trimmed.strip_prefix("!once")
+            && (rest.is_empty() || rest.starts_with([' ', '{']))
+        {
+            if rest.trim() != "{" {
+                return Err("!once expects a block: !once { ... }".to_string());
+            }
+
+            // Collect the body up to the matching closing brace
+            let mut body = String::new();
+            let mut open = 1;
+            for l in lines.by_ref() {
+                open += brace_delta(l);
+                if open <= 0 {
+                    break;
+                }
+                body.push_str(l);
+                body.push('\n');
+            }
+            if open > 0 {
+                return Err("Unterminated block for !once".to_string());
+            }
+
+            let mut hasher = DefaultHasher::new();
+            body.hash(&mut hasher);
+            if run.once_seen.insert(hasher.finish()) {
+                let processed = process_internal(&body, vars, scopes, &config, depth + 1, run)?;
+                output.push_str(&processed);
+            }
         } else if trimmed.starts_with("!emit ") {
             if config.safe_mode {
                 return Err("!emit directive is disabled in safe mode".to_string());
@@ -246,7 +814,7 @@ fn process_internal(
             let cmd_str = trimmed
                 .strip_prefix("!emit ")
                 .ok_or_else(|| "Invalid !emit directive".to_string())?;
-            let result = run_command(cmd_str, None, vars)?;
+            let result = run_command(cmd_str, None, vars, &config)?;
             validate_tauq_output(&result, "!emit", cmd_str)?;
             output.push_str(&result);
             output.push('\n');
@@ -254,16 +822,14 @@ fn process_internal(
             if config.safe_mode {
                 return Err("!env directive is disabled in safe mode".to_string());
             }
-            let var_name = trimmed
+            let env_ref = trimmed
                 .strip_prefix("!env ")
-                .ok_or_else(|| "Invalid !env directive".to_string())?
-                .trim();
-            if let Ok(val) = std::env::var(var_name) {
-                // Emit as string
-                output.push_str(&format!("\"{}\"\n", val));
-            } else {
-                return Err(format!("Environment variable '{}' not found", var_name));
-            }
+                .ok_or_else(|| "Invalid !env directive".to_string())?;
+            // Emit as string
+            let val = read_env(env_ref)?;
+            let json_str = serde_json::to_string(&val).map_err(|e| e.to_string())?;
+            output.push_str(&json_str);
+            output.push('\n');
         } else if trimmed.starts_with("!read ") {
             if config.safe_mode {
                 return Err("!read directive is disabled in safe mode".to_string());
@@ -276,7 +842,7 @@ fn process_internal(
 
             // Use secure file reading to prevent TOCTOU
             let content = secure_read_file(clean_path, &config.base_dir)?;
-            let json_str = serde_json::to_string(&content).map_err(|e| e.to_string())?;
+            let json_str = serde_json::to_string(strip_bom(&content)).map_err(|e| e.to_string())?;
             output.push_str(&json_str);
             output.push('\n');
         } else if trimmed.starts_with("!json ") {
@@ -292,12 +858,25 @@ fn process_internal(
             // Use secure file reading to prevent TOCTOU
             let content = secure_read_file(clean_path, &config.base_dir)?;
 
-            let json_val: serde_json::Value = serde_json::from_str(&content)
+            let json_val: serde_json::Value = serde_json::from_str(strip_bom(&content))
                 .map_err(|e| format!("Failed to parse JSON file '{}': {}", clean_path, e))?;
 
             let tauq_str = super::json_to_tauq(&json_val);
             output.push_str(&tauq_str);
             output.push('\n');
+        } else if trimmed.starts_with("!csv ") {
+            if config.safe_mode {
+                return Err("!csv directive is disabled in safe mode".to_string());
+            }
+            let path_str = trimmed
+                .strip_prefix("!csv ")
+                .ok_or_else(|| "Invalid !csv directive".to_string())?
+                .trim();
+            let clean_path = path_str.trim_matches('"');
+
+            // Use secure file reading to prevent TOCTOU
+            let content = secure_read_file(clean_path, &config.base_dir)?;
+            output.push_str(&csv_schema_rows(clean_path, strip_bom(&content))?);
         } else if trimmed.starts_with("!run ") {
             if config.safe_mode {
                 return Err("!run directive is disabled in safe mode".to_string());
@@ -362,7 +941,7 @@ fn process_internal(
                 code_block.push('\n');
             }
 
-            let result = run_code_block(program, args, &code_block, vars, None)?;
+            let result = run_code_block(program, args, &code_block, vars, None, &config)?;
             validate_tauq_output(&result, "!run", program)?;
             output.push_str(&result);
             output.push('\n');
@@ -429,27 +1008,615 @@ fn process_internal(
                 }
 
                 // Execute block with input
-                let result = run_code_block(program, args, &code_block, vars, Some(&output))?;
+                let result =
+                    run_code_block(program, args, &code_block, vars, Some(&output), &config)?;
                 validate_tauq_output(&result, "!pipe", program)?;
                 output = result;
             } else {
                 // Standard single-line pipe
                 // Top-down pipe: transform current output
-                let result = run_command(cmd_str, Some(&output), vars)?;
+                let result = run_command(cmd_str, Some(&output), vars, &config)?;
                 validate_tauq_output(&result, "!pipe", cmd_str)?;
                 output = result;
             }
         } else if trimmed.starts_with('#') || trimmed.is_empty() {
             // Ignore comments and empty lines
         } else {
-            output.push_str(line);
+            output.push_str(&interpolate(line, vars, config.safe_mode)?);
             output.push('\n');
         }
     }
 
+    if !conditionals.is_empty() {
+        return Err("Unterminated !if block: expected !end".to_string());
+    }
+    if scopes.len() > outer_scopes {
+        return Err("Unterminated !scope block: expected !endscope".to_string());
+    }
+
     Ok(output)
 }
 
+/// Collect the lines of a `!for` body up to its matching `!end`.
+fn collect_loop_body<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<String, String> {
+    let mut body = String::new();
+    // One entry per open block inside the body; `true` for loops
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut loop_depth = 1;
+
+    for line in lines {
+        match line.split_whitespace().next() {
+            Some("!if") => blocks.push(false),
+            Some("!for") => {
+                blocks.push(true);
+                loop_depth = loop_depth.max(1 + blocks.iter().filter(|&&is_loop| is_loop).count());
+                if loop_depth > MAX_LOOP_DEPTH {
+                    return Err(format!(
+                        "Maximum !for nesting depth ({}) exceeded",
+                        MAX_LOOP_DEPTH
+                    ));
+                }
+            }
+            Some("!end") if blocks.pop().is_none() => return Ok(body),
+            _ => {}
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
+
+    Err("Unterminated !for block: expected !end".to_string())
+}
+
+/// Parse `VAR in v1 v2 ...`. A `$NAME` value expands to the whitespace-separated
+/// words of that variable.
+fn parse_for_spec(
+    spec: &str,
+    vars: &HashMap<String, String>,
+    safe_mode: bool,
+) -> Result<(String, Vec<String>), String> {
+    let mut words = spec.split_whitespace();
+    let (Some(name), Some("in")) = (words.next(), words.next()) else {
+        return Err("!for expects '!for VAR in <values>'".to_string());
+    };
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("Invalid !for variable name '{}'", name));
+    }
+
+    let mut values = Vec::new();
+    for word in words {
+        match word.strip_prefix('$') {
+            Some(var) => {
+                let list = lookup_var(var, vars, safe_mode)
+                    .ok_or_else(|| format!("Undefined variable '${}' in !for", var))?;
+                values.extend(list.split_whitespace().map(str::to_string));
+            }
+            None => values.push(word.trim_matches('"').to_string()),
+        }
+    }
+    Ok((name.to_string(), values))
+}
+
+/// Replace each `$NAME` in a loop body with `value`, leaving alone longer
+/// names that start with it (`$ITEM` for `$I`)
+fn substitute_loop_var(body: &str, name: &str, value: &str) -> String {
+    let reference = format!("${}", name);
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find(&reference) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + reference.len()..];
+        if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            out.push_str(&reference);
+        } else {
+            out.push_str(value);
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// State of one open `!if` block
+struct IfState {
+    /// Whether the enclosing block is emitting lines
+    parent_active: bool,
+    /// Whether some branch of this block has already been selected
+    taken: bool,
+    /// Whether the current branch is emitting lines
+    active: bool,
+    /// Whether `!else` has been seen
+    in_else: bool,
+}
+
+fn branch_active(conditionals: &[IfState]) -> bool {
+    conditionals.last().is_none_or(|state| state.active)
+}
+
+/// Handle `!if`, `!elif`, `!else` and `!end`. Returns `false` for any other line.
+///
+/// Conditions are only evaluated when the enclosing branch is active, so a
+/// discarded branch never runs shell expressions.
+fn conditional_directive(
+    trimmed: &str,
+    conditionals: &mut Vec<IfState>,
+    vars: &HashMap<String, String>,
+    config: &ProcessConfig,
+) -> Result<bool, String> {
+    let directive = trimmed.split_whitespace().next().unwrap_or("");
+    let expr = trimmed[directive.len()..].trim();
+    match directive {
+        "!if" => {
+            if conditionals.len() >= MAX_CONDITIONAL_DEPTH {
+                return Err(format!(
+                    "Maximum !if nesting depth ({}) exceeded",
+                    MAX_CONDITIONAL_DEPTH
+                ));
+            }
+            let parent_active = branch_active(conditionals);
+            let active = parent_active && eval_condition(expr, vars, config)?;
+            conditionals.push(IfState {
+                parent_active,
+                taken: active,
+                active,
+                in_else: false,
+            });
+        }
+        "!elif" => {
+            let state = conditionals
+                .last_mut()
+                .ok_or_else(|| "!elif without matching !if".to_string())?;
+            if state.in_else {
+                return Err("!elif after !else".to_string());
+            }
+            state.active =
+                state.parent_active && !state.taken && eval_condition(expr, vars, config)?;
+            state.taken |= state.active;
+        }
+        "!else" => {
+            let state = conditionals
+                .last_mut()
+                .ok_or_else(|| "!else without matching !if".to_string())?;
+            if state.in_else {
+                return Err("Duplicate !else in !if block".to_string());
+            }
+            state.in_else = true;
+            state.active = state.parent_active && !state.taken;
+            state.taken = true;
+        }
+        "!end" => {
+            conditionals
+                .pop()
+                .ok_or_else(|| "!end without matching !if".to_string())?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Evaluate an `!if`, `!elif` or `!assert` condition.
+///
+/// Supported forms, optionally prefixed with `not`:
+/// - `defined $VAR` - the variable is set
+/// - `$VAR == value`, `!=`, `<`, `<=`, `>`, `>=` - numeric when both sides are numbers
+/// - `$VAR` - truthy unless empty, `false` or `0`
+/// - `$VAR/users/0/name` - JSON Pointer into a variable holding a JSON or Tauq value
+///
+/// Variables come from `!set`, falling back to the process environment outside
+/// safe mode. `$(cmd args)` substitutes command output and is rejected in safe mode.
+fn eval_condition(
+    expr: &str,
+    vars: &HashMap<String, String>,
+    config: &ProcessConfig,
+) -> Result<bool, String> {
+    let words = split_condition(expr)?;
+    let (negate, words) = match words.split_first() {
+        Some((first, rest)) if first == "not" => (true, rest),
+        _ => (false, &words[..]),
+    };
+
+    let result = match words {
+        [] => return Err("!if requires a condition".to_string()),
+        [keyword, var] if keyword == "defined" => {
+            let name = var
+                .strip_prefix('$')
+                .ok_or_else(|| format!("defined expects a variable like $NAME, got '{}'", var))?;
+            lookup_var(name, vars, config.safe_mode).is_some()
+        }
+        [operand] => {
+            let value = resolve_operand(operand, vars, config)?;
+            !matches!(value.as_str(), "" | "false" | "0")
+        }
+        [lhs, op, rhs] => {
+            let lhs = resolve_operand(lhs, vars, config)?;
+            let rhs = resolve_operand(rhs, vars, config)?;
+            compare_operands(&lhs, op, &rhs)?
+        }
+        _ => return Err(format!("Invalid condition '{}'", expr)),
+    };
+    Ok(result != negate)
+}
+
+fn lookup_var(name: &str, vars: &HashMap<String, String>, safe_mode: bool) -> Option<String> {
+    vars.get(name).cloned().or_else(|| {
+        if safe_mode {
+            None
+        } else {
+            std::env::var(name).ok()
+        }
+    })
+}
+
+fn resolve_operand(
+    operand: &str,
+    vars: &HashMap<String, String>,
+    config: &ProcessConfig,
+) -> Result<String, String> {
+    if let Some(cmd) = operand
+        .strip_prefix("$(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        if config.safe_mode {
+            return Err("Shell expressions in conditions are disabled in safe mode".to_string());
+        }
+        return Ok(run_command(cmd, None, vars, config)?.trim_end().to_string());
+    }
+    if let Some((name, pointer)) = operand
+        .strip_prefix('$')
+        .and_then(|reference| reference.find('/').map(|i| reference.split_at(i)))
+    {
+        return resolve_pointer(name, pointer, vars, config.safe_mode);
+    }
+    if let Some(name) = operand.strip_prefix('$') {
+        return lookup_var(name, vars, config.safe_mode).ok_or_else(|| {
+            format!(
+                "Undefined variable '${}' in condition (use 'defined ${}' to test for it)",
+                name, name
+            )
+        });
+    }
+    if let Some(quoted) = operand
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return Ok(quoted.to_string());
+    }
+    Ok(operand.to_string())
+}
+
+/// Resolve `$NAME/pointer`: the variable holds a JSON or Tauq value and the
+/// JSON Pointer selects part of it. Strings compare without quotes and null
+/// as the empty string.
+fn resolve_pointer(
+    name: &str,
+    pointer: &str,
+    vars: &HashMap<String, String>,
+    safe_mode: bool,
+) -> Result<String, String> {
+    let raw = lookup_var(name, vars, safe_mode)
+        .ok_or_else(|| format!("Undefined variable '${}' in condition", name))?;
+    let value = serde_json::from_str::<serde_json::Value>(&raw)
+        .ok()
+        .or_else(|| Parser::parse_single_value(&raw).ok())
+        .ok_or_else(|| format!("Variable '${}' does not hold a JSON or Tauq value", name))?;
+    match crate::json_pointer(&value, pointer) {
+        Some(serde_json::Value::String(s)) => Ok(s.clone()),
+        Some(serde_json::Value::Null) => Ok(String::new()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(format!("No value at pointer {} in ${}", pointer, name)),
+    }
+}
+
+fn compare_operands(lhs: &str, op: &str, rhs: &str) -> Result<bool, String> {
+    let numbers = lhs.parse::<f64>().ok().zip(rhs.parse::<f64>().ok());
+    match (op, numbers) {
+        ("==", Some((a, b))) => Ok(a == b),
+        ("!=", Some((a, b))) => Ok(a != b),
+        ("==", None) => Ok(lhs == rhs),
+        ("!=", None) => Ok(lhs != rhs),
+        ("<", Some((a, b))) => Ok(a < b),
+        ("<=", Some((a, b))) => Ok(a <= b),
+        (">", Some((a, b))) => Ok(a > b),
+        (">=", Some((a, b))) => Ok(a >= b),
+        ("<" | "<=" | ">" | ">=", None) => Err(format!(
+            "Operator '{}' requires numeric operands, got '{}' and '{}'",
+            op, lhs, rhs
+        )),
+        _ => Err(format!("Unknown operator '{}' in condition", op)),
+    }
+}
+
+/// Read `VAR [default]` from the environment. The default may be quoted to
+/// include spaces; without one, an unset variable is an error.
+fn read_env(env_ref: &str) -> Result<String, String> {
+    let env_ref = env_ref.trim();
+    let (name, default) = match env_ref.split_once(char::is_whitespace) {
+        Some((name, default)) => (name, Some(default.trim())),
+        None => (env_ref, None),
+    };
+    if name.is_empty() {
+        return Err("!env requires a variable name".to_string());
+    }
+    match (std::env::var(name), default) {
+        (Ok(val), _) => Ok(val),
+        (Err(_), Some(default)) => {
+            let unquoted = default
+                .strip_prefix('"')
+                .and_then(|d| d.strip_suffix('"'))
+                .unwrap_or(default);
+            Ok(unquoted.to_string())
+        }
+        (Err(_), None) => Err(format!("Environment variable '{}' not found", name)),
+    }
+}
+
+/// Render a CSV file as a `!def` block named after the file stem, with the
+/// header cells as fields and one row per record
+fn csv_schema_rows(path: &str, content: &str) -> Result<String, String> {
+    let records = super::csv_to_value(content, false)
+        .map_err(|e| format!("Failed to parse CSV file '{}': {}", path, e))?;
+    let mut records = records
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|record| record.as_object());
+    let Some(header) = records.next() else {
+        return Err(format!("CSV file '{}' has no header row", path));
+    };
+
+    let formatter = super::Formatter::new();
+    let mut fields = Vec::new();
+    let mut seen = HashSet::new();
+    for cell in header.values() {
+        let field = match cell {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if !formatter.is_valid_identifier(&field) {
+            return Err(format!(
+                "CSV column '{}' in '{}' is not a valid field name",
+                field, path
+            ));
+        }
+        if !seen.insert(field.clone()) {
+            return Err(format!("Duplicate CSV column '{}' in '{}'", field, path));
+        }
+        fields.push(field);
+    }
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut name: String = stem
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "Row");
+    }
+
+    let mut block = format!("!def {} {}\n", name, fields.join(" "));
+    for record in records {
+        let values: Vec<String> = record
+            .values()
+            .map(|v| formatter.format_value_for_row(v))
+            .collect();
+        block.push_str(&values.join(" "));
+        block.push('\n');
+    }
+    Ok(block)
+}
+
+/// Split `!assert <condition> "message"` into its condition and message. The
+/// message is the trailing quoted word; without one, the condition is used.
+fn split_assert(rest: &str) -> Result<(String, String), String> {
+    let mut words = split_condition(rest)?;
+    let message = match words.last() {
+        Some(last)
+            if words.len() > 1
+                && last.len() >= 2
+                && last.starts_with('"')
+                && last.ends_with('"') =>
+        {
+            let last = words.pop().unwrap_or_default();
+            last[1..last.len() - 1].to_string()
+        }
+        Some(_) => rest.trim().to_string(),
+        None => return Err("!assert requires a condition".to_string()),
+    };
+    Ok((words.join(" "), message))
+}
+
+/// Whether an output line is data, rather than blank, a directive or `---`
+fn is_data_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('!') && trimmed != "---"
+}
+
+/// Split a condition into words, keeping quoted strings and `$(...)` intact.
+fn split_condition(expr: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_quote = false;
+    let mut parens = 0usize;
+    let mut chars = expr.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if parens == 0 => in_quote = !in_quote,
+            '$' if !in_quote && chars.peek() == Some(&'(') => {
+                current.push(c);
+                current.push('(');
+                chars.next();
+                parens += 1;
+                continue;
+            }
+            '(' if parens > 0 => parens += 1,
+            ')' if parens > 0 => parens -= 1,
+            c if c.is_whitespace() && !in_quote && parens == 0 => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+
+    if in_quote {
+        return Err("Unterminated quote in condition".to_string());
+    }
+    if parens > 0 {
+        return Err("Unterminated $( in condition".to_string());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+/// Expand `${VAR}` and `${VAR?default}` references in a plain Tauq line.
+///
+/// Values substituted inside a quoted string are escaped so the string stays
+/// valid; elsewhere they are inserted verbatim, so multi-word values belong in
+/// quotes. References may nest: `${${META}}` looks up the variable named by `META`.
+/// `$${` is written out as a literal `${`.
+fn interpolate(
+    line: &str,
+    vars: &HashMap<String, String>,
+    safe_mode: bool,
+) -> Result<String, String> {
+    if !line.contains("${") {
+        return Ok(line.to_string());
+    }
+
+    let mut out = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("${") {
+            let end = reference_end(after)
+                .ok_or_else(|| format!("Unterminated variable reference in '{}'", line.trim()))?;
+            let value = resolve_reference(&after[..end], vars, safe_mode)?;
+            if in_string {
+                out.push_str(&escape_in_string(&value));
+            } else {
+                out.push_str(&value);
+            }
+            rest = &after[end + 1..];
+            continue;
+        }
+
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '#' {
+            // Leave comments untouched
+            out.push_str(rest);
+            break;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(out)
+}
+
+/// Byte index of the `}` closing a reference whose `${` has already been consumed.
+fn reference_end(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"${") {
+            depth += 1;
+            i += 2;
+            continue;
+        }
+        if bytes[i] == b'}' {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+fn resolve_reference(
+    reference: &str,
+    vars: &HashMap<String, String>,
+    safe_mode: bool,
+) -> Result<String, String> {
+    let reference = interpolate(reference, vars, safe_mode)?;
+    let (name, default) = match reference.split_once('?') {
+        Some((name, default)) => (name, Some(default)),
+        None => (reference.as_str(), None),
+    };
+    lookup_var(name, vars, safe_mode)
+        .or_else(|| default.map(str::to_string))
+        .ok_or_else(|| {
+            format!(
+                "Undefined variable '{}' in ${{{}}} (use ${{{}?default}} to supply a fallback)",
+                name, name, name
+            )
+        })
+}
+
+fn escape_in_string(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Net change in `{`/`}` nesting for one line, ignoring quoted strings and comments
+fn brace_delta(line: &str) -> i32 {
+    let mut delta = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '#' => break,
+            '{' => delta += 1,
+            '}' => delta -= 1,
+            _ => {}
+        }
+    }
+    delta
+}
+
 /// Validate that command output is valid Tauq notation.
 /// Returns Ok(output) if valid, Err with helpful message if not.
 fn validate_tauq_output(output: &str, directive: &str, source_hint: &str) -> Result<(), String> {
@@ -492,7 +1659,18 @@ fn validate_tauq_output(output: &str, directive: &str, source_hint: &str) -> Res
 }
 
 /// Validate that a command is in the allowlist
-fn validate_command(program: &str) -> Result<(), String> {
+fn validate_command(program: &str, config: &ProcessConfig) -> Result<(), String> {
+    // The configured allowlist compares the program exactly as written, so
+    // `echo` does not also admit `/tmp/echo`
+    if let Some(allowlist) = &config.command_allowlist
+        && !allowlist.contains(program)
+    {
+        return Err(format!(
+            "Command '{}' is not in the configured command allowlist",
+            program
+        ));
+    }
+
     // Extract just the command name (handle paths like /usr/bin/python3)
     let cmd_name = Path::new(program)
         .file_name()
@@ -530,6 +1708,7 @@ fn run_command(
     cmd_str: &str,
     input: Option<&str>,
     vars: &HashMap<String, String>,
+    config: &ProcessConfig,
 ) -> Result<String, String> {
     let parts = split_args(cmd_str)?;
     if parts.is_empty() {
@@ -540,12 +1719,12 @@ fn run_command(
     let args = &parts[1..];
 
     // Validate command is in allowlist
-    validate_command(program)?;
+    validate_command(program, config)?;
 
     // Filter dangerous environment variables
     let safe_vars = filter_env_vars(vars);
 
-    let mut child = Command::new(program)
+    let child = Command::new(program)
         .args(args)
         .env_clear()
         .envs(&safe_vars)
@@ -558,17 +1737,7 @@ fn run_command(
         .spawn()
         .map_err(|e| format!("Failed to spawn command '{}': {}", program, e))?;
 
-    if let Some(input_str) = input
-        && let Some(mut stdin) = child.stdin.take()
-    {
-        stdin
-            .write_all(input_str.as_bytes())
-            .map_err(|e| format!("Failed to write to stdin: {}", e))?;
-    }
-
-    let output = child
-        .wait_with_output()
-        .map_err(|e| format!("Failed to wait on command: {}", e))?;
+    let output = wait_with_timeout(child, input, config.timeout, cmd_str)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -585,9 +1754,10 @@ fn run_code_block(
     code: &str,
     vars: &HashMap<String, String>,
     input: Option<&str>,
+    config: &ProcessConfig,
 ) -> Result<String, String> {
     // Validate command is in allowlist
-    validate_command(program)?;
+    validate_command(program, config)?;
 
     // Filter dangerous environment variables
     let safe_vars = filter_env_vars(vars);
@@ -601,7 +1771,7 @@ fn run_code_block(
     let path = temp_file.path().to_str().ok_or("Invalid temp file path")?;
 
     // Execute the interpreter with the file
-    let mut child = Command::new(program)
+    let child = Command::new(program)
         .args(args)
         .arg(path)
         .env_clear()
@@ -615,17 +1785,7 @@ fn run_code_block(
         .spawn()
         .map_err(|e| format!("Failed to spawn interpreter '{}': {}", program, e))?;
 
-    if let Some(input_str) = input
-        && let Some(mut stdin) = child.stdin.take()
-    {
-        stdin
-            .write_all(input_str.as_bytes())
-            .map_err(|e| format!("Failed to write to stdin: {}", e))?;
-    }
-
-    let output = child
-        .wait_with_output()
-        .map_err(|e| format!("Failed to wait on interpreter: {}", e))?;
+    let output = wait_with_timeout(child, input, config.timeout, program)?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -635,6 +1795,93 @@ fn run_code_block(
     String::from_utf8(output.stdout).map_err(|e| format!("Code output is not valid UTF-8: {}", e))
 }
 
+/// Wait for a command to finish, killing it once it outlives `timeout`.
+/// `input` is written to stdin and stdout drained on separate threads, so
+/// a child that never reads its input or fills its output pipe cannot
+/// stall it.
+fn wait_with_timeout(
+    mut child: Child,
+    input: Option<&str>,
+    timeout: Option<Duration>,
+    cmd_str: &str,
+) -> Result<Output, String> {
+    let writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => {
+            let input = input.to_string();
+            // Dropping stdin at the end closes the child's input
+            Some(std::thread::spawn(move || {
+                stdin.write_all(input.as_bytes())
+            }))
+        }
+        _ => None,
+    };
+
+    let Some(timeout) = timeout else {
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to wait on command: {}", e))?;
+        join_writer(writer)?;
+        return Ok(output);
+    };
+
+    let reader = child.stdout.take().map(|mut stdout| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| format!("Failed to wait on command: {}", e))?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            // The reader and writer are left behind: grandchildren may still
+            // hold the pipes
+            return Err(format!(
+                "Command timed out after {}s: {}",
+                timeout.as_secs(),
+                cmd_str
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let stdout = match reader {
+        Some(handle) => handle
+            .join()
+            .map_err(|_| "Failed to read command output".to_string())?
+            .map_err(|e| format!("Failed to read command output: {}", e))?,
+        None => Vec::new(),
+    };
+    join_writer(writer)?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr: Vec::new(),
+    })
+}
+
+/// Wait for the thread writing a command's stdin. A command may exit
+/// without reading all of its input, which is not an error.
+fn join_writer(writer: Option<std::thread::JoinHandle<std::io::Result<()>>>) -> Result<(), String> {
+    let Some(handle) = writer else {
+        return Ok(());
+    };
+    match handle.join() {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        Ok(Err(e)) => Err(format!("Failed to write to stdin: {}", e)),
+        Err(_) => Err("Failed to write to stdin".to_string()),
+    }
+}
+
 /// Split command string into arguments, respecting quotes.
 fn split_args(input: &str) -> Result<Vec<String>, String> {
     let mut args = Vec::new();
@@ -1,12 +1,18 @@
+use crate::error::Span;
+use std::borrow::Cow;
+
 /// Token types for Tauq lexer
+///
+/// Identifiers and string literals borrow from the source unless the lexer
+/// had to rewrite them (escape sequences in a quoted string).
 #[derive(Debug, Clone, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     /// Directive (e.g. `!def`, `!use`)
     Directive(String),
     /// Identifier
-    Ident(String),
+    Ident(Cow<'a, str>),
     /// String literal
-    String(String),
+    String(Cow<'a, str>),
     /// Signed integer literal
     Integer(i64),
     /// Unsigned integer literal (for values > i64::MAX)
@@ -19,6 +25,8 @@ pub enum Token {
     Null,
     /// Triple dash separator `---`
     TripleDash,
+    /// Document separator `===` between independent documents
+    DocSeparator,
     /// Colon separator `:`
     Colon,
     /// Semicolon separator `;`
@@ -35,6 +43,31 @@ pub enum Token {
     RBracket,
 }
 
+impl Token<'_> {
+    /// Copy any borrowed text so the token no longer refers to its source
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::Directive(name) => Token::Directive(name),
+            Token::Ident(s) => Token::Ident(Cow::Owned(s.into_owned())),
+            Token::String(s) => Token::String(Cow::Owned(s.into_owned())),
+            Token::Integer(i) => Token::Integer(i),
+            Token::UnsignedInteger(u) => Token::UnsignedInteger(u),
+            Token::Float(f) => Token::Float(f),
+            Token::Bool(b) => Token::Bool(b),
+            Token::Null => Token::Null,
+            Token::TripleDash => Token::TripleDash,
+            Token::DocSeparator => Token::DocSeparator,
+            Token::Colon => Token::Colon,
+            Token::Semi => Token::Semi,
+            Token::Newline => Token::Newline,
+            Token::LBrace => Token::LBrace,
+            Token::RBrace => Token::RBrace,
+            Token::LBracket => Token::LBracket,
+            Token::RBracket => Token::RBracket,
+        }
+    }
+}
+
 /// Source location for error reporting
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Location {
@@ -59,11 +92,46 @@ impl Location {
 
 /// A token with its source location
 #[derive(Debug, Clone, PartialEq)]
-pub struct SpannedToken {
+pub struct SpannedToken<'a> {
     /// The token definition
-    pub token: Token,
+    pub token: Token<'a>,
     /// Start location of the token
     pub start: Location,
     /// End location of the token
     pub end: Location,
 }
+
+impl std::fmt::Display for SpannedToken<'_> {
+    /// `line:column-line:column Token`, for debugging
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}:{} {:?}",
+            self.start.line, self.start.column, self.end.line, self.end.column, self.token
+        )
+    }
+}
+
+impl SpannedToken<'_> {
+    /// Copy any borrowed text so the token no longer refers to its source
+    pub fn into_owned(self) -> SpannedToken<'static> {
+        SpannedToken {
+            token: self.token.into_owned(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    /// Error span covering the whole token
+    pub fn span(&self) -> Span {
+        if self.token == Token::Newline {
+            return Span::point(self.start.line, self.start.column);
+        }
+        Span::range(
+            self.start.line,
+            self.start.column,
+            self.end.line,
+            self.end.column,
+        )
+    }
+}
@@ -66,11 +66,9 @@ impl ParallelBatchEncoder {
             let num_threads = current_num_threads();
             let items_per_thread = self.batch_size / num_threads;
 
-            if items_per_thread > 0 {
-                (total_items / items_per_thread).min(num_threads)
-            } else {
-                1
-            }
+            total_items
+                .checked_div(items_per_thread)
+                .map_or(1, |n| n.min(num_threads))
         }
         #[cfg(not(feature = "performance"))]
         {
@@ -0,0 +1,258 @@
+//! Derive macro for Tauq schemas
+//!
+//! `#[derive(TauqSchema)]` implements [`tauq::TauqSchema`] for a struct with
+//! named fields, so the `!def` line and row decoding always match the struct
+//! definition.
+//!
+//! # Example
+//!
+//! ```
+//! use serde::Deserialize;
+//! use tauq::serde_json::json;
+//! use tauq::TauqSchema;
+//! use tauq_derive::TauqSchema;
+//!
+//! #[derive(TauqSchema, Deserialize, Debug, PartialEq)]
+//! struct User {
+//!     id: u32,
+//!     #[serde(rename = "full_name")]
+//!     name: String,
+//!     active: bool,
+//! }
+//!
+//! assert_eq!(User::schema_name(), "User");
+//! assert_eq!(User::fields(), ["id", "full_name", "active"]);
+//! assert_eq!(User::tauq_header(), "!def User id full_name active\n");
+//!
+//! let user = User::from_tauq_row(&[json!(1), json!("Alice"), json!(true)]).unwrap();
+//! assert_eq!(user, User { id: 1, name: "Alice".into(), active: true });
+//!
+//! let source = User::tauq_header() + "1 Alice true\n2 Bob false\n";
+//! let users: Vec<User> = tauq::from_str(&source).unwrap();
+//! assert_eq!(users[1], User { id: 2, name: "Bob".into(), active: false });
+//! ```
+//!
+//! The struct must also implement `Deserialize`, which `from_tauq_row` uses
+//! to convert the row. Fields marked `#[serde(skip)]` or
+//! `#[serde(skip_deserializing)]` are left out of the schema, and a
+//! container-level `#[serde(rename_all = "...")]` renames the others as
+//! serde does.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+/// Derive macro for `tauq::TauqSchema`
+///
+/// The schema is named after the struct and lists its fields in
+/// declaration order, using `#[serde(rename = "...")]` names where present
+/// and the container's `rename_all` rule otherwise.
+#[proc_macro_derive(TauqSchema)]
+pub fn derive_tauq_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return syn::Error::new_spanned(
+                    name,
+                    "TauqSchema can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(name, "TauqSchema can only be derived for structs")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let container = match SerdeAttrs::parse(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let mut field_names = Vec::new();
+    for field in fields {
+        let attrs = match SerdeAttrs::parse(&field.attrs) {
+            Ok(attrs) => attrs,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        if attrs.skip {
+            continue;
+        }
+        let ident = unraw(field.ident.as_ref().unwrap());
+        field_names.push(match (attrs.rename, container.rename_all) {
+            (Some(rename), _) => rename,
+            (None, Some(rule)) => rule.apply(&ident),
+            (None, None) => ident,
+        });
+    }
+
+    let schema_name = container.rename.unwrap_or_else(|| unraw(name));
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(Self: ::serde::de::DeserializeOwned));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics ::tauq::TauqSchema for #name #ty_generics #where_clause {
+            fn schema_name() -> &'static str {
+                #schema_name
+            }
+
+            fn fields() -> &'static [&'static str] {
+                &[#(#field_names),*]
+            }
+
+            fn from_tauq_row(
+                values: &[::tauq::serde_json::Value],
+            ) -> ::core::result::Result<Self, ::tauq::TauqError> {
+                ::tauq::from_schema_row(#schema_name, Self::fields(), values)
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// The `#[serde(...)]` options that change a schema
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    skip: bool,
+}
+
+/// A `#[serde(rename_all = "...")]` case convention
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(value: &LitStr) -> syn::Result<Self> {
+        Ok(match value.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    format!("unknown rename rule `rename_all = {:?}`", other),
+                ));
+            }
+        })
+    }
+
+    /// Rename a snake_case field as serde does
+    fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = SerdeAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if meta.input.peek(syn::token::Paren) {
+                        // rename(serialize = "...", deserialize = "...")
+                        meta.parse_nested_meta(|inner| {
+                            let value: LitStr = inner.value()?.parse()?;
+                            if inner.path.is_ident("deserialize") {
+                                parsed.rename = Some(value.value());
+                            }
+                            Ok(())
+                        })?;
+                    } else {
+                        let value: LitStr = meta.value()?.parse()?;
+                        parsed.rename = Some(value.value());
+                    }
+                } else if meta.path.is_ident("rename_all") {
+                    if meta.input.peek(syn::token::Paren) {
+                        // rename_all(serialize = "...", deserialize = "...")
+                        meta.parse_nested_meta(|inner| {
+                            let value: LitStr = inner.value()?.parse()?;
+                            let rule = RenameRule::parse(&value)?;
+                            if inner.path.is_ident("deserialize") {
+                                parsed.rename_all = Some(rule);
+                            }
+                            Ok(())
+                        })?;
+                    } else {
+                        let value: LitStr = meta.value()?.parse()?;
+                        parsed.rename_all = Some(RenameRule::parse(&value)?);
+                    }
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                    parsed.skip = true;
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|inner| {
+                        if inner.input.peek(syn::Token![=]) {
+                            inner.value()?.parse::<syn::Expr>()?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Identifier text without a raw `r#` prefix, as serde names it
+fn unraw(ident: &syn::Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_string).unwrap_or(name)
+}
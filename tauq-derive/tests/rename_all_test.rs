@@ -0,0 +1,55 @@
+use serde::Deserialize;
+use tauq::TauqSchema;
+use tauq::serde_json::json;
+use tauq_derive::TauqSchema;
+
+#[derive(TauqSchema, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Account {
+    account_id: u32,
+    #[serde(rename = "label")]
+    display_name: String,
+    is_active: bool,
+}
+
+#[derive(TauqSchema, Deserialize)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "SCREAMING-KEBAB-CASE"))]
+struct Env {
+    log_level: String,
+}
+
+#[derive(TauqSchema, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Point {
+    x_pos: i32,
+    r#type: String,
+}
+
+#[test]
+fn test_rename_all_applies_to_fields() {
+    assert_eq!(Account::fields(), ["accountId", "label", "isActive"]);
+    assert_eq!(Env::fields(), ["LOG-LEVEL"]);
+    assert_eq!(Point::fields(), ["XPos", "Type"]);
+
+    let env = Env::from_tauq_row(&[json!("debug")]).unwrap();
+    assert_eq!(env.log_level, "debug");
+    let point = Point::from_tauq_row(&[json!(3), json!("corner")]).unwrap();
+    assert_eq!((point.x_pos, point.r#type.as_str()), (3, "corner"));
+}
+
+#[test]
+fn test_rename_all_rows_round_trip() {
+    let account = Account::from_tauq_row(&[json!(7), json!("Ops"), json!(true)]).unwrap();
+    assert_eq!(
+        account,
+        Account {
+            account_id: 7,
+            display_name: "Ops".into(),
+            is_active: true
+        }
+    );
+
+    let source = Account::tauq_header() + "1 Alice false\n2 Bob true\n";
+    let accounts: Vec<Account> = tauq::from_str(&source).unwrap();
+    assert_eq!(accounts[1].display_name, "Bob");
+}
@@ -0,0 +1,68 @@
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tauq::Formatter;
+
+#[test]
+fn test_aligned_four_column_table() {
+    let value = json!([
+        {"id": 1, "name": "Al", "city": "New York", "score": 7},
+        {"id": 22, "name": null, "city": "Oslo", "score": 45},
+        {"id": 333, "name": "Beatrice", "city": "Rome", "score": 1200}
+    ]);
+    let aligned = Formatter::new().with_alignment(true).format(&value);
+    // Numbers are right-aligned, quotes do not count towards the width
+    assert_eq!(
+        aligned,
+        "!def Record id name? city score\n  \
+           1 Al       \"New York\"    7\n \
+          22 null     Oslo       45\n\
+         333 Beatrice Rome     1200"
+    );
+    assert_eq!(tauq::compile_tauq(&aligned).unwrap(), value);
+}
+
+#[test]
+fn test_alignment_leaves_last_string_column_unpadded() {
+    let value = json!({"users": [
+        {"id": 1, "name": "Alice"},
+        {"id": 10, "name": "Bo"},
+        {"id": 100, "name": "Christopher"}
+    ]});
+    let aligned = Formatter::new().with_alignment(true).format(&value);
+    assert!(
+        aligned.contains("\n    1 Alice\n   10 Bo\n  100 Christopher\n"),
+        "{}",
+        aligned
+    );
+    assert!(
+        !aligned.lines().any(|line| line.ends_with(' ')),
+        "{}",
+        aligned
+    );
+    assert_eq!(tauq::compile_tauq(&aligned).unwrap(), value);
+
+    // Minified output ignores alignment
+    let minified = Formatter::new().with_alignment(true).minified();
+    assert_eq!(
+        minified.format(&value),
+        Formatter::new().minified().format(&value)
+    );
+}
+
+#[test]
+fn test_prettify_align_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.tqn");
+    fs::write(&input, "!def Item sku qty\nA1 5\nB200 1500\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["prettify", input.to_str().unwrap(), "--align"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "!def Row sku qty\nA1      5\nB200 1500\n"
+    );
+}
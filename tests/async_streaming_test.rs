@@ -0,0 +1,116 @@
+#![cfg(feature = "tokio")]
+
+use futures::StreamExt;
+use serde_json::json;
+use tauq::tauq::AsyncStreamingParser;
+use tokio::io::BufReader;
+use tokio_test::io::Builder;
+
+/// Build a mock reader that delivers `input` in `chunk`-byte reads.
+fn chunked(input: &str, chunk: usize) -> BufReader<tokio_test::io::Mock> {
+    let mut builder = Builder::new();
+    for piece in input.as_bytes().chunks(chunk) {
+        builder.read(piece);
+    }
+    BufReader::new(builder.build())
+}
+
+#[test]
+fn test_large_schema_table_in_chunks() {
+    let mut input = String::from("!def Row id name active\n");
+    for i in 0..2000 {
+        input.push_str(&format!("{} \"row {}\" {}\n", i, i, i % 2 == 0));
+    }
+
+    tokio_test::block_on(async {
+        let mut parser = AsyncStreamingParser::new(chunked(&input, 7));
+        let mut count = 0;
+        while let Some(record) = parser.next().await {
+            let record = record.unwrap();
+            assert_eq!(record["id"], count);
+            assert_eq!(record["name"], format!("row {}", count));
+            count += 1;
+        }
+        assert_eq!(count, 2000);
+    });
+}
+
+#[test]
+fn test_matches_sync_streaming_parser() {
+    let input = "!def User id addr tags\n1 { city Paris } [a\nb]\n2 { city Rome } []\n---\nname \"multi\nline\"\nnote <<<EOT\nhello\nworld\nEOT\n";
+    let expected: Vec<_> = tauq::StreamingParser::new(input)
+        .map(|r| r.unwrap())
+        .collect();
+
+    tokio_test::block_on(async {
+        let records: Vec<_> = tauq::parse_async(chunked(input, 3))
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        assert_eq!(records, expected);
+        assert_eq!(records[0]["tags"], json!(["a", "b"]));
+        assert_eq!(
+            records[2],
+            json!({"name": "multi\nline", "note": "hello\nworld"})
+        );
+    });
+}
+
+#[test]
+fn test_schema_state_survives_between_reads() {
+    tokio_test::block_on(async {
+        let reader = BufReader::new(
+            Builder::new()
+                .read(b"!def User id name\n")
+                .read(b"1 Alice\n")
+                .read(b"2 Bo")
+                .read(b"b")
+                .build(),
+        );
+        let records: Vec<_> = AsyncStreamingParser::new(reader)
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            records,
+            vec![
+                json!({"id": 1, "name": "Alice"}),
+                json!({"id": 2, "name": "Bob"})
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_errors_report_stream_line_numbers() {
+    tokio_test::block_on(async {
+        let input = "!def User id name\n1 Alice\n2 Bob\n!use Missing\n";
+        let records: Vec<_> = AsyncStreamingParser::new(chunked(input, 5)).collect().await;
+        let err = records
+            .iter()
+            .find_map(|r| r.as_ref().err())
+            .expect("expected an error");
+        assert!(err.message.contains("Missing"), "{}", err);
+        assert_eq!(err.span.line, 4);
+
+        let records: Vec<_> = AsyncStreamingParser::new(chunked("a 1\nb \"open", 4))
+            .collect()
+            .await;
+        let err = records.last().unwrap().as_ref().unwrap_err();
+        assert!(err.message.contains("unterminated"), "{}", err);
+        assert_eq!(err.span.line, 2);
+    });
+}
+
+#[test]
+fn test_zero_field_rows_across_reads() {
+    let input = "!def Tag\n!def Event id\n1\n!use Tag\n\n\n";
+    tokio_test::block_on(async {
+        let parser = AsyncStreamingParser::new(chunked(input, 3));
+        let records: Vec<_> = parser.map(|record| record.unwrap()).collect().await;
+        assert_eq!(
+            records,
+            vec![json!({"id": 1}), json!({}), json!({}), json!({})]
+        );
+    });
+}
@@ -0,0 +1,109 @@
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+const SOURCE: &str = "!import \"subdir/schema.tqn\"\n\
+                      users [\n!use User\n1 Alice\n2 Bob\n]\n\
+                      roles [\n!use Role\nadmin 3\n]\n";
+
+#[test]
+fn test_compile_with_base_dir_resolves_imports() {
+    let json = tauq::compile_tauq_with_base_dir(SOURCE, &fixtures()).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "roles": [{"name": "admin", "level": 3}]
+        })
+    );
+
+    // Without a base directory the import is not found from the crate root
+    assert!(tauq::compile_tauq(SOURCE).is_err());
+}
+
+#[test]
+fn test_process_tauqq_with_base_dir() {
+    let source = "!import \"subdir/schema.tqn\"\n!use User\n7 Carol\n";
+    let processed = tauq::process_tauqq_with_base_dir(source, &fixtures(), false).unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&processed).unwrap(),
+        json!({"id": 7, "name": "Carol"})
+    );
+}
+
+#[test]
+fn test_formatter_import_base_dir() {
+    let formatter = tauq::Formatter::new().with_import_base_dir(fixtures());
+    let formatted = formatter
+        .format_source(&format!("# team\n{}", SOURCE))
+        .unwrap();
+    assert!(formatted.starts_with("# team\n"), "{}", formatted);
+    assert_eq!(
+        tauq::compile_tauq(&formatted).unwrap(),
+        tauq::compile_tauq_with_base_dir(SOURCE, &fixtures()).unwrap()
+    );
+
+    assert!(tauq::Formatter::new().format_source(SOURCE).is_err());
+}
+
+#[test]
+fn test_prettify_resolves_imports_next_to_input() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("subdir")).unwrap();
+    std::fs::copy(
+        fixtures().join("subdir/schema.tqn"),
+        dir.path().join("subdir/schema.tqn"),
+    )
+    .unwrap();
+    let input = dir.path().join("team.tqn");
+    std::fs::write(&input, SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("prettify")
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let pretty = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&pretty).unwrap()["roles"],
+        json!([{"name": "admin", "level": 3}])
+    );
+}
+
+#[test]
+fn test_prettify_from_inside_config_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("schemas")).unwrap();
+    std::fs::create_dir(dir.path().join("config")).unwrap();
+    std::fs::write(dir.path().join("schemas/user.tqn"), "!def User id name\n").unwrap();
+    std::fs::write(
+        dir.path().join("config/app.tqn"),
+        "!import \"../schemas/user.tqn\"\n!use User\n1 Alice\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .current_dir(dir.path().join("config"))
+        .args(["prettify", "app.tqn"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let pretty = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&pretty).unwrap(),
+        json!({"id": 1, "name": "Alice"})
+    );
+}
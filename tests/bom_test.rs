@@ -0,0 +1,63 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+#[test]
+fn test_bom_is_skipped() {
+    let json = tauq::compile_tauq("\u{FEFF}host localhost").unwrap();
+    assert_eq!(json, json!({"host": "localhost"}));
+
+    let json = tauq::compile_tauq("\u{FEFF}!def User id name\n1 Alice\n").unwrap();
+    assert_eq!(json, json!({"id": 1, "name": "Alice"}));
+
+    let mut parser = tauq::Parser::new("\u{FEFF}# note\nport 80\n");
+    assert_eq!(parser.parse().unwrap(), json!({"port": 80}));
+    assert_eq!(parser.comments()[&1], " note");
+}
+
+#[test]
+fn test_bom_only_stripped_at_start() {
+    let json = tauq::compile_tauq("name \"\u{FEFF}x\"").unwrap();
+    assert_eq!(json["name"], "\u{FEFF}x");
+}
+
+#[test]
+fn test_bom_in_tauqq_and_included_files() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("shared.tqn"), "\u{FEFF}region eu\n").unwrap();
+    fs::write(dir.path().join("note.txt"), "\u{FEFF}hello").unwrap();
+    fs::write(dir.path().join("data.json"), "\u{FEFF}{\"ok\": true}").unwrap();
+
+    let config = ProcessConfig {
+        base_dir: Some(dir.path().to_path_buf()),
+        safe_mode: false,
+        ..Default::default()
+    };
+    let input = "\u{FEFF}!set X 1\n!import \"shared.tqn\"\n";
+    let output = tauqq::process_with_config(input, &mut HashMap::new(), &config).unwrap();
+    assert_eq!(output, "region eu\n\n");
+
+    let output =
+        tauqq::process_with_config("!read \"note.txt\"\n", &mut HashMap::new(), &config).unwrap();
+    assert_eq!(output, "\"hello\"\n");
+
+    let output =
+        tauqq::process_with_config("!json \"data.json\"\n", &mut HashMap::new(), &config).unwrap();
+    assert_eq!(tauq::compile_tauq(&output).unwrap(), json!({"ok": true}));
+
+    // The parser's own !import also skips the mark
+    let path = dir.path().join("main.tqn");
+    fs::write(
+        dir.path().join("schemas.tqn"),
+        "\u{FEFF}!def User id name\n",
+    )
+    .unwrap();
+    fs::write(&path, "!import \"schemas.tqn\"\n!use User\n1 Alice\n").unwrap();
+    let source = fs::read_to_string(&path).unwrap();
+    let mut parser = tauq::Parser::new_with_context(
+        &source,
+        tauq::tauq::parser::Context::with_base_dir(dir.path().to_path_buf()),
+    );
+    assert_eq!(parser.parse().unwrap(), json!({"id": 1, "name": "Alice"}));
+}
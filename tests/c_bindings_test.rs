@@ -0,0 +1,104 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use tauq::c_bindings::{
+    json_to_tauq_c, tauq_format_json_with_options, tauq_format_options_default,
+    tauq_format_options_t, tauq_free_string, tauq_get_last_error,
+};
+
+const USERS: &str =
+    r#"[{"id":1,"name":"Alice","role":"admin"},{"id":2,"name":"Bob","role":"user"}]"#;
+
+fn take(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe {
+        let s = CStr::from_ptr(ptr).to_str().unwrap().to_string();
+        tauq_free_string(ptr);
+        Some(s)
+    }
+}
+
+fn format(json: &str, opts: Option<&tauq_format_options_t>) -> Option<String> {
+    let input = CString::new(json).unwrap();
+    let opts = opts.map_or(std::ptr::null(), |o| o as *const _);
+    take(unsafe { tauq_format_json_with_options(input.as_ptr(), opts) })
+}
+
+fn last_error() -> String {
+    let mut buf = [0u8; 256];
+    unsafe { tauq_get_last_error(buf.as_mut_ptr().cast(), buf.len()) };
+    CStr::from_bytes_until_nul(&buf)
+        .unwrap()
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_null_options_match_json_to_tauq() {
+    let input = CString::new(USERS).unwrap();
+    let plain = take(unsafe { json_to_tauq_c(input.as_ptr()) }).unwrap();
+    assert_eq!(format(USERS, None).unwrap(), plain);
+
+    let defaults = tauq_format_options_default();
+    assert_eq!(defaults.delimiter, b's');
+    assert_eq!(format(USERS, Some(&defaults)).unwrap(), plain);
+}
+
+#[test]
+fn test_comma_delimiter() {
+    let opts = tauq_format_options_t {
+        delimiter: b'c',
+        ..tauq_format_options_default()
+    };
+    let out = format(USERS, Some(&opts)).unwrap();
+    assert!(out.contains("1,Alice,admin"), "{}", out);
+    assert!(out.contains("2,Bob,user"), "{}", out);
+    assert_eq!(
+        tauq::compile_tauq(&out).unwrap(),
+        serde_json::from_str::<serde_json::Value>(USERS).unwrap()
+    );
+}
+
+#[test]
+fn test_minify() {
+    let opts = tauq_format_options_t {
+        minify: true,
+        ..tauq_format_options_default()
+    };
+    let json = r#"{"app":{"name":"demo","port":8080},"tags":["a","b"]}"#;
+    let out = format(json, Some(&opts)).unwrap();
+    assert!(!out.contains('\n'), "{}", out);
+    assert_eq!(
+        tauq::compile_tauq(&out).unwrap(),
+        serde_json::from_str::<serde_json::Value>(json).unwrap()
+    );
+}
+
+#[test]
+fn test_indent_and_stable() {
+    let opts = tauq_format_options_t {
+        indent_size: 4,
+        stable: true,
+        ..tauq_format_options_default()
+    };
+    let out = format(r#"{"b":{"y":1,"x":2},"a":true}"#, Some(&opts)).unwrap();
+    assert_eq!(out, "a true\nb {\n    x 2\n    y 1\n}");
+}
+
+#[test]
+fn test_invalid_input() {
+    let opts = tauq_format_options_t {
+        delimiter: b'x',
+        ..tauq_format_options_default()
+    };
+    assert!(format(USERS, Some(&opts)).is_none());
+    assert_eq!(last_error(), "Invalid delimiter 'x': expected 's' or 'c'");
+
+    assert!(format("{not json", None).is_none());
+    assert!(last_error().starts_with("JSON parse error"));
+
+    let null = unsafe { tauq_format_json_with_options(std::ptr::null(), std::ptr::null()) };
+    assert!(null.is_null());
+    assert_eq!(last_error(), "Input pointer is null");
+}
@@ -0,0 +1,88 @@
+use std::ffi::{CStr, CString};
+use tauq::c_bindings::{
+    tauq_free_string, tauq_get_last_error, tauq_parse_streaming_free, tauq_parse_streaming_new,
+    tauq_parse_streaming_next, tauq_validate,
+};
+
+fn last_error() -> String {
+    unsafe {
+        let len = tauq_get_last_error(std::ptr::null_mut(), 0);
+        let mut buf = vec![0u8; len + 1];
+        tauq_get_last_error(buf.as_mut_ptr().cast(), buf.len());
+        CStr::from_bytes_until_nul(&buf)
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Drain a stream, returning the records and the error left behind
+fn collect(source: &str) -> (Vec<serde_json::Value>, String) {
+    let input = CString::new(source).unwrap();
+    let mut records = Vec::new();
+    unsafe {
+        let handle = tauq_parse_streaming_new(input.as_ptr());
+        assert!(!handle.is_null());
+        drop(input);
+        loop {
+            let record = tauq_parse_streaming_next(handle);
+            if record.is_null() {
+                break;
+            }
+            let json = CStr::from_ptr(record).to_str().unwrap();
+            records.push(serde_json::from_str(json).unwrap());
+            tauq_free_string(record);
+        }
+        let error = last_error();
+        assert!(tauq_parse_streaming_next(handle).is_null());
+        tauq_parse_streaming_free(handle);
+        (records, error)
+    }
+}
+
+#[test]
+fn test_validate() {
+    let valid = CString::new("!def User id name\n1 Alice\n").unwrap();
+    let invalid = CString::new("{ unclosed").unwrap();
+    unsafe {
+        assert!(tauq_validate(valid.as_ptr()));
+        assert!(!tauq_validate(invalid.as_ptr()));
+        assert!(
+            last_error().starts_with("Parse error at line 1"),
+            "{}",
+            last_error()
+        );
+        assert!(!tauq_validate(std::ptr::null()));
+        assert_eq!(last_error(), "Input pointer is null");
+    }
+}
+
+#[test]
+fn test_streaming_yields_records() {
+    let mut source = String::from("!def User id name\n");
+    for i in 0..100 {
+        source.push_str(&format!("{} user{}\n", i, i));
+    }
+    let (records, error) = collect(&source);
+    assert_eq!(records.len(), 100);
+    assert_eq!(records[42], serde_json::json!({"id": 42, "name": "user42"}));
+    assert_eq!(error, "");
+}
+
+#[test]
+fn test_streaming_stops_at_error() {
+    let (records, error) = collect("!def User id name\n1 Alice\n2 [3\n");
+    assert_eq!(records, [serde_json::json!({"id": 1, "name": "Alice"})]);
+    assert!(error.contains("Unterminated array"), "{}", error);
+}
+
+#[test]
+fn test_streaming_null_pointers() {
+    unsafe {
+        assert!(tauq_parse_streaming_new(std::ptr::null()).is_null());
+        assert_eq!(last_error(), "Input pointer is null");
+        assert!(tauq_parse_streaming_next(std::ptr::null_mut()).is_null());
+        assert_eq!(last_error(), "Stream handle is null");
+        tauq_parse_streaming_free(std::ptr::null_mut());
+    }
+}
@@ -0,0 +1,72 @@
+use serde_json::json;
+use tauq::tauq::{CollectingHandler, json_to_tauq_optimized, json_to_tauq_ultra, parse_sax};
+
+#[test]
+fn test_comma_separated_def_fields() {
+    let expected = json!([
+        {"id": 1, "name": "Alice", "role": "admin"},
+        {"id": 2, "name": "Bob", "role": "dev"}
+    ]);
+    for source in [
+        "!def Record id,name,role\n1,Alice,admin\n2,Bob,dev\n",
+        "!def Record id, name, role\n1, Alice, admin\n2 Bob dev\n",
+        "!schemas\nRecord id,name,role\n---\n!use Record\n1,Alice,admin\n2,Bob,dev\n",
+    ] {
+        assert_eq!(tauq::compile_tauq(source).unwrap(), expected, "{}", source);
+    }
+
+    // Annotations, optional fields and defaults keep working between commas
+    let json =
+        tauq::compile_tauq("!def Record id:int,email?,role=user\n1,\"a@x.io\"\n2\n").unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {"id": 1, "email": "a@x.io", "role": "user"},
+            {"id": 2, "email": null, "role": "user"}
+        ])
+    );
+}
+
+#[test]
+fn test_quoted_commas_stay_in_values() {
+    let source = "!def Record id,name,note\n1,\"Smith, Jane\",\"a,b\"\n";
+    let expected = json!({"id": 1, "name": "Smith, Jane", "note": "a,b"});
+    assert_eq!(tauq::compile_tauq(source).unwrap(), expected);
+
+    let rows: Vec<_> = tauq::StreamingParser::new(source)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rows, vec![expected.clone()]);
+
+    let mut handler = CollectingHandler::new();
+    parse_sax(source, &mut handler).unwrap();
+    assert_eq!(handler.into_values(), vec![expected]);
+}
+
+#[test]
+fn test_comma_delimited_output_round_trips() {
+    let value = json!({
+        "records": [
+            {"id": 1, "name": "Alice", "email": "a@x.io"},
+            {"id": 2, "name": "Bob, Jr", "email": null}
+        ],
+        "pairs": [{"a": 1, "b": "x y"}, {"a": 2, "b": ""}]
+    });
+    let optimized = json_to_tauq_optimized(&value);
+    assert!(
+        optimized.contains("!def Record id,name,email?"),
+        "{}",
+        optimized
+    );
+    assert!(optimized.contains("2,\"Bob, Jr\""), "{}", optimized);
+    assert_eq!(tauq::compile_tauq(&optimized).unwrap(), value);
+
+    let ultra = json_to_tauq_ultra(&value);
+    assert_eq!(tauq::compile_tauq(&ultra).unwrap(), value);
+
+    let table = json!([{"id": 1, "tags": ["a", "b"]}, {"id": 2, "tags": []}]);
+    assert_eq!(
+        tauq::compile_tauq(&json_to_tauq_optimized(&table)).unwrap(),
+        table
+    );
+}
@@ -0,0 +1,59 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use tauq::{Lexer, Parser};
+
+#[test]
+fn test_lexer_records_full_line_comments() {
+    let mut lexer = Lexer::new("# header\nhost localhost # inline\n  #indented\nport 80\n");
+    while lexer.next_token().is_some() {}
+    let comments = lexer.comments();
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[&1], " header");
+    assert_eq!(comments[&3], "indented");
+}
+
+#[test]
+fn test_comment_round_trips_with_its_line() {
+    let source = "# Production server\nhost localhost\nport 8080\n# Credentials\nuser admin\n";
+    let mut parser = Parser::new(source);
+    let value = parser.parse().unwrap();
+    let annotated = tauq::json_to_tauq_annotated(&value, parser.comments());
+    assert_eq!(annotated, source.trim_end());
+    assert_eq!(tauq::compile_tauq(&annotated).unwrap(), value);
+}
+
+#[test]
+fn test_comments_skip_heredocs_and_trail() {
+    let value = json!({"note": "one\ntwo\nthree", "id": 1});
+    let comments = HashMap::from([(3, " inside".to_string()), (9, " end".to_string())]);
+    let annotated = tauq::json_to_tauq_annotated(&value, &comments);
+    assert_eq!(
+        annotated,
+        "note <<<EOT\none\ntwo\nthree\nEOT\n# inside\nid 1\n# end"
+    );
+    assert_eq!(tauq::compile_tauq(&annotated).unwrap(), value);
+}
+
+#[test]
+fn test_prettify_keeps_comments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.tqn");
+    fs::write(
+        &path,
+        "# Production server\nhost localhost\n# Port\nport 8080\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("prettify")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "# Production server\nhost localhost\n# Port\nport 8080\n"
+    );
+}
@@ -0,0 +1,180 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauq::Parser;
+use tauq::tauq::parser::Context;
+
+/// Lay out `app.tqn` next to a `schemas` directory it imports from
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("schemas")).unwrap();
+    fs::write(dir.path().join("schemas/user.tqn"), "!def User id name\n").unwrap();
+    fs::write(
+        dir.path().join("app.tqn"),
+        "!import \"schemas/user.tqn\"\n!use User\n1 Alice\n2 Bob\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_import_resolves_from_file_directory() {
+    let dir = project();
+    // The test runs from the crate root, which does not contain `schemas/`
+    assert!(!Path::new("schemas/user.tqn").exists());
+
+    let json = tauq::compile_tauq_from_file(&dir.path().join("app.tqn")).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}])
+    );
+}
+
+#[test]
+fn test_from_file_resolves_imports() {
+    #[derive(Deserialize)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    let dir = project();
+    let users: Vec<User> = tauq::from_file(dir.path().join("app.tqn")).unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[1].id, 2);
+    assert_eq!(users[1].name, "Bob");
+}
+
+/// Add `config/app.tqn`, which imports `../schemas/user.tqn`
+fn sibling_import(dir: &Path) -> std::path::PathBuf {
+    fs::create_dir(dir.join("config")).unwrap();
+    let path = dir.join("config/app.tqn");
+    fs::write(
+        &path,
+        "!import \"../schemas/user.tqn\"\n!use User\n1 Alice\n",
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn test_imports_may_not_leave_file_directory() {
+    let dir = project();
+    let path = sibling_import(dir.path());
+    let err = tauq::compile_tauq_from_file(&path).unwrap_err();
+    assert!(
+        err.to_string().contains("path traversal blocked"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_import_root_opts_in_to_sibling_directory() {
+    let dir = project();
+    let path = sibling_import(dir.path());
+    let source = fs::read_to_string(&path).unwrap();
+    let context = Context::for_file(&path).with_import_root(dir.path().to_path_buf());
+    let json = Parser::new_with_context(&source, context).parse().unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "name": "Alice"}));
+}
+
+#[test]
+fn test_cli_commands_resolve_imports_alike() {
+    let dir = project();
+    let path = sibling_import(dir.path());
+    let elsewhere = tempfile::tempdir().unwrap();
+    for (command, args) in [
+        ("build", &[][..]),
+        ("build", &["--strict"][..]),
+        ("build", &["--multi"][..]),
+        ("validate", &[][..]),
+        ("lint", &[][..]),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .current_dir(elsewhere.path())
+            .arg(command)
+            .arg(&path)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{} {:?}: {}",
+            command,
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_missing_file_is_io_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = tauq::compile_tauq_from_file(&dir.path().join("missing.tqn")).unwrap_err();
+    assert!(matches!(err, tauq::TauqError::Io(_)), "{:?}", err);
+}
+
+#[test]
+fn test_cli_build_from_other_directory() {
+    let dir = project();
+    let elsewhere = tempfile::tempdir().unwrap();
+    for args in [&[][..], &["--strict"][..]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .current_dir(elsewhere.path())
+            .arg("build")
+            .arg(dir.path().join("app.tqn"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json[0]["name"], "Alice");
+    }
+}
+
+#[test]
+fn test_default_context_confines_imports_to_base_dir() {
+    let dir = project();
+    sibling_import(dir.path());
+    let mut context = Context::new();
+    context.base_dir = Some(dir.path().join("config"));
+    let source = "!import \"../schemas/user.tqn\"\n!use User\n1 Alice\n";
+    let err = Parser::new_with_context(source, context)
+        .parse()
+        .unwrap_err();
+    assert!(err.message.contains("path traversal blocked"), "{}", err);
+}
+
+#[test]
+fn test_nested_imports_are_confined_to_their_directory() {
+    let dir = project();
+    fs::write(
+        dir.path().join("schemas/all.tqn"),
+        "!import \"../app.tqn\"\n",
+    )
+    .unwrap();
+    let source = format!(
+        "!import \"{}\"\n",
+        dir.path().join("schemas/all.tqn").display()
+    );
+    let err = tauq::compile_tauq(&source).unwrap_err();
+    assert!(
+        err.to_string().contains("path traversal blocked"),
+        "{}",
+        err
+    );
+
+    // Absolute paths in a source without a base directory are allowed
+    let source = format!(
+        "!import \"{}\"\n!use User\n1 Alice\n",
+        dir.path().join("schemas/user.tqn").display()
+    );
+    assert_eq!(tauq::compile_tauq(&source).unwrap()["name"], "Alice");
+}
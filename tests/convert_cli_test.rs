@@ -0,0 +1,95 @@
+use serde_json::json;
+use std::fs;
+use std::process::{Command, Output};
+
+/// Write `source` to a temp file named `name` and run `tauq convert` on it
+fn convert(name: &str, source: &str, extra: &[&str]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    fs::write(&path, source).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("convert")
+        .arg(&path)
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+fn converted_json(output: &Output) -> serde_json::Value {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    tauq::compile_tauq(&String::from_utf8_lossy(&output.stdout)).unwrap()
+}
+
+#[test]
+fn test_convert_json() {
+    let output = convert("data.json", r#"[{"id": 1, "name": "Alice"}]"#, &[]);
+    assert_eq!(converted_json(&output), json!([{"id": 1, "name": "Alice"}]));
+}
+
+#[test]
+fn test_convert_csv_with_quoted_commas() {
+    let csv = "id,name,address\n1,Alice,\"12 Main St, Springfield\"\n2,Bob,\"Flat 3, \"\"The Towers\"\"\"\n";
+    let output = convert("people.csv", csv, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(stdout.starts_with("!def "), "{}", stdout);
+    assert_eq!(
+        converted_json(&output),
+        json!([
+            {"id": 1, "name": "Alice", "address": "12 Main St, Springfield"},
+            {"id": 2, "name": "Bob", "address": "Flat 3, \"The Towers\""}
+        ])
+    );
+
+    let output = convert(
+        "people.txt",
+        csv,
+        &["--from", "csv", "--delimiter", "comma"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(stdout.contains("1,Alice,"), "{}", stdout);
+    assert_eq!(converted_json(&output).as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_unknown_extension_needs_from() {
+    let output = convert("data.txt", "{}", &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown input format: txt"), "{}", stderr);
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_convert_yaml_with_nested_objects() {
+    let yaml = "service: api\ndatabase:\n  host: localhost\n  pool:\n    min: 1\n    max: 8\ntags:\n  - web\n  - internal\n";
+    let output = convert("config.yaml", yaml, &[]);
+    assert_eq!(
+        converted_json(&output),
+        json!({
+            "service": "api",
+            "database": {"host": "localhost", "pool": {"min": 1, "max": 8}},
+            "tags": ["web", "internal"]
+        })
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_convert_toml_with_array_of_tables() {
+    let toml = "title = \"inventory\"\nupdated = 2024-05-01\n\n[[items]]\nsku = \"A1\"\nqty = 3\n\n[[items]]\nsku = \"B2\"\nqty = 5\n";
+    let output = convert("inventory.toml", toml, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(stdout.contains("!def"), "{}", stdout);
+    assert_eq!(
+        converted_json(&output),
+        json!({
+            "title": "inventory",
+            "updated": "2024-05-01",
+            "items": [{"sku": "A1", "qty": 3}, {"sku": "B2", "qty": 5}]
+        })
+    );
+}
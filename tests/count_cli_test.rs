@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn count(file: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("count")
+        .arg(file)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn write(dir: &Path, name: &str, source: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, source).unwrap();
+    path
+}
+
+#[test]
+fn test_count_thousand_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut source = String::from("!def User id name tags\n");
+    for i in 0..1000 {
+        source.push_str(&format!("{} \"user {}\" [a b]\n", i, i));
+    }
+    let path = write(dir.path(), "users.tqn", &source);
+
+    // Timing is measured by the `count_cli_1000_rows` benchmark
+    assert_eq!(count(&path, &[]), "1000\n");
+}
+
+#[test]
+fn test_count_interleaved_schemas() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "!def User id name\n!def Order id total\n!use User\n1 Alice\n2 Bob\n\
+                  !use Order\n10 9.5\n!use User\n3 Carol\n!use Order\n11 20\n12 7\n";
+    let path = write(dir.path(), "mixed.tqn", source);
+
+    assert_eq!(count(&path, &[]), "6\n");
+    assert_eq!(
+        count(&path, &["--schemas"]),
+        "User   3\nOrder  3\ntotal  6\n"
+    );
+}
+
+#[test]
+fn test_count_key_value_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "# settings\nhost localhost\nports [80\n  443]\ndb {\n  host \"db # 1\"\n}\n";
+    let path = write(dir.path(), "config.tqn", source);
+    assert_eq!(count(&path, &[]), "3\n");
+    assert_eq!(count(&path, &["--schemas"]), "(keys)  3\ntotal   3\n");
+}
+
+#[test]
+fn test_count_falls_back_to_parser() {
+    let dir = tempfile::tempdir().unwrap();
+    // `;` separators, a `!schemas` block, multi-line rows and rows with more
+    // values than fields all need a real parse
+    let cases = [
+        (
+            "!def A x;1;2;!def B y;3\n",
+            "A      2\nB      1\ntotal  3\n",
+        ),
+        (
+            "!schemas\nUser id name\n---\n!use User\n1 Alice\n2 Bob\n",
+            "User   2\ntotal  2\n",
+        ),
+        (
+            "!def P x y\n1 [a\n b]\n2 {\n k v\n}\n",
+            "P      2\ntotal  2\n",
+        ),
+        ("!def P x y\n1 2 3 4\n", "P      2\ntotal  2\n"),
+    ];
+    for (source, expected) in cases {
+        let path = write(dir.path(), "case.tqn", source);
+        assert_eq!(count(&path, &["--schemas"]), expected, "{:?}", source);
+    }
+}
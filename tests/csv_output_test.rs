@@ -0,0 +1,64 @@
+use serde_json::json;
+use tauq::Formatter;
+
+#[test]
+fn test_csv_round_trip_with_quoting() {
+    let csv =
+        "id,name,note\n1,\"Smith, Jane\",\"said \"\"hi\"\"\"\n2,Bob,\"line one\nline two\"\n3,007,";
+    let tauq = tauq::csv_to_tauq(csv, true).unwrap();
+    let value = tauq::compile_tauq(&tauq).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            {"id": 1, "name": "Smith, Jane", "note": "said \"hi\""},
+            {"id": 2, "name": "Bob", "note": "line one\nline two"},
+            {"id": 3, "name": "007", "note": null}
+        ])
+    );
+    assert_eq!(Formatter::new().to_csv(&value).unwrap(), csv);
+}
+
+#[test]
+fn test_strings_that_look_typed_are_quoted() {
+    let value = json!([{"code": "42", "flag": "true", "empty": "", "n": 4.5}]);
+    let csv = Formatter::new().to_csv(&value).unwrap();
+    assert_eq!(csv, "code,flag,empty,n\n\"42\",\"true\",\"\",4.5");
+    let back = tauq::compile_tauq(&tauq::csv_to_tauq(&csv, true).unwrap()).unwrap();
+    assert_eq!(back, value);
+}
+
+#[test]
+fn test_non_tabular_values_have_no_csv() {
+    let formatter = Formatter::new();
+    assert!(formatter.to_csv(&json!({"a": 1})).is_none());
+    assert!(formatter.to_csv(&json!([])).is_none());
+    assert!(formatter.to_csv(&json!([{"a": 1}, 2])).is_none());
+    assert!(formatter.to_csv(&json!([{"a": 1}, {"b": 1}])).is_none());
+    assert!(formatter.to_csv(&json!([{"a": {"b": 1}}])).is_none());
+    assert!(formatter.to_csv(&json!([{"a": [1]}])).is_none());
+}
+
+#[test]
+fn test_csv_without_header() {
+    let tauq = tauq::csv_to_tauq("a,1\r\nb,2\r\n", false).unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&tauq).unwrap(),
+        json!([{"col1": "a", "col2": 1}, {"col1": "b", "col2": 2}])
+    );
+}
+
+#[test]
+fn test_malformed_csv_is_an_error() {
+    let err = tauq::csv_to_tauq("a,b\n1,2,3\n", true).unwrap_err();
+    assert!(err.to_string().contains("3 fields, expected 2"), "{}", err);
+
+    let err = tauq::csv_to_tauq("a,b\n\"open,2\n", true).unwrap_err();
+    assert!(err.to_string().contains("Unterminated"), "{}", err);
+
+    let err = tauq::csv_to_tauq("a,a\n1,2\n", true).unwrap_err();
+    assert!(
+        err.to_string().contains("Duplicate CSV column 'a'"),
+        "{}",
+        err
+    );
+}
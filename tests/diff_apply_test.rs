@@ -0,0 +1,122 @@
+use std::fs;
+use std::process::Command;
+
+const TARGET: &str = "!def User id name role\n1 Alice admin\n2 Bob user\n";
+
+/// Write `target` and `patch` into a temp dir, run `tauq diff --apply`, and
+/// return the re-parsed JSON plus the raw Tauq output.
+fn apply(target: &str, patch: &str, extra: &[&str]) -> (serde_json::Value, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let target_path = dir.path().join("target.tqn");
+    let patch_path = dir.path().join("patch.json");
+    let output_path = dir.path().join("output.tqn");
+    fs::write(&target_path, target).unwrap();
+    fs::write(&patch_path, patch).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("diff")
+        .arg("--apply")
+        .arg(&patch_path)
+        .arg(&target_path)
+        .arg("-o")
+        .arg(&output_path)
+        .args(extra)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = fs::read_to_string(&output_path).unwrap();
+    let json = tauq::compile_tauq(&output).unwrap();
+    (json, output)
+}
+
+#[test]
+fn test_diff_apply_add() {
+    let patch =
+        r#"[{"op": "add", "path": "/-", "value": {"id": 3, "name": "Carol", "role": "user"}}]"#;
+    let (json, output) = apply(TARGET, patch, &[]);
+
+    let rows = json.as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2]["name"], "Carol");
+    // Field set unchanged, so the original schema name is kept
+    assert!(output.contains("!def User id name role"), "{}", output);
+}
+
+#[test]
+fn test_diff_apply_remove() {
+    let patch = r#"[{"op": "remove", "path": "/0"}]"#;
+    let (json, _) = apply("!def User id name\n1 Alice\n2 Bob\n3 Carol\n", patch, &[]);
+
+    let rows = json.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["name"], "Bob");
+    assert_eq!(rows[1]["name"], "Carol");
+}
+
+#[test]
+fn test_diff_apply_replace() {
+    let patch = r#"[{"op": "replace", "path": "/1/role", "value": "admin"}]"#;
+    let (json, _) = apply(TARGET, patch, &[]);
+
+    assert_eq!(json[1]["role"], "admin");
+    assert_eq!(json[0]["role"], "admin");
+    assert_eq!(json[1]["name"], "Bob");
+}
+
+#[test]
+fn test_diff_apply_nested_schema_name_preserved() {
+    let target =
+        "!def Member id name\n---\nteam core\nmembers [\n  !use Member\n  1 Alice\n  2 Bob\n]\n";
+    let patch = r#"[{"op": "replace", "path": "/team", "value": "platform"}]"#;
+    let (json, output) = apply(target, patch, &[]);
+
+    assert_eq!(json["team"], "platform");
+    assert_eq!(json["members"][1]["name"], "Bob");
+    assert!(output.contains("!def Member id name"), "{}", output);
+    assert!(output.contains("!use Member"), "{}", output);
+}
+
+#[test]
+fn test_diff_apply_optimized_mode() {
+    let patch = r#"[{"op": "replace", "path": "/0/name", "value": "Alicia"}]"#;
+    let (json, output) = apply(TARGET, patch, &["--format-mode", "optimized"]);
+
+    assert_eq!(json[0]["name"], "Alicia");
+    assert!(output.contains("1,Alicia,admin"), "{}", output);
+}
+
+#[test]
+fn test_diff_apply_dry_run_leaves_target_untouched() {
+    let dir = tempfile::tempdir().unwrap();
+    let target_path = dir.path().join("target.tqn");
+    let patch_path = dir.path().join("patch.json");
+    fs::write(&target_path, TARGET).unwrap();
+    fs::write(&patch_path, r#"[{"op": "remove", "path": "/1"}]"#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("diff")
+        .arg("--apply")
+        .arg(&patch_path)
+        .arg(&target_path)
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let preview = String::from_utf8(output.stdout).unwrap();
+    assert!(preview.contains("Alice"));
+    assert!(!preview.contains("Bob"));
+    assert_eq!(fs::read_to_string(&target_path).unwrap(), TARGET);
+}
+
+#[test]
+fn test_diff_apply_leaves_directive_text_in_strings() {
+    let target = "!def User id name role\n1 \"!use Record\" admin\n2 Bob \"!def Record x\"\n";
+    let patch = r#"[{"op": "replace", "path": "/1/name", "value": "Bobby"}]"#;
+    let (json, output) = apply(target, patch, &[]);
+
+    assert!(output.contains("!def User id name role"), "{}", output);
+    assert_eq!(json[0]["name"], "!use Record");
+    assert_eq!(json[1]["role"], "!def Record x");
+}
@@ -0,0 +1,85 @@
+use tauq::{EmitOptions, format_error_with_source, format_warnings_with_source};
+
+const PLAIN: EmitOptions = EmitOptions {
+    color: false,
+    context_lines: 0,
+};
+
+fn error_output(source: &str, options: &EmitOptions) -> String {
+    let err = tauq::compile_tauq(source).unwrap_err();
+    format_error_with_source(source, &err, options)
+}
+
+#[test]
+fn test_error_underlines_full_span() {
+    let source = "!def User id name\n1 Alice\n!use Missing\n3 Carol\n";
+    assert_eq!(
+        error_output(source, &PLAIN),
+        "Error: Parse error at line 3, column 6: !use references undefined schema 'Missing'\n\
+         \x20  |\n\
+         \x203 | !use Missing\n\
+         \x20  |      ^^^^^^^\n\
+         \x20  |\n"
+    );
+}
+
+#[test]
+fn test_error_context_lines() {
+    let source = "a 1\nb }\nc 2\nd 3\n";
+    let options = EmitOptions {
+        context_lines: 1,
+        ..PLAIN
+    };
+    assert_eq!(
+        error_output(source, &options),
+        "Error: Parse error at line 2, column 3: Unexpected token: RBrace\n\
+         \x20  |\n\
+         \x201 | a 1\n\
+         \x202 | b }\n\
+         \x20  |   ^\n\
+         \x203 | c 2\n\
+         \x20  |\n"
+    );
+}
+
+#[test]
+fn test_error_span_over_several_lines() {
+    let source = "x \"abc\ndef\n";
+    assert_eq!(
+        error_output(source, &PLAIN),
+        "Error: Parse error at line 1, column 3: unterminated string literal\n\
+         \x20  |\n\
+         \x201 | x \"abc\n\
+         \x20  |   ^^^^\n\
+         \x202 | def\n\
+         \x20  | ^^^\n\
+         \x20  |\n"
+    );
+}
+
+#[test]
+fn test_error_without_span() {
+    let err = tauq::TauqError::Io(std::io::Error::other("disk full"));
+    assert_eq!(
+        format_error_with_source("a 1\n", &err, &PLAIN),
+        "Error: IO error: disk full\n"
+    );
+}
+
+#[test]
+fn test_warnings_in_color() {
+    let source = "!def User id name\n!use User\n1 Alice\n";
+    let (_, warnings) = tauq::compile_tauq_verbose(source).unwrap();
+    let options = EmitOptions {
+        color: true,
+        ..PLAIN
+    };
+    assert_eq!(
+        format_warnings_with_source(source, &warnings, &options),
+        "\x1b[33mW\x1b[0m: Warning at line 2, column 6: Schema 'User' is already active; !use is redundant\n\
+         \x20  |\n\
+         \x202 | !use User\n\
+         \x20  |      \x1b[33m^^^^\x1b[0m\n\
+         \x20  |\n"
+    );
+}
@@ -0,0 +1,34 @@
+use tauq::Parser;
+use tauq::error::Span;
+
+fn error_span(src: &str) -> Span {
+    Parser::new(src).parse().unwrap_err().span
+}
+
+#[test]
+fn test_unclosed_string_spans_to_end_of_input() {
+    // The span starts at the opening quote and covers the rest of the string
+    assert_eq!(error_span("name \"Alice"), Span::range(1, 6, 1, 12));
+
+    let err = tauq::compile_tauq("id 1\nname \"Bob").unwrap_err();
+    match err {
+        tauq::TauqError::Parse(e) => assert_eq!(e.span, Span::range(2, 6, 2, 10)),
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn test_parse_errors_underline_the_whole_token() {
+    assert_eq!(
+        error_span("!def User a\n!use Nope\n"),
+        Span::range(2, 6, 2, 10)
+    );
+    assert_eq!(error_span("a 1\n}"), Span::range(2, 1, 2, 2));
+}
+
+#[test]
+fn test_errors_at_line_end_are_single_character() {
+    let span = error_span("!use\n");
+    assert_eq!(span, Span::point(1, 5));
+    assert!(span.is_point());
+}
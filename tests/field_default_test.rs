@@ -0,0 +1,73 @@
+use serde_json::json;
+use tauq::tauq::{Formatter, StreamingParser};
+
+#[test]
+fn test_default_fills_missing_field() {
+    let json = tauq::compile_tauq("!def S host port=8080\nlocalhost\n").unwrap();
+    assert_eq!(json, json!({"host": "localhost", "port": 8080}));
+}
+
+#[test]
+fn test_default_literal_kinds() {
+    let input = "!def Config host=\"local host\" port:int=8080 tls=false ratio=0.5 mode=fast note=null\n\nexample.com 443\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "host": "example.com",
+            "port": 443,
+            "tls": false,
+            "ratio": 0.5,
+            "mode": "fast",
+            "note": null
+        })
+    );
+
+    let json = tauq::compile_tauq("!def S host=\"local host\" port=1\nport:2\n").unwrap();
+    assert_eq!(json, json!({"host": "local host", "port": 2}));
+}
+
+#[test]
+fn test_non_scalar_default_is_rejected() {
+    assert!(tauq::compile_tauq("!def S a b=[1]\nx\n").is_err());
+    assert!(tauq::compile_tauq("!def S a b=\nx\n").is_err());
+}
+
+#[test]
+fn test_streaming_and_sax_apply_defaults() {
+    let input = "!def S host port=8080\nlocalhost\nexample.com 80\n";
+    let rows: Vec<_> = StreamingParser::new(input).map(|r| r.unwrap()).collect();
+    assert_eq!(rows[0], json!({"host": "localhost", "port": 8080}));
+
+    let mut handler = tauq::tauq::CollectingHandler::new();
+    tauq::tauq::parse_sax(input, &mut handler).unwrap();
+    assert_eq!(
+        handler.into_values(),
+        vec![
+            json!({"host": "localhost", "port": 8080}),
+            json!({"host": "example.com", "port": 80})
+        ]
+    );
+}
+
+#[test]
+fn test_formatter_emits_field_defaults() {
+    let value = json!({
+        "servers": [
+            {"host": "a", "port": 8080, "region": "eu west"},
+            {"host": "b", "port": 9090, "region": "eu west"},
+            {"host": "c", "port": 8080, "region": "us"}
+        ]
+    });
+    let tauq = Formatter::new()
+        .with_field_default("port", json!(8080))
+        .with_field_default("region", json!("eu west"))
+        .format(&value);
+    assert!(
+        tauq.contains(r#"!def Server host port=8080 region="eu west""#),
+        "{}",
+        tauq
+    );
+    assert!(tauq.contains("\n  a\n  b 9090\n  c 8080 us\n"), "{}", tauq);
+    assert_eq!(tauq::compile_tauq(&tauq).unwrap(), value);
+}
@@ -0,0 +1,34 @@
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Employee {
+    pub id: i64,
+    pub name: String,
+    pub active: bool,
+    pub rate: f64,
+    pub tags: serde_json::Value,
+}
+
+impl Employee {
+    /// The `!def` line this struct was generated from
+    pub fn tauq_def() -> &'static str {
+        "!def Employee id:int name:string active:bool rate:float tags"
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Team {
+    pub name: String,
+    pub lead: Employee,
+    pub members: Vec<Employee>,
+    pub parent: Option<Box<Team>>,
+    pub r#type: serde_json::Value,
+    pub note: Option<String>,
+    #[serde(rename = "display-name")]
+    pub display_name: serde_json::Value,
+}
+
+impl Team {
+    /// The `!def` line this struct was generated from
+    pub fn tauq_def() -> &'static str {
+        "!def Team name:string lead:Employee members:[Employee] parent?:Team type note?:string display-name"
+    }
+}
@@ -0,0 +1,157 @@
+use serde_json::{Value, json};
+use std::io;
+use tauq::Formatter;
+
+fn sample_values() -> Vec<Value> {
+    vec![
+        json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": null}]),
+        json!([{"id": 1, "name": "Alice"}]),
+        json!([{"id": 1}, {"name": "x"}, 3, "four"]),
+        json!({
+            "service": "api",
+            "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+            "mixed": [{"a": 1}, {"b": 2}],
+            "tags": ["x", "y z"],
+            "nested": {"deep": {"roles": [{"r": "admin"}, {"r": "dev"}]}},
+            "empty": [],
+            "note": "line one\nline two\nline three"
+        }),
+        json!("scalar"),
+        json!([]),
+    ]
+}
+
+fn formatters() -> Vec<Formatter> {
+    vec![
+        Formatter::new(),
+        Formatter::new().minified(),
+        Formatter::new().with_comma_delimiter(),
+        Formatter::new().with_version_tag(),
+        Formatter::new().with_schemas_block_threshold(1),
+        Formatter::new().without_schemas(),
+    ]
+}
+
+#[test]
+fn test_writer_output_matches_format() {
+    for value in sample_values() {
+        for formatter in formatters() {
+            let mut buf = Vec::new();
+            formatter.format_to_writer(&value, &mut buf).unwrap();
+            assert_eq!(
+                String::from_utf8(buf).unwrap(),
+                formatter.format(&value),
+                "{}",
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn test_json_to_tauq_writer() {
+    let mut rows = Vec::new();
+    for i in 0..1000 {
+        rows.push(json!({"id": i, "label": format!("row {}", i)}));
+    }
+    let value = json!({"rows": rows});
+
+    let mut buf = Vec::new();
+    tauq::json_to_tauq_writer(&value, &mut buf).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    assert_eq!(text, tauq::json_to_tauq(&value));
+    assert_eq!(tauq::compile_tauq(&text).unwrap(), value);
+}
+
+/// Accepts `limit` bytes, then fails every write
+struct FailingWriter {
+    limit: usize,
+    written: usize,
+    flushed: bool,
+}
+
+impl io::Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() > self.limit {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "disk full"));
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushed = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_io_errors_propagate() {
+    let value = json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+
+    let mut writer = FailingWriter {
+        limit: 20,
+        written: 0,
+        flushed: false,
+    };
+    let err = Formatter::new()
+        .format_to_writer(&value, &mut writer)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!(err.to_string(), "disk full");
+    assert!(!writer.flushed);
+
+    let mut writer = FailingWriter {
+        limit: usize::MAX,
+        written: 0,
+        flushed: false,
+    };
+    Formatter::new()
+        .format_to_writer(&value, &mut writer)
+        .unwrap();
+    assert!(writer.flushed);
+}
+
+/// Records how much had been written at each flush
+#[derive(Default)]
+struct FlushLog {
+    buf: Vec<u8>,
+    flushes: Vec<usize>,
+}
+
+impl io::Write for FlushLog {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes.push(self.buf.len());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_writer_flushed_after_each_row() {
+    let value = json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+    let mut writer = FlushLog::default();
+    Formatter::new()
+        .format_to_writer(&value, &mut writer)
+        .unwrap();
+    let text = String::from_utf8(writer.buf).unwrap();
+    let flushed: Vec<&str> = writer.flushes.iter().map(|&n| &text[..n]).collect();
+    assert_eq!(
+        flushed,
+        [
+            "!def Record id name\n1 Alice",
+            "!def Record id name\n1 Alice\n2 Bob",
+            "!def Record id name\n1 Alice\n2 Bob",
+        ]
+    );
+
+    let mut writer = FlushLog::default();
+    Formatter::new()
+        .format_to_writer(&json!({"a": 1, "b": 2}), &mut writer)
+        .unwrap();
+    assert_eq!(writer.flushes, [3, 7, 7]);
+}
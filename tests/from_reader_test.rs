@@ -0,0 +1,179 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::fs::{self, File};
+use std::io::{BufReader, Cursor, Read};
+use std::process::{Command, Stdio};
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn test_from_reader_cursor() {
+    let input = Cursor::new(b"!def User id name\n1 Alice\n2 \"Bob Smith\"\n".as_slice());
+    let users: Vec<User> = tauq::from_reader(input).unwrap();
+    assert_eq!(
+        users,
+        vec![
+            User {
+                id: 1,
+                name: "Alice".into()
+            },
+            User {
+                id: 2,
+                name: "Bob Smith".into()
+            }
+        ]
+    );
+
+    let err = tauq::from_reader::<User, _>(Cursor::new(b"name \"open".as_slice())).unwrap_err();
+    assert!(err.to_string().contains("unterminated"), "{}", err);
+
+    let err = tauq::from_reader::<User, _>(Cursor::new(b"\xff\xfe".as_slice())).unwrap_err();
+    assert!(matches!(err, tauq::TauqError::Io(_)), "{}", err);
+}
+
+#[test]
+fn test_from_reader_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("user.tqn");
+    fs::write(&path, "id 7\nname Carol\n").unwrap();
+
+    let user: User = tauq::from_reader(BufReader::new(File::open(&path).unwrap())).unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "Carol".into()
+        }
+    );
+}
+
+#[test]
+fn test_streaming_from_reader() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("users.tqn");
+    fs::write(&path, "!def User id name\n1 Alice\n2 Bob\n").unwrap();
+
+    let records: Vec<_> = tauq::streaming_from_reader(BufReader::new(File::open(&path).unwrap()))
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        records,
+        vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 2, "name": "Bob"})
+        ]
+    );
+
+    let records: Vec<_> =
+        tauq::streaming_from_reader(Cursor::new(b"!def R v\n1\n]\n2\n".as_slice())).collect();
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_ok());
+    assert!(records[1].is_err());
+}
+
+/// Endless `N\n` rows
+struct Rows(u64);
+
+impl Read for Rows {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let row = format!("{}\n", self.0);
+        if buf.len() < row.len() {
+            return Ok(0);
+        }
+        self.0 += 1;
+        buf[..row.len()].copy_from_slice(row.as_bytes());
+        Ok(row.len())
+    }
+}
+
+#[test]
+fn test_streaming_from_endless_reader() {
+    let input = Cursor::new(b"!def Row n\n".as_slice()).chain(Rows(0));
+    let records: Vec<_> = tauq::streaming_from_reader(input)
+        .take(3)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        records,
+        vec![json!({"n": 0}), json!({"n": 1}), json!({"n": 2})]
+    );
+}
+
+#[test]
+#[cfg(feature = "rhai")]
+fn test_query_reads_stdin() {
+    use std::io::Write;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["query", "-", ".name"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"id 1\nname Alice\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("Alice"));
+}
+
+#[test]
+fn test_query_pointer_reads_stdin() {
+    use std::io::Write;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["query", "-", "/name"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"id 1\nname Alice\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("Alice"));
+}
+
+#[test]
+fn test_format_reads_stdin() {
+    use std::io::Write;
+
+    for args in [&["format", "-"][..], &["format", "-", "--stats"][..]] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(br#"[{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]"#)
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let tauq = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            tauq::compile_tauq(&tauq).unwrap(),
+            json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}])
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn tauq(args: &[&str], file: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(args)
+        .arg(file)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn users(dir: &Path, rows: usize) -> std::path::PathBuf {
+    let mut source = String::from("# exported users\n!def User id name active\n");
+    for i in 0..rows {
+        source.push_str(&format!("{} user{} {}\n", i, i, i % 2 == 0));
+    }
+    let path = dir.join("users.tqn");
+    fs::write(&path, source).unwrap();
+    path
+}
+
+#[test]
+fn test_head_first_rows_with_def() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = users(dir.path(), 1000);
+
+    let out = tauq(&["head", "-n", "5"], &path);
+    assert_eq!(out.lines().next(), Some("!def User id name active"));
+    assert_eq!(out.lines().count(), 6, "{}", out);
+    let rows = tauq::compile_tauq(&out).unwrap();
+    assert_eq!(rows.as_array().unwrap().len(), 5);
+    assert_eq!(rows[0], json!({"id": 0, "name": "user0", "active": true}));
+    assert_eq!(rows[4]["name"], "user4");
+
+    // Ten rows by default
+    let out = tauq(&["head"], &path);
+    assert_eq!(out.lines().count(), 11, "{}", out);
+}
+
+#[test]
+fn test_tail_last_rows_with_def() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = users(dir.path(), 1000);
+
+    let out = tauq(&["tail", "-n", "3"], &path);
+    assert_eq!(
+        out,
+        "!def User id name active\n997 user997 false\n998 user998 true\n999 user999 false\n"
+    );
+}
+
+#[test]
+fn test_fewer_rows_than_requested() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = users(dir.path(), 2);
+    let expected = "!def User id name active\n0 user0 true\n1 user1 false\n";
+    assert_eq!(tauq(&["head", "-n", "5"], &path), expected);
+    assert_eq!(tauq(&["tail", "-n", "5"], &path), expected);
+}
+
+#[test]
+fn test_key_value_pairs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.tqn");
+    fs::write(&path, "host localhost\nport 8080\ndebug true\n").unwrap();
+
+    assert_eq!(
+        tauq(&["head", "-n", "2"], &path),
+        "host localhost\nport 8080\n"
+    );
+    assert_eq!(tauq(&["tail", "-n", "1"], &path), "debug true\n");
+}
+
+#[test]
+fn test_invalid_arguments() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["head", "-n", "x", "users.tqn"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("-n expects a number"));
+}
+
+#[test]
+fn test_header_lines_kept_as_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("users.tqn");
+    fs::write(
+        &path,
+        "!def User id:int name:string email?:string\n1 Alice \"a@x.io\" # first\n2 Bob\n3 Carol\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        tauq(&["head", "-n", "1"], &path),
+        "!def User id:int name:string email?:string\n1 Alice \"a@x.io\"\n"
+    );
+    assert_eq!(
+        tauq(&["tail", "-n", "1"], &path),
+        "!def User id:int name:string email?:string\n3 Carol\n"
+    );
+}
+
+#[test]
+fn test_tail_keeps_schema_switches() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mixed.tqn");
+    fs::write(
+        &path,
+        "!def User id name\n!def Order id total\n!use User\n1 Alice\n2 Bob\n\
+         !use Order\n10 9.5\n11 [\n  20\n]\n",
+    )
+    .unwrap();
+
+    let out = tauq(&["tail", "-n", "2"], &path);
+    assert_eq!(
+        out,
+        "!def User id name\n!def Order id total\n!use User\n!use Order\n10 9.5\n11 [\n  20\n]\n"
+    );
+    assert_eq!(
+        tauq::compile_tauq(&out).unwrap(),
+        json!([{"id": 10, "total": 9.5}, {"id": 11, "total": [20]}])
+    );
+
+    let path = dir.path().join("semi.tqn");
+    fs::write(&path, "!def A x;1;2;3\n").unwrap();
+    assert_eq!(tauq(&["head", "-n", "2"], &path), "!def A x\n1\n2\n");
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use tauq::tauq::Formatter;
+
+#[test]
+fn test_heredoc_field() {
+    let input = "query <<<SQL\nSELECT *\n  FROM users\n WHERE name = \"a\\b\"\nSQL\nlimit 10\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json["query"],
+        "SELECT *\n  FROM users\n WHERE name = \"a\\b\""
+    );
+    assert_eq!(json["limit"], 10);
+}
+
+#[test]
+fn test_heredoc_in_row_and_nested_object() {
+    let input = "!def Note id body\n1 <<<END\nfirst\nsecond\nEND\n2 short\n---\nmeta {\n  text <<<EOT\n  indented\nEOT\n}\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {"id": 1, "body": "first\nsecond"},
+            {"id": 2, "body": "short"},
+            {"meta": {"text": "  indented"}}
+        ])
+    );
+}
+
+#[test]
+fn test_unterminated_heredoc_is_an_error() {
+    assert!(tauq::compile_tauq("text <<<EOT\nnever closed\n").is_err());
+}
+
+#[test]
+fn test_formatter_emits_heredocs() {
+    let value = json!({
+        "poem": "roses\nviolets\nsugar",
+        "one_break": "a\nb",
+        "long": "x ".repeat(50).trim_end(),
+        "nested": {"body": "EOT\nline\nend"}
+    });
+
+    let tauq = Formatter::new().format(&value);
+    assert!(
+        tauq.contains("poem <<<EOT\nroses\nviolets\nsugar\nEOT"),
+        "{}",
+        tauq
+    );
+    assert!(tauq.contains(r#"one_break "a\nb""#), "{}", tauq);
+    assert!(tauq.contains("long <<<EOT\nx x"), "{}", tauq);
+    // A content line equal to the delimiter forces a different one
+    assert!(
+        tauq.contains("body <<<EOT1\nEOT\nline\nend\nEOT1"),
+        "{}",
+        tauq
+    );
+    assert_eq!(tauq::compile_tauq(&tauq).unwrap(), value);
+
+    let short = Formatter::new()
+        .with_heredoc_threshold(10)
+        .format(&json!({"s": "eleven char"}));
+    assert!(short.contains("s <<<EOT\neleven char\nEOT"), "{}", short);
+}
+
+#[test]
+fn test_heredocs_disabled_in_minified_and_rows() {
+    let value = json!({
+        "poem": "roses\nviolets\nsugar",
+        "rows": [{"id": 1, "text": "a\nb\nc"}, {"id": 2, "text": "d"}]
+    });
+
+    let minified = Formatter::new().minified().format(&value);
+    assert!(!minified.contains("<<<"), "{}", minified);
+    assert_eq!(tauq::compile_tauq(&minified).unwrap(), value);
+
+    let pretty = Formatter::new().format(&value);
+    assert!(pretty.contains(r#"1 "a\nb\nc""#), "{}", pretty);
+    assert_eq!(tauq::compile_tauq(&pretty).unwrap(), value);
+
+    let quoted = Formatter::new().without_heredocs().format(&value);
+    assert!(!quoted.contains("<<<"), "{}", quoted);
+}
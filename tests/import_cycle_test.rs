@@ -0,0 +1,61 @@
+use std::fs;
+use tauq::tauq::parser::{Context, Parser};
+
+/// Parse `source` as if it lived in `dir`, so `!import` paths resolve there.
+fn parse_in(dir: &std::path::Path, source: &str) -> Result<serde_json::Value, String> {
+    let context = Context::with_base_dir(dir.to_path_buf());
+    let mut parser = Parser::new_with_context(source, context);
+    parser.parse().map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_direct_import_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tqn"), "!import \"a.tqn\"\n").unwrap();
+
+    let err = parse_in(dir.path(), "!import \"a.tqn\"").unwrap_err();
+    assert!(err.contains("Circular import detected"), "{}", err);
+    assert!(err.contains("a.tqn → "), "{}", err);
+}
+
+#[test]
+fn test_indirect_import_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("a.tqn"), "!import \"b.tqn\"\n").unwrap();
+    fs::write(dir.path().join("b.tqn"), "!import \"c.tqn\"\n").unwrap();
+    fs::write(dir.path().join("c.tqn"), "!import \"a.tqn\"\n").unwrap();
+
+    let err = parse_in(dir.path(), "!import \"a.tqn\"").unwrap_err();
+    assert!(err.contains("Circular import detected"), "{}", err);
+    // The reported chain starts and ends with the file that closed the loop
+    let chain = err.rsplit("Circular import detected: ").next().unwrap();
+    let files: Vec<&str> = chain.lines().next().unwrap().split(" → ").collect();
+    assert_eq!(files.len(), 4, "{}", err);
+    assert!(files[0].ends_with("a.tqn"));
+    assert!(files[3].ends_with("a.tqn"));
+}
+
+#[test]
+fn test_diamond_import_is_allowed() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("b.tqn"), "!import \"d.tqn\"\n").unwrap();
+    fs::write(dir.path().join("c.tqn"), "!import \"d.tqn\"\n").unwrap();
+    fs::write(dir.path().join("d.tqn"), "!def User id name\n").unwrap();
+
+    let source = "!import \"b.tqn\"\n!import \"c.tqn\"\n!use User\n1 Alice\n2 Bob\n";
+    let result = parse_in(dir.path(), source).unwrap();
+    let rows = result.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1]["name"], "Bob");
+}
+
+#[test]
+fn test_sequential_reimport_is_not_a_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("schemas.tqn"), "!def Point x y\n").unwrap();
+
+    let source = "!import \"schemas.tqn\"\n!import \"schemas.tqn\"\n!use Point\n1 2\n";
+    let result = parse_in(dir.path(), source).unwrap();
+    assert_eq!(result["x"].as_i64(), Some(1));
+    assert_eq!(result["y"].as_i64(), Some(2));
+}
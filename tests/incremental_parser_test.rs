@@ -0,0 +1,176 @@
+#![cfg(feature = "lsp")]
+
+use serde_json::json;
+use tauq::Parser;
+use tauq::tauq::IncrementalParser;
+
+/// Documents and warnings as comparable strings
+fn summary(documents: Vec<Result<serde_json::Value, tauq::error::ParseError>>) -> Vec<String> {
+    documents
+        .into_iter()
+        .map(|doc| match doc {
+            Ok(value) => value.to_string(),
+            Err(e) => format!("{} {:?}", e.message, e.span),
+        })
+        .collect()
+}
+
+fn assert_matches_full_parse(parser: &IncrementalParser) {
+    let mut full = Parser::new(parser.source());
+    let expected = summary(full.parse_documents());
+    assert_eq!(
+        summary(parser.documents()),
+        expected,
+        "{:?}",
+        parser.source()
+    );
+    let spans =
+        |w: Vec<tauq::ParseWarning>| -> Vec<_> { w.iter().map(|w| (w.kind, w.span)).collect() };
+    assert_eq!(spans(parser.warnings()), spans(full.take_warnings()));
+}
+
+/// Replace the first occurrence of `from` and re-parse
+fn replace(parser: &mut IncrementalParser, from: &str, to: &str) -> usize {
+    let start = parser.source().find(from).unwrap();
+    parser.edit(start, start + from.len(), to);
+    parser.reparse()
+}
+
+fn table(rows: usize) -> String {
+    let mut source = String::from("!def User id name active\n");
+    for i in 0..rows {
+        source.push_str(&format!("{} user{} {}\n", i, i, i % 2 == 0));
+    }
+    source
+}
+
+#[test]
+fn test_row_edit_reparses_few_lines() {
+    let mut parser = IncrementalParser::new(table(1000));
+    let lines = replace(&mut parser, "500 user500", "500 renamed");
+    assert!(lines <= 2, "re-parsed {} lines", lines);
+
+    let docs = parser.documents();
+    let rows = docs[0].as_ref().unwrap().as_array().unwrap();
+    assert_eq!(rows.len(), 1000);
+    assert_eq!(
+        rows[500],
+        json!({"id": 500, "name": "renamed", "active": true})
+    );
+    assert_eq!(rows[501]["name"], "user501");
+    assert_matches_full_parse(&parser);
+}
+
+#[test]
+fn test_schema_edit_reparses_to_the_end() {
+    let mut parser = IncrementalParser::new(table(100));
+    let lines = replace(&mut parser, "id name active", "id name enabled");
+    assert_eq!(lines, 101);
+    assert_eq!(
+        parser.documents()[0].as_ref().unwrap()[99]["enabled"],
+        false
+    );
+    assert!(parser.schemas()["User"].iter().any(|f| f.name == "enabled"));
+    assert_matches_full_parse(&parser);
+}
+
+#[test]
+fn test_inserted_and_removed_lines_shift_later_errors() {
+    let source = "!def User id name\n1 Alice\n2 Bob\n===\na {\n===\nb 1\n!use Missing\n";
+    let mut parser = IncrementalParser::new(source);
+    assert_matches_full_parse(&parser);
+
+    replace(&mut parser, "2 Bob\n", "2 Bob\n3 Carol\n4 Dave\n");
+    assert_matches_full_parse(&parser);
+    let errors: Vec<_> = parser
+        .documents()
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[1].span.line, 10);
+
+    replace(&mut parser, "1 Alice\n", "");
+    assert_matches_full_parse(&parser);
+    replace(&mut parser, "a {", "a { x 1 }");
+    assert_matches_full_parse(&parser);
+}
+
+#[test]
+fn test_errors_on_the_first_line_move_down() {
+    for source in ["}\nname Alice\n", "] x\n"] {
+        let mut parser = IncrementalParser::new(source);
+        assert_matches_full_parse(&parser);
+        parser.edit(0, 0, "# header\n");
+        parser.reparse();
+        assert_matches_full_parse(&parser);
+        let error = parser.documents().remove(0).unwrap_err();
+        assert_eq!(error.span.line, 2, "{:?}", source);
+    }
+}
+
+#[test]
+fn test_edits_match_full_parse() {
+    let source =
+        "host localhost\nport 8080\n!def Point x y\n1 2\n3 4\n===\nname demo\ntags [a b]\n";
+    let edits: &[(&str, &str)] = &[
+        ("port 8080", "port 9090"),
+        ("3 4", "3 4\n5 6"),
+        ("1 2\n", "1 [2\n"),
+        ("1 [2\n", "1 2\n"),
+        ("===\n", ""),
+        ("name demo", "!use Point\n7 8"),
+        ("tags [a b]", "tags \"open"),
+        ("\"open", "\"closed\""),
+        ("host localhost\n", "!def Point x y z\n"),
+        ("1 2", "1 2 0"),
+        ("!def Point x y z\n", ""),
+    ];
+    let mut parser = IncrementalParser::new(source);
+    assert_matches_full_parse(&parser);
+    for (from, to) in edits {
+        replace(&mut parser, from, to);
+        assert_matches_full_parse(&parser);
+    }
+}
+
+#[test]
+fn test_several_edits_in_one_reparse() {
+    let mut parser = IncrementalParser::new(table(50));
+    let first = parser.source().find("10 user10").unwrap();
+    parser.edit(first, first + 2, "110");
+    // Offsets of later edits see the earlier ones applied
+    let second = parser.source().find("40 user40").unwrap() + 1;
+    parser.edit(second, second + 2, "41");
+    parser.reparse();
+    assert!(parser.source().contains("\n110 user10 true\n"));
+    assert!(parser.source().contains("\n41 user40 true\n"));
+    assert_matches_full_parse(&parser);
+    assert_eq!(parser.reparse(), 0);
+}
+
+#[test]
+fn test_warnings_follow_edits() {
+    let mut parser = IncrementalParser::new("!def User id name\n!use User\n1 Alice\n");
+    assert_eq!(parser.warnings().len(), 1);
+    replace(&mut parser, "!def", "# header\n!def");
+    assert_eq!(parser.warnings()[0].span.line, 3);
+    assert_matches_full_parse(&parser);
+    replace(&mut parser, "!use User\n", "");
+    assert!(parser.warnings().is_empty());
+}
+
+#[test]
+fn test_unused_marker_warning_follows_edits() {
+    let mut parser = IncrementalParser::new("!def Tag\n!def Point x y\n1 2\n3 4\n5 6\n");
+    assert_eq!(parser.warnings().len(), 1);
+    assert_matches_full_parse(&parser);
+    replace(&mut parser, "3 4\n", "3 4\n!use Tag\n\n!use Point\n");
+    assert!(parser.warnings().is_empty());
+    assert_matches_full_parse(&parser);
+    replace(&mut parser, "!use Tag\n\n!use Point\n", "");
+    assert_eq!(parser.warnings()[0].span.line, 1);
+    assert_matches_full_parse(&parser);
+    replace(&mut parser, "1 2\n", "0 0\n1 2\n");
+    assert_matches_full_parse(&parser);
+}
@@ -0,0 +1,95 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Run the CLI and parse its stderr as a JSON diagnostics array
+fn diagnostics(args: &[&str], file: &Path) -> Vec<Value> {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(args)
+        .arg(file)
+        .arg("--json-errors")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    match serde_json::from_str(&stderr) {
+        Ok(Value::Array(items)) => items,
+        _ => panic!("stderr is not a JSON array: {}", stderr),
+    }
+}
+
+#[test]
+fn test_build_reports_parse_error_as_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.tqn");
+    fs::write(&path, "id 1\nname \"Bob\n").unwrap();
+
+    let diags = diagnostics(&["build"], &path);
+    assert_eq!(diags.len(), 1);
+    let d = &diags[0];
+    assert_eq!(d["level"], "error");
+    assert_eq!(d["message"], "unterminated string literal");
+    assert_eq!(d["file"], path.to_str().unwrap());
+    assert_eq!(d["line"], 2);
+    assert_eq!(d["column"], 6);
+    assert_eq!(d["span_end_line"], 3);
+    assert_eq!(d["span_end_column"], 1);
+}
+
+#[test]
+fn test_validate_reports_every_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("docs.tqn");
+    fs::write(&path, "a 1\n}\n===\nok true\n===\n!use Nope\n").unwrap();
+
+    let diags = diagnostics(&["validate"], &path);
+    assert_eq!(diags.len(), 2);
+    assert_eq!(diags[0]["line"], 2);
+    assert_eq!(diags[1]["line"], 6);
+    assert_eq!(diags[1]["column"], 6);
+    assert_eq!(diags[1]["span_end_column"], 10);
+}
+
+#[test]
+fn test_exec_and_option_errors_are_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.tqq");
+    fs::write(&path, "!if 1 == 1\na 1\n").unwrap();
+
+    let diags = diagnostics(&["exec"], &path);
+    assert_eq!(diags.len(), 1);
+    assert!(
+        diags[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Unterminated !if")
+    );
+
+    // Failures without a source location are still reported as JSON
+    let diags = diagnostics(&["build"], &dir.path().join("missing.tqn"));
+    assert!(
+        diags[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Failed to read")
+    );
+}
+
+#[test]
+fn test_validate_reports_warnings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("warn.tqn");
+    fs::write(&path, "!def User id name\n!use User\n1 Alice\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["validate"])
+        .arg(&path)
+        .arg("--json-errors")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let diags: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(diags[0]["level"], "warning");
+    assert_eq!(diags[0]["line"], 2);
+}
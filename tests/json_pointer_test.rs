@@ -0,0 +1,40 @@
+use serde_json::json;
+use std::process::Command;
+
+#[test]
+fn test_json_pointer() {
+    let value = json!({"users": [{"name": "Alice"}], "a/b": {"~": 1}});
+    assert_eq!(
+        tauq::json_pointer(&value, "/users/0/name"),
+        Some(&json!("Alice"))
+    );
+    assert_eq!(tauq::json_pointer(&value, "/a~1b/~0"), Some(&json!(1)));
+    assert_eq!(tauq::json_pointer(&value, ""), Some(&value));
+
+    assert_eq!(tauq::json_pointer(&value, "users/0"), None);
+    assert_eq!(tauq::json_pointer(&value, "/users/1/name"), None);
+    assert_eq!(tauq::json_pointer(&value, "/users/01"), None);
+}
+
+#[test]
+fn test_query_cli_with_pointer() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("users.tqn");
+    std::fs::write(&path, "!def User id name\n1 Alice\n2 Bob\n").unwrap();
+
+    let query = |pointer: &str| {
+        Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .arg("query")
+            .arg(&path)
+            .arg(pointer)
+            .output()
+            .unwrap()
+    };
+    let output = query("/1/name");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "\"Bob\"\n");
+
+    let output = query("/5/name");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No value at pointer /5/name"));
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::process::Command;
+use tauq::tauq::{LintDiagnostic, LintSeverity, Linter};
+
+fn lint(source: &str) -> Vec<LintDiagnostic> {
+    let mut parser = tauq::Parser::new(source);
+    let value = parser.parse().unwrap();
+    Linter::new().lint(&value, parser.take_warnings(), source)
+}
+
+fn rules(diagnostics: &[LintDiagnostic]) -> Vec<&str> {
+    diagnostics.iter().map(|d| d.rule).collect()
+}
+
+#[test]
+fn test_unused_schema() {
+    let diagnostics = lint("!def User id name\n!def Order id total\n!use User\n1 Alice\n");
+    assert_eq!(rules(&diagnostics), ["unused-schemas"]);
+    assert_eq!(diagnostics[0].severity, LintSeverity::Warning);
+    assert_eq!(
+        diagnostics[0].message,
+        "Schema 'Order' is defined but never used"
+    );
+    assert_eq!(
+        (diagnostics[0].span.line, diagnostics[0].span.column),
+        (2, 6)
+    );
+}
+
+#[test]
+fn test_schemas_used_by_rows_references_and_types() {
+    let source = "!schemas\n\
+                  Address street city\n\
+                  Person name home:Address\n\
+                  Employee !extends Person team\n\
+                  ---\n\
+                  staff [\n!use Employee\nAl { s c } ops\n]\n\
+                  !def Tag\n\n";
+    let diagnostics = lint(source);
+    // A zero-field schema with rows is a marker, not an empty schema
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_type_reference_before_definition() {
+    let diagnostics =
+        lint("!def Person name home:Address\n!def Address street city\n!use Person\nAl { s c }\n");
+    assert_eq!(rules(&diagnostics), ["use-before-def"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "Schema 'Address' is referenced before its definition on line 2"
+    );
+}
+
+#[test]
+fn test_shadowed_keys() {
+    let diagnostics = lint("port 80\nserver { host a host b }\nport 8080\n");
+    assert_eq!(rules(&diagnostics), ["shadow-keys", "shadow-keys"]);
+    assert_eq!(
+        diagnostics[0].message,
+        "Key 'host' is already set on line 2; only the last value is kept (\"b\")"
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "Key 'port' is already set on line 1; only the last value is kept (8080)"
+    );
+
+    // Objects split by a directive are separate values
+    assert!(
+        lint("a 1\n!def U x\n1\n2\na 2\n")
+            .iter()
+            .all(|d| d.rule != "shadow-keys")
+    );
+}
+
+#[test]
+fn test_long_rows() {
+    let diagnostics = lint("!def User id name\n1 Alice 2 Bob\n3 Cy\n");
+    assert_eq!(rules(&diagnostics), ["long-rows"]);
+    let span = diagnostics[0].span;
+    assert_eq!((span.line, span.column, span.end_column), (2, 9, 14));
+}
+
+#[test]
+fn test_parser_warnings_pass_through_as_info() {
+    let diagnostics = lint("!def User id name\n!use User\n1 Alice\n");
+    assert_eq!(rules(&diagnostics), ["redundant-use"]);
+    assert_eq!(diagnostics[0].severity, LintSeverity::Info);
+}
+
+#[test]
+fn test_rule_selection() {
+    let source = "x 1\nx 2\n!def Unused a\n";
+    let mut parser = tauq::Parser::new(source);
+    let value = parser.parse().unwrap();
+    let linter = Linter::with_rules(["shadow-keys"]).unwrap();
+    assert_eq!(
+        rules(&linter.lint(&value, parser.take_warnings(), source)),
+        ["shadow-keys"]
+    );
+
+    let err = Linter::with_rules(["all", "typo"]).unwrap_err();
+    assert!(err.contains("Unknown lint rule 'typo'"), "{}", err);
+}
+
+fn run_lint(source: &str, args: &[&str]) -> std::process::Output {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.tqn");
+    fs::write(&input, source).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("lint")
+        .arg(&input)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_lint_cli() {
+    let source = "!def User id name\n!def Order id\n!use User\n1 Alice\n";
+    let output = run_lint(source, &[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(":2:6: warning[unused-schemas]: Schema 'Order' is defined but never used"),
+        "{}",
+        stdout
+    );
+
+    let output = run_lint(source, &["--error-on-warn"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    // Disabled rules neither print nor fail
+    let output = run_lint(source, &["--rules", "shadow-keys", "--error-on-warn"]);
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("No lint issues")
+    );
+
+    let output = run_lint("!use Missing\n1\n", &[]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,109 @@
+use serde_json::json;
+use tauq::tauq::streaming::StreamingParser;
+use tauq::{Formatter, Parser};
+
+#[test]
+fn test_blank_lines_are_empty_rows() {
+    let source = "!def Tag; !use Tag\n\n\n";
+    assert_eq!(tauq::compile_tauq(source).unwrap(), json!([{}, {}, {}]));
+
+    let records: Vec<_> = StreamingParser::new(source)
+        .map(|record| record.unwrap())
+        .collect();
+    assert_eq!(records, vec![json!({}); 3]);
+}
+
+#[test]
+fn test_final_newline_is_not_a_row() {
+    assert_eq!(tauq::compile_tauq("!def Tag\n").unwrap(), json!([]));
+    assert_eq!(tauq::compile_tauq("!def Tag\n\n").unwrap(), json!([{}, {}]));
+    assert_eq!(StreamingParser::new("!def Tag\n").count(), 0);
+}
+
+#[test]
+fn test_values_under_a_marker_are_an_error() {
+    let source = "!def Tag\n\nkey value\n";
+    let err = tauq::compile_tauq(source).unwrap_err().to_string();
+    assert!(err.contains("Schema 'Tag' has no fields"), "{}", err);
+
+    let records: Vec<_> = StreamingParser::new(source).collect();
+    assert_eq!(records.len(), 2, "{:?}", records);
+    assert_eq!(records[0].as_ref().unwrap(), &json!({}));
+    assert_eq!(records[1].as_ref().unwrap_err().span.line, 3);
+
+    let mut rows = 0;
+    struct Count<'a>(&'a mut usize);
+    impl tauq::tauq::TauqHandler for Count<'_> {
+        fn on_row_start(&mut self, _schema: &str) {
+            *self.0 += 1;
+        }
+    }
+    let err = tauq::tauq::parse_sax(source, &mut Count(&mut rows)).unwrap_err();
+    assert_eq!(err.span.line, 3);
+    assert_eq!(rows, 1);
+}
+
+#[test]
+fn test_used_markers_are_not_empty_schemas() {
+    for source in ["!def Tag\n\n\n", "!def Tag\n!def Event id\n1\n!use Tag\n"] {
+        let (_, warnings) = tauq::compile_tauq_verbose(source).unwrap();
+        assert!(warnings.is_empty(), "{:?}: {:?}", source, warnings);
+
+        let mut parser = StreamingParser::new(source);
+        while parser.next_record().is_some() {}
+        assert!(parser.drain_warnings().is_empty(), "{:?}", source);
+    }
+
+    let (_, warnings) = tauq::compile_tauq_verbose("!def Tag\n!def Event id\n1\n").unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, tauq::WarningKind::EmptySchema);
+    assert_eq!(warnings[0].span.line, 1);
+}
+
+#[test]
+fn test_markers_between_tables() {
+    let source =
+        "!def EventStart\n!def Event id name\n1 boot\n!use EventStart\n\n!use Event\n2 halt\n";
+    assert_eq!(
+        tauq::compile_tauq(source).unwrap(),
+        json!([{"id": 1, "name": "boot"}, {}, {"id": 2, "name": "halt"}])
+    );
+}
+
+#[test]
+fn test_sax_reports_empty_rows() {
+    #[derive(Default)]
+    struct Rows(Vec<String>);
+    impl tauq::tauq::TauqHandler for Rows {
+        fn on_row_start(&mut self, schema: &str) {
+            self.0.push(schema.to_string());
+        }
+    }
+
+    let mut rows = Rows::default();
+    tauq::tauq::parse_sax("!def Tag; !use Tag\n\n\n", &mut rows).unwrap();
+    assert_eq!(rows.0, ["Tag", "Tag", "Tag"]);
+}
+
+#[test]
+fn test_format_array_of_empty_objects() {
+    let value = json!([{}, {}, {}]);
+    let text = Formatter::new().format(&value);
+    assert_eq!(text, "!def Marker\n\n\n");
+    assert_eq!(tauq::compile_tauq(&text).unwrap(), value);
+    assert_eq!(
+        Formatter::new().detect_uniform_objects(value.as_array().unwrap()),
+        Some(vec![])
+    );
+
+    // Blank rows cannot be told apart inside a list or a minified document
+    for (value, formatter) in [
+        (json!({"events": [{}, {}]}), Formatter::new()),
+        (json!([{}, {}]), Formatter::new().minified()),
+        (json!([{}]), Formatter::new()),
+    ] {
+        let text = formatter.format(&value);
+        assert!(!text.contains("!def"), "{}", text);
+        assert_eq!(Parser::new(&text).parse().unwrap(), value, "{}", text);
+    }
+}
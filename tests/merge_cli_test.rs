@@ -0,0 +1,135 @@
+use serde_json::json;
+use std::fs;
+use std::process::{Command, Output};
+
+/// Write `files` into a temp dir and run `tauq merge` on them
+fn merge(files: &[&str], extra: &[&str]) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<_> = files
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            let path = dir.path().join(format!("part{}.tqn", i));
+            fs::write(&path, source).unwrap();
+            path
+        })
+        .collect();
+
+    Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("merge")
+        .args(&paths)
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+fn merged_json(output: &Output) -> serde_json::Value {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    tauq::compile_tauq(&String::from_utf8_lossy(&output.stdout)).unwrap()
+}
+
+#[test]
+fn test_merge_tables_with_same_schema() {
+    let output = merge(
+        &[
+            "!def User id name\n1 Alice\n2 Bob\n",
+            "!def User id name\n3 Carol\n4 Dan\n",
+        ],
+        &[],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert_eq!(stdout.matches("!def User id name").count(), 1, "{}", stdout);
+    assert_eq!(
+        merged_json(&output),
+        json!([
+            {"id": 1, "name": "Alice"},
+            {"id": 2, "name": "Bob"},
+            {"id": 3, "name": "Carol"},
+            {"id": 4, "name": "Dan"}
+        ])
+    );
+
+    // A single-row file parses as one object and is appended as a row
+    let output = merge(
+        &[
+            "!def User id name\n1 Alice\n2 Bob\n",
+            "!def User id name\n3 Carol\n",
+        ],
+        &[],
+    );
+    assert_eq!(merged_json(&output).as_array().unwrap().len(), 3);
+
+    // Two single-row files are still two rows, not one merged object
+    let output = merge(
+        &["!def User id name\n1 Alice\n", "!def User id name\n2 Bob\n"],
+        &["--strategy", "error"],
+    );
+    assert_eq!(
+        merged_json(&output),
+        json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}])
+    );
+}
+
+#[test]
+fn test_conflicting_schemas() {
+    let files = [
+        "!def User id name\n1 Alice\n2 Bob\n",
+        "!def User id email\n3 c@example.com\n4 d@example.com\n",
+    ];
+    let output = merge(&files, &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Schema 'User'"), "{}", stderr);
+
+    let output = merge(&files, &["--allow-schema-conflicts"]);
+    assert_eq!(merged_json(&output).as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn test_merge_config_objects_with_overlapping_keys() {
+    let files = [
+        "name app\ndb { host localhost port 5432 }\ndebug false\n",
+        "db { port 6432 }\ndebug false\nreplicas 3\n",
+    ];
+
+    let output = merge(&files, &[]);
+    assert_eq!(
+        merged_json(&output),
+        json!({
+            "name": "app",
+            "db": {"host": "localhost", "port": 6432},
+            "debug": false,
+            "replicas": 3
+        })
+    );
+
+    let output = merge(&files, &["--strategy", "keep-first"]);
+    assert_eq!(merged_json(&output)["db"]["port"], 5432);
+
+    // Equal values never conflict
+    let output = merge(&files, &["--strategy", "error"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Conflicting values for 'db.port': 5432 vs 6432"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_merge_writes_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("merged.tqn");
+    let output = merge(&["a 1\n", "b 2\n"], &["-o", out.to_str().unwrap()]);
+    assert!(output.status.success());
+    let merged = fs::read_to_string(&out).unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&merged).unwrap(),
+        json!({"a": 1, "b": 2})
+    );
+}
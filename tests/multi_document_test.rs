@@ -0,0 +1,275 @@
+use serde_json::json;
+use std::process::Command;
+use tauq::tauq::{ParseMode, Parser};
+
+#[test]
+fn test_two_documents() {
+    let source = "name app\nport 8080\n===\nname worker\nport 9090\n";
+    let docs = Parser::new(source).parse_multi().unwrap();
+    assert_eq!(
+        docs,
+        vec![
+            json!({"name": "app", "port": 8080}),
+            json!({"name": "worker", "port": 9090}),
+        ]
+    );
+}
+
+#[test]
+fn test_three_documents_with_schemas() {
+    let source = "!def User id name\n1 Alice\n2 Bob\n===\n!def User id\n3\n===\nversion 2\n";
+    let docs = Parser::new(source).parse_multi().unwrap();
+    assert_eq!(docs.len(), 3);
+    assert_eq!(
+        docs[0],
+        json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}])
+    );
+    assert_eq!(docs[1], json!({"id": 3}));
+    assert_eq!(docs[2], json!({"version": 2}));
+
+    // Definitions do not carry over
+    let err = Parser::new("!def User id\n1\n===\n!use User\n2\n")
+        .parse_multi()
+        .unwrap_err();
+    assert!(err.message.contains("undefined schema 'User'"), "{}", err);
+}
+
+#[test]
+fn test_triple_dash_documents_keep_definitions() {
+    let source = "!def User id name\n1 Alice\n2 Bob\n---\n!use User\n3 Carol\n---\nversion 2\n";
+    let docs = Parser::new(source).parse_multi().unwrap();
+    assert_eq!(docs.len(), 3);
+    assert_eq!(
+        docs[0],
+        json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}])
+    );
+    // Definitions carry over; the active schema does not
+    assert_eq!(docs[1], json!({"id": 3, "name": "Carol"}));
+    assert_eq!(docs[2], json!({"version": 2}));
+
+    let docs = Parser::new("a 1\n---\nb 2\n---\nc 3\n")
+        .parse_multi()
+        .unwrap();
+    assert_eq!(
+        docs,
+        vec![json!({"a": 1}), json!({"b": 2}), json!({"c": 3})]
+    );
+}
+
+#[test]
+fn test_schema_block_separator_is_not_a_document_break() {
+    let source = "!schemas\nPoint x y\n---\n!use Point\n1 2\n---\n!use Point\n3 4\n";
+    let docs = Parser::new(source).parse_multi().unwrap();
+    assert_eq!(docs, vec![json!({"x": 1, "y": 2}), json!({"x": 3, "y": 4})]);
+
+    // parse_separated splits only at `===`
+    let docs = Parser::new(source).parse_separated().unwrap();
+    assert_eq!(docs, vec![json!([{"x": 1, "y": 2}, {"x": 3, "y": 4}])]);
+}
+
+#[test]
+fn test_empty_documents_are_skipped() {
+    let source = "===\na 1\n===\n===\nb 2\n===\n";
+    let docs = Parser::new(source).parse_multi().unwrap();
+    assert_eq!(docs, vec![json!({"a": 1}), json!({"b": 2})]);
+}
+
+#[test]
+fn test_single_document_mode_unchanged() {
+    let source = "!def User id name\n1 Alice\n---\nname app\n";
+    let result = Parser::new(source).parse().unwrap();
+    assert_eq!(result, json!([{"id": 1, "name": "Alice"}, {"name": "app"}]));
+
+    let result = Parser::new(source)
+        .with_mode(ParseMode::MultiDocument)
+        .parse()
+        .unwrap();
+    assert_eq!(result, json!([{"id": 1, "name": "Alice"}, {"name": "app"}]));
+    let result = Parser::new("a 1\n===\nb 2")
+        .with_mode(ParseMode::MultiDocument)
+        .parse()
+        .unwrap();
+    assert_eq!(result, json!([{"a": 1}, {"b": 2}]));
+}
+
+#[test]
+fn test_documents_fail_independently_at_triple_dash() {
+    let source = "a 1\n---\nb [1 2\n---\nc 3\n---\n!use Missing\n1\n";
+    let docs = Parser::new(source).parse_documents();
+    assert_eq!(docs.len(), 4);
+    assert_eq!(docs[0].as_ref().unwrap(), &json!({"a": 1}));
+    assert!(docs[1].is_err());
+    assert_eq!(docs[2].as_ref().unwrap(), &json!({"c": 3}));
+    assert!(docs[3].is_err());
+}
+
+#[test]
+fn test_documents_fail_independently() {
+    let source = "a 1\n===\nb [1 2\n===\nc 3\n===\n!use Missing\n1\n";
+    let docs = Parser::new(source).parse_documents();
+    assert_eq!(docs.len(), 4);
+    assert_eq!(docs[0].as_ref().unwrap(), &json!({"a": 1}));
+    assert!(docs[1].is_err());
+    assert_eq!(docs[2].as_ref().unwrap(), &json!({"c": 3}));
+    assert!(docs[3].is_err());
+
+    assert!(Parser::new(source).parse_multi().is_err());
+}
+
+#[test]
+fn test_validate_reports_each_document() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("docs.tqn");
+
+    for source in ["a 1\n===\nb 2\n", "a 1\n---\nb 2\n"] {
+        std::fs::write(&path, source).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .arg("validate")
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("(2 documents)"));
+    }
+
+    std::fs::write(&path, "a 1\n===\nb }\n===\nc 3\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("validate")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Document 2:"), "{}", stderr);
+    assert!(stderr.contains("1 of 3 documents"), "{}", stderr);
+}
+
+#[test]
+fn test_doc_separator_isolates_schemas() {
+    let source = "!def User id name\n1 Alice\n2 Bob\n===\n!use User\n3 Carol\n";
+    let err = tauq::compile_tauq_multi(source).unwrap_err();
+    assert!(
+        err.to_string().contains("undefined schema 'User'"),
+        "{}",
+        err
+    );
+    // Lines count from the start of the file
+    assert!(err.to_string().contains("line 5"), "{}", err);
+
+    let source =
+        "!def User id name\n1 Alice\n===\n!def User id email\n1 a@x.io\n===\nenv prod\n===\n";
+    let docs = tauq::compile_tauq_multi(source).unwrap();
+    assert_eq!(
+        docs,
+        vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 1, "email": "a@x.io"}),
+            json!({"env": "prod"}),
+        ]
+    );
+}
+
+#[test]
+fn test_doc_separator_rejected_by_single_document_parsers() {
+    let err = tauq::compile_tauq("a 1\n===\nb 2\n").unwrap_err();
+    assert!(err.to_string().contains("compile_tauq_multi"), "{}", err);
+
+    let mut parser = tauq::StreamingParser::new("a 1\n===\nb 2\n");
+    assert_eq!(parser.next_record().unwrap().unwrap(), json!({"a": 1}));
+    assert!(parser.next_record().unwrap().is_err());
+
+    // Inside a string it is just text
+    let docs = tauq::compile_tauq_multi("title \"===\"\n").unwrap();
+    assert_eq!(docs, vec![json!({"title": "==="})]);
+}
+
+#[test]
+fn test_streaming_multi_resets_schemas() {
+    let source = "!def User id name\n1 Alice\n===\n!def User id role\n1 admin\n===\n!use User\n";
+    let mut parser = tauq::StreamingParser::new_multi(source);
+    assert_eq!(
+        parser.next_record().unwrap().unwrap(),
+        json!({"id": 1, "name": "Alice"})
+    );
+    assert_eq!(
+        parser.next_record().unwrap().unwrap(),
+        json!({"id": 1, "role": "admin"})
+    );
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.message.contains("undefined schema"), "{}", err);
+}
+
+#[test]
+fn test_to_multi_doc_round_trip() {
+    let values = vec![
+        json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]),
+        json!({"env": "staging", "replicas": 2}),
+    ];
+    let text = tauq::to_multi_doc(&values);
+    assert_eq!(
+        text,
+        "!def Record id name\n1 Alice\n2 Bob\n===\nenv staging\nreplicas 2"
+    );
+    assert_eq!(tauq::compile_tauq_multi(&text).unwrap(), values);
+}
+
+#[test]
+fn test_build_multi() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("envs.tqn");
+    std::fs::write(&path, "env dev\n===\nenv prod\nreplicas 3\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("build")
+        .arg(&path)
+        .arg("--multi")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        json!([{"env": "dev"}, {"env": "prod", "replicas": 3}])
+    );
+}
+
+#[test]
+fn test_build_multi_strict() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("envs.tqn");
+    std::fs::write(
+        &path,
+        "!def Env name:string\ndev\n===\n!def Env port:int\nx\n",
+    )
+    .unwrap();
+
+    let build = |strict: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_tauq"));
+        command.arg("build").arg(&path).arg("--multi");
+        if strict {
+            command.arg("--strict");
+        }
+        command.output().unwrap()
+    };
+    assert!(build(false).status.success());
+    let output = build(true);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("line 5"));
+}
+
+#[test]
+fn test_lint_accepts_doc_separator() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("envs.tqn");
+    std::fs::write(&path, "env dev\n===\nenv prod\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("lint")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
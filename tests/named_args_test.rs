@@ -25,3 +25,38 @@ fn test_named_args() {
     assert_eq!(arr[2]["name"], "Carol");
     assert_eq!(arr[2]["role"], "admin");
 }
+
+#[test]
+fn test_named_args_wide_schema() {
+    let fields: Vec<String> = (0..50).map(|i| format!("f{}", i)).collect();
+    let row: Vec<String> = fields
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, f)| format!("{}:{}", f, i))
+        .collect();
+    let input = format!("!def Wide {}\n{}\n", fields.join(" "), row.join(" "));
+
+    let result = tauq::compile_tauq(&input).expect("Failed to parse");
+    assert_eq!(result["f49"].as_i64(), Some(0));
+    assert_eq!(result["f0"].as_i64(), Some(49));
+    assert_eq!(result.as_object().unwrap().len(), 50);
+}
+
+#[test]
+fn test_field_set_index() {
+    use tauq::tauq::parser::{FieldDef, FieldSet, TypeDef};
+
+    let set = FieldSet::new(vec![
+        FieldDef::new("id", TypeDef::Scalar),
+        FieldDef::new("owner", TypeDef::Object("User".to_string())),
+    ]);
+
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.index["owner"], 1);
+    assert!(matches!(
+        set.get("owner").map(|f| &f.type_def),
+        Some(TypeDef::Object(name)) if name == "User"
+    ));
+    assert!(set.get("missing").is_none());
+}
@@ -0,0 +1,121 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::process::Command;
+use tauq::{Formatter, ndjson_to_tauq, ndjson_to_tauq_writer, tauq_to_ndjson};
+
+fn ndjson(records: &[Value]) -> String {
+    records.iter().map(|r| format!("{}\n", r)).collect()
+}
+
+#[test]
+fn test_thousand_line_roundtrip() {
+    let records: Vec<Value> = (0..1000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "event": if i % 2 == 0 { "login" } else { "logout" },
+                "user": format!("user {}", i % 37),
+                "latency": i as f64 / 8.0,
+                "ok": i % 5 != 0,
+                "tag": if i % 7 == 0 { Value::Null } else { json!("web") }
+            })
+        })
+        .collect();
+    let input = ndjson(&records);
+
+    let tauq = ndjson_to_tauq(&input).unwrap();
+    assert!(
+        tauq.starts_with("!def Item id event user latency ok tag?\n"),
+        "{}",
+        tauq
+    );
+    assert_eq!(tauq, Formatter::new().format(&Value::Array(records)));
+    assert_eq!(tauq_to_ndjson(&tauq).unwrap(), input);
+
+    let mut written = Vec::new();
+    ndjson_to_tauq_writer(&input, &mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), tauq);
+}
+
+#[test]
+fn test_heterogeneous_records_stay_separate_objects() {
+    let records = [
+        json!({"id": 1, "name": "Alice"}),
+        json!({"id": 2, "email": "bob@example.com"}),
+        json!({"id": 3, "name": "Cy", "tags": ["a", "b"]}),
+    ];
+    let input = ndjson(&records);
+    let tauq = ndjson_to_tauq(&input).unwrap();
+    assert!(!tauq.contains("!def"), "{}", tauq);
+    assert_eq!(
+        tauq,
+        "[\n{ id 1 name Alice }\n{ id 2 email bob@example.com }\n{ id 3 name Cy tags [a b] }\n]"
+    );
+    assert_eq!(tauq, Formatter::new().format(&json!(records)));
+    assert_eq!(tauq_to_ndjson(&tauq).unwrap(), input);
+}
+
+#[test]
+fn test_edge_cases() {
+    // A single record is not a table, which would read back as an object
+    let tauq = ndjson_to_tauq("{\"a\":1}\n").unwrap();
+    assert_eq!(tauq_to_ndjson(&tauq).unwrap(), "{\"a\":1}\n");
+
+    // Blank lines are skipped; empty input is an empty array
+    assert_eq!(
+        ndjson_to_tauq("\n{\"a\":1}\n\n{\"a\":2}\n").unwrap(),
+        "!def Row a\n1\n2"
+    );
+    assert_eq!(ndjson_to_tauq("").unwrap(), "[]");
+    assert_eq!(tauq_to_ndjson("[]").unwrap(), "");
+
+    let err = ndjson_to_tauq("{\"a\":1}\n{\"a\":\n").unwrap_err();
+    assert!(
+        err.to_string().contains("Invalid JSON on line 2"),
+        "{}",
+        err
+    );
+
+    // Non-array documents are a single line
+    assert_eq!(
+        tauq_to_ndjson("name Alice").unwrap(),
+        "{\"name\":\"Alice\"}\n"
+    );
+}
+
+#[test]
+fn test_convert_cli_from_ndjson() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("events.log");
+    let output = dir.path().join("events.tqn");
+    fs::write(
+        &input,
+        "{\"id\":1,\"kind\":\"a\"}\n{\"id\":2,\"kind\":\"b\"}\n",
+    )
+    .unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["convert", input.to_str().unwrap(), "--from", "ndjson", "-o"])
+        .arg(&output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "!def Item id kind\n1 a\n2 b\n"
+    );
+
+    // .jsonl files are detected as NDJSON
+    let jsonl = dir.path().join("events.jsonl");
+    fs::write(&jsonl, "{\"id\":1}\nnot json\n").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["convert", jsonl.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    assert!(
+        String::from_utf8_lossy(&out.stderr).contains("Invalid JSON on line 2"),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
@@ -0,0 +1,103 @@
+use serde_json::json;
+use tauq::Parser;
+use tauq::error::Span;
+use tauq::tauq::{Formatter, StreamingParser};
+
+#[test]
+fn test_optional_trailing_field() {
+    let input = "!def User id name role?\n1 Alice admin\n2 Bob\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {"id": 1, "name": "Alice", "role": "admin"},
+            {"id": 2, "name": "Bob", "role": null}
+        ])
+    );
+}
+
+#[test]
+fn test_missing_required_field_is_an_error() {
+    let err = tauq::compile_tauq("!def User id name role?\n1\n").unwrap_err();
+    assert!(err.to_string().contains("name"), "{}", err);
+}
+
+#[test]
+fn test_missing_field_error_spans_the_row() {
+    let expected = Span::range(3, 1, 3, 4);
+    for input in ["!def U a b c\n1 2 3\n4 5", "!def U a b c\n1 2 3\n4 5\n"] {
+        let err = Parser::new(input).parse().unwrap_err();
+        assert_eq!(err.span, expected, "{:?}", input);
+
+        let err = StreamingParser::new(input).nth(1).unwrap().unwrap_err();
+        assert_eq!(err.span, expected, "{:?}", input);
+
+        let mut handler = tauq::tauq::CollectingHandler::new();
+        let err = tauq::tauq::parse_sax(input, &mut handler).unwrap_err();
+        assert_eq!(err.span, expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_optional_field_with_type_and_named_args() {
+    let input = "!def Addr city\n!def User id addr?:Addr\n1 { Paris }\n2\nid:3 addr:{ Rome }\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json[0]["addr"]["city"], "Paris");
+    assert_eq!(json[1]["addr"], json!(null));
+    assert_eq!(json[2]["addr"]["city"], "Rome");
+}
+
+#[test]
+fn test_streaming_and_sax_fill_optional_fields() {
+    let input = "!def User id name role?\n1 Alice admin\n2 Bob\n";
+    let rows: Vec<_> = StreamingParser::new(input).map(|r| r.unwrap()).collect();
+    assert_eq!(rows[1], json!({"id": 2, "name": "Bob", "role": null}));
+
+    let mut handler = tauq::tauq::CollectingHandler::new();
+    tauq::tauq::parse_sax(input, &mut handler).unwrap();
+    let values = handler.into_values();
+    assert_eq!(values[1], json!({"id": 2, "name": "Bob", "role": null}));
+}
+
+#[test]
+fn test_formatter_marks_nullable_fields_optional() {
+    let value = json!([
+        {"id": 1, "name": "Alice", "role": "admin"},
+        {"id": 2, "name": "Bob", "role": null},
+        {"id": 3, "name": null, "role": null}
+    ]);
+    let tauq = Formatter::new().format(&value);
+    assert!(tauq.starts_with("!def Record id name? role?\n"), "{}", tauq);
+    assert!(tauq.ends_with("\n2 Bob\n3"), "{}", tauq);
+    assert_eq!(tauq::compile_tauq(&tauq).unwrap(), value);
+
+    let nested = json!({"users": value});
+    let tauq = Formatter::new().format(&nested);
+    assert!(tauq.contains("!def User id name? role?"), "{}", tauq);
+    assert_eq!(tauq::compile_tauq(&tauq).unwrap(), nested);
+
+    let minified = Formatter::new().minified().format(&nested);
+    assert_eq!(tauq::compile_tauq(&minified).unwrap(), nested);
+}
+
+#[test]
+fn test_streaming_and_sax_reject_missing_required_fields() {
+    let input = "!def User id name role?\n1 Alice\n2\n";
+    let message = "Missing value for required field 'name' in schema 'User'";
+
+    let mut parser = StreamingParser::new(input);
+    assert!(parser.next_record().unwrap().is_ok());
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+
+    let mut parser = tauq::tauq::PushParser::new();
+    parser.push(input);
+    parser.finish();
+    assert!(parser.next_record().unwrap().is_ok());
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+
+    let mut handler = tauq::tauq::CollectingHandler::new();
+    let err = tauq::tauq::parse_sax(input, &mut handler).unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauq::tauq::tauqq::{self, PackOptions, ProcessConfig};
+
+/// Write `files` into `dir`, creating subdirectories as needed
+fn write_files(dir: &Path, files: &[(&str, &str)]) {
+    for (name, source) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+}
+
+/// Value of the entry file with its imports inlined in place
+fn original_value(entry: &Path) -> serde_json::Value {
+    let config = ProcessConfig {
+        base_dir: entry.parent().map(|p| p.to_path_buf()),
+        ..Default::default()
+    };
+    let source = fs::read_to_string(entry).unwrap();
+    let processed = tauqq::process_with_config(&source, &mut HashMap::new(), &config).unwrap();
+    tauq::compile_tauq(&processed).unwrap()
+}
+
+#[test]
+fn test_pack_inlines_schemas_and_data() {
+    let dir = tempfile::tempdir().unwrap();
+    write_files(
+        dir.path(),
+        &[
+            ("schemas/users.tqn", "# User records\n!def User id name\n"),
+            (
+                "schemas/orders.tqn",
+                "!import \"users.tqn\"\n!def Order id user_id total\n",
+            ),
+            ("data.tqn", "!use User\n1 Alice\n2 \"Bob Smith\"\n"),
+            (
+                "main.tqn",
+                "# Shop export\n!import \"schemas/users.tqn\"\n!import \"schemas/orders.tqn\"\n!import \"data.tqn\"\n!use Order\n10 1 99.5\n11 2 5\n",
+            ),
+        ],
+    );
+    let entry = dir.path().join("main.tqn");
+
+    let packed = tauqq::pack(&entry, PackOptions::default()).unwrap();
+    assert!(
+        packed.starts_with("!schemas\nUser id name\nOrder id user_id total\n---\n"),
+        "{}",
+        packed
+    );
+    assert!(!packed.contains("!import"), "{}", packed);
+    assert!(!packed.contains("!def"), "{}", packed);
+    assert!(packed.contains("# User records"), "{}", packed);
+    assert_eq!(tauq::compile_tauq(&packed).unwrap(), original_value(&entry));
+
+    let stripped = tauqq::pack(
+        &entry,
+        PackOptions {
+            strip_comments: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!stripped.contains('#'), "{}", stripped);
+    assert_eq!(
+        tauq::compile_tauq(&stripped).unwrap(),
+        original_value(&entry)
+    );
+}
+
+#[test]
+fn test_conflicting_schemas_are_prefixed() {
+    let dir = tempfile::tempdir().unwrap();
+    write_files(
+        dir.path(),
+        &[
+            ("catalog.tqn", "!def Item id name\n1 Lamp\n"),
+            ("stock.tqn", "!def Item sku qty\nA7 3\n"),
+            ("tags.tqn", "!def Item id name\n2 Desk\n"),
+            (
+                "main.tqn",
+                "!import \"catalog.tqn\"\n!import \"stock.tqn\"\n!use Item\nB2 9\n!import \"tags.tqn\"\n",
+            ),
+        ],
+    );
+    let entry = dir.path().join("main.tqn");
+
+    let packed = tauqq::pack(&entry, PackOptions::default()).unwrap();
+    assert!(
+        packed.starts_with("!schemas\nItem id name\nstock_Item sku qty\n---\n"),
+        "{}",
+        packed
+    );
+    assert!(packed.contains("!use stock_Item\nA7 3\n!use stock_Item\nB2 9\n"));
+    assert_eq!(tauq::compile_tauq(&packed).unwrap(), original_value(&entry));
+
+    let err = tauqq::pack(
+        &entry,
+        PackOptions {
+            no_prefix: true,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Schema 'Item' in 'stock' conflicts"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_circular_imports_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    write_files(
+        dir.path(),
+        &[
+            ("a.tqn", "!import \"b.tqn\"\n!def A x\n"),
+            ("b.tqn", "!import \"a.tqn\"\n!def B y\n"),
+        ],
+    );
+
+    let err = tauqq::pack(&dir.path().join("a.tqn"), PackOptions::default()).unwrap_err();
+    assert!(err.contains("Circular import detected"), "{}", err);
+}
+
+#[test]
+fn test_pack_command_writes_output() {
+    let dir = tempfile::tempdir().unwrap();
+    write_files(
+        dir.path(),
+        &[
+            ("users.tqn", "!def User id name\n"),
+            ("main.tqn", "!import \"users.tqn\"\n# people\n1 Alice\n"),
+        ],
+    );
+    let out = dir.path().join("packed.tqn");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("pack")
+        .arg(dir.path().join("main.tqn"))
+        .arg("-o")
+        .arg(&out)
+        .arg("--strip-comments")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        "!schemas\nUser id name\n---\n!use User\n1 Alice\n"
+    );
+}
+
+#[test]
+fn test_pack_resolves_sibling_directory_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    write_files(
+        dir.path(),
+        &[
+            ("schemas/users.tqn", "!def User id name\n"),
+            (
+                "config/main.tqn",
+                "!import \"../schemas/users.tqn\"\n1 Alice\n",
+            ),
+        ],
+    );
+
+    let packed = tauqq::pack(&dir.path().join("config/main.tqn"), PackOptions::default()).unwrap();
+    assert_eq!(packed, "!schemas\nUser id name\n---\n!use User\n1 Alice\n");
+}
+
+#[test]
+fn test_directives_in_strings_and_heredocs_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = "!def Note id body\n1 <<<EOT\n!def Fake a b\n!use Fake\n# not a comment\nEOT\n2 \"!use Note\"\n";
+    write_files(dir.path(), &[("main.tqn", main)]);
+    let entry = dir.path().join("main.tqn");
+
+    let packed = tauqq::pack(
+        &entry,
+        PackOptions {
+            strip_comments: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        packed,
+        format!("!schemas\nNote id body\n---\n!use Note{}", &main[17..])
+    );
+    assert_eq!(
+        tauq::compile_tauq(&packed).unwrap(),
+        tauq::compile_tauq(main).unwrap()
+    );
+}
@@ -0,0 +1,65 @@
+use tauq::tauq::streaming::StreamingParser;
+use tauq::{Parser, WarningKind};
+
+fn warnings(src: &str) -> Vec<(WarningKind, usize)> {
+    let (_, warnings) = tauq::compile_tauq_verbose(src).unwrap();
+    warnings.iter().map(|w| (w.kind, w.span.line)).collect()
+}
+
+#[test]
+fn test_redundant_use() {
+    let src = "!def User id name\n!use User\n1 Alice\n!def Role title\nadmin\n!use User\n2 Bob\n";
+    assert_eq!(warnings(src), vec![(WarningKind::RedundantUse, 2)]);
+}
+
+#[test]
+fn test_empty_schema() {
+    let (_, found) = tauq::compile_tauq_verbose("!def Marker\n---\nok true\n").unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].kind, WarningKind::EmptySchema);
+    assert!(found[0].message.contains("'Marker'"), "{}", found[0]);
+
+    // Fields inherited from a parent count
+    assert!(warnings("!def Base id\n!def Child !extends Base\n1\n").is_empty());
+}
+
+#[test]
+fn test_short_row_with_optional_fields() {
+    let src = "!def User id name role? team=core\n1 Alice admin core\n2 Bob\n3 Carol dev\n";
+    let (json, found) = tauq::compile_tauq_verbose(src).unwrap();
+    assert_eq!(json[1]["role"], serde_json::Value::Null);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].kind, WarningKind::ShortRow);
+    assert_eq!(found[0].span.line, 3);
+    assert!(found[0].message.contains("role, team"), "{}", found[0]);
+    assert_eq!(found[1].span.line, 4);
+
+    // Named arguments choose their fields explicitly
+    assert!(warnings("!def User id name role?\nid:1 name:Alice\n").is_empty());
+}
+
+#[test]
+fn test_take_warnings_drains() {
+    let mut parser = Parser::new("!def A x\n!use A\n1\n");
+    parser.parse().unwrap();
+    assert_eq!(parser.take_warnings().len(), 1);
+    assert!(parser.take_warnings().is_empty());
+}
+
+#[test]
+fn test_streaming_drain_between_records() {
+    let mut parser = StreamingParser::new("!def User id name?\n1 Alice\n2\n!use User\n3 Carol\n");
+
+    parser.next_record().unwrap().unwrap();
+    assert!(parser.drain_warnings().is_empty());
+
+    parser.next_record().unwrap().unwrap();
+    let found = parser.drain_warnings();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].kind, WarningKind::ShortRow);
+
+    parser.next_record().unwrap().unwrap();
+    let found = parser.drain_warnings();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].kind, WarningKind::RedundantUse);
+}
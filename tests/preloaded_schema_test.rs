@@ -0,0 +1,71 @@
+use tauq::{Parser, StreamingParser};
+
+const HEADER: &str = "!def User id name role?\n!def Tag label\n";
+
+fn rows(n: usize) -> String {
+    (0..n)
+        .map(|i| {
+            format!(
+                "{} user{} {}\n",
+                i,
+                i,
+                if i % 3 == 0 { "admin" } else { "" }
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_preloaded_schemas_match_inline_def() {
+    let rows = rows(1000);
+    let inline_source = format!("{}!use User\n{}", HEADER, rows);
+    let expected = tauq::compile_tauq(&inline_source).unwrap();
+    assert_eq!(expected.as_array().unwrap().len(), 1000);
+
+    let schemas = Parser::extract_schemas(HEADER).unwrap();
+    let snippet = format!("!use User\n{}", rows);
+    let value = Parser::new_with_schemas(&snippet, schemas.clone())
+        .parse()
+        .unwrap();
+    assert_eq!(value, expected);
+
+    let streamed: Vec<_> = StreamingParser::new_with_schemas(&snippet, schemas)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(serde_json::Value::Array(streamed), expected);
+}
+
+#[test]
+fn test_preloaded_schemas_are_not_active() {
+    let schemas = Parser::extract_schemas(HEADER).unwrap();
+    let value = Parser::new_with_schemas("name Alice\n", schemas.clone())
+        .parse()
+        .unwrap();
+    assert_eq!(value, serde_json::json!({"name": "Alice"}));
+
+    // Snippets can still define and override schemas of their own
+    let value = Parser::new_with_schemas("!def User id\n7\n", schemas)
+        .parse()
+        .unwrap();
+    assert_eq!(value, serde_json::json!({"id": 7}));
+}
+
+#[test]
+fn test_extract_schemas_stops_at_header_end() {
+    let schemas =
+        Parser::extract_schemas("!schemas\nPoint x y\n---\n!def Tag label\nred\n!def Late a\n")
+            .unwrap();
+    let mut names: Vec<_> = schemas.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["Point", "Tag"]);
+    let fields: Vec<_> = schemas["Point"].iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["x", "y"]);
+
+    // Nothing after the first `---` is read
+    let schemas = Parser::extract_schemas("!def A a\n---\n!def B b\n").unwrap();
+    assert!(schemas.contains_key("A") && !schemas.contains_key("B"));
+
+    // Values after the header are not validated
+    assert!(Parser::extract_schemas("!def A a\n1 } ]\n").is_ok());
+    assert!(Parser::extract_schemas("!use Missing\n").is_err());
+}
@@ -136,6 +136,26 @@ mod edge_cases {
         let _ = compile_tauq(input);
     }
 
+    /// Regression: the streaming iterator yielded the same error forever when
+    /// an error left the offending token unconsumed.
+    #[test]
+    fn test_fuzz_streaming_errors_make_progress() {
+        for input in ["x:[User]", "key ]", "}", "a 1\n]\nb 2"] {
+            let records: Vec<_> = tauq::StreamingParser::new(input).collect();
+            assert!(records.iter().any(|r| r.is_err()), "{:?}", input);
+            assert!(records.len() <= input.len() + 1, "{:?}", input);
+        }
+    }
+
+    /// Regression: a `${` reference followed by a multi-byte character panicked
+    /// on a non-char-boundary slice in the TauqQ preprocessor.
+    #[test]
+    fn test_fuzz_unterminated_reference_before_multibyte() {
+        let mut vars = std::collections::HashMap::new();
+        let err = tauq::tauq::tauqq::process("!version ${é", &mut vars, true).unwrap_err();
+        assert!(err.contains("Unterminated"), "{}", err);
+    }
+
     // -------------------------------------------------------------------------
     // Medium dataset via !def schema
     // -------------------------------------------------------------------------
@@ -0,0 +1,110 @@
+//! Property-based round-trip tests: `compile_tauq(json_to_tauq(v)) == v`
+
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+/// Strings mixing plain text with characters that need quoting or escaping
+/// and words that read as other literals (`true`, `null`, `42`, `-1.5`)
+fn arb_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z_][a-zA-Z0-9_]{0,10}",
+        "[ -~]{0,12}",
+        "[a-z \\n\\t\"\\\\#!:;,{}\\[\\]-]{0,12}",
+        Just("true".to_string()),
+        Just("false".to_string()),
+        Just("null".to_string()),
+        Just("---".to_string()),
+        any::<i64>().prop_map(|n| n.to_string()),
+        (-1e6f64..1e6).prop_map(|f| f.to_string()),
+    ]
+}
+
+fn arb_key() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "[a-zA-Z_][a-zA-Z0-9_]{0,8}",
+        1 => "[ -~]{1,8}",
+    ]
+}
+
+fn arb_leaf() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        (-1e9f64..1e9).prop_map(Value::from),
+        arb_string().prop_map(Value::String),
+    ]
+}
+
+/// JSON trees up to 4 levels deep
+fn arb_value() -> impl Strategy<Value = Value> {
+    arb_leaf().prop_recursive(4, 48, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::vec((arb_key(), inner), 0..6)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect::<Map<_, _>>())),
+        ]
+    })
+}
+
+/// Uniform arrays of objects, the shape that gets `!def` schemas
+fn arb_table() -> impl Strategy<Value = Value> {
+    prop::collection::vec(arb_key(), 1..5).prop_flat_map(|keys| {
+        let width = keys.len();
+        prop::collection::vec(prop::collection::vec(arb_leaf(), width), 1..8).prop_map(
+            move |rows| {
+                Value::Array(
+                    rows.into_iter()
+                        .map(|row| Value::Object(keys.iter().cloned().zip(row).collect()))
+                        .collect(),
+                )
+            },
+        )
+    })
+}
+
+/// Compare numbers by their f64 value: `1.0` and `1` are the same number
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => n
+            .as_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .unwrap_or_else(|| value.clone()),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        Value::Object(map) => {
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), normalize(v))).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+fn assert_round_trip(value: &Value, tauq: &str) -> Result<(), TestCaseError> {
+    let parsed = tauq::compile_tauq(tauq)
+        .map_err(|e| TestCaseError::fail(format!("{}\n--- tauq ---\n{}", e, tauq)))?;
+    prop_assert_eq!(normalize(&parsed), normalize(value), "tauq:\n{}", tauq);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn formatted_value_round_trips(value in arb_value()) {
+        assert_round_trip(&value, &tauq::json_to_tauq(&value))?;
+    }
+
+    #[test]
+    fn formatted_table_round_trips(value in arb_table()) {
+        assert_round_trip(&value, &tauq::json_to_tauq(&value))?;
+    }
+
+    #[test]
+    fn minified_matches_pretty(value in prop_oneof![arb_value(), arb_table()]) {
+        let minified = tauq::minify_tauq(&value);
+        let pretty = tauq::compile_tauq(&tauq::json_to_tauq(&value)).unwrap();
+        let parsed = tauq::compile_tauq(&minified)
+            .map_err(|e| TestCaseError::fail(format!("{}\n--- tauq ---\n{}", e, minified)))?;
+        prop_assert_eq!(normalize(&parsed), normalize(&pretty), "minified:\n{}", minified);
+    }
+}
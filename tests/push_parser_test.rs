@@ -0,0 +1,128 @@
+use serde_json::{Value, json};
+use tauq::tauq::PushParser;
+
+fn drain(parser: &mut PushParser, out: &mut Vec<Value>) {
+    while let Some(record) = parser.next_record() {
+        out.push(record.unwrap());
+    }
+}
+
+fn table(rows: usize) -> String {
+    let mut source = String::from("!def User id name active\n");
+    for i in 0..rows {
+        source.push_str(&format!("{} \"user {}\" {}\n", i, i, i % 2 == 0));
+    }
+    source
+}
+
+/// Split `source` into `n` chunks at arbitrary byte positions
+fn chunks(source: &str, n: usize) -> Vec<&str> {
+    let size = source.len().div_ceil(n);
+    let mut out = Vec::new();
+    let mut rest = source;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        out.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    out
+}
+
+#[test]
+fn test_thousand_rows_in_ten_chunks() {
+    let source = table(1000);
+    let mut parser = PushParser::new();
+    let mut records = Vec::new();
+    let parts = chunks(&source, 10);
+    assert_eq!(parts.len(), 10);
+    for chunk in parts {
+        parser.push(chunk);
+        drain(&mut parser, &mut records);
+    }
+    parser.finish();
+    drain(&mut parser, &mut records);
+
+    assert_eq!(records.len(), 1000);
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(
+            record,
+            &json!({"id": i, "name": format!("user {}", i), "active": i % 2 == 0})
+        );
+    }
+}
+
+#[test]
+fn test_matches_whole_source_parse() {
+    let source = "!def User id tags\n1 [a b\n c]\n2 \"multi\nline\"\n---\nx { y 1 }\n";
+    let expected: Vec<Value> = tauq::StreamingParser::new(source)
+        .map(Result::unwrap)
+        .collect();
+    for size in [1, 2, 3, 7] {
+        let mut parser = PushParser::new();
+        let mut records = Vec::new();
+        for chunk in chunks(source, source.len().div_ceil(size)) {
+            parser.push(chunk);
+            drain(&mut parser, &mut records);
+        }
+        parser.finish();
+        drain(&mut parser, &mut records);
+        assert_eq!(records, expected, "chunk size {}", size);
+    }
+}
+
+#[test]
+fn test_waits_for_complete_lines() {
+    let mut parser = PushParser::new();
+    parser.push("!def Point x y\n1 2\n3 ");
+    assert_eq!(
+        parser.next_record().unwrap().unwrap(),
+        json!({"x": 1, "y": 2})
+    );
+    assert!(parser.next_record().is_none());
+    parser.push("4");
+    assert!(parser.next_record().is_none());
+    parser.finish();
+    assert_eq!(
+        parser.next_record().unwrap().unwrap(),
+        json!({"x": 3, "y": 4})
+    );
+    assert!(parser.next_record().is_none());
+}
+
+#[test]
+fn test_parse_error_after_earlier_records() {
+    let mut parser = PushParser::new();
+    parser.push("!def User id name\n1 Alice\n!use Missing\n3 Carol\n");
+    parser.finish();
+    assert_eq!(parser.next_record().unwrap().unwrap()["name"], "Alice");
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert_eq!(err.span.line, 3, "{}", err);
+}
+
+#[test]
+fn test_unterminated_string_reported_at_finish() {
+    let mut parser = PushParser::new();
+    parser.push("a 1\nb \"open\n");
+    // Key-value objects stay open until the input ends
+    assert!(parser.next_record().is_none());
+    parser.finish();
+    assert_eq!(parser.next_record().unwrap().unwrap(), json!({"a": 1}));
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert_eq!(err.message, "unterminated string literal");
+    assert_eq!(err.span.line, 2);
+    assert!(parser.next_record().is_none());
+}
+
+#[test]
+fn test_schemas_defined_so_far() {
+    let mut parser = PushParser::new();
+    parser.push("!def User id name\n1 Alice\n");
+    assert_eq!(parser.next_record().unwrap().unwrap()["name"], "Alice");
+    let schemas = parser.schemas();
+    let fields: Vec<&str> = schemas["User"].iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["id", "name"]);
+    assert!(!schemas.contains_key("Order"));
+}
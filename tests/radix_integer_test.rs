@@ -0,0 +1,39 @@
+use serde_json::json;
+use tauq::{Formatter, StreamingParser, compile_tauq};
+
+#[test]
+fn test_hex_binary_and_octal_values() {
+    assert_eq!(compile_tauq("flags 0xFF").unwrap(), json!({"flags": 255}));
+    assert_eq!(compile_tauq("mask 0b1010").unwrap(), json!({"mask": 10}));
+    assert_eq!(compile_tauq("addr -0x10").unwrap(), json!({"addr": -16}));
+    assert_eq!(compile_tauq("mode 0o644").unwrap(), json!({"mode": 420}));
+}
+
+#[test]
+fn test_radix_integers_in_rows_and_arrays() {
+    let source = "!def Reg name addr mask\nctrl 0x4000 0b0001\nstatus 0x4004 0b1100\n";
+    let expected = json!([
+        {"name": "ctrl", "addr": 16384, "mask": 1},
+        {"name": "status", "addr": 16388, "mask": 12}
+    ]);
+    assert_eq!(compile_tauq(source).unwrap(), expected);
+    let streamed: Vec<_> = StreamingParser::new(source).map(Result::unwrap).collect();
+    assert_eq!(json!(streamed), expected);
+
+    assert_eq!(
+        compile_tauq("bits [0b1 0b10 0b100]").unwrap(),
+        json!({"bits": [1, 2, 4]})
+    );
+}
+
+#[test]
+fn test_formatter_writes_decimal_and_quotes_radix_strings() {
+    let value = compile_tauq("flags 0xFF").unwrap();
+    assert_eq!(Formatter::new().format(&value), "flags 255");
+
+    // Strings that look like radix literals keep their quotes
+    let value = json!({"color": "0xFF", "addr": "-0x10", "mask": "0b1010"});
+    let tauq = Formatter::new().format(&value);
+    assert_eq!(tauq, "color \"0xFF\"\naddr \"-0x10\"\nmask \"0b1010\"");
+    assert_eq!(compile_tauq(&tauq).unwrap(), value);
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+#[test]
+fn test_require_satisfied() {
+    let input = "!require tauq >= 0.1\nname Alice\n";
+    let result = tauq::compile_tauq(input).unwrap();
+    assert_eq!(result["name"], "Alice");
+}
+
+#[test]
+fn test_require_without_product_name() {
+    let input = "!require ^0\nname Alice\n";
+    assert!(tauq::compile_tauq(input).is_ok());
+}
+
+#[test]
+fn test_require_unsatisfied() {
+    let input = "!require tauq >= 99.0\nname Alice\n";
+    let err = tauq::compile_tauq(input).unwrap_err().to_string();
+    assert!(
+        err.contains("This file requires Tauq >= 99.0 but you have"),
+        "{}",
+        err
+    );
+    assert!(err.contains(env!("CARGO_PKG_VERSION")), "{}", err);
+    assert!(err.contains("Please upgrade"), "{}", err);
+}
+
+#[test]
+fn test_require_malformed() {
+    let err = tauq::compile_tauq("!require tauq >= banana\n")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Invalid version constraint"), "{}", err);
+
+    let err = tauq::compile_tauq("!require\nname Alice\n")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("needs a version constraint"), "{}", err);
+}
+
+#[test]
+fn test_require_in_tauqq() {
+    let mut vars = HashMap::new();
+    let output = tauqq::process("!require tauq >= 0.1\nname Alice\n", &mut vars, true).unwrap();
+    assert!(!output.contains("!require"));
+
+    let err = tauqq::process("!require tauq >= 99\nname Alice\n", &mut vars, true).unwrap_err();
+    assert!(err.contains("requires Tauq >= 99"), "{}", err);
+}
+
+#[test]
+fn test_require_in_streaming_parser() {
+    use tauq::StreamingParser;
+
+    let records: Vec<_> = StreamingParser::new("!require tauq < 0.10\n!def U id\n1\n2\n")
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 2);
+
+    let mut parser = StreamingParser::new("!require tauq >= 99.0\n!def U id\n1\n");
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.message.contains("requires Tauq >= 99.0"), "{}", err);
+    assert_eq!((err.span.line, err.span.column), (1, 10));
+
+    let mut parser = StreamingParser::new("!require\n");
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(
+        err.message.contains("needs a version constraint"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_require_in_push_parser() {
+    use tauq::tauq::PushParser;
+
+    // `0.10` would lex as the float 0.1, so the constraint is read as text
+    let mut parser = PushParser::new();
+    parser.push("!require tauq <");
+    parser.push(" 0.10 # before 0.10\n!def U id\n1\n");
+    parser.finish();
+    assert_eq!(parser.next_record().unwrap().unwrap()["id"], 1);
+
+    let mut parser = PushParser::new();
+    parser.push("!require tauq >= 99\n1\n");
+    parser.finish();
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.message.contains("requires Tauq >= 99"), "{}", err);
+}
+
+#[test]
+fn test_require_in_sax_parser() {
+    use tauq::tauq::{CollectingHandler, parse_sax};
+
+    let mut handler = CollectingHandler::default();
+    assert!(parse_sax("!require ^0\n!def U id\n1\n", &mut handler).is_ok());
+
+    let err = parse_sax("!require tauq >= 99\n!def U id\n1\n", &mut handler).unwrap_err();
+    assert!(err.message.contains("requires Tauq >= 99"), "{}", err);
+}
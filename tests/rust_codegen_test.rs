@@ -0,0 +1,112 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::process::Command;
+
+const SCHEMAS: &str = include_str!("fixtures/codegen_schemas.tqn");
+
+/// The output of `tauq schema --rust` for `SCHEMAS`, compiled as part of this test
+mod generated {
+    include!("fixtures/codegen_types.rs");
+}
+
+use generated::{Employee, Team};
+
+fn shapes(source: &str) -> HashMap<String, Vec<tauq::tauq::parser::FieldDef>> {
+    let mut parser = tauq::Parser::new(source);
+    parser.parse().unwrap();
+    parser
+        .schema_definitions()
+        .into_iter()
+        .map(|def| (def.name, def.fields))
+        .collect()
+}
+
+#[test]
+fn test_generated_code_matches_fixture() {
+    assert_eq!(
+        tauq::tauq::tauq_schemas_to_rust(&shapes(SCHEMAS)),
+        include_str!("fixtures/codegen_types.rs")
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["schema", "--rust"])
+        .arg(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/codegen_schemas.tqn"
+        ))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        include_str!("fixtures/codegen_types.rs")
+    );
+}
+
+#[test]
+fn test_tauq_def_returns_the_schema_line() {
+    assert_eq!(
+        Employee::tauq_def(),
+        "!def Employee id:int name:string active:bool rate:float tags"
+    );
+    assert_eq!(SCHEMAS.lines().nth(1), Some(Team::tauq_def()));
+
+    let source = format!("{}\n1 Alice true 42.5 [admin ops]\n", Employee::tauq_def());
+    let employee: Employee = tauq::from_str(&source).unwrap();
+    assert_eq!(employee.name, "Alice");
+    assert_eq!(employee.tags, json!(["admin", "ops"]));
+}
+
+#[test]
+fn test_generated_types_round_trip() {
+    let employees = vec![
+        Employee {
+            id: 1,
+            name: "Alice".into(),
+            active: true,
+            rate: 42.5,
+            tags: json!(["admin"]),
+        },
+        Employee {
+            id: 2,
+            name: "Bob Smith".into(),
+            active: false,
+            rate: 38.0,
+            tags: json!([]),
+        },
+    ];
+    let source = tauq::to_string(&employees).unwrap();
+    let parsed: Vec<Employee> = tauq::from_str(&source).unwrap();
+    assert_eq!(
+        serde_json::to_value(&parsed).unwrap(),
+        serde_json::to_value(&employees).unwrap()
+    );
+
+    let team = Team {
+        name: "Core".into(),
+        lead: employees[0].clone(),
+        members: employees.clone(),
+        parent: Some(Box::new(Team {
+            name: "Platform".into(),
+            lead: employees[1].clone(),
+            members: vec![],
+            parent: None,
+            r#type: json!("division"),
+            note: None,
+            display_name: json!(null),
+        })),
+        r#type: json!("squad"),
+        note: Some("on call".into()),
+        display_name: json!("Core Team"),
+    };
+    let source = tauq::to_string(&team).unwrap();
+    let parsed: Team = tauq::from_str(&source).unwrap();
+    assert_eq!(
+        serde_json::to_value(&parsed).unwrap(),
+        serde_json::to_value(&team).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&parsed).unwrap()["display-name"],
+        "Core Team"
+    );
+}
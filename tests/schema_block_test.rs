@@ -57,3 +57,65 @@ fn test_schema_block_with_delimiter() {
         panic!("Expected row, got {:?}", result);
     }
 }
+
+#[test]
+fn test_formatter_block_and_flat_parse_identically() {
+    let value = serde_json::json!({
+        "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}],
+        "products": [{"sku": "A1", "price": 10}, {"sku": "B2", "price": 20}],
+        "orders": [{"id": 7, "user": 1, "sku": "A1"}, {"id": 8, "user": 2, "sku": "B2"}],
+        "team": {"members": [{"id": 1, "role": "lead"}, {"id": 2, "role": "dev"}]}
+    });
+
+    let flat = tauq::Formatter::new().format(&value);
+    let block = tauq::Formatter::new()
+        .with_schemas_block_threshold(2)
+        .format(&value);
+    assert!(flat.contains("!def "), "{}", flat);
+    assert!(block.starts_with("!schemas\n"), "{}", block);
+
+    assert_eq!(tauq::compile_tauq(&flat).unwrap(), value);
+    assert_eq!(tauq::compile_tauq(&block).unwrap(), value);
+
+    let minified = tauq::Formatter::new()
+        .minified()
+        .with_schemas_block_threshold(2)
+        .format(&value);
+    assert!(minified.starts_with("!schemas;"), "{}", minified);
+    assert_eq!(tauq::compile_tauq(&minified).unwrap(), value);
+}
+
+#[test]
+fn test_formatter_block_keeps_def_for_top_level_table() {
+    let value = serde_json::json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+    let output = tauq::Formatter::new()
+        .with_schemas_block_threshold(1)
+        .format(&value);
+    assert!(output.starts_with("!def "), "{}", output);
+    assert_eq!(tauq::compile_tauq(&output).unwrap(), value);
+}
+
+#[test]
+fn test_format_cli_schemas_block_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("data.json");
+    std::fs::write(
+        &input,
+        r#"{"users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]}"#,
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("format")
+        .arg(&input)
+        .arg("--schemas-block")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with("!schemas\nUser id name\n---\n"),
+        "{}",
+        stdout
+    );
+}
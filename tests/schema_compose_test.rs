@@ -0,0 +1,62 @@
+use serde_json::json;
+use tauq::tauq::StreamingParser;
+
+#[test]
+fn test_compose_concatenates_fields() {
+    let input = "!def A x y\n!def B z w\n!compose C A B\n1 2 3 4\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json, json!({"x": 1, "y": 2, "z": 3, "w": 4}));
+}
+
+#[test]
+fn test_compose_keeps_first_occurrence() {
+    let input = "!def A x y:Item\n!def Item id\n!def B y z\n!compose C A B\n1 { 7 } 3\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json, json!({"x": 1, "y": {"id": 7}, "z": 3}));
+}
+
+#[test]
+fn test_compose_is_usable_by_name() {
+    let input = "!def Id id\n!def Named name\n!compose User Id Named\n---\nusers [\n  !use User\n  1 Alice\n]\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json, json!({"users": [{"id": 1, "name": "Alice"}]}));
+}
+
+#[test]
+fn test_compose_errors() {
+    let err = tauq::compile_tauq("!def A x\n!compose A A B\n").unwrap_err();
+    assert!(err.to_string().contains("Circular composition"), "{}", err);
+
+    let err = tauq::compile_tauq("!def A x\n!compose C A Missing\n").unwrap_err();
+    assert!(
+        err.to_string().contains("undefined schema 'Missing'"),
+        "{}",
+        err
+    );
+
+    let err = tauq::compile_tauq("!def A x\n!compose C A\n").unwrap_err();
+    assert!(err.to_string().contains("at least two"), "{}", err);
+}
+
+#[test]
+fn test_formatter_emits_flat_def() {
+    let json =
+        tauq::compile_tauq("!def A x y\n!def B y z\n!compose C A B\n1 2 3\n4 5 6\n").unwrap();
+    let formatted = tauq::format_to_tauq(&json);
+    assert!(!formatted.contains("!compose"), "{}", formatted);
+    assert!(formatted.contains("x y z"), "{}", formatted);
+}
+
+#[test]
+fn test_streaming_and_sax_support_compose() {
+    let input = "!def A id\n!def B id name\n!compose User A B\n1 Alice\n";
+    let rows: Vec<_> = StreamingParser::new(input).map(|r| r.unwrap()).collect();
+    assert_eq!(rows, vec![json!({"id": 1, "name": "Alice"})]);
+
+    let mut handler = tauq::tauq::CollectingHandler::new();
+    tauq::tauq::parse_sax(input, &mut handler).unwrap();
+    assert_eq!(
+        handler.into_values(),
+        vec![json!({"id": 1, "name": "Alice"})]
+    );
+}
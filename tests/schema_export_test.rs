@@ -0,0 +1,176 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+
+const SOURCE: &str = "!def Employee id:int name:string active:bool rate:float\n\
+                      !def Team name:string lead:Employee members:[Employee] tags note?:string\n\
+                      !use Employee\n\
+                      1 Alice true 42.5\n\
+                      2 Bob false 38\n";
+
+fn shapes(source: &str) -> HashMap<String, Vec<tauq::tauq::parser::FieldDef>> {
+    let mut parser = tauq::Parser::new(source);
+    parser.parse().unwrap();
+    parser
+        .schema_definitions()
+        .into_iter()
+        .map(|def| (def.name, def.fields))
+        .collect()
+}
+
+/// Validator for one schema of `document`
+fn validator(document: &Value, name: &str) -> jsonschema::Validator {
+    let mut schema = document.clone();
+    schema["$ref"] = json!(format!("#/$defs/{}", name));
+    jsonschema::validator_for(&schema).unwrap()
+}
+
+#[test]
+fn test_two_schemas_export() {
+    let document = tauq::tauq::tauq_schemas_to_json_schema(&shapes(SOURCE));
+    assert!(jsonschema::meta::is_valid(&document));
+    assert_eq!(
+        document,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": {
+                "Employee": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "name": {"type": "string"},
+                        "active": {"type": "boolean"},
+                        "rate": {"type": "number"}
+                    },
+                    "required": ["id", "name", "active", "rate"]
+                },
+                "Team": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "lead": {"$ref": "#/$defs/Employee"},
+                        "members": {"type": "array", "items": {"$ref": "#/$defs/Employee"}},
+                        "tags": {},
+                        "note": {"type": ["string", "null"]}
+                    },
+                    "required": ["name", "lead", "members", "tags"]
+                }
+            }
+        })
+    );
+}
+
+#[test]
+fn test_schema_accepts_rows_and_rejects_mistyped_values() {
+    let document = tauq::tauq::tauq_schemas_to_json_schema(&shapes(SOURCE));
+    let employee = validator(&document, "Employee");
+
+    let rows = tauq::compile_tauq(SOURCE).unwrap();
+    for row in rows.as_array().unwrap() {
+        assert!(employee.is_valid(row), "{}", row);
+    }
+    assert!(!employee.is_valid(&json!({"id": "1", "name": "Alice", "active": true, "rate": 1})));
+    assert!(!employee.is_valid(&json!({"id": 1.5, "name": "Alice", "active": true, "rate": 1})));
+    assert!(!employee.is_valid(&json!({"id": 1, "name": "Alice", "active": "yes", "rate": 1})));
+    assert!(!employee.is_valid(&json!({"id": 1, "name": "Alice", "active": true})));
+
+    let team = validator(&document, "Team");
+    let alice = json!({"id": 1, "name": "Alice", "active": true, "rate": 42.5});
+    assert!(team.is_valid(&json!({
+        "name": "Core", "lead": alice, "members": [alice], "tags": ["a", 1], "note": null
+    })));
+    assert!(!team.is_valid(&json!({
+        "name": "Core", "lead": alice, "members": [{"id": "x"}], "tags": []
+    })));
+}
+
+#[test]
+fn test_defaults_and_unknown_types() {
+    let document = tauq::tauq::tauq_schemas_to_json_schema(&shapes(
+        "!enum Status active inactive\n!def Server host port:int=8080 status:Status\n",
+    ));
+    assert_eq!(
+        document["$defs"]["Server"],
+        json!({
+            "type": "object",
+            "properties": {
+                "host": {},
+                "port": {"type": "integer", "default": 8080},
+                "status": {}
+            },
+            "required": ["host", "status"]
+        })
+    );
+}
+
+#[test]
+fn test_schema_command_json_schema() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("team.tqn");
+    fs::write(&path, SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["schema", "--json-schema"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let printed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        printed,
+        tauq::tauq::tauq_schemas_to_json_schema(&shapes(SOURCE))
+    );
+}
+
+#[test]
+fn test_typescript_interfaces() {
+    let typescript = tauq::tauq::tauq_schemas_to_typescript(&shapes(SOURCE));
+    assert_eq!(
+        typescript,
+        "export interface Employee {\n  id: number;\n  name: string;\n  active: boolean;\n  rate: number;\n}\n\n\
+         export interface Team {\n  name: string;\n  lead: Employee;\n  members: Employee[];\n  tags: unknown;\n  note?: string | null;\n}\n"
+    );
+}
+
+#[test]
+fn test_typescript_unknown_types_and_forward_references() {
+    let typescript = tauq::tauq::tauq_schemas_to_typescript(&shapes(
+        "!def Account owner:User backups:[User] status:Status scores:[Score] extra?\n\
+         !def User id:int display-name:string\n",
+    ));
+    assert_eq!(
+        typescript,
+        "export interface Account {\n  owner: User;\n  backups: User[];\n  status: unknown;\n  scores: unknown[];\n  extra?: unknown;\n}\n\n\
+         export interface User {\n  id: number;\n  \"display-name\": string;\n}\n"
+    );
+}
+
+#[test]
+fn test_schema_command_typescript() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("team.tqn");
+    let out = dir.path().join("types.d.ts");
+    fs::write(&path, SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["schema", "--typescript"])
+        .arg(&path)
+        .arg("-o")
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        tauq::tauq::tauq_schemas_to_typescript(&shapes(SOURCE))
+    );
+}
@@ -0,0 +1,57 @@
+use serde_json::json;
+use tauq::tauq::StreamingParser;
+
+#[test]
+fn test_extends_chain_of_depth_three() {
+    let input = "!def Base id ts\n!def Event !extends Base kind\n!def Click !extends Event url button\n1 100 click /home left\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json,
+        json!({"id": 1, "ts": 100, "kind": "click", "url": "/home", "button": "left"})
+    );
+}
+
+#[test]
+fn test_child_field_shadows_parent_field() {
+    let input = "!def Point x:int y:int\n!def Item id\n!def Pin !extends Point y:Item label\n1 { 7 } home\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json, json!({"x": 1, "y": {"id": 7}, "label": "home"}));
+}
+
+#[test]
+fn test_extends_in_schemas_block() {
+    let input =
+        "!schemas\nBase id\nUser !extends Base name\n---\nusers [\n  !use User\n  1 Alice\n]\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json, json!({"users": [{"id": 1, "name": "Alice"}]}));
+}
+
+#[test]
+fn test_extends_undefined_parent() {
+    let err = tauq::compile_tauq("!def Click !extends Missing url\n/home\n").unwrap_err();
+    assert!(
+        err.to_string().contains("undefined schema 'Missing'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_extends_itself_is_circular() {
+    let err = tauq::compile_tauq("!def A x\n!def A !extends A y\n1 2\n").unwrap_err();
+    assert!(err.to_string().contains("Circular inheritance"), "{}", err);
+}
+
+#[test]
+fn test_streaming_and_sax_support_extends() {
+    let input = "!def Base id\n!def User !extends Base name\n1 Alice\n";
+    let rows: Vec<_> = StreamingParser::new(input).map(|r| r.unwrap()).collect();
+    assert_eq!(rows, vec![json!({"id": 1, "name": "Alice"})]);
+
+    let mut handler = tauq::tauq::CollectingHandler::new();
+    tauq::tauq::parse_sax(input, &mut handler).unwrap();
+    assert_eq!(
+        handler.into_values(),
+        vec![json!({"id": 1, "name": "Alice"})]
+    );
+}
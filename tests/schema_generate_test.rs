@@ -0,0 +1,89 @@
+use std::process::Command;
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/schema_sample.json"
+);
+
+/// Run `tauq schema --generate` on the fixture and return stdout.
+fn generate(extra: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["schema", "--generate", FIXTURE])
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_generate_schema_field_names() {
+    let output = generate(&[]);
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec![
+            "!def Employee id name salary remote skills",
+            "!def Office city code",
+            "!def Person first last",
+            "!def Skill label level",
+        ]
+    );
+}
+
+#[test]
+fn test_generate_schema_annotations() {
+    let output = generate(&["--annotate"]);
+    assert!(
+        output.contains("!def Employee id:int name:string salary:float remote:bool skills:[Skill]"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("!def Skill label:string level:int"),
+        "{}",
+        output
+    );
+    // Mixed types get no annotation
+    assert!(
+        output.contains("!def Office city:string code\n"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_generate_schema_min_occurrences() {
+    let output = generate(&["--min-occurrences", "3", "--annotate"]);
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec![
+            "!def Employee id:int name:string salary:float remote:bool skills:[Skill]",
+            "!def Skill label:string level:int",
+        ]
+    );
+
+    // Skill rows are split across arrays of two, but still counted together
+    let output = generate(&["--min-occurrences", "4", "--annotate"]);
+    assert_eq!(output, "!def Skill label:string level:int\n");
+}
+
+#[test]
+fn test_generated_schemas_parse_and_write_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("schemas.tqn");
+    let status = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["schema", "--generate", FIXTURE, "-o"])
+        .arg(&out)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mut source = std::fs::read_to_string(&out).unwrap();
+    source.push_str("!use Office\nParis 75\n");
+    let json = tauq::compile_tauq(&source).unwrap();
+    assert_eq!(json["city"], "Paris");
+}
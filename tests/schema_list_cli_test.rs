@@ -0,0 +1,111 @@
+use std::fs;
+use std::process::Command;
+
+/// Run `tauq schema` on `main.tqn` in a temp dir that also holds `files`
+fn schema(main: &str, files: &[(&str, &str)], extra: &[&str]) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, source) in files {
+        fs::write(dir.path().join(name), source).unwrap();
+    }
+    let path = dir.path().join("main.tqn");
+    fs::write(&path, main).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("schema")
+        .arg(&path)
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+const MAIN: &str = "!def User id name email\n!def Order id total items:[Item] note?\n!def Item sku\n1 Alice a@example.com\n";
+
+#[test]
+fn test_schema_table() {
+    assert_eq!(
+        schema(MAIN, &[], &[]),
+        "User: id, name, email (3 fields)\nOrder: id, total, items:[Item], note? (4 fields)\nItem: sku (1 field)\n"
+    );
+}
+
+#[test]
+fn test_schema_json_and_names_only() {
+    let json: serde_json::Value = serde_json::from_str(&schema(MAIN, &[], &["--json"])).unwrap();
+    assert_eq!(
+        json[0],
+        serde_json::json!({"name": "User", "fields": ["id", "name", "email"]})
+    );
+    assert_eq!(json.as_array().unwrap().len(), 3);
+
+    assert_eq!(schema(MAIN, &[], &["--names-only"]), "User\nOrder\nItem\n");
+}
+
+#[test]
+fn test_schemas_from_imports_show_their_file() {
+    let main = "!import \"common.tqn\"\n!def User id name email\n!def Tag label\n";
+    let files = [(
+        "common.tqn",
+        "!def Address street city\n!def User id name\n",
+    )];
+
+    let out = schema(main, &files, &[]);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "Address: street, city (2 fields)");
+    assert!(
+        lines[1].starts_with("User: id, name (2 fields) [") && lines[1].ends_with("common.tqn]"),
+        "{}",
+        out
+    );
+    assert!(lines[2].ends_with("main.tqn]"), "{}", out);
+    assert_eq!(lines[3], "Tag: label (1 field)");
+
+    let json: serde_json::Value = serde_json::from_str(&schema(main, &files, &["--json"])).unwrap();
+    assert!(json[0].get("file").is_none());
+    assert!(json[1]["file"].as_str().unwrap().ends_with("common.tqn"));
+}
+
+#[test]
+fn test_parser_schema_getters() {
+    let mut parser = tauq::Parser::new(MAIN);
+    parser.parse().unwrap();
+
+    let schemas = parser.schemas();
+    assert_eq!(schemas.len(), 3);
+    let names: Vec<&str> = schemas["Order"].iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["id", "total", "items", "note"]);
+
+    let defs = parser.schema_definitions();
+    assert_eq!(defs[2].name, "Item");
+    assert!(defs.iter().all(|d| d.source.is_none()));
+}
+
+#[test]
+fn test_schemas_from_sibling_directory_import() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("schemas")).unwrap();
+    fs::create_dir(dir.path().join("config")).unwrap();
+    fs::write(dir.path().join("schemas/user.tqn"), "!def User id name\n").unwrap();
+    fs::write(
+        dir.path().join("config/main.tqn"),
+        "!import \"../schemas/user.tqn\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .current_dir(dir.path().join("config"))
+        .args(["schema", "main.tqn", "--names-only"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "User\n");
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::process::Command;
+
+/// Run `tauq diff` on two sources and return (exit code, stdout)
+fn diff(a: &str, b: &str, extra: &[&str]) -> (i32, String) {
+    let dir = tempfile::tempdir().unwrap();
+    let a_path = dir.path().join("a.tqn");
+    let b_path = dir.path().join("b.tqn");
+    fs::write(&a_path, a).unwrap();
+    fs::write(&b_path, b).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("diff")
+        .arg(&a_path)
+        .arg(&b_path)
+        .args(extra)
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+const USERS: &str = "!def User id name role\n1 Alice admin\n2 Bob user\n";
+
+#[test]
+fn test_identical_files() {
+    let (code, out) = diff(USERS, USERS, &["--exit-code"]);
+    assert_eq!(code, 0);
+    assert_eq!(out, "");
+
+    // Field order is not a difference
+    let (code, out) = diff(
+        "name app\nport 8080\n",
+        "port 8080\nname app\n",
+        &["--exit-code"],
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out, "");
+}
+
+#[test]
+fn test_added_and_removed_rows() {
+    let b = "!def User id role name\n2 user Bob\n5 user Eve\n";
+    let (code, out) = diff(USERS, b, &[]);
+    assert_eq!(code, 0);
+    assert_eq!(
+        out,
+        "- id=1 name=Alice role=admin\n+ id=5 role=user name=Eve\n"
+    );
+
+    let (code, _) = diff(USERS, b, &["--exit-code"]);
+    assert_eq!(code, 1);
+}
+
+#[test]
+fn test_changed_scalars() {
+    let (_, out) = diff(
+        "name app\nport 8080\ntags [a b]\n",
+        "name app\nport 9090\ntags [a b c]\ndebug true\n",
+        &[],
+    );
+    assert_eq!(
+        out,
+        "- port: 8080\n+ port: 9090\n+ tags[2]: c\n+ debug: true\n"
+    );
+
+    let (_, out) = diff(
+        USERS,
+        "!def User id name role\n2 Bob admin\n1 Alice admin\n",
+        &[],
+    );
+    assert_eq!(out, "- [id=2].role: user\n+ [id=2].role: admin\n");
+}
+
+#[test]
+fn test_key_option_and_color() {
+    let a = "!def Item sku qty\nA1 3\nB2 5\n";
+    let b = "!def Item sku qty\nB2 6\nA1 3\n";
+    let (_, out) = diff(a, b, &["--key", "sku"]);
+    assert_eq!(out, "- [sku=B2].qty: 5\n+ [sku=B2].qty: 6\n");
+
+    // Without a usable key, rows are compared by position
+    let (_, out) = diff(a, b, &[]);
+    assert!(out.contains("- [0].sku: A1\n+ [0].sku: B2\n"), "{}", out);
+
+    let (_, out) = diff(a, b, &["--key", "sku", "--color"]);
+    assert_eq!(
+        out,
+        "\x1b[31m- [sku=B2].qty: 5\x1b[0m\n\x1b[32m+ [sku=B2].qty: 6\x1b[0m\n"
+    );
+}
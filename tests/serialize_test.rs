@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Role {
+    Admin,
+    Guest,
+    Custom(String),
+    Scoped { team: String, level: u8 },
+    Pair(u32, u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct User {
+    id: u64,
+    name: String,
+    role: Role,
+    score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    name: String,
+    version: u32,
+    enabled: bool,
+    address: Address,
+    tags: Vec<String>,
+    users: Vec<User>,
+    limits: BTreeMap<String, i64>,
+    notes: Option<String>,
+    description: String,
+}
+
+fn sample() -> Config {
+    Config {
+        name: "prod cluster".into(),
+        version: 3,
+        enabled: true,
+        address: Address {
+            city: "Berlin".into(),
+            zip: None,
+        },
+        tags: vec!["web".into(), "two words".into()],
+        users: vec![
+            User {
+                id: 1,
+                name: "Alice".into(),
+                role: Role::Admin,
+                score: 9.5,
+            },
+            User {
+                id: 2,
+                name: "Bob Smith".into(),
+                role: Role::Custom("ops".into()),
+                score: 7.0,
+            },
+            User {
+                id: 3,
+                name: "Carol".into(),
+                role: Role::Scoped {
+                    team: "data".into(),
+                    level: 2,
+                },
+                score: -1.25,
+            },
+            User {
+                id: 4,
+                name: "Dan".into(),
+                role: Role::Pair(1, 2),
+                score: 0.0,
+            },
+            User {
+                id: 5,
+                name: "Eve".into(),
+                role: Role::Guest,
+                score: 1e-3,
+            },
+        ],
+        limits: [("cpu".to_string(), 4), ("memory".to_string(), -1)]
+            .into_iter()
+            .collect(),
+        notes: None,
+        description: "line one\nline two\nline three".into(),
+    }
+}
+
+#[test]
+fn test_round_trip_nested_structs_vecs_and_enums() {
+    let config = sample();
+    let tauq = tauq::to_string(&config).unwrap();
+    assert!(tauq.contains("!def User id name role score"), "{}", tauq);
+    assert!(
+        tauq.contains("users [\n  !use User\n  1 Alice Admin 9.5\n"),
+        "{}",
+        tauq
+    );
+    assert!(tauq.contains("description <<<EOT\n"), "{}", tauq);
+
+    let back: Config = tauq::from_str(&tauq).unwrap();
+    assert_eq!(back, config);
+}
+
+#[test]
+fn test_matches_formatter_on_json_value() {
+    let config = sample();
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        tauq::to_string(&config).unwrap(),
+        tauq::Formatter::new().format(&json)
+    );
+}
+
+#[test]
+fn test_top_level_sequence_is_a_table() {
+    let users = vec![
+        Address {
+            city: "Paris".into(),
+            zip: Some("75001".into()),
+        },
+        Address {
+            city: "Rome".into(),
+            zip: None,
+        },
+    ];
+    let tauq = tauq::to_string(&users).unwrap();
+    assert_eq!(tauq, "!def Row city zip?\nParis \"75001\"\nRome");
+    assert_eq!(tauq::from_str::<Vec<Address>>(&tauq).unwrap(), users);
+}
+
+#[test]
+fn test_to_writer() {
+    let mut out = Vec::new();
+    tauq::to_writer(&mut out, &sample()).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        tauq::to_string(&sample()).unwrap()
+    );
+}
+
+/// Records each flush, failing writes once `limit` bytes have been taken
+struct Recorder {
+    written: Vec<u8>,
+    flushes: usize,
+    limit: usize,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written.len() + buf.len() > self.limit {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+        }
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_to_writer_writes_rows_as_it_goes() {
+    let users: Vec<Address> = (0..100)
+        .map(|i| Address {
+            city: format!("City{i}"),
+            zip: None,
+        })
+        .collect();
+    let mut out = Recorder {
+        written: Vec::new(),
+        flushes: 0,
+        limit: usize::MAX,
+    };
+    tauq::to_writer(&mut out, &users).unwrap();
+    assert_eq!(
+        String::from_utf8(out.written).unwrap(),
+        tauq::to_string(&users).unwrap()
+    );
+    assert!(out.flushes > 100, "{} flushes", out.flushes);
+
+    let mut out = Recorder {
+        written: Vec::new(),
+        flushes: 0,
+        limit: 64,
+    };
+    let err = tauq::to_writer(&mut out, &users).unwrap_err();
+    assert!(
+        matches!(&err, tauq::error::TauqError::Io(e) if e.kind() == io::ErrorKind::WriteZero),
+        "{err:?}"
+    );
+    assert!(!out.written.is_empty());
+}
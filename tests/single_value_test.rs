@@ -0,0 +1,48 @@
+use serde_json::{Value, json};
+use tauq::Parser;
+
+#[test]
+fn test_scalars() {
+    assert_eq!(Parser::parse_single_value("42").unwrap(), Value::from(42));
+    assert_eq!(Parser::parse_single_value("-1.5").unwrap(), json!(-1.5));
+    assert_eq!(Parser::parse_single_value("\"a b\"").unwrap(), json!("a b"));
+    assert_eq!(
+        Parser::parse_single_value("localhost").unwrap(),
+        json!("localhost")
+    );
+    assert_eq!(Parser::parse_single_value("null").unwrap(), Value::Null);
+}
+
+#[test]
+fn test_list_and_object() {
+    assert_eq!(
+        Parser::parse_single_value("[1 2 3]").unwrap(),
+        json!([1, 2, 3])
+    );
+    assert_eq!(
+        Parser::parse_single_value("\n{ host localhost\n  port 8080 }\n\n").unwrap(),
+        json!({"host": "localhost", "port": 8080})
+    );
+}
+
+#[test]
+fn test_trailing_tokens_are_rejected() {
+    let err = Parser::parse_single_value("1 2").unwrap_err();
+    assert!(err.message.contains("after value"), "{}", err);
+    assert_eq!((err.span.line, err.span.column), (1, 3));
+
+    // A key-value pair is not a single value
+    assert!(Parser::parse_single_value("port 8080").is_err());
+    assert!(Parser::parse_single_value("[1]\n[2]").is_err());
+}
+
+#[test]
+fn test_missing_or_invalid_value() {
+    let err = Parser::parse_single_value("  \n").unwrap_err();
+    assert!(err.message.contains("end of input"), "{}", err);
+    assert!(Parser::parse_single_value("!def User id").is_err());
+    assert!(Parser::parse_single_value("[1 2").is_err());
+
+    let err = Parser::parse_single_value("\"open").unwrap_err();
+    assert_eq!(err.message, "unterminated string literal");
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use serde_json::json;
+use tauq::tauq::{CollectingHandler, StreamingParser, TauqHandler, TauqValue, parse_sax};
+
+#[test]
+fn test_special_floats_parse_to_names() {
+    let input = "!def R v\n!use R\nInfinity\nNaN\n-Infinity\n+Infinity\n1.5\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {"v": "Infinity"},
+            {"v": "NaN"},
+            {"v": "-Infinity"},
+            {"v": "Infinity"},
+            {"v": 1.5}
+        ])
+    );
+
+    let rows: Vec<_> = StreamingParser::new(input).map(|r| r.unwrap()).collect();
+    assert_eq!(json, serde_json::Value::Array(rows));
+
+    let mut handler = CollectingHandler::new();
+    parse_sax(input, &mut handler).unwrap();
+    assert_eq!(json, serde_json::Value::Array(handler.into_values()));
+}
+
+#[test]
+fn test_other_spellings_stay_barewords() {
+    // Previously these were read as floats and lost as 0
+    let json = tauq::compile_tauq("a inf\nb nan\nc infinity\n").unwrap();
+    assert_eq!(json, json!({"a": "inf", "b": "nan", "c": "infinity"}));
+}
+
+#[test]
+fn test_sax_sees_float_values() {
+    struct Floats(Vec<f64>);
+    impl TauqHandler for Floats {
+        fn on_field(&mut self, _name: &str, value: TauqValue<'_>) {
+            if let TauqValue::Float(n) = value {
+                self.0.push(n);
+            }
+        }
+    }
+    let mut handler = Floats(Vec::new());
+    parse_sax("!def R v\nInfinity\n-Infinity\nNaN\n", &mut handler).unwrap();
+    assert_eq!(handler.0[0], f64::INFINITY);
+    assert_eq!(handler.0[1], f64::NEG_INFINITY);
+    assert!(handler.0[2].is_nan());
+}
+
+#[test]
+fn test_special_floats_round_trip() {
+    let json = tauq::compile_tauq("!def R v\nInfinity\nNaN\n-Infinity\n").unwrap();
+    let formatted = tauq::format_to_tauq(&json);
+    assert_eq!(tauq::compile_tauq(&formatted).unwrap(), json);
+
+    #[derive(Serialize)]
+    struct Reading {
+        low: f64,
+        high: f64,
+        mean: f64,
+    }
+    let text = tauq::to_string(&Reading {
+        low: f64::NEG_INFINITY,
+        high: f64::INFINITY,
+        mean: f64::NAN,
+    })
+    .unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&text).unwrap(),
+        json!({"low": "-Infinity", "high": "Infinity", "mean": "NaN"})
+    );
+
+    let json = tauq::compile_tauq("!strict\n!def R v:float\nNaN\n").unwrap();
+    assert_eq!(json, json!({"v": "NaN"}));
+}
@@ -0,0 +1,78 @@
+use serde_json::{Value, json};
+use std::fs;
+use std::process::Command;
+use tauq::Formatter;
+
+fn reordered() -> (Value, Value) {
+    let a = json!({
+        "service": "api",
+        "users": [{"id": 1, "name": "Alice", "role": "admin"}, {"id": 2, "name": "Bob", "role": "dev"}],
+        "limits": {"rps": 100, "burst": {"size": 10, "window": 1}}
+    });
+    let b = json!({
+        "limits": {"burst": {"window": 1, "size": 10}, "rps": 100},
+        "users": [{"role": "admin", "name": "Alice", "id": 1}, {"name": "Bob", "id": 2, "role": "dev"}],
+        "service": "api"
+    });
+    (a, b)
+}
+
+#[test]
+fn test_key_order_does_not_change_output() {
+    let (a, b) = reordered();
+    let formatter = Formatter::new().with_stable_output(true);
+    let out = formatter.format(&a);
+    assert_eq!(out, formatter.format(&b));
+    assert_eq!(
+        out,
+        "!def User id name role\n---\nlimits {\n  burst {\n    size 10\n    window 1\n  }\n  rps 100\n}\nservice api\nusers [\n  !use User\n  1 Alice admin\n  2 Bob dev\n]"
+    );
+    assert_eq!(tauq::compile_tauq(&out).unwrap(), a);
+
+    // Insertion order is kept by default
+    assert_ne!(Formatter::new().format(&a), Formatter::new().format(&b));
+}
+
+#[test]
+fn test_stable_output_in_every_mode() {
+    let (a, b) = reordered();
+    for formatter in [
+        Formatter::new().minified(),
+        Formatter::new().with_comma_delimiter(),
+        Formatter::new().without_schemas(),
+        Formatter::new().with_schemas_block_threshold(1),
+    ] {
+        let formatter = formatter.with_stable_output(true);
+        assert_eq!(formatter.format(&a), formatter.format(&b));
+
+        let mut buf = Vec::new();
+        formatter.format_to_writer(&b, &mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), formatter.format(&a));
+    }
+
+    let rows_a = json!([{"b": 1, "a": 2}, {"b": 3, "a": 4}]);
+    let rows_b = json!([{"a": 2, "b": 1}, {"a": 4, "b": 3}]);
+    let formatter = Formatter::new().with_stable_output(true);
+    assert_eq!(formatter.format(&rows_a), "!def Row a b\n2 1\n4 3");
+    assert_eq!(formatter.to_csv(&rows_a), formatter.to_csv(&rows_b));
+    assert_eq!(formatter.to_toml(&a), formatter.to_toml(&b));
+}
+
+#[test]
+fn test_format_stable_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let (a, b) = reordered();
+    let mut outputs = Vec::new();
+    for (name, value) in [("a.json", a), ("b.json", b)] {
+        let path = dir.path().join(name);
+        fs::write(&path, value.to_string()).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .args(["format", path.to_str().unwrap(), "--stable"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        outputs.push(String::from_utf8(output.stdout).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert!(outputs[0].starts_with("!def User id name role\n---\nlimits {"));
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::process::Command;
+
+/// 25 user rows; cl100k_base counts measured with tiktoken are below
+fn sample() -> serde_json::Value {
+    let names = [
+        "Alice", "Bob", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi",
+    ];
+    let rows: Vec<_> = (0..25)
+        .map(|i| {
+            let name = names[i % names.len()];
+            serde_json::json!({
+                "id": i + 1,
+                "name": name,
+                "email": format!("{}{}@example.com", name.to_lowercase(), i),
+                "role": if i % 3 == 0 { "admin" } else { "member" },
+                "active": i % 4 != 0,
+                "score": (i * 37) % 100,
+            })
+        })
+        .collect();
+    serde_json::json!({ "users": rows })
+}
+
+const TIKTOKEN_TAUQ: f64 = 356.0;
+const TIKTOKEN_JSON: f64 = 1230.0;
+
+/// Value of the `label:` line in stats output
+fn field<'a>(out: &'a str, label: &str, nth: usize) -> &'a str {
+    out.lines()
+        .filter_map(|l| l.strip_prefix(label))
+        .nth(nth)
+        .unwrap_or_else(|| panic!("no {} line in {}", label, out))
+        .trim()
+}
+
+#[test]
+fn test_stats_compare_savings_match_tiktoken() {
+    let dir = tempfile::tempdir().unwrap();
+    let tqn = dir.path().join("users.tqn");
+    let json = dir.path().join("users.json");
+    let value = sample();
+    fs::write(&tqn, tauq::json_to_tauq(&value)).unwrap();
+    fs::write(&json, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("stats")
+        .arg(&tqn)
+        .arg("--compare")
+        .arg(&json)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let out = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(field(&out, "Records:", 0), "1");
+    assert_eq!(field(&out, "Schemas:", 0), "1");
+    let tokens = |nth| -> f64 {
+        field(&out, "Tokens:", nth)
+            .split(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let (tauq_tokens, json_tokens) = (tokens(0), tokens(1));
+    assert!(
+        ((tauq_tokens - TIKTOKEN_TAUQ) / TIKTOKEN_TAUQ).abs() < 0.05,
+        "{}",
+        out
+    );
+    assert!(
+        ((json_tokens - TIKTOKEN_JSON) / TIKTOKEN_JSON).abs() < 0.05,
+        "{}",
+        out
+    );
+
+    let savings: f64 = field(&out, "Savings:", 0)
+        .split('%')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let actual = (TIKTOKEN_JSON - TIKTOKEN_TAUQ) / TIKTOKEN_JSON * 100.0;
+    assert!(
+        (savings - actual).abs() < 5.0,
+        "{} vs {:.1}",
+        savings,
+        actual
+    );
+}
+
+#[test]
+fn test_stats_counts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.tqn");
+    fs::write(
+        &path,
+        "!def Tag label\n!def User id name\n1 Alice\n2 Bob\n3 Carol\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("stats")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(field(&out, "Bytes:", 0), "55");
+    assert_eq!(field(&out, "Lines:", 0), "5");
+    assert_eq!(field(&out, "Records:", 0), "3");
+    assert_eq!(field(&out, "Schemas:", 0), "2");
+    assert!(!out.contains("Savings:"));
+}
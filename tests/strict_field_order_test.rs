@@ -0,0 +1,59 @@
+use serde_json::json;
+use tauq::Formatter;
+use tauq::tauq::SchemaRegistry;
+
+#[test]
+fn test_field_order_separates_schemas() {
+    let value = json!({
+        "left": [{"b": 1, "a": 2}, {"b": 3, "a": 4}],
+        "right": [{"a": 1, "b": 2}, {"a": 3, "b": 4}]
+    });
+
+    let out = Formatter::new()
+        .with_strict_field_order(true)
+        .format(&value);
+    assert!(
+        out.starts_with("!def Left b a\n!def Right a b\n---\n"),
+        "{}",
+        out
+    );
+    assert!(out.contains("  !use Left\n  1 2\n  3 4\n"), "{}", out);
+    assert!(out.contains("  !use Right\n  1 2\n  3 4\n"), "{}", out);
+    assert_eq!(tauq::compile_tauq(&out).unwrap(), value);
+
+    // By default both arrays share the schema seen first
+    let out = Formatter::new().format(&value);
+    assert!(out.starts_with("!def Left b a\n---\n"), "{}", out);
+    assert!(!out.contains("!def Right"), "{}", out);
+}
+
+#[test]
+fn test_mixed_order_array_is_not_a_table() {
+    let value = json!([{"b": 1, "a": 2}, {"a": 3, "b": 4}]);
+    let formatter = Formatter::new().with_strict_field_order(true);
+    assert_eq!(
+        formatter.detect_uniform_objects(value.as_array().unwrap()),
+        None
+    );
+
+    let out = formatter.format(&value);
+    assert!(!out.contains("!def"), "{}", out);
+    assert_eq!(tauq::compile_tauq(&out).unwrap(), value);
+}
+
+#[test]
+fn test_registry_strict_field_order() {
+    let ab = vec!["a".to_string(), "b".to_string()];
+    let ba = vec!["b".to_string(), "a".to_string()];
+
+    let mut registry = SchemaRegistry::new().with_strict_field_order(true);
+    assert_eq!(registry.get_or_create(&ba, None), "Row");
+    assert_eq!(registry.get_or_create(&ab, None), "Row2");
+    assert_eq!(registry.get_or_create(&ba, None), "Row");
+    assert_eq!(registry.len(), 2);
+
+    let mut registry = SchemaRegistry::new();
+    assert_eq!(registry.get_or_create(&ba, None), "Row");
+    assert_eq!(registry.get_or_create(&ab, None), "Row");
+    assert_eq!(registry.len(), 1);
+}
@@ -0,0 +1,127 @@
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tauq::Parser;
+
+#[test]
+fn test_strict_rejects_mismatched_int() {
+    let input = "!strict\n!def Row count:int\n!use Row\n\"hello\"\n";
+    let err = tauq::compile_tauq(input).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Field 'count' expects int, got \"hello\""),
+        "{}",
+        err
+    );
+
+    // Without !strict the annotation is documentation only
+    let json = tauq::compile_tauq("!def Row count:int\n!use Row\n\"hello\"\n").unwrap();
+    assert_eq!(json, json!({"count": "hello"}));
+}
+
+#[test]
+fn test_strict_scalar_types() {
+    let input = "!strict\n!def Row id:int ratio:float ok:bool name:string gone:null\n1 0.5 true Alice null\n2.0 3 false \"Bob Smith\" null\n";
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json[1]["ratio"], 3);
+
+    for (row, message) in [
+        ("1.5 0.5 true a null", "Field 'id' expects int, got 1.5"),
+        ("1 x true a null", "Field 'ratio' expects float, got \"x\""),
+        ("1 0.5 yes a null", "Field 'ok' expects bool, got \"yes\""),
+        ("1 0.5 true 42 null", "Field 'name' expects string, got 42"),
+        ("1 0.5 true a 0", "Field 'gone' expects null, got 0"),
+    ] {
+        let input = format!(
+            "!strict\n!def Row id:int ratio:float ok:bool name:string gone:null\n{}\n",
+            row
+        );
+        let err = tauq::compile_tauq(&input).unwrap_err();
+        assert!(err.to_string().contains(message), "{}: {}", row, err);
+    }
+}
+
+#[test]
+fn test_strict_checks_named_and_nested_values() {
+    let input = "!strict\n!def Point x:int y:int\n!def Pin at:Point label:Status\n{ 1 two } home\n";
+    let err = tauq::compile_tauq(input).unwrap_err();
+    assert!(err.to_string().contains("Field 'y' expects int"), "{}", err);
+
+    let input = "!strict\n!def Row id:int name\nid: x name: Alice\n";
+    let err = tauq::compile_tauq(input).unwrap_err();
+    assert!(
+        err.to_string().contains("Field 'id' expects int"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_parser_with_strict() {
+    let input = "!def Row count:int\n\"hello\"\n";
+    assert!(Parser::new(input).parse().is_ok());
+    assert!(Parser::new(input).with_strict(true).parse().is_err());
+}
+
+#[test]
+fn test_cli_strict_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.tqn");
+    fs::write(&path, "!def Row count:int\n\"hello\"\n").unwrap();
+
+    for command in ["build", "validate"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .arg(command)
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", command);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+            .arg(command)
+            .arg(&path)
+            .arg("--strict")
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{}", command);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("expects int"), "{}: {}", command, stderr);
+    }
+}
+
+#[test]
+fn test_streaming_push_and_sax_parsers_enforce_strict() {
+    use tauq::tauq::{CollectingHandler, PushParser, StreamingParser, parse_sax};
+
+    let input = "!strict\n!def Row id:int gone:null\n1 null\n2.5 null\n";
+    let message = "Field 'id' expects int, got 2.5";
+
+    let mut parser = StreamingParser::new(input);
+    assert_eq!(
+        parser.next_record().unwrap().unwrap(),
+        json!({"id": 1, "gone": null})
+    );
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+    assert_eq!((err.span.line, err.span.column), (4, 1));
+
+    let mut parser = PushParser::new();
+    parser.push(input);
+    parser.finish();
+    assert!(parser.next_record().unwrap().is_ok());
+    let err = parser.next_record().unwrap().unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+
+    let err = parse_sax(input, &mut CollectingHandler::new()).unwrap_err();
+    assert!(err.to_string().contains(message), "{}", err);
+
+    // The builders turn it on without the directive
+    let input = "!def Row id:int\n\"x\"\n";
+    assert!(StreamingParser::new(input).next_record().unwrap().is_ok());
+    let mut parser = StreamingParser::new(input).with_strict(true);
+    assert!(parser.next_record().unwrap().is_err());
+    let mut parser = PushParser::new().with_strict(true);
+    parser.push(input);
+    parser.finish();
+    assert!(parser.next_record().unwrap().is_err());
+}
@@ -15,3 +15,40 @@ fn test_string_escapes() {
     assert_eq!(json["path"], "C:\\Windows\\System32");
     assert_eq!(json["newline"], "Line1\nLine2");
 }
+
+#[test]
+fn test_unicode_escapes() {
+    let input = r#"
+    accent "caf\u00e9"
+    emoji "\uD83D\uDE00 \u{1F680}"
+    nul "a\u{0}b"
+    "#;
+
+    let json = tauq::compile_tauq(input).unwrap();
+    assert_eq!(json["accent"], "café");
+    assert_eq!(json["emoji"], "😀 🚀");
+    assert_eq!(json["nul"], "a\0b");
+}
+
+#[test]
+fn test_unicode_escape_errors() {
+    assert!(tauq::compile_tauq(r#"bad "\u{110000}""#).is_err());
+    assert!(tauq::compile_tauq(r#"bad "\uDFFF""#).is_err());
+}
+
+#[test]
+fn test_invisible_characters_roundtrip_as_escapes() {
+    let value = serde_json::json!({
+        "zwsp": "a\u{200B}b",
+        "bell": "ding\u{7}",
+        "nul": "x\u{0}y",
+        "plain": "naïve 日本"
+    });
+
+    let tauq = tauq::json_to_tauq(&value);
+    assert!(tauq.contains(r#""a\u200Bb""#), "{}", tauq);
+    assert!(tauq.contains(r#""ding\u0007""#), "{}", tauq);
+    assert!(tauq.contains(r#""x\u0000y""#), "{}", tauq);
+    assert!(tauq.contains("naïve 日本"), "{}", tauq);
+    assert_eq!(tauq::compile_tauq(&tauq).unwrap(), value);
+}
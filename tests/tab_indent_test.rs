@@ -0,0 +1,57 @@
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tauq::Formatter;
+
+#[test]
+fn test_tab_indent_nested_objects() {
+    let value = json!({"a": {"b": {"c": {"d": 1}}}, "tags": ["x\ty", "z"]});
+    let tabbed = Formatter::new().with_tab_indent().format(&value);
+    assert_eq!(
+        tabbed,
+        "a {\n\tb {\n\t\tc {\n\t\t\td 1\n\t\t}\n\t}\n}\ntags [\"x\\ty\" z]"
+    );
+
+    let spaced = Formatter::new().format(&value);
+    assert_eq!(
+        tauq::compile_tauq(&tabbed).unwrap(),
+        tauq::compile_tauq(&spaced).unwrap()
+    );
+    assert_eq!(tauq::compile_tauq(&tabbed).unwrap(), value);
+}
+
+#[test]
+fn test_tab_indent_schema_rows() {
+    let value = json!({"team": {"users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]}});
+    let tabbed = Formatter::new().with_tab_indent().format(&value);
+    assert!(
+        tabbed.contains("\n\tusers [\n\t\t!use User\n\t\t1 Alice\n"),
+        "{}",
+        tabbed
+    );
+    assert_eq!(tauq::compile_tauq(&tabbed).unwrap(), value);
+
+    // A later width switches back to spaces
+    let spaced = Formatter::new()
+        .with_tab_indent()
+        .with_indent(4)
+        .format(&value);
+    assert!(!spaced.contains('\t'), "{}", spaced);
+}
+
+#[test]
+fn test_prettify_tab_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.tqn");
+    fs::write(&input, "server { host localhost limits { rps 100 } }\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["prettify", input.to_str().unwrap(), "--tab"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "server {\n\thost localhost\n\tlimits {\n\t\trps 100\n\t}\n}\n"
+    );
+}
@@ -91,3 +91,43 @@ fn test_large_integers() {
         panic!("Expected object result");
     }
 }
+
+#[test]
+fn test_enum_declaration_is_not_data() {
+    let input = "!enum Status active inactive\n!def User id status:Status\n1 active\n2 inactive";
+    let mut parser = Parser::new(input);
+    let result = parser.parse().unwrap();
+
+    let rows = result.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["status"], "active");
+    assert_eq!(rows[1]["status"], "inactive");
+}
+
+#[test]
+fn test_integer_float_distinction_regression() {
+    assert!(tauq::compile_tauq("x 42").unwrap()["x"].is_i64());
+    assert!(tauq::compile_tauq("x 42.0").unwrap()["x"].is_f64());
+    assert!(tauq::compile_tauq("x 4.2e1").unwrap()["x"].is_f64());
+
+    // JSON -> Tauq -> JSON keeps integer and float types apart
+    let value = serde_json::json!({
+        "port": 8080,
+        "ratio": 1.0,
+        "hosts": [{"port": 80, "weight": 0.5}, {"port": 443, "weight": 2.0}]
+    });
+    let tauq = tauq::json_to_tauq(&value);
+    assert!(tauq.contains("port 8080\n"), "{}", tauq);
+    assert!(!tauq.contains("8080.0"), "{}", tauq);
+    let back = tauq::compile_tauq(&tauq).unwrap();
+    assert_eq!(back, value);
+    assert!(back["port"].is_i64());
+    assert!(back["hosts"][1]["port"].is_i64());
+    assert!(back["ratio"].is_f64());
+
+    // Streaming parser agrees
+    let mut streaming = tauq::StreamingParser::new("!def P port weight\n8080 2.0\n");
+    let row = streaming.next_record().unwrap().unwrap();
+    assert!(row["port"].is_i64());
+    assert!(row["weight"].is_f64());
+}
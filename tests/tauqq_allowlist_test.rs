@@ -0,0 +1,68 @@
+use std::collections::{HashMap, HashSet};
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+fn allowing(commands: &[&str]) -> ProcessConfig {
+    ProcessConfig {
+        command_allowlist: Some(commands.iter().map(|c| c.to_string()).collect()),
+        ..Default::default()
+    }
+}
+
+fn run(input: &str, config: &ProcessConfig) -> Result<String, String> {
+    tauqq::process_with_config(input, &mut HashMap::new(), config)
+}
+
+#[test]
+fn test_allowlist_permits_listed_commands() {
+    let config = allowing(&["echo"]);
+    assert_eq!(
+        run("!emit echo greeting hello\n", &config).unwrap(),
+        "greeting hello\n\n"
+    );
+
+    let err = run("!emit sh -c \"rm /tmp/x\"\n", &config).unwrap_err();
+    assert_eq!(
+        err,
+        "Command 'sh' is not in the configured command allowlist"
+    );
+}
+
+#[test]
+fn test_allowlist_covers_every_command_directive() {
+    let config = allowing(&["echo"]);
+    let err = run("x 1\n!pipe cat\n", &config).unwrap_err();
+    assert!(err.contains("'cat'"), "{}", err);
+    let err = run("!run sh {\n  echo x 1\n}\n", &config).unwrap_err();
+    assert!(err.contains("'sh'"), "{}", err);
+
+    let err = run("!if $(sh -c \"echo 1\")\nx 1\n!end\n", &config).unwrap_err();
+    assert!(err.contains("'sh'"), "{}", err);
+
+    // The name must match as written, not just the file name
+    let err = run("!emit /bin/echo hi\n", &config).unwrap_err();
+    assert!(err.contains("'/bin/echo'"), "{}", err);
+}
+
+#[test]
+fn test_empty_allowlist_blocks_all_commands() {
+    let config = ProcessConfig {
+        command_allowlist: Some(HashSet::new()),
+        ..Default::default()
+    };
+    assert!(run("!emit echo hello\n", &config).is_err());
+    assert!(run("!emit true\n", &config).is_err());
+    // Non-command directives are unaffected
+    assert_eq!(run("x 1\n", &config).unwrap(), "x 1\n");
+}
+
+#[test]
+fn test_allowlist_applies_to_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("part.tqq"), "!emit sh -c \"echo y 2\"\n").unwrap();
+    let config = ProcessConfig {
+        base_dir: Some(dir.path().to_path_buf()),
+        ..allowing(&["echo"])
+    };
+    let err = run("!import part.tqq\n", &config).unwrap_err();
+    assert!(err.contains("'sh'"), "{}", err);
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+fn run(input: &str) -> Result<String, String> {
+    let mut vars = HashMap::new();
+    tauqq::process(input, &mut vars, true)
+}
+
+#[test]
+fn test_passing_assertion_emits_nothing() {
+    let output = run("!set X 5\n!assert $X == 5 \"X should be 5\"\nx ${X}\n").unwrap();
+    assert_eq!(output, "x 5\n");
+}
+
+#[test]
+fn test_failing_assertion_reports_message() {
+    let err = run("!set X 3\n!assert $X == 5 \"X should be 5\"\nx ${X}\n").unwrap_err();
+    assert_eq!(err, "Assertion failed: X should be 5");
+}
+
+#[test]
+fn test_assertion_forms() {
+    assert!(run("!set NAME api\n!assert $NAME != \"\" \"NAME is empty\"\n").is_ok());
+    assert!(run("!set NAME api\n!assert defined $NAME \"NAME missing\"\n").is_ok());
+
+    let err = run("!assert defined $NAME \"NAME missing\"\n").unwrap_err();
+    assert_eq!(err, "Assertion failed: NAME missing");
+
+    // Without a message the condition is reported
+    let err = run("!set N 2\n!assert $N >= 3\n").unwrap_err();
+    assert_eq!(err, "Assertion failed: $N >= 3");
+}
+
+#[test]
+fn test_assertion_skipped_in_false_branch() {
+    let input = "!set MODE dev\n!if $MODE == prod\n!assert defined $TOKEN \"TOKEN required in prod\"\n!end\nmode ${MODE}\n";
+    assert_eq!(run(input).unwrap(), "mode dev\n");
+}
+
+#[test]
+fn test_assertion_shell_expression_rejected_in_safe_mode() {
+    let err = run("!assert $(echo 1) == 1 \"never\"\n").unwrap_err();
+    assert!(err.contains("disabled in safe mode"), "{}", err);
+}
+
+#[test]
+fn test_assert_count() {
+    let input = "!def User id name\n1 Alice\n\n2 Bob\n!for N in 3 4\n${N} User${N}\n!end\n!assert_count 4\n";
+    let output = run(input).unwrap();
+    assert_eq!(output.lines().count(), 5);
+
+    let err = run("!def User id name\n1 Alice\n2 Bob\n!assert_count 3\n").unwrap_err();
+    assert_eq!(err, "Assertion failed: expected 3 rows, found 2");
+
+    let err = run("!assert_count three\n").unwrap_err();
+    assert!(err.contains("expects a number"), "{}", err);
+}
+
+#[test]
+fn test_assertion_json_pointer() {
+    let config = "!set CONFIG {\"users\":[{\"name\":\"Alice\",\"age\":30}]}\n";
+    assert!(
+        run(&format!(
+            "{}!assert $CONFIG/users/0/name == Alice\n",
+            config
+        ))
+        .is_ok()
+    );
+    assert!(run(&format!("{}!assert $CONFIG/users/0/age > 18\n", config)).is_ok());
+
+    // Tauq values work as well
+    assert!(run("!set T { port 8080 }\n!assert $T/port == 8080\n").is_ok());
+
+    let err = run(&format!("{}!assert $CONFIG/users/1/name == Bob\n", config)).unwrap_err();
+    assert_eq!(err, "No value at pointer /users/1/name in $CONFIG");
+}
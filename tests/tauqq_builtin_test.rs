@@ -25,6 +25,7 @@ fn test_tauqq_read() {
     let config = ProcessConfig {
         base_dir: None,
         safe_mode: false,
+        ..Default::default()
     };
     let result = tauqq::process_with_config(&input, &mut vars, &config).unwrap();
     assert!(result.contains("\"file content\""));
@@ -36,6 +37,7 @@ fn test_path_traversal_blocked() {
     let config = ProcessConfig {
         base_dir: Some(std::path::PathBuf::from("/tmp/tauq_test_sandbox")),
         safe_mode: false,
+        ..Default::default()
     };
 
     let input = "!read \"../../etc/passwd\"";
@@ -55,3 +57,49 @@ fn test_path_traversal_blocked() {
         err
     );
 }
+
+#[test]
+fn test_tauqq_env_default() {
+    let mut vars = HashMap::new();
+
+    let input = "!env TAUQ_TEST_UNSET_DB \"localhost:5432\"";
+    let result = tauqq::process(input, &mut vars, false).unwrap();
+    assert_eq!(result, "\"localhost:5432\"\n");
+
+    let input = "!env TAUQ_TEST_UNSET_DB fallback";
+    let result = tauqq::process(input, &mut vars, false).unwrap();
+    assert_eq!(result, "\"fallback\"\n");
+
+    unsafe { std::env::set_var("TAUQ_TEST_SET_DB", "prod-db:5432") };
+    let input = "!env TAUQ_TEST_SET_DB \"localhost:5432\"";
+    let result = tauqq::process(input, &mut vars, false).unwrap();
+    assert_eq!(result, "\"prod-db:5432\"\n");
+
+    let err = tauqq::process("!env TAUQ_TEST_UNSET_DB", &mut vars, false).unwrap_err();
+    assert!(err.contains("not found"), "{}", err);
+}
+
+#[test]
+fn test_tauqq_env_default_compiles() {
+    let mut vars = HashMap::new();
+    let input = "!def Config url\n!env TAUQ_TEST_UNSET_HOST \"my db host:5432\"\n";
+    let result = tauqq::process(input, &mut vars, false).unwrap();
+    let json = tauq::compile_tauq(&result).unwrap();
+    assert_eq!(json, serde_json::json!({"url": "my db host:5432"}));
+}
+
+#[test]
+fn test_tauqq_set_from_env() {
+    unsafe { std::env::set_var("TAUQ_TEST_SET_REGION", "us-east-1") };
+    let input = "!set REGION ?env:TAUQ_TEST_SET_REGION eu-west-1\n\
+                 !set ZONE ?env:TAUQ_TEST_UNSET_ZONE \"zone a\"\n\
+                 region ${REGION}\nzone \"${ZONE}\"\n";
+    let mut vars = HashMap::new();
+    let result = tauqq::process(input, &mut vars, false).unwrap();
+    let json = tauq::compile_tauq(&result).unwrap();
+    assert_eq!(json["region"], "us-east-1");
+    assert_eq!(json["zone"], "zone a");
+
+    let err = tauqq::process("!set X ?env:HOME", &mut vars, true).unwrap_err();
+    assert!(err.contains("safe mode"), "{}", err);
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+const DATA: &str = "!def User id name\n1 Alice\n";
+
+fn process_in(dir: &std::path::Path, input: &str) -> Result<String, String> {
+    let config = ProcessConfig {
+        base_dir: Some(dir.to_path_buf()),
+        safe_mode: false,
+        ..Default::default()
+    };
+    let mut vars = HashMap::new();
+    tauqq::process_with_config(input, &mut vars, &config)
+}
+
+#[test]
+fn test_sha256_hex_known_vector() {
+    assert_eq!(
+        tauqq::sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_import_with_matching_checksum() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("data.tqn"), DATA).unwrap();
+
+    let checksum = tauqq::sha256_hex(DATA.as_bytes());
+    let input = format!("!import \"data.tqn\" sha256:{}\n", checksum);
+    let output = process_in(dir.path(), &input).unwrap();
+    assert!(output.contains("1 Alice"));
+
+    // Uppercase hex is accepted too
+    let input = format!("!import \"data.tqn\" sha256:{}\n", checksum.to_uppercase());
+    assert!(process_in(dir.path(), &input).is_ok());
+}
+
+#[test]
+fn test_import_with_mismatching_checksum() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("data.tqn"), DATA).unwrap();
+
+    let err = process_in(dir.path(), "!import \"data.tqn\" sha256:abc123\n").unwrap_err();
+    assert!(
+        err.starts_with("Integrity check failed for 'data.tqn': expected abc123, got "),
+        "{}",
+        err
+    );
+    assert!(
+        err.ends_with(&tauqq::sha256_hex(DATA.as_bytes())),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_import_without_checksum_still_works() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("data.tqn"), DATA).unwrap();
+
+    let output = process_in(dir.path(), "!import \"data.tqn\"\n").unwrap();
+    assert!(output.contains("1 Alice"));
+}
+
+#[test]
+fn test_checksum_cli_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data.tqn");
+    fs::write(&path, DATA).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("checksum")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        format!("sha256:{}", tauqq::sha256_hex(DATA.as_bytes()))
+    );
+}
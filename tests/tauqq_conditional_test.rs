@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+fn run(input: &str, vars: &[(&str, &str)]) -> Result<String, String> {
+    let mut vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    tauqq::process(input, &mut vars, true)
+}
+
+#[test]
+fn test_if_else_selects_branch() {
+    let input = "!if $MODE == prod\nhost \"db.prod\"\n!else\nhost localhost\n!end\nport 5432\n";
+    let prod = run(input, &[("MODE", "prod")]).unwrap();
+    assert_eq!(prod, "host \"db.prod\"\nport 5432\n");
+
+    let dev = run(input, &[("MODE", "dev")]).unwrap();
+    assert_eq!(dev, "host localhost\nport 5432\n");
+}
+
+#[test]
+fn test_elif_chain_takes_first_match() {
+    let input = "!if $N > 10\nsize large\n!elif $N > 5\nsize medium\n!elif $N > 0\nsize small\n!else\nsize none\n!end\n";
+    assert_eq!(run(input, &[("N", "20")]).unwrap(), "size large\n");
+    assert_eq!(run(input, &[("N", "7")]).unwrap(), "size medium\n");
+    assert_eq!(run(input, &[("N", "1.5")]).unwrap(), "size small\n");
+    assert_eq!(run(input, &[("N", "-3")]).unwrap(), "size none\n");
+}
+
+#[test]
+fn test_nested_conditionals() {
+    let input = "\
+!if defined $REGION
+!if $REGION == eu
+region eu
+!else
+region other
+!end
+!else
+region unknown
+!end
+";
+    assert_eq!(run(input, &[("REGION", "eu")]).unwrap(), "region eu\n");
+    assert_eq!(run(input, &[("REGION", "us")]).unwrap(), "region other\n");
+    // The inner conditions in the discarded branch are never evaluated
+    assert_eq!(run(input, &[]).unwrap(), "region unknown\n");
+}
+
+#[test]
+fn test_conditions_see_set_variables() {
+    let input = "!set TIER \"gold\"\n!if $TIER == \"gold\"\ndiscount 20\n!end\n!if not defined $OTHER\nfallback true\n!end\n";
+    let output = run(input, &[]).unwrap();
+    assert_eq!(output, "discount 20\nfallback true\n");
+
+    let json = tauq::compile_tauq(&output).unwrap();
+    assert_eq!(json["discount"], 20);
+}
+
+#[test]
+fn test_set_inside_false_branch_is_ignored() {
+    let input = "!if 1 == 2\n!set X yes\n!end\n!if defined $X\nleaked true\n!end\n";
+    assert_eq!(run(input, &[]).unwrap(), "");
+}
+
+#[test]
+fn test_safe_mode_blocks_shell_expressions() {
+    let err = run("!if $(echo yes) == yes\na 1\n!end\n", &[]).unwrap_err();
+    assert!(err.contains("safe mode"), "{}", err);
+
+    let mut vars = HashMap::new();
+    let output = tauqq::process("!if $(echo yes) == yes\na 1\n!end\n", &mut vars, false).unwrap();
+    assert_eq!(output, "a 1\n");
+}
+
+#[test]
+fn test_conditional_errors() {
+    let err = run("!if 1 == 1\na 1\n", &[]).unwrap_err();
+    assert!(err.contains("Unterminated !if"), "{}", err);
+
+    let err = run("!end\n", &[]).unwrap_err();
+    assert!(err.contains("!end without matching !if"), "{}", err);
+
+    let err = run("!if 1 == 1\n!else\n!elif 2 == 2\n!end\n", &[]).unwrap_err();
+    assert!(err.contains("!elif after !else"), "{}", err);
+
+    let err = run("!if $MISSING == x\n!end\n", &[]).unwrap_err();
+    assert!(err.contains("Undefined variable '$MISSING'"), "{}", err);
+
+    let err = run("!if abc < 3\n!end\n", &[]).unwrap_err();
+    assert!(err.contains("numeric"), "{}", err);
+
+    let deep = "!if 1 == 1\n".repeat(51) + &"!end\n".repeat(51);
+    let err = run(&deep, &[]).unwrap_err();
+    assert!(err.contains("nesting depth (50)"), "{}", err);
+
+    let ok = "!if 1 == 1\n".repeat(50) + "a 1\n" + &"!end\n".repeat(50);
+    assert_eq!(run(&ok, &[]).unwrap(), "a 1\n");
+}
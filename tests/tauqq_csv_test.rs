@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::fs;
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+fn run_in(dir: &std::path::Path, input: &str) -> Result<String, String> {
+    let config = ProcessConfig {
+        base_dir: Some(dir.to_path_buf()),
+        safe_mode: false,
+        ..Default::default()
+    };
+    tauqq::process_with_config(input, &mut HashMap::new(), &config)
+}
+
+#[test]
+fn test_csv_round_trips_through_compile() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut csv = String::from("id,name,score\n");
+    for i in 1..=10 {
+        csv.push_str(&format!("{},User {},{}.5\n", i, i, i * 10));
+    }
+    fs::write(dir.path().join("user_scores.csv"), &csv).unwrap();
+
+    let output = run_in(dir.path(), "!csv \"user_scores.csv\"\n").unwrap();
+    assert!(
+        output.starts_with("!def UserScores id name score\n1 \"User 1\" 10.5\n"),
+        "{}",
+        output
+    );
+    assert_eq!(
+        tauq::compile_tauq(&output).unwrap(),
+        tauq::csv_to_value(&csv, true).unwrap()
+    );
+}
+
+#[test]
+fn test_csv_quoted_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = "city,note\n\"Paris, FR\",\"said \"\"hi\"\"\"\nOslo,\n";
+    fs::write(dir.path().join("cities.csv"), csv).unwrap();
+
+    let output = run_in(dir.path(), "!csv cities.csv\n").unwrap();
+    assert_eq!(
+        tauq::compile_tauq(&output).unwrap(),
+        serde_json::json!([
+            {"city": "Paris, FR", "note": "said \"hi\""},
+            {"city": "Oslo", "note": null}
+        ])
+    );
+}
+
+#[test]
+fn test_csv_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("bad.csv"), "first name,id\nAda,1\n").unwrap();
+    let err = run_in(dir.path(), "!csv bad.csv\n").unwrap_err();
+    assert!(err.contains("not a valid field name"), "{}", err);
+
+    let err = run_in(dir.path(), "!csv ../outside.csv\n").unwrap_err();
+    assert!(
+        err.contains("traversal") || err.contains("outside"),
+        "{}",
+        err
+    );
+
+    let err = tauqq::process("!csv data.csv\n", &mut HashMap::new(), true).unwrap_err();
+    assert_eq!(err, "!csv directive is disabled in safe mode");
+}
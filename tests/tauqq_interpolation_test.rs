@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+fn run(input: &str) -> Result<String, String> {
+    let mut vars = HashMap::new();
+    tauqq::process(input, &mut vars, true)
+}
+
+#[test]
+fn test_interpolates_set_variable() {
+    let output = run("!set DB_HOST prod-db-01\nhost ${DB_HOST}\nport 5432\n").unwrap();
+    assert_eq!(output, "host prod-db-01\nport 5432\n");
+}
+
+#[test]
+fn test_multi_word_value_inside_quotes() {
+    let input = "!set MOTTO say \"hi\" twice\nmotto \"${MOTTO}\"\nlabel \"we ${MOTTO}\"\n";
+    let output = run(input).unwrap();
+    let json = tauq::compile_tauq(&output).unwrap();
+    assert_eq!(json["motto"], "say \"hi\" twice");
+    assert_eq!(json["label"], "we say \"hi\" twice");
+
+    let output = run("!set FULL \"Ada Lovelace\"\nname \"${FULL}\"\n").unwrap();
+    assert_eq!(output, "name \"Ada Lovelace\"\n");
+    assert_eq!(tauq::compile_tauq(&output).unwrap()["name"], "Ada Lovelace");
+}
+
+#[test]
+fn test_escaped_reference_is_literal() {
+    let output = run("template \"Hello $${NAME}\"\n").unwrap();
+    assert_eq!(output, "template \"Hello ${NAME}\"\n");
+    assert_eq!(
+        tauq::compile_tauq(&output).unwrap()["template"],
+        "Hello ${NAME}"
+    );
+    assert_eq!(run("raw $${HOME}\n").unwrap(), "raw ${HOME}\n");
+
+    // Escapes and references can share a line
+    let output = run("!set NAME Ada\nline \"${NAME} $${NAME}\"\n").unwrap();
+    assert_eq!(output, "line \"Ada ${NAME}\"\n");
+}
+
+#[test]
+fn test_default_fallback() {
+    let output = run("region ${REGION?eu-west-1}\n").unwrap();
+    assert_eq!(output, "region eu-west-1\n");
+
+    let output = run("!set REGION us-east-1\nregion ${REGION?eu-west-1}\n").unwrap();
+    assert_eq!(output, "region us-east-1\n");
+
+    let output = run("suffix \"${SUFFIX?}\"\n").unwrap();
+    assert_eq!(output, "suffix \"\"\n");
+}
+
+#[test]
+fn test_unresolved_reference_is_an_error() {
+    let err = run("host ${NOPE}\n").unwrap_err();
+    assert!(err.contains("Undefined variable 'NOPE'"), "{}", err);
+
+    let err = run("host ${NOPE\n").unwrap_err();
+    assert!(err.contains("Unterminated"), "{}", err);
+}
+
+#[test]
+fn test_nested_interpolation() {
+    let input = "!set TARGET PROD_URL\n!set PROD_URL \"https://prod\"\nurl \"${${TARGET}}\"\n";
+    let output = run(input).unwrap();
+    assert_eq!(output, "url \"https://prod\"\n");
+
+    let output = run("!set KIND cache\nsize ${${KIND}_SIZE?64}\n").unwrap();
+    assert_eq!(output, "size 64\n");
+}
+
+#[test]
+fn test_interpolation_with_loop_variable() {
+    let input = "!def Service name url\n!for SVC in api web\n${SVC} \"https://${SVC}.${DOMAIN?example.com}\"\n!end\n";
+    let output = run(input).unwrap();
+    assert_eq!(
+        output,
+        "!def Service name url\napi \"https://api.example.com\"\nweb \"https://web.example.com\"\n"
+    );
+}
+
+#[test]
+fn test_comments_and_plain_dollars_are_untouched() {
+    let output = run("price \"$5\"\n# uses ${UNSET}\nnote 1 # ${ALSO_UNSET}\n").unwrap();
+    assert_eq!(output, "price \"$5\"\nnote 1 # ${ALSO_UNSET}\n");
+}
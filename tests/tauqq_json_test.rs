@@ -26,6 +26,7 @@ fn test_tauqq_json_directive() {
     let config = ProcessConfig {
         base_dir: None,
         safe_mode: false,
+        ..Default::default()
     };
     let result = tauqq::process_with_config(&input, &mut vars, &config).unwrap();
 
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+fn run(input: &str, verbose: bool) -> Result<String, String> {
+    let config = ProcessConfig {
+        safe_mode: true,
+        verbose,
+        ..Default::default()
+    };
+    tauqq::process_with_config(input, &mut HashMap::new(), &config)
+}
+
+#[test]
+fn test_log_leaves_output_empty() {
+    let output = run("!set COUNT 3\n!log \"Processing ${COUNT} items\"\n", true).unwrap();
+    assert_eq!(output, "");
+}
+
+#[test]
+fn test_log_suppressed_when_quiet() {
+    let output = run("!set COUNT 3\n!log \"Processing ${COUNT} items\"\n", false).unwrap();
+    assert_eq!(output, "");
+}
+
+#[test]
+fn test_log_message_not_in_output() {
+    let input = "!set COUNT 3\n!log \"Processing ${COUNT} items\"\n!def User id name\n1 Alice\n";
+    let output = run(input, true).unwrap();
+    assert!(!output.contains("Processing"), "{}", output);
+    assert_eq!(output, "!def User id name\n1 Alice\n");
+}
+
+#[test]
+fn test_log_undefined_variable_is_error() {
+    let err = run("!log \"Missing ${NOPE}\"\n", false).unwrap_err();
+    assert!(err.contains("NOPE"), "{}", err);
+}
+
+fn exec(dir: &std::path::Path, extra: &[&str]) -> std::process::Output {
+    let path = dir.join("script.tqq");
+    fs::write(
+        &path,
+        "!set COUNT 3\n!log \"Processing ${COUNT} items\"\n!def User id name\n1 Alice\n",
+    )
+    .unwrap();
+    Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("exec")
+        .arg(&path)
+        .args(extra)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_exec_verbose_writes_log_to_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = exec(dir.path(), &["--verbose"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr, "Processing 3 items\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Processing"), "{}", stdout);
+}
+
+#[test]
+fn test_exec_quiet_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = exec(dir.path(), &[]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "name": "Alice"}));
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use tauq::tauq::tauqq;
+
+fn run(input: &str) -> Result<String, String> {
+    let mut vars = HashMap::new();
+    tauqq::process(input, &mut vars, true)
+}
+
+#[test]
+fn test_for_generates_row_per_value() {
+    let input = "!def Env name url\n!for ENV in prod staging dev\n$ENV \"https://$ENV.example.com\"\n!end\n";
+    let output = run(input).unwrap();
+    assert_eq!(
+        output,
+        "!def Env name url\nprod \"https://prod.example.com\"\nstaging \"https://staging.example.com\"\ndev \"https://dev.example.com\"\n"
+    );
+
+    let json = tauq::compile_tauq(&output).unwrap();
+    assert_eq!(json[1]["name"], "staging");
+    assert_eq!(json[2]["url"], "https://dev.example.com");
+}
+
+#[test]
+fn test_nested_loops() {
+    let input = "!def Cell row col\n!for R in 1 2\n!for C in a b\n$R $C\n!end\n!end\n";
+    let output = run(input).unwrap();
+    assert_eq!(output, "!def Cell row col\n1 a\n1 b\n2 a\n2 b\n");
+}
+
+#[test]
+fn test_loop_variable_that_prefixes_another() {
+    let input = "!for ITEM in x y\n!for I in 1 2\n$ITEM$I $I_$ITEM\n!end\n!end\n";
+    assert_eq!(run(input).unwrap(), "x1 $I_x\nx2 $I_x\ny1 $I_y\ny2 $I_y\n");
+
+    let input = "!for I in 1\n!for ITEM in a\n$I $ITEM\n!end\n!end\n";
+    assert_eq!(run(input).unwrap(), "1 a\n");
+}
+
+#[test]
+fn test_loop_over_empty_list_produces_nothing() {
+    assert_eq!(run("!for X in\nvalue $X\n!end\n").unwrap(), "");
+    assert_eq!(
+        run("!set NONE \"\"\n!for X in $NONE\nvalue $X\n!end\n").unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn test_loop_over_set_variable() {
+    let input = "!set REGIONS \"eu us ap\"\n!def Region code\n!for R in $REGIONS\n$R\n!end\n";
+    let output = run(input).unwrap();
+    assert_eq!(output, "!def Region code\neu\nus\nap\n");
+}
+
+#[test]
+fn test_loop_body_sees_and_sets_variables() {
+    let input = "!for ENV in prod dev\n!if $ENV == prod\nreplicas_$ENV 3\n!else\nreplicas_$ENV 1\n!end\n!set LAST $ENV\n!end\n";
+    let mut vars = HashMap::new();
+    let output = tauqq::process(input, &mut vars, true).unwrap();
+    assert_eq!(output, "replicas_prod 3\nreplicas_dev 1\n");
+    assert_eq!(vars.get("LAST").map(String::as_str), Some("dev"));
+    // The loop variable is unbound again after the loop
+    assert!(!vars.contains_key("ENV"));
+}
+
+#[test]
+fn test_loop_inside_false_branch_is_skipped() {
+    let input = "!if 1 == 2\n!for X in a b\nv $X\n!end\n!end\ndone true\n";
+    assert_eq!(run(input).unwrap(), "done true\n");
+}
+
+#[test]
+fn test_loop_errors() {
+    let err = run("!for X in a b\nv $X\n").unwrap_err();
+    assert!(err.contains("Unterminated !for"), "{}", err);
+
+    let err = run("!for X a b\n!end\n").unwrap_err();
+    assert!(err.contains("!for VAR in"), "{}", err);
+
+    let err = run("!for X in $MISSING\n!end\n").unwrap_err();
+    assert!(err.contains("Undefined variable '$MISSING'"), "{}", err);
+
+    let nested = |n: usize| {
+        (0..n)
+            .map(|i| format!("!for V{} in x\n", i))
+            .collect::<String>()
+            + &"!end\n".repeat(n)
+    };
+    assert!(run(&nested(10)).is_ok());
+    let err = run(&nested(11)).unwrap_err();
+    assert!(err.contains("nesting depth (10)"), "{}", err);
+}
+
+#[test]
+fn test_set_in_loop_is_global_and_local_is_scoped() {
+    let mut vars = HashMap::new();
+    tauqq::process("!for I in 1 2 3\n!set TMP $I\n!end\n", &mut vars, true).unwrap();
+    assert_eq!(vars.get("TMP").map(String::as_str), Some("3"));
+
+    let mut vars = HashMap::new();
+    tauqq::process("!for I in 1 2 3\n!local TMP $I\n!end\n", &mut vars, true).unwrap();
+    assert!(!vars.contains_key("TMP"));
+}
+
+#[test]
+fn test_local_is_reset_each_iteration() {
+    let input = "!for I in 1 2\n!if defined $SEEN\nrepeat $I\n!end\n!local SEEN yes\nitem_$I ${SEEN}\n!end\n";
+    assert_eq!(run(input).unwrap(), "item_1 yes\nitem_2 yes\n");
+}
+
+#[test]
+fn test_manual_scope_shadows_and_restores() {
+    let input = "!set NAME outer\n!scope\n!local NAME inner\ninner ${NAME}\n!set NAME changed\n!set OTHER kept\nchanged ${NAME}\n!endscope\nouter ${NAME}\n";
+    let mut vars = HashMap::new();
+    let output = tauqq::process(input, &mut vars, true).unwrap();
+    assert_eq!(output, "inner inner\nchanged changed\nouter outer\n");
+    assert_eq!(vars.get("NAME").map(String::as_str), Some("outer"));
+    // `!set` of a name with no local binding stays global
+    assert_eq!(vars.get("OTHER").map(String::as_str), Some("kept"));
+}
+
+#[test]
+fn test_scope_errors() {
+    let err = run("!endscope\n").unwrap_err();
+    assert!(err.contains("without a matching !scope"), "{}", err);
+
+    let err = run("!scope\n!local X 1\n").unwrap_err();
+    assert!(err.contains("Unterminated !scope"), "{}", err);
+
+    // A loop body cannot close a scope opened outside it
+    let err = run("!scope\n!for X in a\n!endscope\n!end\n!endscope\n").unwrap_err();
+    assert!(err.contains("without a matching !scope"), "{}", err);
+}
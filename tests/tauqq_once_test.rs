@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::fs;
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+fn config_in(dir: &std::path::Path) -> ProcessConfig {
+    ProcessConfig {
+        base_dir: Some(dir.to_path_buf()),
+        safe_mode: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_once_block_emitted_once_across_imports() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("schemas.tqq"),
+        "!once {\n!def User id name\n}\n",
+    )
+    .unwrap();
+
+    let input = "!import \"schemas.tqq\"\n!import \"schemas.tqq\"\n!use User\n1 Alice\n";
+    let mut vars = HashMap::new();
+    let output = tauqq::process_with_config(input, &mut vars, &config_in(dir.path())).unwrap();
+
+    assert_eq!(output.matches("!def User").count(), 1, "{}", output);
+    let json = tauq::compile_tauq(&output).unwrap();
+    assert_eq!(json["name"], "Alice");
+}
+
+#[test]
+fn test_once_block_in_same_file() {
+    let input =
+        "!once {\n!def Point x y\n}\n!once {\n!def Point x y\n}\n!once {\n!def Size w h\n}\n";
+    let mut vars = HashMap::new();
+    let output = tauqq::process(input, &mut vars, true).unwrap();
+
+    assert_eq!(output.matches("!def Point").count(), 1, "{}", output);
+    assert_eq!(output.matches("!def Size").count(), 1, "{}", output);
+}
+
+#[test]
+fn test_once_starts_fresh_on_each_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = config_in(dir.path());
+    let block = "!once {\nversion 1\n}\n";
+    let mut vars = HashMap::new();
+
+    let first = tauqq::process_with_config(block, &mut vars, &config).unwrap();
+    let second = tauqq::process_with_config(block, &mut vars, &config).unwrap();
+    assert_eq!(first, "version 1\n");
+    assert_eq!(second, first);
+}
+
+#[test]
+fn test_once_body_with_nested_braces() {
+    let input = "!once {\nserver {\n  host \"a}b\"\n  port 80\n}\n}\nafter 1\n";
+    let mut vars = HashMap::new();
+    let output = tauqq::process(input, &mut vars, true).unwrap();
+
+    let json = tauq::compile_tauq(&output).unwrap();
+    assert_eq!(json["server"]["host"], "a}b");
+    assert_eq!(json["after"], 1);
+}
+
+#[test]
+fn test_once_unterminated() {
+    let mut vars = HashMap::new();
+    let err = tauqq::process("!once {\n!def A x\n", &mut vars, true).unwrap_err();
+    assert!(err.contains("Unterminated"), "{}", err);
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauq::tauq::tauqq::{self, ProcessConfig};
+
+fn run(input: &str) -> Result<String, String> {
+    tauqq::process(input, &mut HashMap::new(), false)
+}
+
+#[test]
+fn test_timeout_kills_slow_command() {
+    let start = Instant::now();
+    let err = run("!timeout 1\n!emit sh -c \"sleep 5\"\n").unwrap_err();
+    assert_eq!(err, "Command timed out after 1s: sh -c \"sleep 5\"");
+    assert!(start.elapsed() < Duration::from_secs(4));
+}
+
+#[test]
+fn test_timeout_allows_fast_commands() {
+    let output = run("!timeout 5\n!emit echo 42\nx 1\n!pipe cat\n").unwrap();
+    assert_eq!(output, "42\n\nx 1\n");
+}
+
+#[test]
+fn test_timeout_applies_to_run_blocks() {
+    let err = run("!timeout 1\n!run sh {\n  sleep 5\n}\n").unwrap_err();
+    assert!(err.starts_with("Command timed out after 1s"), "{}", err);
+}
+
+#[test]
+fn test_timeout_zero_lifts_limit() {
+    let output = run("!timeout 1\n!timeout 0\n!emit sh -c \"sleep 1.2; echo 7\"\n").unwrap();
+    assert_eq!(output.trim(), "7");
+}
+
+#[test]
+fn test_timeout_from_config() {
+    let config = ProcessConfig::default().with_timeout(Some(Duration::from_secs(1)));
+    let err = tauqq::process_with_config("!emit sh -c \"sleep 5\"\n", &mut HashMap::new(), &config)
+        .unwrap_err();
+    assert!(err.contains("timed out"), "{}", err);
+}
+
+#[test]
+fn test_timeout_validation_and_safe_mode() {
+    let err = run("!timeout soon\n").unwrap_err();
+    assert_eq!(err, "!timeout expects a number of seconds, got 'soon'");
+
+    let output = tauqq::process("!timeout 1\nx 1\n", &mut HashMap::new(), true).unwrap();
+    assert_eq!(output, "x 1\n");
+}
+
+#[test]
+fn test_timeout_does_not_outlive_run() {
+    let config = ProcessConfig::default();
+    let mut vars = HashMap::new();
+    tauqq::process_with_config("!timeout 1\n", &mut vars, &config).unwrap();
+    assert_eq!(config.timeout, None);
+    let output =
+        tauqq::process_with_config("!emit sh -c \"sleep 1.2; echo 7\"\n", &mut vars, &config)
+            .unwrap();
+    assert_eq!(output.trim(), "7");
+}
+
+/// Input larger than a pipe buffer
+fn large_input() -> String {
+    (0..20_000).map(|i| format!("row {}\n", i)).collect()
+}
+
+#[test]
+fn test_timeout_applies_while_writing_unread_input() {
+    let source = format!("!timeout 1\n{}!pipe sh -c \"sleep 5\"\n", large_input());
+    assert!(source.len() > 64 * 1024);
+    let start = Instant::now();
+    let err = run(&source).unwrap_err();
+    assert!(err.starts_with("Command timed out after 1s"), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(4));
+}
+
+#[test]
+fn test_large_input_is_piped_through() {
+    let input = large_input();
+    let output = run(&format!("{}!pipe cat\n", input)).unwrap();
+    assert_eq!(output, input);
+}
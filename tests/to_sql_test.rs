@@ -0,0 +1,146 @@
+use std::fs;
+use std::process::Command;
+
+const USERS: &str =
+    "!def User id name email active\n1 Alice alice@example.com true\n2 Bob bob@example.com false\n";
+
+/// Run `tauq to-sql` on `source` with extra args and return stdout.
+fn to_sql(source: &str, extra: &[&str]) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("users.tqn");
+    fs::write(&input, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .arg("to-sql")
+        .arg(&input)
+        .args(extra)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_to_sql_postgres() {
+    let sql = to_sql(USERS, &["--table", "users"]);
+    assert_eq!(
+        sql,
+        "INSERT INTO users (id, name, email, active) VALUES (1, 'Alice', 'alice@example.com', TRUE), (2, 'Bob', 'bob@example.com', FALSE);\n"
+    );
+}
+
+#[test]
+fn test_to_sql_mysql() {
+    let sql = to_sql(
+        "!def Row order note\n1 \"it's a\\\\b\"\n",
+        &["--table", "notes", "--dialect", "mysql"],
+    );
+    assert_eq!(
+        sql,
+        "INSERT INTO notes (`order`, note) VALUES (1, 'it''s a\\\\b');\n"
+    );
+}
+
+#[test]
+fn test_to_sql_sqlite() {
+    let sql = to_sql(USERS, &["--table", "users", "--dialect", "sqlite"]);
+    assert_eq!(
+        sql,
+        "INSERT INTO users (id, name, email, active) VALUES (1, 'Alice', 'alice@example.com', 1), (2, 'Bob', 'bob@example.com', 0);\n"
+    );
+}
+
+#[test]
+fn test_to_sql_mssql() {
+    let sql = to_sql(
+        "!def Row id label\n1 null\n",
+        &["--table", "user", "--dialect", "mssql"],
+    );
+    assert_eq!(sql, "INSERT INTO [user] (id, label) VALUES (1, NULL);\n");
+}
+
+#[test]
+fn test_to_sql_batch_size() {
+    let source = "!def Item id\n1\n2\n3\n4\n5\n";
+    let sql = to_sql(source, &["--table", "items", "--batch-size", "2"]);
+    let statements: Vec<&str> = sql.lines().collect();
+    assert_eq!(
+        statements,
+        vec![
+            "INSERT INTO items (id) VALUES (1), (2);",
+            "INSERT INTO items (id) VALUES (3), (4);",
+            "INSERT INTO items (id) VALUES (5);",
+        ]
+    );
+}
+
+#[test]
+fn test_to_sql_create_table() {
+    let source = "!def Reading id value ok label\n1 1.5 true a\n2 2 false null\n";
+    let sql = to_sql(source, &["--table", "readings", "--create-table"]);
+    assert!(sql.starts_with(
+        "CREATE TABLE readings (\n  id BIGINT NOT NULL,\n  value DOUBLE PRECISION NOT NULL,\n  ok BOOLEAN NOT NULL,\n  label TEXT\n);\n"
+    ), "{}", sql);
+    assert!(
+        sql.ends_with("VALUES (1, 1.5, TRUE, 'a'), (2, 2, FALSE, NULL);\n"),
+        "{}",
+        sql
+    );
+
+    let sql = to_sql(
+        source,
+        &[
+            "--table",
+            "readings",
+            "--create-table",
+            "--dialect",
+            "sqlite",
+        ],
+    );
+    assert!(sql.contains("  ok INTEGER NOT NULL,\n"), "{}", sql);
+    assert!(sql.contains("  value REAL NOT NULL,\n"), "{}", sql);
+}
+
+#[test]
+fn test_to_sql_default_table_from_file_name() {
+    let sql = to_sql("!def T id\n7\n", &[]);
+    assert_eq!(sql, "INSERT INTO users (id) VALUES (7);\n");
+}
+
+#[test]
+fn test_to_sql_rows_without_columns_use_defaults() {
+    // Rows of a zero-field schema are blank lines
+    let source = "!def Ping\n\n\n";
+    assert_eq!(
+        to_sql(source, &["--table", "pings"]),
+        "INSERT INTO pings DEFAULT VALUES;\n".repeat(3)
+    );
+    assert_eq!(
+        to_sql(source, &["--table", "pings", "--dialect", "mysql"]),
+        "INSERT INTO pings () VALUES ();\n".repeat(3)
+    );
+}
+
+#[test]
+fn test_to_sql_mssql_batches_at_most_1000_rows() {
+    let mut source = String::from("!def Row id\n");
+    for i in 0..2500 {
+        source.push_str(&format!("{}\n", i));
+    }
+    let rows_per_insert = |sql: &str| -> Vec<usize> {
+        sql.lines()
+            .map(|line| line.matches('(').count() - 1)
+            .collect()
+    };
+
+    let sql = to_sql(&source, &["--dialect", "mssql"]);
+    assert_eq!(rows_per_insert(&sql), [1000, 1000, 500]);
+    let sql = to_sql(&source, &["--dialect", "mssql", "--batch-size", "5000"]);
+    assert_eq!(rows_per_insert(&sql), [1000, 1000, 500]);
+    let sql = to_sql(&source, &["--dialect", "postgres"]);
+    assert_eq!(rows_per_insert(&sql), [2500]);
+}
@@ -0,0 +1,78 @@
+use serde_json::json;
+use tauq::{Formatter, TomlFormatter};
+
+#[test]
+fn test_scalars_and_sections() {
+    let value = json!({
+        "title": "Tauq \"config\"",
+        "port": 8080,
+        "ratio": 2.0,
+        "debug": false,
+        "database": {"host": "localhost", "replica set": "rs0"},
+        "tags": ["a", "b"]
+    });
+    let toml = tauq::to_toml(&value);
+    assert_eq!(
+        toml,
+        "title = \"Tauq \\\"config\\\"\"\nport = 8080\nratio = 2.0\ndebug = false\ntags = [\"a\", \"b\"]\n\n[database]\nhost = \"localhost\"\n\"replica set\" = \"rs0\""
+    );
+    assert_eq!(Formatter::new().to_toml(&value), toml);
+}
+
+#[test]
+fn test_arrays_of_objects_become_array_of_tables() {
+    let value = json!({
+        "server": {
+            "users": [
+                {"id": 1, "name": "Alice", "roles": [{"r": "admin"}]},
+                {"id": 2, "name": "Bob", "meta": {"active": true}}
+            ]
+        }
+    });
+    assert_eq!(
+        TomlFormatter::new().format(&value),
+        "[server]\n\n[[server.users]]\nid = 1\nname = \"Alice\"\n\n[[server.users.roles]]\nr = \"admin\"\n\n[[server.users]]\nid = 2\nname = \"Bob\"\n\n[server.users.meta]\nactive = true"
+    );
+}
+
+#[test]
+fn test_tauq_source_to_toml() {
+    let json = tauq::compile_tauq("!def User id name\n1 Alice\n2 Bob\n").unwrap();
+    assert_eq!(
+        tauq::to_toml(&json),
+        "[[value]]\nid = 1\nname = \"Alice\"\n\n[[value]]\nid = 2\nname = \"Bob\""
+    );
+    assert_eq!(tauq::to_toml(&json!(42)), "value = 42");
+}
+
+#[test]
+fn test_unrepresentable_values_emit_warnings() {
+    let value = json!({
+        "mixed": ["a", 1, {"k": null}],
+        "missing": null,
+        "sparse": [1, null, 3]
+    });
+    assert_eq!(
+        tauq::to_toml(&value),
+        "# warning: 'mixed': mixed-type array written inline\n\
+         # warning: 'mixed': null field 'k' omitted\n\
+         mixed = [\"a\", 1, {}]\n\
+         # warning: 'missing' is null, which TOML cannot represent; omitted\n\
+         # warning: 'sparse': null array elements omitted\n\
+         sparse = [1, 3]"
+    );
+}
+
+#[test]
+fn test_integers_beyond_i64_written_as_strings() {
+    let value = json!({"max": i64::MAX, "big": u64::MAX, "ids": [1, u64::MAX]});
+    assert_eq!(
+        tauq::to_toml(&value),
+        "max = 9223372036854775807\n\
+         # warning: 'big': 18446744073709551615 exceeds TOML's 64-bit integer range; written as a string\n\
+         big = \"18446744073709551615\"\n\
+         # warning: 'ids': mixed-type array written inline\n\
+         # warning: 'ids': 18446744073709551615 exceeds TOML's 64-bit integer range; written as a string\n\
+         ids = [1, \"18446744073709551615\"]"
+    );
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "watch")]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Kills the watcher even if an assertion fails
+struct Watcher(Child);
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn spawn_watch(dir: &Path, args: &[&str]) -> Watcher {
+    let child = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .current_dir(dir)
+        .arg("watch")
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    Watcher(child)
+}
+
+/// Poll `path` until its contents satisfy `pred`
+fn wait_for(path: &Path, pred: impl Fn(&str) -> bool) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        if pred(&text) {
+            return text;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "timed out; last output: {:?}",
+            text
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn test_rebuilds_on_write() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("in.tqn"), "name Alice\n").unwrap();
+    let out = dir.path().join("out.json");
+
+    let _watcher = spawn_watch(dir.path(), &["in.tqn", "-o", "out.json"]);
+    wait_for(&out, |t| t.contains("Alice"));
+
+    // Give the watcher time to register before the edit
+    thread::sleep(Duration::from_millis(300));
+    fs::write(dir.path().join("in.tqn"), "name Bob\nage 30\n").unwrap();
+    let text = wait_for(&out, |t| t.contains("Bob"));
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json, serde_json::json!({"name": "Bob", "age": 30}));
+}
+
+#[test]
+fn test_rebuilds_when_import_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("lib")).unwrap();
+    fs::write(dir.path().join("lib/schemas.tqn"), "!def User id name\n").unwrap();
+    fs::write(
+        dir.path().join("main.tqn"),
+        "!import \"lib/schemas.tqn\"\n!use User\n1 Alice\n",
+    )
+    .unwrap();
+    let out = dir.path().join("out.json");
+
+    let _watcher = spawn_watch(dir.path(), &["main.tqn", "-o", "out.json"]);
+    wait_for(&out, |t| t.contains("\"name\""));
+
+    thread::sleep(Duration::from_millis(300));
+    fs::write(dir.path().join("lib/schemas.tqn"), "!def User id login\n").unwrap();
+    let text = wait_for(&out, |t| t.contains("\"login\""));
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "login": "Alice"}));
+}
+
+#[test]
+fn test_watches_imports_next_to_file_in_subdirectory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("sub/schema.tqn"), "!def User id name\n").unwrap();
+    // A file of the same name in the working directory is not the import
+    fs::write(dir.path().join("schema.tqn"), "!def User id other\n").unwrap();
+    fs::write(
+        dir.path().join("sub/main.tqn"),
+        "!import \"schema.tqn\"\n!use User\n1 Alice\n",
+    )
+    .unwrap();
+    let out = dir.path().join("out.json");
+
+    let _watcher = spawn_watch(dir.path(), &["sub/main.tqn", "-o", "out.json"]);
+    wait_for(&out, |t| t.contains("\"name\""));
+
+    thread::sleep(Duration::from_millis(300));
+    fs::write(dir.path().join("sub/schema.tqn"), "!def User id login\n").unwrap();
+    let text = wait_for(&out, |t| t.contains("\"login\""));
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "login": "Alice"}));
+}
+
+#[test]
+fn test_json_errors_rejected() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tauq"))
+        .args(["watch", "missing.tqn", "--json-errors"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--json-errors"));
+}